@@ -0,0 +1,695 @@
+// Analyses that are too expensive to run on large files in the browser. They are shared by the
+// backend, which runs them on uploaded files, and the command-line tool, which runs them on files
+// given to it. The loudness and true peak measurements are also used by the frontend, so that all
+// of them measure the same way.
+
+use std::{
+    f64::consts::PI,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use hound::{SampleFormat, WavReader};
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+use serde::{Deserialize, Serialize};
+
+const BLOCK_SECONDS: f64 = 0.4;
+const STEP_SECONDS: f64 = 0.1;
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+const OVERSAMPLING: usize = 4;
+const INTERPOLATION_HALF_TAPS: i64 = 12;
+
+// Spectrogram frames overlap by half, and are written this many to a tile.
+pub const FRAME_LEN: usize = 4096;
+const HOP: usize = FRAME_LEN / 2;
+const TILE_FRAMES: usize = 64;
+
+// Windows of the RMS level series, which do not overlap.
+const RMS_SECONDS: f64 = 0.01;
+
+const FLOOR_DB: f64 = -120.0;
+
+// The highest sample rate and channel count a file is decoded with, well above any real recording.
+const MAX_SAMPLE_RATE: u32 = 1_536_000;
+const MAX_CHANNELS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Analysis {
+    pub sample_rate: u32,
+    pub channels: usize,
+    pub duration_seconds: f64,
+    // Gated integrated loudness in LUFS, or `None` if the signal is too short or too quiet.
+    pub integrated_lufs: Option<f64>,
+    pub true_peak_dbtp: f64,
+    pub spectrogram: Spectrogram,
+    pub series: Vec<Series>,
+}
+
+// How the spectrogram of the channels mixed to mono was tiled, in a pyramid of `zooms` levels like
+// map tiles. Level 0 holds the `frames` frames themselves, and each level above it half as many
+// as the one below, each averaging the power of two of them, up to a level that fits in one tile.
+// Each tile holds `tile_frames` frames, the last one of a level fewer, of `frame_len / 2` bin levels
+// each as little-endian 32-bit floats in dBFS relative to a full-scale sine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Spectrogram {
+    pub frame_len: usize,
+    pub hop: usize,
+    pub frames: usize,
+    pub tile_frames: usize,
+    pub zooms: usize,
+}
+
+// The loudness of a signal according to ITU-R BS.1770-4.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loudness {
+    // Gated integrated loudness in LUFS, or `None` if the signal is too short or too quiet.
+    pub integrated_lufs: Option<f64>,
+    // The loudness of 400 ms blocks every 100 ms, the first centred 200 ms in.
+    pub momentary_lufs: Vec<f32>,
+    // The highest true peak of any channel.
+    pub true_peak_dbtp: f64,
+}
+
+// The Hann-windowed spectra of frames of channels mixed to mono, overlapping by half, with bin
+// levels in dBFS relative to a full-scale sine.
+pub struct Spectra<'a> {
+    channels: &'a [Vec<f32>],
+    frame_len: usize,
+    window: Vec<f64>,
+    scale: f64,
+    fft: Arc<dyn Fft<f64>>,
+}
+
+// A time series of levels written to `<name>.bin` in the series directory of a job, as little-endian
+// 32-bit floats. Point `n` is measured over the window centred on `start_seconds + n *
+// step_seconds`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Series {
+    pub name: String,
+    pub unit: String,
+    pub start_seconds: f64,
+    pub step_seconds: f64,
+    pub points: usize,
+}
+
+// Analyses the WAV file at `path`, writing the spectrogram tiles to `tile_dir` and the time series
+// to `series_dir`, and reporting the share of the work done, along with the number of tiles of
+// level 0 written, to `on_progress` along the way. Those tiles are written in order, before the
+// levels above, and each appears in full once written.
+pub fn analyse(
+    path: &Path,
+    tile_dir: &Path,
+    series_dir: &Path,
+    mut on_progress: impl FnMut(f64, usize),
+) -> Result<Analysis, String> {
+    let (channels, sample_rate) = decode(path).map_err(|error| error.to_string())?;
+    on_progress(0.1, 0);
+    let Loudness {
+        integrated_lufs,
+        momentary_lufs,
+        true_peak_dbtp,
+    } = loudness(&channels, sample_rate);
+    let step = step_len(sample_rate);
+    let rms_window = ((RMS_SECONDS * f64::from(sample_rate)) as usize).max(1);
+    let rms = rms_levels(&channels, rms_window);
+    let seconds = |len: usize| len as f64 / f64::from(sample_rate);
+    let series = [
+        (
+            "momentary_loudness",
+            "LUFS",
+            BLOCK_SECONDS / 2.0,
+            step,
+            momentary_lufs,
+        ),
+        ("rms", "dBFS", seconds(rms_window) / 2.0, rms_window, rms),
+    ]
+    .into_iter()
+    .map(|(name, unit, start_seconds, step, levels)| {
+        write_series(series_dir, name, &levels)?;
+        Ok(Series {
+            name: name.to_owned(),
+            unit: unit.to_owned(),
+            start_seconds,
+            step_seconds: seconds(step),
+            points: levels.len(),
+        })
+    })
+    .collect::<io::Result<_>>()
+    .map_err(|error: io::Error| format!("failed to write series: {error}"))?;
+    on_progress(0.4, 0);
+    let mut tiles = 0;
+    let frames = write_spectrogram(&channels, tile_dir, |written, total| {
+        tiles = written;
+        on_progress(0.4 + 0.5 * written as f64 / total as f64, written)
+    })
+    .map_err(|error| format!("failed to write spectrogram tiles: {error}"))?;
+    let zooms = write_pyramid(tile_dir, frames, |zoom, zooms| {
+        on_progress(0.9 + 0.1 * zoom as f64 / zooms as f64, tiles)
+    })
+    .map_err(|error| format!("failed to write spectrogram tiles: {error}"))?;
+
+    let len = channels.first().map_or(0, Vec::len);
+    Ok(Analysis {
+        sample_rate,
+        channels: channels.len(),
+        duration_seconds: len as f64 / f64::from(sample_rate),
+        integrated_lufs,
+        true_peak_dbtp,
+        spectrogram: Spectrogram {
+            frame_len: FRAME_LEN,
+            hop: HOP,
+            frames,
+            tile_frames: TILE_FRAMES,
+            zooms,
+        },
+        series,
+    })
+}
+
+pub fn loudness(channels: &[Vec<f32>], sample_rate: u32) -> Loudness {
+    let block_powers = block_powers(channels, sample_rate);
+    Loudness {
+        integrated_lufs: gated_loudness(&block_powers),
+        momentary_lufs: block_powers.iter().map(|&p| to_lufs(p) as f32).collect(),
+        true_peak_dbtp: channels
+            .iter()
+            .map(|channel| true_peak_dbtp(channel))
+            .fold(FLOOR_DB, f64::max),
+    }
+}
+
+impl<'a> Spectra<'a> {
+    pub fn new(channels: &'a [Vec<f32>], frame_len: usize) -> Self {
+        let window: Vec<f64> = (0..frame_len)
+            .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f64 / frame_len as f64).cos())
+            .collect();
+        // A full-scale sine has a bin magnitude of half the window sum, and the channels are
+        // averaged.
+        let scale = 2.0 / window.iter().sum::<f64>() / channels.len() as f64;
+        Self {
+            channels,
+            frame_len,
+            window,
+            scale,
+            fft: FftPlanner::new().plan_fft_forward(frame_len),
+        }
+    }
+
+    pub fn hop(&self) -> usize {
+        self.frame_len / 2
+    }
+
+    pub fn frames(&self) -> usize {
+        let len = self.channels.first().map_or(0, Vec::len);
+        if len < self.frame_len {
+            0
+        } else {
+            (len - self.frame_len) / self.hop() + 1
+        }
+    }
+
+    // The levels of the `frame_len / 2` bins below the Nyquist frequency of a frame.
+    pub fn frame(&self, frame: usize) -> Vec<f32> {
+        self.magnitudes(frame)
+            .map(|magnitude| to_dbfs(magnitude) as f32)
+            .collect()
+    }
+
+    // The levels of the average power of each bin over all frames, like the spectrum the frontend
+    // shows with Welch's method.
+    pub fn average(&self) -> Vec<f32> {
+        let frames = self.frames();
+        let mut powers = vec![0.0; self.frame_len / 2];
+        for frame in 0..frames {
+            for (power, magnitude) in powers.iter_mut().zip(self.magnitudes(frame)) {
+                *power += magnitude * magnitude / frames as f64;
+            }
+        }
+        powers
+            .into_iter()
+            .map(|power| to_dbfs(power.sqrt()) as f32)
+            .collect()
+    }
+
+    fn magnitudes(&self, frame: usize) -> impl Iterator<Item = f64> {
+        let offset = frame * self.hop();
+        let mut buffer: Vec<_> = self
+            .window
+            .iter()
+            .enumerate()
+            .map(|(n, w)| {
+                let x: f64 = self
+                    .channels
+                    .iter()
+                    .map(|channel| f64::from(channel[offset + n]))
+                    .sum();
+                Complex::from(x * w)
+            })
+            .collect();
+        self.fft.process(&mut buffer);
+        let scale = self.scale;
+        buffer.truncate(self.frame_len / 2);
+        buffer.into_iter().map(move |c| c.norm() * scale)
+    }
+}
+
+pub fn series_path(series_dir: &Path, name: &str) -> PathBuf {
+    series_dir.join(format!("{name}.bin"))
+}
+
+fn write_series(series_dir: &Path, name: &str, levels: &[f32]) -> io::Result<()> {
+    fs::create_dir_all(series_dir)?;
+    let bytes: Vec<u8> = levels
+        .iter()
+        .flat_map(|level| level.to_le_bytes())
+        .collect();
+    fs::write(series_path(series_dir, name), bytes)
+}
+
+// Splits `levels` into at most `max_points` buckets of consecutive levels, returning the number of
+// levels in each, the last of which may hold fewer, and the lowest and highest level of each.
+pub fn downsample(levels: &[f32], max_points: usize) -> (usize, Vec<f32>, Vec<f32>) {
+    let bucket = levels.len().div_ceil(max_points.max(1)).max(1);
+    let (min, max) = levels
+        .chunks(bucket)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &level| {
+                    (min.min(level), max.max(level))
+                })
+        })
+        .unzip();
+    (bucket, min, max)
+}
+
+pub fn tile_path(tile_dir: &Path, zoom: usize, tile: usize) -> PathBuf {
+    tile_dir.join(zoom.to_string()).join(format!("{tile}.bin"))
+}
+
+// Written under another name first, so that a tile is never served half written.
+fn write_tile(tile_dir: &Path, zoom: usize, tile: usize, levels: &[f32]) -> io::Result<()> {
+    let path = tile_path(tile_dir, zoom, tile);
+    let partial = path.with_extension("bin.partial");
+    let bytes: Vec<u8> = levels
+        .iter()
+        .flat_map(|level| level.to_le_bytes())
+        .collect();
+    fs::write(&partial, bytes)?;
+    fs::rename(partial, path)
+}
+
+// The samples of each channel of a WAV file relative to full scale, and its sample rate.
+pub fn decode(path: &Path) -> Result<(Vec<Vec<f32>>, u32), hound::Error> {
+    let file_len = fs::metadata(path)?.len();
+    let mut reader = WavReader::open(path)?;
+    let spec = reader.spec();
+    let num_channels = usize::from(spec.channels);
+    if spec.sample_rate == 0 || spec.sample_rate > MAX_SAMPLE_RATE {
+        return Err(hound::Error::FormatError("invalid sample rate"));
+    }
+    if num_channels == 0 || num_channels > MAX_CHANNELS {
+        return Err(hound::Error::FormatError("invalid channel count"));
+    }
+    // The declared size of the sample data is checked against what is actually there, as it is
+    // used to reserve memory for the samples.
+    let declared_bytes = u64::from(reader.len()) * u64::from(spec.bits_per_sample).div_ceil(8);
+    if declared_bytes > file_len {
+        return Err(hound::Error::FormatError("data chunk exceeds the file"));
+    }
+
+    let frames = reader.duration() as usize;
+    let mut channels = vec![Vec::with_capacity(frames); num_channels];
+    let full_scale = 2_f32.powi(i32::from(spec.bits_per_sample) - 1);
+    match spec.sample_format {
+        SampleFormat::Float => {
+            for (n, sample) in reader.samples::<f32>().enumerate() {
+                channels[n % num_channels].push(sample?);
+            }
+        }
+        SampleFormat::Int => {
+            for (n, sample) in reader.samples::<i32>().enumerate() {
+                channels[n % num_channels].push(sample? as f32 / full_scale);
+            }
+        }
+    }
+    // A truncated last frame is left out.
+    let len = channels.iter().map(Vec::len).min().unwrap_or(0);
+    channels
+        .iter_mut()
+        .for_each(|channel| channel.truncate(len));
+    Ok((channels, spec.sample_rate))
+}
+
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&self, samples: &mut [f64]) {
+        let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+        for sample in samples {
+            let x = *sample;
+            let y =
+                self.b[0] * x + self.b[1] * x1 + self.b[2] * x2 - self.a[0] * y1 - self.a[1] * y2;
+            (x2, x1, y2, y1) = (x1, x, y1, y);
+            *sample = y;
+        }
+    }
+}
+
+// The two stages of the K-weighting pre-filter, a high shelf modelling the acoustic effect of the
+// head followed by a high-pass. The analog prototypes are fitted to the 48 kHz coefficients given
+// in ITU-R BS.1770-4, so that they can be derived for any sample rate.
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let bilinear = |frequency: f64, q: f64| {
+        let k = (PI * frequency / f64::from(sample_rate)).tan();
+        (k, q, 1.0 + k / q + k * k)
+    };
+
+    let (k, q, a0) = bilinear(1681.974450955533, 0.7071752369554196);
+    let vh = 10_f64.powf(3.999843853973347 / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    };
+
+    let (k, q, a0) = bilinear(38.13547087602444, 0.5003270373238773);
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    };
+
+    [shelf, high_pass]
+}
+
+fn step_len(sample_rate: u32) -> usize {
+    ((STEP_SECONDS * f64::from(sample_rate)) as usize).max(1)
+}
+
+// The K-weighted power of overlapping 400 ms blocks according to ITU-R BS.1770-4, with all channels
+// weighted equally as only mono and stereo signals are supported. Samples are relative to full
+// scale, and blocks only cover the length of the shortest channel.
+pub fn block_powers<S: Copy + Into<f64>>(
+    channels: &[impl AsRef<[S]>],
+    sample_rate: u32,
+) -> Vec<f64> {
+    let block_len = (BLOCK_SECONDS * f64::from(sample_rate)) as usize;
+    let step = step_len(sample_rate);
+    let len = channels
+        .iter()
+        .map(|channel| channel.as_ref().len())
+        .min()
+        .unwrap_or_default();
+    if block_len == 0 || len < block_len {
+        return Vec::new();
+    }
+
+    let mut powers = vec![0.0; (len - block_len) / step + 1];
+    for channel in channels {
+        let mut weighted: Vec<f64> = channel.as_ref()[..len].iter().map(|&x| x.into()).collect();
+        for stage in k_weighting(sample_rate) {
+            stage.process(&mut weighted);
+        }
+        let mut sums = Vec::with_capacity(len + 1);
+        sums.push(0.0);
+        for x in weighted {
+            sums.push(sums[sums.len() - 1] + x * x);
+        }
+        for (n, power) in powers.iter_mut().enumerate() {
+            let start = n * step;
+            *power += (sums[start + block_len] - sums[start]).max(0.0) / block_len as f64;
+        }
+    }
+    powers
+}
+
+// Gated loudness of blocks according to ITU-R BS.1770-4.
+pub fn gated_loudness(powers: &[f64]) -> Option<f64> {
+    let gated_mean = |threshold: f64| {
+        let gated: Vec<f64> = powers
+            .iter()
+            .copied()
+            .filter(|&power| to_lufs(power) > threshold)
+            .collect();
+        (!gated.is_empty()).then(|| gated.iter().sum::<f64>() / gated.len() as f64)
+    };
+
+    let relative_gate = to_lufs(gated_mean(ABSOLUTE_GATE_LUFS)?) + RELATIVE_GATE_LU;
+    gated_mean(relative_gate.max(ABSOLUTE_GATE_LUFS)).map(to_lufs)
+}
+
+// The RMS level of consecutive windows of `window` samples, with the power of all channels
+// averaged.
+fn rms_levels(channels: &[Vec<f32>], window: usize) -> Vec<f32> {
+    let len = channels.first().map_or(0, Vec::len);
+    (0..len / window)
+        .map(|n| {
+            let power = channels
+                .iter()
+                .flat_map(|channel| &channel[n * window..(n + 1) * window])
+                .map(|&x| f64::from(x) * f64::from(x))
+                .sum::<f64>()
+                / (window * channels.len()) as f64;
+            to_dbfs(power.sqrt()) as f32
+        })
+        .collect()
+}
+
+pub fn to_lufs(power: f64) -> f64 {
+    -0.691 + 10.0 * power.max(1e-20).log10()
+}
+
+// Peak of the waveform reconstructed between the samples, estimated by oversampling four times as
+// described in ITU-R BS.1770-4 Annex 2.
+pub fn true_peak_dbtp<S: Copy + Into<f64>>(samples: &[S]) -> f64 {
+    to_dbfs(true_peaks(samples).into_iter().fold(0.0, f64::max))
+}
+
+// Magnitude of each sample, or of the waveform between it and the next one if that is larger,
+// relative to full scale.
+pub fn true_peaks<S: Copy + Into<f64>>(samples: &[S]) -> Vec<f64> {
+    let phases: Vec<Vec<f64>> = (1..OVERSAMPLING)
+        .map(|phase| interpolation_taps(phase as f64 / OVERSAMPLING as f64))
+        .collect();
+
+    (0..samples.len())
+        .map(|n| {
+            phases
+                .iter()
+                .map(|taps| {
+                    taps.iter()
+                        .zip(-INTERPOLATION_HALF_TAPS + 1..)
+                        .filter_map(|(tap, offset)| {
+                            let index = usize::try_from(n as i64 + offset).ok()?;
+                            samples.get(index).map(|&x| tap * Into::<f64>::into(x))
+                        })
+                        .sum::<f64>()
+                        .abs()
+                })
+                .fold(Into::<f64>::into(samples[n]).abs(), f64::max)
+        })
+        .collect()
+}
+
+// Blackman-windowed sinc taps for interpolating at `fraction` of a sample period after a sample,
+// normalized to unity gain at DC.
+fn interpolation_taps(fraction: f64) -> Vec<f64> {
+    let half_width = INTERPOLATION_HALF_TAPS as f64;
+    let taps: Vec<f64> = (-INTERPOLATION_HALF_TAPS + 1..=INTERPOLATION_HALF_TAPS)
+        .map(|offset| {
+            let t = offset as f64 - fraction;
+            let sinc = if t == 0.0 {
+                1.0
+            } else {
+                (PI * t).sin() / (PI * t)
+            };
+            let window =
+                0.42 + 0.5 * (PI * t / half_width).cos() + 0.08 * (2.0 * PI * t / half_width).cos();
+            sinc * window
+        })
+        .collect();
+    let sum = taps.iter().sum::<f64>();
+    taps.into_iter().map(|tap| tap / sum).collect()
+}
+
+// Writes the Hann-windowed spectra of the channels mixed to mono to the tiles of level 0,
+// reporting the number of tiles written so far and in total to `on_tiles`, and returns the number
+// of frames.
+fn write_spectrogram(
+    channels: &[Vec<f32>],
+    tile_dir: &Path,
+    mut on_tiles: impl FnMut(usize, usize),
+) -> io::Result<usize> {
+    let spectra = Spectra::new(channels, FRAME_LEN);
+    let frames = spectra.frames();
+    let tiles = frames.div_ceil(TILE_FRAMES);
+
+    fs::create_dir_all(tile_dir.join("0"))?;
+    for tile in 0..tiles {
+        let mut levels = Vec::with_capacity(TILE_FRAMES * FRAME_LEN / 2);
+        for frame in tile * TILE_FRAMES..((tile + 1) * TILE_FRAMES).min(frames) {
+            levels.extend(spectra.frame(frame));
+        }
+        write_tile(tile_dir, 0, tile, &levels)?;
+        on_tiles(tile + 1, tiles);
+    }
+    Ok(frames)
+}
+
+// Writes the levels above level 0 from the tiles below them, reporting each level written and the
+// number of levels in all to `on_zoom`, and returns the number of levels in all.
+fn write_pyramid(
+    tile_dir: &Path,
+    frames: usize,
+    mut on_zoom: impl FnMut(usize, usize),
+) -> io::Result<usize> {
+    let bins = FRAME_LEN / 2;
+    let mut zooms = 1;
+    while frames_at_zoom(frames, zooms - 1) > TILE_FRAMES {
+        zooms += 1;
+    }
+
+    for zoom in 1..zooms {
+        fs::create_dir_all(tile_dir.join(zoom.to_string()))?;
+        for tile in 0..frames_at_zoom(frames, zoom).div_ceil(TILE_FRAMES) {
+            // The two tiles below, the second of which is missing at the end of an odd number.
+            let mut below = fs::read(tile_path(tile_dir, zoom - 1, 2 * tile))?;
+            if let Ok(next) = fs::read(tile_path(tile_dir, zoom - 1, 2 * tile + 1)) {
+                below.extend(next);
+            }
+            let below: Vec<f32> = below
+                .chunks_exact(4)
+                .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .collect();
+
+            let levels: Vec<f32> = below
+                .chunks(2 * bins)
+                .flat_map(|pair| {
+                    let (first, second) = pair.split_at(bins.min(pair.len()));
+                    (0..bins).map(move |bin| match second.get(bin) {
+                        Some(&level) => average_power(first[bin], level),
+                        None => first[bin],
+                    })
+                })
+                .collect();
+            write_tile(tile_dir, zoom, tile, &levels)?;
+        }
+        on_zoom(zoom, zooms);
+    }
+    Ok(zooms)
+}
+
+// The number of frames in a level of the pyramid.
+fn frames_at_zoom(frames: usize, zoom: usize) -> usize {
+    (0..zoom).fold(frames, |frames, _| frames.div_ceil(2))
+}
+
+// The level of the average power of two levels in dB.
+fn average_power(a: f32, b: f32) -> f32 {
+    let power = |level: f32| 10_f64.powf(f64::from(level) / 10.0);
+    (10.0 * ((power(a) + power(b)) / 2.0).log10()) as f32
+}
+
+fn to_dbfs(value: f64) -> f64 {
+    (20.0 * value.log10()).max(FLOOR_DB)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    #[test]
+    fn full_scale_sine_is_measured() {
+        // A 997 Hz sine at full scale reads -3.01 LUFS per channel, and so 0 LUFS in stereo.
+        const SAMPLE_RATE: u32 = 48000;
+        let sine: Vec<f32> = (0..SAMPLE_RATE * 2)
+            .map(|n| (2.0 * PI * 997.0 * f64::from(n) / f64::from(SAMPLE_RATE)).sin() as f32)
+            .collect();
+
+        let integrated_loudness = |channels: &[Vec<f32>]| {
+            super::gated_loudness(&super::block_powers(channels, SAMPLE_RATE))
+        };
+        let mono = integrated_loudness(std::slice::from_ref(&sine)).unwrap_or_default();
+        assert!((mono + 3.01).abs() < 0.05, "{mono}");
+        let stereo = integrated_loudness(&[sine.clone(), sine.clone()]);
+        assert!(stereo.unwrap_or_default().abs() < 0.05, "{stereo:?}");
+        assert!(super::true_peak_dbtp(&sine).abs() < 0.1);
+    }
+
+    #[test]
+    fn truncated_file_with_oversized_header_is_rejected() {
+        // A header declaring close to 4 GiB of 32-bit samples, followed by a few bytes of data.
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&u32::MAX.to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16_u32.to_le_bytes());
+        wav.extend_from_slice(&1_u16.to_le_bytes());
+        wav.extend_from_slice(&1_u16.to_le_bytes());
+        wav.extend_from_slice(&48000_u32.to_le_bytes());
+        wav.extend_from_slice(&(48000_u32 * 4).to_le_bytes());
+        wav.extend_from_slice(&4_u16.to_le_bytes());
+        wav.extend_from_slice(&32_u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&0xffff_fff0_u32.to_le_bytes());
+        wav.extend_from_slice(&[0; 16]);
+
+        let path = std::env::temp_dir().join(format!("truncated-test-{}.wav", std::process::id()));
+        std::fs::write(&path, wav).expect("should write file");
+        let decoded = super::decode(&path);
+        std::fs::remove_file(&path).ok();
+        assert!(
+            matches!(
+                decoded,
+                Err(hound::Error::FormatError("data chunk exceeds the file"))
+            ),
+            "{decoded:?}"
+        );
+    }
+
+    #[test]
+    fn pyramid_averages_power() {
+        use super::{tile_path, write_pyramid, write_tile, FRAME_LEN, TILE_FRAMES};
+
+        let tile_dir = std::env::temp_dir().join(format!("pyramid-test-{}", std::process::id()));
+        std::fs::create_dir_all(tile_dir.join("0")).expect("should create directory");
+        // Frames alternating between 0 dB and -120 dB, so that pairs average to -3 dB.
+        let frames = 3 * TILE_FRAMES;
+        for tile in 0..3 {
+            let levels: Vec<f32> = (0..TILE_FRAMES)
+                .flat_map(|frame| [-120.0 * (frame % 2) as f32; FRAME_LEN / 2])
+                .collect();
+            write_tile(&tile_dir, 0, tile, &levels).expect("should write tile");
+        }
+
+        let zooms = write_pyramid(&tile_dir, frames, |_, _| {}).expect("should write pyramid");
+        assert_eq!(zooms, 3);
+        let read = |zoom, tile| std::fs::read(tile_path(&tile_dir, zoom, tile)).ok();
+        let top = read(2, 0).expect("should have a top tile");
+        assert_eq!(top.len(), frames / 4 * FRAME_LEN / 2 * 4);
+        let level = f32::from_le_bytes([top[0], top[1], top[2], top[3]]);
+        assert!((level + 3.01).abs() < 0.01, "{level}");
+        assert!(read(1, 1).is_some() && read(1, 2).is_none());
+        let _ = std::fs::remove_dir_all(tile_dir);
+    }
+
+    #[test]
+    fn downsampling_keeps_extremes() {
+        let levels: Vec<f32> = (0..10).map(|n| n as f32).collect();
+        let (bucket, min, max) = super::downsample(&levels, 4);
+        assert_eq!(bucket, 3);
+        assert_eq!(min, [0.0, 3.0, 6.0, 9.0]);
+        assert_eq!(max, [2.0, 5.0, 8.0, 9.0]);
+        assert_eq!(super::downsample(&levels, 20).0, 1);
+    }
+}
@@ -0,0 +1,125 @@
+use rocket::{get, http::Status, routes, serde::json::Json, Route, State};
+use serde::Serialize;
+use sqlx::{sqlite::SqlitePool, FromRow};
+
+use crate::auth::{Admin, User};
+
+// Entries returned unless asked for fewer or more, and the most ever returned.
+const DEFAULT_LIMIT: u32 = 100;
+const MAX_LIMIT: u32 = 1000;
+
+// A record of who did what to which uploaded file and when, kept in the SQLite database and never
+// changed once written.
+#[derive(Clone)]
+pub struct AuditLog {
+    pool: SqlitePool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Upload,
+    // The analysis of an upload finished, whether or not it succeeded.
+    Analyse,
+    // The analysis of an upload cut off by the last shutdown was started again.
+    Resume,
+    Delete,
+}
+
+impl Action {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Upload => "upload",
+            Self::Analyse => "analyse",
+            Self::Resume => "resume",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+#[derive(Serialize, FromRow)]
+struct Entry {
+    id: i64,
+    // ISO 8601, in UTC.
+    at: String,
+    // The user who made the request.
+    actor: String,
+    action: String,
+    job: i64,
+    file_name: Option<String>,
+    // What came of it, such as why an analysis failed.
+    detail: Option<String>,
+}
+
+impl AuditLog {
+    pub async fn new(pool: SqlitePool) -> Result<Self, sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS audit (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                at TEXT NOT NULL,
+                actor TEXT NOT NULL,
+                action TEXT NOT NULL,
+                job INTEGER NOT NULL,
+                file_name TEXT,
+                detail TEXT
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self { pool })
+    }
+
+    pub fn routes() -> Vec<Route> {
+        routes![entries]
+    }
+
+    pub async fn record(
+        &self,
+        actor: &User,
+        action: Action,
+        job: u64,
+        file_name: Option<&str>,
+        detail: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO audit (at, actor, action, job, file_name, detail)
+             VALUES (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'), ?, ?, ?, ?, ?)",
+        )
+        .bind(&actor.0)
+        .bind(action.name())
+        .bind(job as i64)
+        .bind(file_name)
+        .bind(detail)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+// The audit trail, the most recent first, optionally only of one job, actor or action.
+#[get("/audit?<job>&<actor>&<action>&<limit>")]
+async fn entries(
+    audit_log: &State<AuditLog>,
+    _admin: Admin,
+    job: Option<i64>,
+    actor: Option<&str>,
+    action: Option<&str>,
+    limit: Option<u32>,
+) -> Result<Json<Vec<Entry>>, Status> {
+    sqlx::query_as(
+        "SELECT id, at, actor, action, job, file_name, detail FROM audit
+         WHERE (?1 IS NULL OR job = ?1) AND (?2 IS NULL OR actor = ?2)
+             AND (?3 IS NULL OR action = ?3)
+         ORDER BY id DESC LIMIT ?4",
+    )
+    .bind(job)
+    .bind(actor)
+    .bind(action)
+    .bind(limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT))
+    .fetch_all(&audit_log.pool)
+    .await
+    .map(Json)
+    .map_err(|error| {
+        eprintln!("failed to read audit trail: {error}");
+        Status::InternalServerError
+    })
+}
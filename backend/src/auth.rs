@@ -0,0 +1,277 @@
+use rocket::{
+    fairing::AdHoc,
+    get,
+    http::{uri::Origin, RawStr, Status},
+    request::{FromRequest, Outcome, Request},
+    routes,
+    serde::json::Json,
+    Route, State,
+};
+use serde::Serialize;
+
+// The tokens granting access to the API, each with the name of the user it belongs to, and the
+// names of the users allowed to administer the backend. With no tokens configured, requests are not
+// authenticated, which suits running the tool locally.
+pub struct Tokens {
+    users: Vec<(String, String)>,
+    admins: Vec<String>,
+}
+
+// The user making a request, by the name their token is configured with, or by the address of the
+// client when requests are not authenticated. Routes under `/api` take it as a guard, failing with
+// 401 Unauthorized without a valid token.
+#[derive(Debug, Clone)]
+pub struct User(pub String);
+
+// A user allowed to administer the backend, as a guard failing with 403 Forbidden for others. With
+// no administrators named, every user is one.
+#[derive(Debug, Clone)]
+pub struct Admin(pub User);
+
+// A user following an event stream. Event sources cannot send headers, so for these routes only
+// the token may also be given in the `access_token` query parameter.
+#[derive(Debug, Clone)]
+pub struct EventStreamUser(pub User);
+
+// The `access_token` query parameter of a request, which is taken out of its URI before the
+// request is logged.
+struct QueryToken(Option<String>);
+
+#[derive(Serialize)]
+struct Identity {
+    name: String,
+    authenticated: bool,
+}
+
+impl Tokens {
+    // Parses comma-separated `name=token` pairs.
+    pub fn parse(config: &str) -> Result<Self, String> {
+        config
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((name, token)) if !name.is_empty() && !token.is_empty() => {
+                    Ok((name.to_owned(), token.to_owned()))
+                }
+                _ => Err(format!("expected a `name=token` pair, got {pair:?}")),
+            })
+            .collect::<Result<_, _>>()
+            .map(|users| Self {
+                users,
+                admins: Vec::new(),
+            })
+    }
+
+    // Limits administration to the users of the comma-separated names.
+    pub fn with_admins(self, names: &str) -> Self {
+        let admins = names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_owned)
+            .collect();
+        Self { admins, ..self }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.users.is_empty()
+    }
+
+    fn is_admin(&self, user: &User) -> bool {
+        self.admins.is_empty() || self.admins.contains(&user.0)
+    }
+
+    pub fn routes() -> Vec<Route> {
+        routes![whoami]
+    }
+
+    // Takes the token out of the query of every request, so that it does not end up in the logs,
+    // and keeps it for the guard of event streams.
+    pub fn fairing() -> AdHoc {
+        AdHoc::on_request("Strip access tokens", |request, _| {
+            Box::pin(async move {
+                let Some(query) = request.uri().query() else {
+                    return;
+                };
+                let mut token = None;
+                let mut rest = Vec::new();
+                for pair in query.as_str().split('&') {
+                    match pair.strip_prefix("access_token=") {
+                        Some(value) => {
+                            token = RawStr::new(value).url_decode().ok().map(String::from)
+                        }
+                        None => rest.push(pair),
+                    }
+                }
+                if token.is_none() {
+                    return;
+                }
+
+                let path = request.uri().path().as_str().to_owned();
+                let uri = if rest.is_empty() {
+                    path
+                } else {
+                    format!("{path}?{}", rest.join("&"))
+                };
+                if let Ok(uri) = Origin::parse_owned(uri) {
+                    request.set_uri(uri);
+                }
+                request.local_cache(|| QueryToken(token));
+            })
+        })
+    }
+
+    // Every token is compared in full, so that how long it takes gives nothing away.
+    fn user(&self, token: &str) -> Option<&str> {
+        self.users.iter().fold(None, |user, (name, expected)| {
+            let matches = expected.len() == token.len()
+                && expected
+                    .bytes()
+                    .zip(token.bytes())
+                    .fold(0, |difference, (a, b)| difference | (a ^ b))
+                    == 0;
+            if matches {
+                Some(name.as_str())
+            } else {
+                user
+            }
+        })
+    }
+}
+
+impl User {
+    // Authenticates the request by the token in its `Authorization` header, or also by that in its
+    // query if `query_token` is set.
+    fn authenticate(request: &Request<'_>, query_token: bool) -> Outcome<Self, ()> {
+        let tokens = match request.rocket().state::<Tokens>() {
+            Some(tokens) if tokens.is_enabled() => tokens,
+            _ => {
+                let address = request
+                    .client_ip()
+                    .map_or_else(|| "unknown".to_owned(), |ip| ip.to_string());
+                return Outcome::Success(Self(address));
+            }
+        };
+
+        let token = request
+            .headers()
+            .get_one("Authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .or_else(|| {
+                let QueryToken(token) = request.local_cache(|| QueryToken(None));
+                token.as_deref().filter(|_| query_token)
+            });
+        match token.and_then(|token| tokens.user(token)) {
+            Some(name) => Outcome::Success(Self(name.to_owned())),
+            None => Outcome::Failure((Status::Unauthorized, ())),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for User {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Self::authenticate(request, false)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for EventStreamUser {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        User::authenticate(request, true).map(Self)
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Admin {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let user = match request.guard::<User>().await {
+            Outcome::Success(user) => user,
+            Outcome::Failure(failure) => return Outcome::Failure(failure),
+            Outcome::Forward(forward) => return Outcome::Forward(forward),
+        };
+        match request.rocket().state::<Tokens>() {
+            Some(tokens) if !tokens.is_admin(&user) => Outcome::Failure((Status::Forbidden, ())),
+            _ => Outcome::Success(Self(user)),
+        }
+    }
+}
+
+// Who the token in the request belongs to, for the frontend to tell whether it needs to log in.
+#[get("/whoami")]
+fn whoami(tokens: &State<Tokens>, user: User) -> Json<Identity> {
+    Json(Identity {
+        name: user.0,
+        authenticated: tokens.is_enabled(),
+    })
+}
+
+// Public like the modules of the routes, so that the URI macros generated for the test routes are
+// not reported as unused imports.
+#[cfg(test)]
+pub mod tests {
+    use rocket::{
+        get,
+        http::{uri::Origin, Header, Status},
+        local::blocking::Client,
+        routes,
+    };
+
+    #[get("/plain")]
+    fn plain(user: super::User) -> String {
+        user.0
+    }
+
+    #[get("/stream?<other>")]
+    fn stream(user: super::EventStreamUser, uri: &Origin<'_>, other: u32) -> String {
+        format!("{} {uri} {other}", user.0 .0)
+    }
+
+    #[test]
+    fn tokens_are_parsed_and_matched() {
+        let tokens = super::Tokens::parse("alice=secret, bob=hunter2,").expect("should parse");
+        assert_eq!(tokens.user("hunter2"), Some("bob"));
+        assert_eq!(tokens.user("secret"), Some("alice"));
+        assert_eq!(tokens.user("secre"), None);
+        assert!(super::Tokens::parse("alice").is_err());
+        assert!(!super::Tokens::parse("").expect("should parse").is_enabled());
+
+        let bob = super::User("bob".to_owned());
+        assert!(tokens.is_admin(&bob));
+        assert!(!tokens.with_admins("alice, carol").is_admin(&bob));
+    }
+
+    #[test]
+    fn query_tokens_are_only_taken_by_event_streams() {
+        let tokens = super::Tokens::parse("alice=secret").expect("should parse");
+        let rocket = rocket::build()
+            .manage(tokens)
+            .attach(super::Tokens::fairing())
+            .mount("/", routes![plain, stream]);
+        let client = Client::untracked(rocket).expect("should launch");
+
+        let response = client.get("/plain?access_token=secret").dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        let response = client
+            .get("/plain")
+            .header(Header::new("Authorization", "Bearer secret"))
+            .dispatch();
+        assert_eq!(response.into_string().as_deref(), Some("alice"));
+
+        // The token is gone from the URI by the time the request is routed and logged.
+        let response = client.get("/stream?other=1&access_token=secret").dispatch();
+        assert_eq!(
+            response.into_string().as_deref(),
+            Some("alice /stream?other=1 1")
+        );
+        let response = client.get("/stream?other=1&access_token=wrong").dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+    }
+}
@@ -0,0 +1,12 @@
+use std::path::Path;
+
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool};
+
+// The SQLite database shared by sessions and the audit trail, created if missing. Each of them
+// creates its own tables.
+pub async fn open(path: &Path) -> Result<SqlitePool, sqlx::Error> {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true);
+    SqlitePool::connect_with(options).await
+}
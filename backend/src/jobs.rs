@@ -0,0 +1,802 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    fs,
+    io::{self, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+
+use rocket::{
+    data::{Data, Limits, ToByteUnit},
+    delete,
+    fs::NamedFile,
+    get,
+    http::Status,
+    post,
+    response::stream::{self, EventStream},
+    routes,
+    serde::json::{self, Json},
+    tokio::{
+        fs::File,
+        io::{AsyncReadExt, AsyncSeekExt},
+        select,
+        sync::broadcast::{self, error::RecvError},
+        task, time,
+    },
+    Route, Shutdown, State,
+};
+use serde::{Deserialize, Serialize};
+
+use signal_inspector_analysis::{self as analysis, Analysis};
+
+use crate::{
+    audit::{Action, AuditLog},
+    auth::{Admin, EventStreamUser, User},
+    negotiate::{csv_field, Negotiated, ToCsv},
+    retention::{Retention, Upload},
+};
+
+const UPLOAD_FILE_NAME: &str = "upload.wav";
+const RECORD_FILE_NAME: &str = "job.json";
+const TILE_DIR_NAME: &str = "tiles";
+const SERIES_DIR_NAME: &str = "series";
+// Points returned from a series unless asked for fewer or more, and the most ever returned.
+const DEFAULT_MAX_POINTS: usize = 1000;
+const MAX_POINTS: usize = 100_000;
+// Events kept for subscribers that fall behind. There is about one per tile, and a subscriber that
+// misses some can tell from the next state which tiles have been written.
+const EVENT_CAPACITY: usize = 256;
+// The actor recorded in the audit trail for uploads deleted by the retention policy on its own.
+pub const RETENTION_ACTOR: &str = "retention";
+// How many times an analysis is started before it is failed instead of resumed, as one that keeps
+// being cut off may be what takes the server down.
+const MAX_ATTEMPTS: u32 = 2;
+
+// Uploaded files and the analyses run on them in the background, each in a directory named by the
+// ID of its job.
+pub struct Jobs {
+    dir: PathBuf,
+    next_id: AtomicU64,
+    entries: Arc<Mutex<HashMap<u64, Job>>>,
+    retention: Retention,
+    // When each job was last requested in this run. Jobs of earlier runs not requested since count
+    // as last used when they were uploaded.
+    used: Mutex<HashMap<u64, SystemTime>>,
+    // Set once the server is shutting down, after which no more uploads are taken or analysed.
+    draining: AtomicBool,
+    // The number of analyses running, up to and including recording their results.
+    active: Arc<AtomicUsize>,
+}
+
+// What is kept of a job along with its files, written once the file is stored and again once the
+// analysis has finished. A job recorded as running was interrupted, and is analysed again on the
+// next start.
+#[derive(Serialize, Deserialize)]
+struct JobRecord {
+    name: Option<String>,
+    // The user who uploaded the file.
+    actor: String,
+    state: JobState,
+    // The number of times the analysis has been started, counted before it runs.
+    #[serde(default)]
+    attempts: u32,
+}
+
+struct Job {
+    // The name of the uploaded file, if the client gave one.
+    name: Option<String>,
+    state: JobState,
+    sender: broadcast::Sender<JobEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum JobState {
+    // The share of the analysis done so far, and the number of spectrogram tiles written, which
+    // can be fetched before the job is done.
+    Running { progress: f64, tiles: usize },
+    Done { analysis: Analysis },
+    Failed { error: String },
+}
+
+#[derive(Debug, Clone)]
+enum JobEvent {
+    State(JobState),
+    // The index of a spectrogram tile just written.
+    Tile(usize),
+}
+
+#[derive(Serialize)]
+struct Created {
+    id: u64,
+}
+
+// The uploads deleted by a run of the retention policy.
+#[derive(Debug, Default, Serialize)]
+pub struct Cleanup {
+    pub deleted: Vec<u64>,
+    pub freed_bytes: u64,
+}
+
+// Part of a series, downsampled to the lowest and highest level of buckets of consecutive points.
+#[derive(Serialize)]
+struct SeriesPage {
+    // The time of the first point of the first bucket, and the time between buckets.
+    start_seconds: f64,
+    step_seconds: f64,
+    min: Vec<f32>,
+    max: Vec<f32>,
+}
+
+impl ToCsv for JobState {
+    // A row for each field, with the same names as in JSON, and one for the name of each series.
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("field,value\n");
+        let mut row = |field: &str, value: &dyn std::fmt::Display| {
+            let _ = writeln!(csv, "{field},{}", csv_field(&value.to_string()));
+        };
+        match self {
+            Self::Running { progress, tiles } => {
+                row("state", &"running");
+                row("progress", progress);
+                row("tiles", tiles);
+            }
+            Self::Done { analysis } => {
+                row("state", &"done");
+                row("sample_rate", &analysis.sample_rate);
+                row("channels", &analysis.channels);
+                row("duration_seconds", &analysis.duration_seconds);
+                // Missing values are left empty, for spreadsheets to read as such.
+                let integrated = analysis
+                    .integrated_lufs
+                    .map_or_else(String::new, |l| l.to_string());
+                row("integrated_lufs", &integrated);
+                row("true_peak_dbtp", &analysis.true_peak_dbtp);
+                for series in &analysis.series {
+                    row("series", &series.name);
+                }
+            }
+            Self::Failed { error } => {
+                row("state", &"failed");
+                row("error", error);
+            }
+        }
+        csv
+    }
+}
+
+impl ToCsv for SeriesPage {
+    // A row for each bucket, with its time and its lowest and highest level.
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("seconds,min,max\n");
+        for (n, (min, max)) in self.min.iter().zip(&self.max).enumerate() {
+            let seconds = self.start_seconds + n as f64 * self.step_seconds;
+            let _ = writeln!(csv, "{seconds},{min},{max}");
+        }
+        csv
+    }
+}
+
+impl Jobs {
+    pub fn new(dir: impl Into<PathBuf>, retention: Retention) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        // IDs continue after those of earlier runs, whose files are left in place.
+        let ids: Vec<u64> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .collect();
+        let last_id = ids.iter().copied().max().unwrap_or(0);
+
+        // Jobs of earlier runs are restored from their records, those without one having been
+        // stored before records were kept or not stored in full.
+        let mut entries = HashMap::new();
+        for id in ids {
+            let Some(record) = read_record(&dir.join(id.to_string())) else {
+                continue;
+            };
+            let job = Job {
+                name: record.name,
+                state: record.state,
+                sender: broadcast::channel(EVENT_CAPACITY).0,
+            };
+            entries.insert(id, job);
+        }
+
+        Ok(Self {
+            dir,
+            next_id: AtomicU64::new(last_id + 1),
+            entries: Arc::new(Mutex::new(entries)),
+            retention,
+            used: Mutex::default(),
+            draining: AtomicBool::new(false),
+            active: Arc::default(),
+        })
+    }
+
+    // Analyses again the jobs interrupted by the last shutdown, recording each in the audit trail.
+    // Those already started `MAX_ATTEMPTS` times are failed instead.
+    pub async fn resume(&self, audit_log: &AuditLog) {
+        let interrupted: Vec<(u64, Option<String>)> = match self.entries.lock() {
+            Ok(entries) => entries
+                .iter()
+                .filter(|(_, job)| matches!(job.state, JobState::Running { .. }))
+                .map(|(&id, job)| (id, job.name.clone()))
+                .collect(),
+            Err(_) => return,
+        };
+        for (id, name) in interrupted {
+            let dir = self.job_dir(id);
+            let (actor, attempts) = read_record(&dir)
+                .map_or_else(Default::default, |record| (record.actor, record.attempts));
+            let user = User(actor);
+            if attempts < MAX_ATTEMPTS {
+                let recorded = audit_log
+                    .record(&user, Action::Resume, id, name.as_deref(), None)
+                    .await;
+                if let Err(error) = recorded {
+                    eprintln!("failed to record resuming analysis {id}: {error}");
+                }
+                self.start(id, name, user, attempts, audit_log.clone());
+                continue;
+            }
+
+            let error = format!("the analysis was cut off {attempts} times");
+            let state = JobState::Failed {
+                error: error.clone(),
+            };
+            let record = JobRecord {
+                name: name.clone(),
+                actor: user.0.clone(),
+                state: state.clone(),
+                attempts,
+            };
+            if let Err(error) = write_record(&dir, &record) {
+                eprintln!("failed to record the result of job {id}: {error}");
+            }
+            set_state(&self.entries, id, state);
+            let recorded = audit_log
+                .record(&user, Action::Analyse, id, name.as_deref(), Some(&error))
+                .await;
+            if let Err(error) = recorded {
+                eprintln!("failed to record analysis {id}: {error}");
+            }
+        }
+    }
+
+    // Stops taking uploads and waits for the running analyses to finish, for at most `timeout`.
+    // Those still running after it are analysed again on the next start.
+    pub async fn drain(&self, timeout: Duration) {
+        self.draining.store(true, Ordering::SeqCst);
+        let waiting = async {
+            while self.active.load(Ordering::SeqCst) > 0 {
+                time::sleep(Duration::from_millis(100)).await;
+            }
+        };
+        if time::timeout(timeout, waiting).await.is_err() {
+            eprintln!(
+                "{} analyses did not finish before shutting down, and will be resumed on start",
+                self.active.load(Ordering::SeqCst)
+            );
+        }
+    }
+
+    // Analyses a stored file in the background, recording the result along with the file and in
+    // the audit trail. Nothing is started while shutting down, leaving the job to be resumed.
+    // `attempts` is the number of times it has been started before.
+    fn start(&self, id: u64, name: Option<String>, user: User, attempts: u32, audit_log: AuditLog) {
+        if self.draining.load(Ordering::SeqCst) {
+            return;
+        }
+        let active = ActiveGuard::new(self.active.clone());
+        let entries = self.entries.clone();
+        let dir = self.job_dir(id);
+        task::spawn(async move {
+            let record_name = name.clone();
+            let actor = user.0.clone();
+            let analysed = task::spawn_blocking(move || {
+                let tile_dir = dir.join(TILE_DIR_NAME);
+                let series_dir = dir.join(SERIES_DIR_NAME);
+                let path = dir.join(UPLOAD_FILE_NAME);
+                // Counted before running, so that an analysis taking the process down counts too.
+                let mut record = JobRecord {
+                    name: record_name,
+                    actor,
+                    state: JobState::Running {
+                        progress: 0.0,
+                        tiles: 0,
+                    },
+                    attempts: attempts + 1,
+                };
+                if let Err(error) = write_record(&dir, &record) {
+                    eprintln!("failed to record the start of job {id}: {error}");
+                }
+                let result = analysis::analyse(&path, &tile_dir, &series_dir, |progress, tiles| {
+                    set_state(&entries, id, JobState::Running { progress, tiles })
+                });
+                let state = match result {
+                    Ok(analysis) => JobState::Done { analysis },
+                    Err(error) => JobState::Failed { error },
+                };
+                record.state = state.clone();
+                if let Err(error) = write_record(&dir, &record) {
+                    eprintln!("failed to record the result of job {id}: {error}");
+                }
+                set_state(&entries, id, state.clone());
+                state
+            })
+            .await;
+            let detail = match analysed {
+                Ok(JobState::Failed { error }) => Some(error),
+                Err(error) => Some(error.to_string()),
+                _ => None,
+            };
+            let recorded = audit_log
+                .record(
+                    &user,
+                    Action::Analyse,
+                    id,
+                    name.as_deref(),
+                    detail.as_deref(),
+                )
+                .await;
+            if let Err(error) = recorded {
+                eprintln!("failed to record analysis {id}: {error}");
+            }
+            drop(active);
+        });
+    }
+
+    pub fn routes() -> Vec<Route> {
+        routes![
+            upload,
+            job,
+            delete_job,
+            events,
+            spectrogram_tile,
+            series,
+            cleanup
+        ]
+    }
+
+    // Deletes the uploads the retention policy no longer allows, except those still being stored
+    // or analysed, recording each deletion in the audit trail as made by `actor`.
+    pub async fn collect_garbage(&self, audit_log: &AuditLog, actor: &User) -> io::Result<Cleanup> {
+        let mut cleanup = Cleanup::default();
+        if !self.retention.is_enabled() {
+            return Ok(cleanup);
+        }
+        let uploads = self.uploads()?;
+        let bytes: HashMap<_, _> = uploads
+            .iter()
+            .map(|upload| (upload.id, upload.bytes))
+            .collect();
+
+        for (id, reason) in self.retention.select(uploads, SystemTime::now()) {
+            // Jobs started since they were listed are left alone.
+            let Ok(name) = self.remove(id) else {
+                continue;
+            };
+            let recorded = audit_log
+                .record(
+                    actor,
+                    Action::Delete,
+                    id,
+                    name.as_deref(),
+                    Some(reason.describe()),
+                )
+                .await;
+            if let Err(error) = recorded {
+                eprintln!("failed to record deletion of upload {id}: {error}");
+            }
+            cleanup.deleted.push(id);
+            cleanup.freed_bytes += bytes[&id];
+        }
+        Ok(cleanup)
+    }
+
+    fn job_dir(&self, id: u64) -> PathBuf {
+        self.dir.join(id.to_string())
+    }
+
+    // Marks a job as used now, for the least recently used to be deleted first.
+    fn touch(&self, id: u64) {
+        if let Ok(mut used) = self.used.lock() {
+            used.insert(id, SystemTime::now());
+        }
+    }
+
+    // Removes a job that is not running along with its files, returning the name of its file.
+    fn remove(&self, id: u64) -> Result<Option<String>, Status> {
+        let dir = self.job_dir(id);
+        let name = {
+            let mut entries = self
+                .entries
+                .lock()
+                .map_err(|_| Status::InternalServerError)?;
+            match entries.get(&id) {
+                Some(Job {
+                    state: JobState::Running { .. },
+                    ..
+                }) => return Err(Status::Conflict),
+                Some(_) => entries.remove(&id).and_then(|job| job.name),
+                None if dir.is_dir() => None,
+                None => return Err(Status::NotFound),
+            }
+        };
+        if let Ok(mut used) = self.used.lock() {
+            used.remove(&id);
+        }
+
+        fs::remove_dir_all(&dir).map_err(|error| {
+            eprintln!("failed to delete upload {id}: {error}");
+            Status::InternalServerError
+        })?;
+        Ok(name)
+    }
+
+    // The stored uploads that are neither being stored nor analysed.
+    fn uploads(&self) -> io::Result<Vec<Upload>> {
+        let running: Vec<u64> = match self.entries.lock() {
+            Ok(entries) => entries
+                .iter()
+                .filter(|(_, job)| matches!(job.state, JobState::Running { .. }))
+                .map(|(&id, _)| id)
+                .collect(),
+            Err(_) => return Err(io::Error::other("job lock poisoned")),
+        };
+        let used = self
+            .used
+            .lock()
+            .map(|used| used.clone())
+            .unwrap_or_default();
+
+        let mut uploads = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let Some(id) = entry.file_name().to_str().and_then(|id| id.parse().ok()) else {
+                continue;
+            };
+            if running.contains(&id) || !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let path = entry.path();
+            let uploaded = fs::metadata(path.join(UPLOAD_FILE_NAME))
+                .or_else(|_| entry.metadata())?
+                .modified()?;
+            uploads.push(Upload {
+                id,
+                uploaded,
+                used: used.get(&id).copied().unwrap_or(uploaded),
+                bytes: dir_size(&path)?,
+            });
+        }
+        Ok(uploads)
+    }
+
+    fn state(&self, id: u64) -> Option<JobState> {
+        Some(self.entries.lock().ok()?.get(&id)?.state.clone())
+    }
+
+    // The state of a job along with a receiver of the events after it.
+    fn subscribe(&self, id: u64) -> Option<(JobState, broadcast::Receiver<JobEvent>)> {
+        let entries = self.entries.lock().ok()?;
+        let job = entries.get(&id)?;
+        Some((job.state.clone(), job.sender.subscribe()))
+    }
+}
+
+// Updates the state of a job, announcing it along with any tiles written since the last update.
+fn set_state(entries: &Mutex<HashMap<u64, Job>>, id: u64, state: JobState) {
+    let Ok(mut entries) = entries.lock() else {
+        return;
+    };
+    let Some(job) = entries.get_mut(&id) else {
+        return;
+    };
+    let tiles_before = match job.state {
+        JobState::Running { tiles, .. } => tiles,
+        _ => 0,
+    };
+    if let JobState::Running { tiles, .. } = state {
+        for tile in tiles_before..tiles {
+            // Sending only fails when nobody is listening, which is fine.
+            let _ = job.sender.send(JobEvent::Tile(tile));
+        }
+    }
+    job.state = state.clone();
+    let _ = job.sender.send(JobEvent::State(state));
+}
+
+// Counts an analysis as running for as long as it lives.
+struct ActiveGuard(Arc<AtomicUsize>);
+
+impl ActiveGuard {
+    fn new(active: Arc<AtomicUsize>) -> Self {
+        active.fetch_add(1, Ordering::SeqCst);
+        Self(active)
+    }
+}
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn read_record(job_dir: &Path) -> Option<JobRecord> {
+    let json = fs::read(job_dir.join(RECORD_FILE_NAME)).ok()?;
+    json::from_slice(&json)
+        .map_err(|error| eprintln!("failed to read the record of {job_dir:?}: {error}"))
+        .ok()
+}
+
+// Written under another name first, so that a record is never read half written.
+fn write_record(job_dir: &Path, record: &JobRecord) -> io::Result<()> {
+    let path = job_dir.join(RECORD_FILE_NAME);
+    let partial = path.with_extension("json.partial");
+    fs::write(&partial, json::to_string(record).map_err(io::Error::other)?)?;
+    fs::rename(partial, path)
+}
+
+// The total size of the files in a directory and those below it.
+fn dir_size(dir: &Path) -> io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
+}
+
+// Stores the WAV file in the body, by the name of the file if given, and starts analysing it,
+// returning the ID of the job to follow. Bodies are limited to the `upload` limit in the Rocket
+// configuration, or 8 GiB by default. Once the file is stored, the uploads the retention policy no
+// longer allows are deleted. Uploads are refused with 503 Service Unavailable while shutting down.
+#[post("/upload?<name>", data = "<data>")]
+async fn upload(
+    jobs: &State<Jobs>,
+    audit_log: &State<AuditLog>,
+    user: User,
+    limits: &Limits,
+    name: Option<String>,
+    data: Data<'_>,
+) -> Result<Json<Created>, Status> {
+    if jobs.draining.load(Ordering::SeqCst) {
+        return Err(Status::ServiceUnavailable);
+    }
+    let id = jobs.next_id.fetch_add(1, Ordering::Relaxed);
+    let dir = jobs.job_dir(id);
+    let path = dir.join(UPLOAD_FILE_NAME);
+    let internal_error = |error: io::Error| {
+        eprintln!("failed to store upload {id}: {error}");
+        Status::InternalServerError
+    };
+
+    // The job is running from the start, so that the retention policy leaves it alone while the
+    // file is being stored.
+    let entries = jobs.entries.clone();
+    if let Ok(mut entries) = entries.lock() {
+        let running = JobState::Running {
+            progress: 0.0,
+            tiles: 0,
+        };
+        let job = Job {
+            name: name.clone(),
+            state: running,
+            sender: broadcast::channel(EVENT_CAPACITY).0,
+        };
+        entries.insert(id, job);
+    }
+    let stored = async {
+        fs::create_dir_all(&dir).map_err(internal_error)?;
+        let limit = limits.get("upload").unwrap_or(8.gibibytes());
+        let file = data
+            .open(limit)
+            .into_file(&path)
+            .await
+            .map_err(internal_error)?;
+        if !file.is_complete() {
+            return Err(Status::PayloadTooLarge);
+        }
+        let record = JobRecord {
+            name: name.clone(),
+            actor: user.0.clone(),
+            state: JobState::Running {
+                progress: 0.0,
+                tiles: 0,
+            },
+            attempts: 0,
+        };
+        write_record(&dir, &record).map_err(internal_error)?;
+        audit_log
+            .record(&user, Action::Upload, id, name.as_deref(), None)
+            .await
+            .map_err(|error| {
+                eprintln!("failed to record upload {id}: {error}");
+                Status::InternalServerError
+            })
+    }
+    .await;
+    if let Err(status) = stored {
+        // A partial file is of no use.
+        if let Ok(mut entries) = entries.lock() {
+            entries.remove(&id);
+        }
+        let _ = fs::remove_dir_all(&dir);
+        return Err(status);
+    }
+    let actor = User(RETENTION_ACTOR.to_owned());
+    if let Err(error) = jobs.collect_garbage(audit_log, &actor).await {
+        eprintln!("failed to apply the retention policy: {error}");
+    }
+    jobs.start(id, name, user, 0, (*audit_log).clone());
+
+    Ok(Json(Created { id }))
+}
+
+#[get("/jobs/<id>")]
+fn job(jobs: &State<Jobs>, _user: User, id: u64) -> Option<Negotiated<JobState>> {
+    jobs.touch(id);
+    jobs.state(id).map(Negotiated)
+}
+
+// Deletes an uploaded file along with everything computed from it, once it is no longer being
+// analysed. Files left by earlier runs can be deleted too.
+#[delete("/jobs/<id>")]
+async fn delete_job(
+    jobs: &State<Jobs>,
+    audit_log: &State<AuditLog>,
+    user: User,
+    id: u64,
+) -> Status {
+    let name = match jobs.remove(id) {
+        Ok(name) => name,
+        Err(status) => return status,
+    };
+    let recorded = audit_log
+        .record(&user, Action::Delete, id, name.as_deref(), None)
+        .await;
+    if let Err(error) = recorded {
+        eprintln!("failed to record deletion of upload {id}: {error}");
+        return Status::InternalServerError;
+    }
+    Status::NoContent
+}
+
+// Server-sent events following a job: its current state as a `state` event with the same JSON as
+// `job` returns, then a `state` event each time it changes and a `tile` event with the index of
+// each spectrogram tile of level 0 as soon as it is written. The stream ends once the job has
+// finished.
+#[get("/jobs/<id>/events")]
+fn events(
+    jobs: &State<Jobs>,
+    _user: EventStreamUser,
+    id: u64,
+    mut shutdown: Shutdown,
+) -> Option<EventStream![]> {
+    jobs.touch(id);
+    let (mut state, mut receiver) = jobs.subscribe(id)?;
+    Some(EventStream! {
+        yield stream::Event::json(&state).event("state");
+        while let JobState::Running { .. } = state {
+            let event = select! {
+                event = receiver.recv() => match event {
+                    Ok(event) => event,
+                    Err(RecvError::Closed) => break,
+                    Err(RecvError::Lagged(_)) => continue,
+                },
+                _ = &mut shutdown => break,
+            };
+            match event {
+                JobEvent::State(new_state) => {
+                    yield stream::Event::json(&new_state).event("state");
+                    state = new_state;
+                }
+                JobEvent::Tile(tile) => yield stream::Event::data(tile.to_string()).event("tile"),
+            }
+        }
+    })
+}
+
+// A tile of the spectrogram of an uploaded file, by the ID of its job, at a level of the pyramid
+// described by `analysis::Spectrogram`. Tiles are served as soon as they are written, before the
+// job is done.
+#[get("/spectrogram/<id>/<zoom>/<tile>")]
+async fn spectrogram_tile(
+    jobs: &State<Jobs>,
+    _user: User,
+    id: u64,
+    zoom: usize,
+    tile: usize,
+) -> Option<NamedFile> {
+    if let JobState::Failed { .. } = jobs.state(id)? {
+        return None;
+    }
+    jobs.touch(id);
+    let tile_dir = jobs.job_dir(id).join(TILE_DIR_NAME);
+    NamedFile::open(analysis::tile_path(&tile_dir, zoom, tile))
+        .await
+        .ok()
+}
+
+// The points of a series of a finished job between `start` and `end` seconds, or all of them,
+// downsampled so that no more than `max_points` buckets are returned.
+#[get("/jobs/<id>/series/<name>?<start>&<end>&<max_points>")]
+async fn series(
+    jobs: &State<Jobs>,
+    _user: User,
+    id: u64,
+    name: &str,
+    start: Option<f64>,
+    end: Option<f64>,
+    max_points: Option<usize>,
+) -> Result<Negotiated<SeriesPage>, Status> {
+    let Some(JobState::Done { analysis }) = jobs.state(id) else {
+        return Err(Status::NotFound);
+    };
+    jobs.touch(id);
+    let series = analysis
+        .series
+        .iter()
+        .find(|series| series.name == name)
+        .ok_or(Status::NotFound)?;
+
+    let index = |seconds: f64| {
+        ((seconds - series.start_seconds) / series.step_seconds)
+            .ceil()
+            .clamp(0.0, series.points as f64) as usize
+    };
+    let first = start.map_or(0, index);
+    let last = end.map_or(series.points, index).max(first);
+    let path = analysis::series_path(&jobs.job_dir(id).join(SERIES_DIR_NAME), &series.name);
+    let internal_error = |error: io::Error| {
+        eprintln!("failed to read series {name} of job {id}: {error}");
+        Status::InternalServerError
+    };
+    let mut file = File::open(path).await.map_err(internal_error)?;
+    file.seek(SeekFrom::Start(first as u64 * 4))
+        .await
+        .map_err(internal_error)?;
+    let mut bytes = vec![0; (last - first) * 4];
+    file.read_exact(&mut bytes).await.map_err(internal_error)?;
+    let levels: Vec<f32> = bytes
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect();
+
+    let max_points = max_points.unwrap_or(DEFAULT_MAX_POINTS).min(MAX_POINTS);
+    let (bucket, min, max) = analysis::downsample(&levels, max_points);
+    Ok(Negotiated(SeriesPage {
+        start_seconds: series.start_seconds + first as f64 * series.step_seconds,
+        step_seconds: bucket as f64 * series.step_seconds,
+        min,
+        max,
+    }))
+}
+
+// Applies the retention policy now rather than at the next upload, returning the IDs of the
+// uploads deleted and the space freed.
+#[post("/admin/cleanup")]
+async fn cleanup(
+    jobs: &State<Jobs>,
+    audit_log: &State<AuditLog>,
+    admin: Admin,
+) -> Result<Json<Cleanup>, Status> {
+    jobs.collect_garbage(audit_log, &admin.0)
+        .await
+        .map(Json)
+        .map_err(|error| {
+            eprintln!("failed to apply the retention policy: {error}");
+            Status::InternalServerError
+        })
+}
@@ -1,14 +1,112 @@
-use std::{borrow::Cow, env};
+use std::{borrow::Cow, env, time::Duration};
 
 use rocket::{
+    fairing::AdHoc,
     fs::{relative, FileServer},
     launch,
 };
 
+pub mod audit;
+pub mod auth;
+mod database;
+// Public so that the URI macros generated for the routes are not reported as unused imports.
+pub mod jobs;
+mod negotiate;
+mod retention;
+pub mod sessions;
+pub mod watch;
+
+use audit::AuditLog;
+use auth::{Tokens, User};
+use jobs::Jobs;
+use retention::Retention;
+use sessions::Sessions;
+use watch::WatchFolder;
+
 #[launch]
 async fn rocket() -> _ {
     let static_dir = env::var("SIGNAL_INSPECTOR_STATIC_DIR")
         .map(Cow::Owned)
         .unwrap_or_else(|_| relative!("../frontend/dist/").into());
-    rocket::build().mount("/", FileServer::from(&*static_dir))
+    let upload_dir = env::var("SIGNAL_INSPECTOR_UPLOAD_DIR")
+        .map(Into::into)
+        .unwrap_or_else(|_| env::temp_dir().join("signal-inspector"));
+    let retention =
+        Retention::from_env().unwrap_or_else(|error| panic!("invalid retention policy: {error}"));
+    let jobs = Jobs::new(&upload_dir, retention)
+        .unwrap_or_else(|error| panic!("failed to store uploads in {upload_dir:?}: {error}"));
+    let database = env::var("SIGNAL_INSPECTOR_DATABASE")
+        .map(Into::into)
+        .unwrap_or_else(|_| upload_dir.join("sessions.sqlite"));
+    let (pool, sessions, audit_log) = async {
+        let pool = database::open(&database).await?;
+        Ok::<_, sqlx::Error>((
+            pool.clone(),
+            Sessions::new(pool.clone()).await?,
+            AuditLog::new(pool).await?,
+        ))
+    }
+    .await
+    .unwrap_or_else(|error| panic!("failed to open {database:?}: {error}"));
+    // Uploads left by earlier runs may already be beyond the limits.
+    let cleanup = jobs
+        .collect_garbage(&audit_log, &User(jobs::RETENTION_ACTOR.to_owned()))
+        .await
+        .unwrap_or_else(|error| panic!("failed to apply the retention policy: {error}"));
+    if !cleanup.deleted.is_empty() {
+        eprintln!(
+            "deleted {} uploads, freeing {} bytes",
+            cleanup.deleted.len(),
+            cleanup.freed_bytes
+        );
+    }
+    jobs.resume(&audit_log).await;
+    let drain_timeout = match env::var("SIGNAL_INSPECTOR_DRAIN_SECONDS") {
+        Ok(seconds) => seconds
+            .trim()
+            .parse()
+            .map(Duration::from_secs)
+            .unwrap_or_else(|_| panic!("invalid SIGNAL_INSPECTOR_DRAIN_SECONDS {seconds:?}")),
+        Err(_) => Duration::from_secs(30),
+    };
+    let tokens = Tokens::parse(&env::var("SIGNAL_INSPECTOR_TOKENS").unwrap_or_default())
+        .unwrap_or_else(|error| panic!("invalid SIGNAL_INSPECTOR_TOKENS: {error}"))
+        .with_admins(&env::var("SIGNAL_INSPECTOR_ADMINS").unwrap_or_default());
+    if !tokens.is_enabled() {
+        eprintln!(
+            "SIGNAL_INSPECTOR_TOKENS is not set, so the API is open to anyone who can reach it"
+        );
+    }
+    let rocket = rocket::build()
+        .mount("/", FileServer::from(&*static_dir))
+        .manage(tokens)
+        .attach(Tokens::fairing())
+        .mount("/api", Tokens::routes())
+        .manage(jobs)
+        .mount("/api", Jobs::routes())
+        .manage(sessions)
+        .mount("/api", Sessions::routes())
+        .manage(audit_log)
+        .mount("/api", AuditLog::routes())
+        // Running analyses are given time to finish and the database is closed before exiting, so
+        // that a restart does not cut off what is being written.
+        .attach(AdHoc::on_shutdown("Drain jobs", move |rocket| {
+            Box::pin(async move {
+                if let Some(jobs) = rocket.state::<Jobs>() {
+                    jobs.drain(drain_timeout).await;
+                }
+                pool.close().await;
+            })
+        }));
+
+    match env::var("SIGNAL_INSPECTOR_WATCH_DIR") {
+        Ok(watch_dir) => {
+            let watch_folder = WatchFolder::new(&watch_dir)
+                .unwrap_or_else(|error| panic!("failed to watch {watch_dir}: {error}"));
+            rocket
+                .manage(watch_folder)
+                .mount("/api/watch", WatchFolder::routes())
+        }
+        Err(_) => rocket,
+    }
 }
@@ -0,0 +1,106 @@
+use rocket::{
+    http::{Accept, ContentType, Status},
+    request::Request,
+    response::{self, Responder, Response},
+    serde::{json::Json, msgpack},
+};
+use serde::Serialize;
+
+// Results of analyses in the format the request accepts: JSON by default, CSV for `text/csv`, which
+// is easier to read with curl and to load in spreadsheets, and MessagePack for
+// `application/msgpack`, which is smaller and faster to decode for clients fetching a lot of them.
+// Requests accepting none of them are answered with 406 Not Acceptable.
+pub struct Negotiated<T>(pub T);
+
+// Results that can be written as CSV, with a header row.
+pub trait ToCsv {
+    fn to_csv(&self) -> String;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Json,
+    Csv,
+    MsgPack,
+}
+
+impl<'r, T: Serialize + ToCsv> Responder<'r, 'static> for Negotiated<T> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let response = match format(request.accept()) {
+            Some(Format::Json) => Json(self.0).respond_to(request)?,
+            Some(Format::Csv) => (ContentType::CSV, self.0.to_csv()).respond_to(request)?,
+            // With field names, unlike the compact encoding of `MsgPack`, so that it can be read
+            // like the JSON.
+            Some(Format::MsgPack) => {
+                let bytes = msgpack::to_vec(&self.0).map_err(|error| {
+                    eprintln!("failed to encode MessagePack: {error}");
+                    Status::InternalServerError
+                })?;
+                (ContentType::MsgPack, bytes).respond_to(request)?
+            }
+            None => return Err(Status::NotAcceptable),
+        };
+        // Caches must not serve a response in one format to a request for another.
+        Response::build_from(response)
+            .raw_header("Vary", "Accept")
+            .ok()
+    }
+}
+
+// The supported format the request accepts with the highest weight, the first listed of those
+// with the same weight.
+fn format(accept: Option<&Accept>) -> Option<Format> {
+    let Some(accept) = accept else {
+        return Some(Format::Json);
+    };
+    let mut media_types: Vec<_> = accept
+        .iter()
+        .filter(|media_type| media_type.weight_or(1.0) > 0.0)
+        .collect();
+    media_types.sort_by(|a, b| b.weight_or(1.0).total_cmp(&a.weight_or(1.0)));
+    media_types.into_iter().find_map(|media_type| {
+        let (top, sub) = (media_type.top(), media_type.sub());
+        if top == "*" || top == "application" && (sub == "*" || sub == "json") {
+            Some(Format::Json)
+        } else if top == "application" && (sub == "msgpack" || sub == "x-msgpack") {
+            Some(Format::MsgPack)
+        } else if top == "text" && (sub == "*" || sub == "csv") {
+            Some(Format::Csv)
+        } else {
+            None
+        }
+    })
+}
+
+// A CSV field, quoted if it has to be.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn the_most_wanted_supported_format_is_chosen() {
+        use rocket::http::Accept;
+
+        use super::{format, Format};
+
+        let format_of = |accept: &str| format(Some(&accept.parse::<Accept>().unwrap()));
+        assert_eq!(format(None), Some(Format::Json));
+        assert_eq!(format_of("*/*"), Some(Format::Json));
+        assert_eq!(format_of("text/csv"), Some(Format::Csv));
+        assert_eq!(
+            format_of("text/html, application/msgpack;q=0.9, */*;q=0.1"),
+            Some(Format::MsgPack)
+        );
+        assert_eq!(
+            format_of("application/json;q=0.5, text/csv"),
+            Some(Format::Csv)
+        );
+        assert_eq!(format_of("text/csv;q=0, image/png"), None);
+    }
+}
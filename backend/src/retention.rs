@@ -0,0 +1,145 @@
+use std::{
+    env,
+    time::{Duration, SystemTime},
+};
+
+use rocket::data::ByteUnit;
+
+// Limits on how long uploads and what was computed from them are kept and on how much space they
+// take together, so that the disk of a shared instance does not silently fill up. Without limits
+// uploads are kept until deleted.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Retention {
+    // How long after being uploaded a file is kept.
+    pub max_age: Option<Duration>,
+    // The most space uploads may take. Beyond it, the least recently used are deleted first.
+    pub max_total_bytes: Option<u64>,
+}
+
+// An upload as far as retention is concerned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Upload {
+    pub id: u64,
+    pub uploaded: SystemTime,
+    // When the upload or anything computed from it was last requested.
+    pub used: SystemTime,
+    // The size of the upload along with everything computed from it.
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reason {
+    Expired,
+    OverSize,
+}
+
+impl Retention {
+    // The limits set by `SIGNAL_INSPECTOR_MAX_UPLOAD_AGE_HOURS` and
+    // `SIGNAL_INSPECTOR_MAX_UPLOAD_SIZE`, a size such as "20 GiB".
+    pub fn from_env() -> Result<Self, String> {
+        let max_age = match env::var("SIGNAL_INSPECTOR_MAX_UPLOAD_AGE_HOURS") {
+            // Negative, infinite and unrepresentably long ages are all rejected by the conversion.
+            Ok(hours) => Some(
+                hours
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(|hours| Duration::try_from_secs_f64(hours * 3600.0).ok())
+                    .ok_or_else(|| format!("invalid maximum upload age {hours:?}"))?,
+            ),
+            Err(_) => None,
+        };
+        let max_total_bytes = match env::var("SIGNAL_INSPECTOR_MAX_UPLOAD_SIZE") {
+            Ok(size) => Some(
+                size.trim()
+                    .parse::<ByteUnit>()
+                    .map_err(|_| format!("invalid maximum upload size {size:?}"))?
+                    .as_u64(),
+            ),
+            Err(_) => None,
+        };
+        Ok(Self {
+            max_age,
+            max_total_bytes,
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_age.is_some() || self.max_total_bytes.is_some()
+    }
+
+    // The uploads to delete and why: those older than the maximum age, then as many of the rest
+    // as needed to fit in the maximum size, the least recently used first.
+    pub fn select(&self, mut uploads: Vec<Upload>, now: SystemTime) -> Vec<(u64, Reason)> {
+        let mut selected = Vec::new();
+        if let Some(max_age) = self.max_age {
+            uploads.retain(|upload| {
+                let expired = now
+                    .duration_since(upload.uploaded)
+                    .is_ok_and(|age| age > max_age);
+                if expired {
+                    selected.push((upload.id, Reason::Expired));
+                }
+                !expired
+            });
+        }
+        if let Some(max_total_bytes) = self.max_total_bytes {
+            uploads.sort_by_key(|upload| upload.used);
+            let mut total: u64 = uploads.iter().map(|upload| upload.bytes).sum();
+            for upload in uploads {
+                if total <= max_total_bytes {
+                    break;
+                }
+                total -= upload.bytes;
+                selected.push((upload.id, Reason::OverSize));
+            }
+        }
+        selected
+    }
+}
+
+impl Reason {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Expired => "older than the maximum upload age",
+            Self::OverSize => "least recently used beyond the maximum upload size",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::{Reason, Retention, Upload};
+
+    #[test]
+    fn expired_then_least_recently_used_are_selected() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(100_000);
+        let upload = |id, age, idle, bytes| Upload {
+            id,
+            uploaded: now - Duration::from_secs(age),
+            used: now - Duration::from_secs(idle),
+            bytes,
+        };
+        let uploads = vec![
+            upload(1, 10_000, 10, 100),
+            upload(2, 500, 400, 100),
+            upload(3, 500, 300, 100),
+            upload(4, 500, 20, 100),
+        ];
+        let retention = Retention {
+            max_age: Some(Duration::from_secs(1000)),
+            max_total_bytes: Some(150),
+        };
+        assert_eq!(
+            retention.select(uploads.clone(), now),
+            vec![
+                (1, Reason::Expired),
+                (2, Reason::OverSize),
+                (3, Reason::OverSize)
+            ],
+        );
+        assert!(Retention::default().select(uploads, now).is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+use rocket::{get, http::Status, post, routes, serde::json::Json, Route, State};
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow};
+
+use crate::auth::User;
+
+// Sessions saved from the frontend, kept in a SQLite database. The state is stored as the JSON the
+// frontend sent, so that new fields in it need no migration here.
+pub struct Sessions {
+    pool: SqlitePool,
+}
+
+#[derive(Deserialize)]
+struct NewSession {
+    name: String,
+    // The name of the file the session was of, if it had one.
+    file_name: Option<String>,
+    notes: String,
+    state: rocket::serde::json::Value,
+}
+
+#[derive(Serialize, FromRow)]
+struct SessionSummary {
+    id: i64,
+    name: String,
+    file_name: Option<String>,
+    notes: String,
+    // ISO 8601, in UTC.
+    saved_at: String,
+}
+
+#[derive(Serialize)]
+struct Session {
+    #[serde(flatten)]
+    summary: SessionSummary,
+    state: rocket::serde::json::Value,
+}
+
+#[derive(Serialize)]
+struct Created {
+    id: i64,
+}
+
+impl Sessions {
+    pub async fn new(pool: SqlitePool) -> Result<Self, sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                file_name TEXT,
+                notes TEXT NOT NULL,
+                saved_at TEXT NOT NULL,
+                state TEXT NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self { pool })
+    }
+
+    pub fn routes() -> Vec<Route> {
+        routes![create, list, session]
+    }
+}
+
+fn internal_error(error: sqlx::Error) -> Status {
+    eprintln!("failed to access sessions: {error}");
+    Status::InternalServerError
+}
+
+#[post("/sessions", data = "<session>")]
+async fn create(
+    sessions: &State<Sessions>,
+    _user: User,
+    session: Json<NewSession>,
+) -> Result<Json<Created>, Status> {
+    let NewSession {
+        name,
+        file_name,
+        notes,
+        state,
+    } = session.into_inner();
+    if name.trim().is_empty() || !state.is_object() {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    let result = sqlx::query(
+        "INSERT INTO sessions (name, file_name, notes, saved_at, state)
+         VALUES (?, ?, ?, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'), ?)",
+    )
+    .bind(name.trim())
+    .bind(file_name)
+    .bind(notes)
+    .bind(state.to_string())
+    .execute(&sessions.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(Created {
+        id: result.last_insert_rowid(),
+    }))
+}
+
+// The saved sessions without their state, the most recently saved first.
+#[get("/sessions")]
+async fn list(
+    sessions: &State<Sessions>,
+    _user: User,
+) -> Result<Json<Vec<SessionSummary>>, Status> {
+    sqlx::query_as("SELECT id, name, file_name, notes, saved_at FROM sessions ORDER BY id DESC")
+        .fetch_all(&sessions.pool)
+        .await
+        .map(Json)
+        .map_err(internal_error)
+}
+
+#[get("/sessions/<id>")]
+async fn session(
+    sessions: &State<Sessions>,
+    _user: User,
+    id: i64,
+) -> Result<Option<Json<Session>>, Status> {
+    let row: Option<(i64, String, Option<String>, String, String, String)> = sqlx::query_as(
+        "SELECT id, name, file_name, notes, saved_at, state FROM sessions WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_optional(&sessions.pool)
+    .await
+    .map_err(internal_error)?;
+    let Some((id, name, file_name, notes, saved_at, state)) = row else {
+        return Ok(None);
+    };
+
+    let state = rocket::serde::json::from_str(&state).map_err(|error| {
+        eprintln!("failed to parse session {id}: {error}");
+        Status::InternalServerError
+    })?;
+    Ok(Some(Json(Session {
+        summary: SessionSummary {
+            id,
+            name,
+            file_name,
+            notes,
+            saved_at,
+        },
+        state,
+    })))
+}
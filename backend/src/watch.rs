@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+
+use notify::{
+    event::{AccessKind, AccessMode, ModifyKind, RenameMode},
+    Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use rocket::{
+    fs::NamedFile,
+    get,
+    response::stream::{self, EventStream},
+    routes,
+    tokio::{
+        select,
+        sync::broadcast::{self, error::RecvError},
+    },
+    Route, Shutdown, State,
+};
+
+use crate::auth::{EventStreamUser, User};
+
+pub struct WatchFolder {
+    dir: PathBuf,
+    sender: broadcast::Sender<String>,
+    _watcher: RecommendedWatcher,
+}
+
+impl WatchFolder {
+    pub fn new(dir: impl Into<PathBuf>) -> notify::Result<Self> {
+        let dir = dir.into();
+        let (sender, _) = broadcast::channel(16);
+
+        let event_sender = sender.clone();
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            let event = match result {
+                Ok(event) => event,
+                Err(error) => {
+                    eprintln!("watch folder error: {error}");
+                    return;
+                }
+            };
+
+            // Only announce files once they are complete, i.e. when closed after writing or when
+            // moved into the folder.
+            if !matches!(
+                event.kind,
+                EventKind::Access(AccessKind::Close(AccessMode::Write))
+                    | EventKind::Modify(ModifyKind::Name(RenameMode::To))
+            ) {
+                return;
+            }
+
+            for name in event.paths.iter().filter_map(|path| wav_file_name(path)) {
+                // Sending only fails when nobody is listening, which is fine.
+                let _ = event_sender.send(name);
+            }
+        })?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+        Ok(Self {
+            dir,
+            sender,
+            _watcher: watcher,
+        })
+    }
+
+    pub fn routes() -> Vec<Route> {
+        routes![events, file]
+    }
+}
+
+fn wav_file_name(path: &Path) -> Option<String> {
+    let is_wav = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("wav"));
+    is_wav
+        .then(|| path.file_name()?.to_str().map(String::from))
+        .flatten()
+}
+
+// Server-sent events with the names of new files in the watch folder.
+#[get("/events")]
+fn events(
+    watch_folder: &State<WatchFolder>,
+    _user: EventStreamUser,
+    mut shutdown: Shutdown,
+) -> EventStream![] {
+    let mut receiver = watch_folder.sender.subscribe();
+    EventStream! {
+        loop {
+            let name = select! {
+                name = receiver.recv() => match name {
+                    Ok(name) => name,
+                    Err(RecvError::Closed) => break,
+                    Err(RecvError::Lagged(_)) => continue,
+                },
+                _ = &mut shutdown => break,
+            };
+            yield stream::Event::data(name).event("file");
+        }
+    }
+}
+
+#[get("/files/<name>")]
+async fn file(watch_folder: &State<WatchFolder>, _user: User, name: &str) -> Option<NamedFile> {
+    // Only serve files directly within the watch folder.
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        return None;
+    }
+    NamedFile::open(watch_folder.dir.join(name)).await.ok()
+}
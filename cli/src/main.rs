@@ -0,0 +1,292 @@
+// Runs the analyses of the backend on WAV files from the command line, so that the measurements
+// shown in the browser can be scripted over many files.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use signal_inspector_analysis::{self as analysis, Loudness, Spectra, FRAME_LEN};
+
+// The range of levels below 0 dBFS drawn in a spectrogram, and its colors from the lowest level to
+// the highest, as in the frontend.
+const RANGE_DB: f64 = 100.0;
+const COLOR_MAP: [[f64; 3]; 5] = [
+    [11.0, 11.0, 30.0],
+    [63.0, 167.0, 214.0],
+    [59.0, 206.0, 172.0],
+    [255.0, 210.0, 63.0],
+    [238.0, 66.0, 102.0],
+];
+
+#[derive(Parser)]
+#[command(version, about = "Measures WAV files the way Signal Inspector does")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the format, integrated loudness and true peak of each file as a line of JSON
+    Analyze {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Print the integrated loudness, highest momentary loudness and true peak of each file as
+    /// tab-separated values
+    Loudness {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Print the average spectrum of a file, the level of each frequency bin
+    Spectrum {
+        file: PathBuf,
+        /// Print comma-separated values instead of a table
+        #[arg(long)]
+        csv: bool,
+        /// Samples in each frame, which sets the frequency resolution
+        #[arg(long, default_value_t = FRAME_LEN)]
+        frame_len: usize,
+    },
+    /// Draw the spectrogram of a file, with frequency on a logarithmic scale
+    Spectrogram {
+        file: PathBuf,
+        /// The image to write
+        #[arg(long)]
+        png: PathBuf,
+        #[arg(long, default_value_t = 1024)]
+        width: usize,
+        #[arg(long, default_value_t = 256)]
+        height: usize,
+    },
+}
+
+#[derive(Serialize)]
+struct Summary<'a> {
+    file: &'a Path,
+    sample_rate: u32,
+    channels: usize,
+    duration_seconds: f64,
+    integrated_lufs: Option<f64>,
+    true_peak_dbtp: f64,
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Analyze { files } => for_each_file(&files, analyze),
+        Command::Loudness { files } => {
+            println!("file\tintegrated_lufs\tmax_momentary_lufs\ttrue_peak_dbtp");
+            for_each_file(&files, loudness)
+        }
+        Command::Spectrum {
+            file,
+            csv,
+            frame_len,
+        } => spectrum(&file, csv, frame_len),
+        Command::Spectrogram {
+            file,
+            png,
+            width,
+            height,
+        } => spectrogram(&file, &png, width, height),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Runs `measure` on each file, carrying on past files that fail so that one bad file does not stop
+// a batch, but failing in the end if any did.
+fn for_each_file(
+    files: &[PathBuf],
+    mut measure: impl FnMut(&Path) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut failed = 0;
+    for file in files {
+        if let Err(error) = measure(file) {
+            eprintln!("{}: {error}", file.display());
+            failed += 1;
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        _ => Err(format!(
+            "failed to measure {failed} of {} files",
+            files.len()
+        )),
+    }
+}
+
+fn decode(file: &Path) -> Result<(Vec<Vec<f32>>, u32), String> {
+    analysis::decode(file).map_err(|error| error.to_string())
+}
+
+fn analyze(file: &Path) -> Result<(), String> {
+    let (channels, sample_rate) = decode(file)?;
+    let Loudness {
+        integrated_lufs,
+        true_peak_dbtp,
+        ..
+    } = analysis::loudness(&channels, sample_rate);
+    let len = channels.first().map_or(0, Vec::len);
+    let summary = Summary {
+        file,
+        sample_rate,
+        channels: channels.len(),
+        duration_seconds: len as f64 / f64::from(sample_rate),
+        integrated_lufs,
+        true_peak_dbtp,
+    };
+    let line = serde_json::to_string(&summary).map_err(|error| error.to_string())?;
+    println!("{line}");
+    Ok(())
+}
+
+fn loudness(file: &Path) -> Result<(), String> {
+    let (channels, sample_rate) = decode(file)?;
+    let loudness = analysis::loudness(&channels, sample_rate);
+    // Missing values are left empty, for spreadsheets to read as such.
+    let format = |value: Option<f64>| value.map_or_else(String::new, |value| format!("{value:.1}"));
+    let max_momentary = loudness
+        .momentary_lufs
+        .iter()
+        .copied()
+        .reduce(f32::max)
+        .map(f64::from);
+    println!(
+        "{}\t{}\t{}\t{:.1}",
+        file.display(),
+        format(loudness.integrated_lufs),
+        format(max_momentary),
+        loudness.true_peak_dbtp
+    );
+    Ok(())
+}
+
+fn spectrum(file: &Path, csv: bool, frame_len: usize) -> Result<(), String> {
+    if frame_len < 2 {
+        return Err(format!("frame length {frame_len} is too short"));
+    }
+    let (channels, sample_rate) = decode(file)?;
+    let spectra = Spectra::new(&channels, frame_len);
+    if spectra.frames() == 0 {
+        return Err(format!("{} is shorter than a frame", file.display()));
+    }
+
+    let mut output = BufWriter::new(io::stdout().lock());
+    let result = (|| {
+        if csv {
+            writeln!(output, "frequency_hz,level_dbfs")?;
+        }
+        for (bin, level) in spectra.average().into_iter().enumerate() {
+            let frequency = bin as f64 * f64::from(sample_rate) / frame_len as f64;
+            if csv {
+                writeln!(output, "{frequency},{level}")?;
+            } else {
+                writeln!(output, "{frequency:>10.1} Hz {level:>8.1} dBFS")?;
+            }
+        }
+        output.flush()
+    })();
+    result.map_err(|error| error.to_string())
+}
+
+fn spectrogram(file: &Path, png: &Path, width: usize, height: usize) -> Result<(), String> {
+    if width == 0 || height == 0 {
+        return Err("the image must not be empty".to_owned());
+    }
+    let (channels, sample_rate) = decode(file)?;
+    let spectra = Spectra::new(&channels, FRAME_LEN);
+    let frames = spectra.frames();
+    if frames == 0 {
+        return Err(format!("{} is shorter than a frame", file.display()));
+    }
+
+    // The bin of each row, from the Nyquist frequency at the top down to 1 Hz at the bottom.
+    let bins = FRAME_LEN / 2;
+    let nyquist_log = (f64::from(sample_rate) / 2.0).log10();
+    let rows: Vec<_> = (0..height)
+        .map(|row| {
+            let frequency_log = nyquist_log * (1.0 - (row as f64 + 0.5) / height as f64);
+            let bin = 10_f64.powf(frequency_log - nyquist_log) * bins as f64;
+            (bin as usize).min(bins - 1)
+        })
+        .collect();
+
+    // Each column shows the average power of its frames, each frame computed once even when it is
+    // shown in several columns.
+    let mut intensities = vec![0.0; width * height];
+    let mut last = None;
+    for column in 0..width {
+        let range = column_frames(column, width, frames);
+        let mut powers = vec![0.0; bins];
+        for frame in range.clone() {
+            let levels = match last.take() {
+                Some((last_frame, levels)) if last_frame == frame => levels,
+                _ => spectra.frame(frame),
+            };
+            for (power, &level) in powers.iter_mut().zip(&levels) {
+                *power += 10_f64.powf(f64::from(level) / 10.0) / range.len() as f64;
+            }
+            last = Some((frame, levels));
+        }
+        for (row, &bin) in rows.iter().enumerate() {
+            let level = 10.0 * powers[bin].log10();
+            intensities[row * width + column] = 1.0 + level / RANGE_DB;
+        }
+    }
+
+    let pixels: Vec<u8> = intensities.into_iter().flat_map(color).collect();
+    write_png(png, width, height, &pixels)
+        .map_err(|error| format!("failed to write {}: {error}", png.display()))
+}
+
+// The frames shown in a column, at least one even when there are fewer frames than columns.
+fn column_frames(column: usize, columns: usize, frames: usize) -> Range<usize> {
+    let start = column * frames / columns;
+    start..((column + 1) * frames / columns).max(start + 1)
+}
+
+// The color of an intensity between 0 and 1.
+fn color(intensity: f64) -> [u8; 3] {
+    let position = intensity.clamp(0.0, 1.0) * (COLOR_MAP.len() - 1) as f64;
+    let low = (position.floor() as usize).min(COLOR_MAP.len() - 2);
+    let fraction = position - low as f64;
+    let (from, to) = (COLOR_MAP[low], COLOR_MAP[low + 1]);
+    [0, 1, 2].map(|i| (from[i] + (to[i] - from[i]) * fraction).round() as u8)
+}
+
+fn write_png(path: &Path, width: usize, height: usize, pixels: &[u8]) -> Result<(), String> {
+    let file = File::create(path).map_err(|error| error.to_string())?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|error| error.to_string())?;
+    writer
+        .write_image_data(pixels)
+        .map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn every_column_has_frames() {
+        use super::column_frames;
+
+        // More frames than columns are split between them, and fewer are repeated.
+        let ranges: Vec<_> = (0..4).map(|column| column_frames(column, 4, 10)).collect();
+        assert_eq!(ranges, [0..2, 2..5, 5..7, 7..10]);
+        let ranges: Vec<_> = (0..4).map(|column| column_frames(column, 4, 2)).collect();
+        assert_eq!(ranges, [0..1, 0..1, 1..2, 1..2]);
+    }
+}
@@ -0,0 +1,167 @@
+use std::ops::Range;
+
+use web_sys::KeyboardEvent;
+
+// Everything that can be triggered from the keyboard or the command palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppAction {
+    TogglePlayback,
+    ToggleLoop,
+    ClearSelection,
+    ToggleSpectrum,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    PanLeft,
+    PanRight,
+    SelectLeftChannel,
+    SelectRightChannel,
+    NextMarker,
+    PreviousMarker,
+    ClearSession,
+    ExportSvg,
+    ExportPng,
+    ExportReport,
+    PrintReport,
+    ExportSelection,
+    Undo,
+    Redo,
+}
+
+impl AppAction {
+    pub const ALL: [Self; 21] = [
+        Self::TogglePlayback,
+        Self::ToggleLoop,
+        Self::ClearSelection,
+        Self::ToggleSpectrum,
+        Self::ZoomIn,
+        Self::ZoomOut,
+        Self::ResetZoom,
+        Self::PanLeft,
+        Self::PanRight,
+        Self::SelectLeftChannel,
+        Self::SelectRightChannel,
+        Self::NextMarker,
+        Self::PreviousMarker,
+        Self::ClearSession,
+        Self::ExportSvg,
+        Self::ExportPng,
+        Self::ExportReport,
+        Self::PrintReport,
+        Self::ExportSelection,
+        Self::Undo,
+        Self::Redo,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::TogglePlayback => "Play/pause",
+            Self::ToggleLoop => "Loop selection",
+            Self::ClearSelection => "Clear selection",
+            Self::ToggleSpectrum => "Toggle frequency spectrum",
+            Self::ZoomIn => "Zoom in",
+            Self::ZoomOut => "Zoom out",
+            Self::ResetZoom => "Reset zoom",
+            Self::PanLeft => "Pan left",
+            Self::PanRight => "Pan right",
+            Self::SelectLeftChannel => "Select left channel",
+            Self::SelectRightChannel => "Select right channel",
+            Self::NextMarker => "Jump to next marker",
+            Self::PreviousMarker => "Jump to previous marker",
+            Self::ClearSession => "Clear session",
+            Self::ExportSvg => "Export image as SVG",
+            Self::ExportPng => "Export image as PNG",
+            Self::ExportReport => "Export report as HTML",
+            Self::PrintReport => "Print report",
+            Self::ExportSelection => "Export selection as WAV",
+            Self::Undo => "Undo",
+            Self::Redo => "Redo",
+        }
+    }
+
+    pub fn shortcut(self) -> Option<&'static str> {
+        match self {
+            Self::TogglePlayback => Some("Space"),
+            Self::ToggleLoop => Some("O"),
+            Self::ClearSelection => Some("Esc"),
+            Self::ToggleSpectrum => Some("S"),
+            Self::ZoomIn => Some("+"),
+            Self::ZoomOut => Some("−"),
+            Self::ResetZoom => Some("0"),
+            Self::PanLeft => Some("←"),
+            Self::PanRight => Some("→"),
+            Self::SelectLeftChannel => Some("L"),
+            Self::SelectRightChannel => Some("R"),
+            Self::NextMarker => Some("]"),
+            Self::PreviousMarker => Some("["),
+            Self::Undo => Some("Ctrl+Z"),
+            Self::Redo => Some("Ctrl+Shift+Z"),
+            Self::ClearSession
+            | Self::ExportSvg
+            | Self::ExportPng
+            | Self::ExportReport
+            | Self::PrintReport
+            | Self::ExportSelection => None,
+        }
+    }
+
+    pub fn from_key(event: &KeyboardEvent) -> Option<Self> {
+        if event.alt_key() {
+            return None;
+        }
+        if event.ctrl_key() || event.meta_key() {
+            return match event.key().as_str() {
+                "z" | "Z" if event.shift_key() => Some(Self::Redo),
+                "z" | "Z" => Some(Self::Undo),
+                "y" | "Y" => Some(Self::Redo),
+                _ => None,
+            };
+        }
+        match event.key().as_str() {
+            " " => Some(Self::TogglePlayback),
+            "o" | "O" => Some(Self::ToggleLoop),
+            "Escape" => Some(Self::ClearSelection),
+            "s" | "S" => Some(Self::ToggleSpectrum),
+            "+" | "=" => Some(Self::ZoomIn),
+            "-" => Some(Self::ZoomOut),
+            "0" => Some(Self::ResetZoom),
+            "ArrowLeft" => Some(Self::PanLeft),
+            "ArrowRight" => Some(Self::PanRight),
+            "l" | "L" => Some(Self::SelectLeftChannel),
+            "r" | "R" => Some(Self::SelectRightChannel),
+            "]" => Some(Self::NextMarker),
+            "[" => Some(Self::PreviousMarker),
+            _ => None,
+        }
+    }
+}
+
+// Scales the view around its center by `factor`, where the whole signal is shown as `None`.
+pub fn zoom(view: Option<Range<usize>>, len: usize, factor: f64) -> Option<Range<usize>> {
+    let view = view.unwrap_or(0..len);
+    let center = view.start + view.len() / 2;
+    let half = ((view.len() as f64 * factor / 2.0) as usize).max(1);
+    let start = center.saturating_sub(half);
+    let end = center.saturating_add(half).min(len);
+    (start > 0 || end < len).then_some(start..end)
+}
+
+// Moves the view by `fraction` of its length, stopping at either end of the signal.
+pub fn pan(view: Option<Range<usize>>, len: usize, fraction: f64) -> Option<Range<usize>> {
+    let view = view?;
+    // Sample positions of long recordings do not fit in an `isize` on 32-bit targets.
+    let offset = (view.len() as f64 * fraction) as i64;
+    let start = (view.start as i64 + offset).clamp(0, len.saturating_sub(view.len()) as i64);
+    Some(start as usize..start as usize + view.len())
+}
+
+// Centers the view on `position`, zooming in to a tenth of the signal if all of it is shown.
+pub fn center(view: Option<Range<usize>>, len: usize, position: usize) -> Option<Range<usize>> {
+    let view_len = view
+        .map_or(len / 10, |view| view.len())
+        .clamp(1, len.max(1));
+    let start = position
+        .saturating_sub(view_len / 2)
+        .min(len.saturating_sub(view_len));
+    Some(start..start + view_len)
+}
@@ -0,0 +1,174 @@
+use serde_json::{json, Map, Value};
+use web_sys::UrlSearchParams;
+
+use crate::{markers::Marker, model::Signal, settings::AnalysisSettings};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Analyzer {
+    Statistics,
+    Loudness,
+    Metrics,
+    Speech,
+    Silence,
+}
+
+impl Analyzer {
+    pub const ALL: [Self; 5] = [
+        Self::Statistics,
+        Self::Loudness,
+        Self::Metrics,
+        Self::Speech,
+        Self::Silence,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Statistics => "statistics",
+            Self::Loudness => "loudness",
+            Self::Metrics => "metrics",
+            Self::Speech => "speech",
+            Self::Silence => "silence",
+        }
+    }
+}
+
+// Actions requested through query parameters when the page is loaded, so that the inspector can
+// be driven from test scripts and bookmarks, e.g.
+// `?open=capture.wav&analyze=loudness,silence&export=json`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Automation {
+    // Name of a file in the backend watch folder.
+    #[cfg(feature = "server")]
+    pub open: Option<String>,
+    pub analyzers: Vec<Analyzer>,
+    pub export_json: bool,
+}
+
+impl Automation {
+    pub fn from_query() -> Self {
+        let Some(params) = gloo::utils::window()
+            .location()
+            .search()
+            .ok()
+            .and_then(|search| UrlSearchParams::new_with_str(&search).ok())
+        else {
+            return Self::default();
+        };
+
+        Self {
+            #[cfg(feature = "server")]
+            open: params.get("open").filter(|name| !name.is_empty()),
+            analyzers: params
+                .get("analyze")
+                .map(|names| {
+                    names
+                        .split(',')
+                        .filter_map(|name| Analyzer::ALL.into_iter().find(|a| a.name() == name))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            export_json: params.get("export").as_deref() == Some("json"),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // Enables the analyzers that have a switch in the settings.
+    pub fn apply(&self, settings: &mut AnalysisSettings) {
+        for analyzer in &self.analyzers {
+            match analyzer {
+                Analyzer::Speech => settings.speech.enabled = true,
+                Analyzer::Silence => settings.silence.enabled = true,
+                _ => (),
+            }
+        }
+    }
+
+    // Results of the requested analyzers, or of all of them if none were requested, along with the
+    // markers.
+    pub fn report(
+        &self,
+        signal: &Signal,
+        name: Option<&str>,
+        markers: &[Marker],
+        settings: &AnalysisSettings,
+    ) -> Value {
+        let analyzers = if self.analyzers.is_empty() {
+            &Analyzer::ALL[..]
+        } else {
+            &self.analyzers[..]
+        };
+        let channels: Vec<_> = (0..signal.num_channels())
+            .map(|n| signal.channel(n))
+            .collect();
+        let first = channels[0];
+
+        let mut report = Map::new();
+        report.insert("file".to_owned(), json!(name));
+        report.insert("sample_rate".to_owned(), json!(first.sample_rate()));
+        report.insert("channels".to_owned(), json!(channels.len()));
+        report.insert("samples".to_owned(), json!(first.count()));
+        report.insert(
+            "markers".to_owned(),
+            markers
+                .iter()
+                .map(|marker| {
+                    json!({
+                        "position": marker.position,
+                        "seconds": marker.position as f64 / first.sample_rate() as f64,
+                        "label": marker.label,
+                    })
+                })
+                .collect(),
+        );
+
+        for &analyzer in analyzers {
+            let value = match analyzer {
+                Analyzer::Statistics => channels
+                    .iter()
+                    .map(|channel| {
+                        let stats = channel.statistics();
+                        json!({
+                            "peak_dbfs": stats.peak_dbfs,
+                            "rms_dbfs": stats.rms_dbfs,
+                            "crest_factor_db": stats.crest_factor_db,
+                            "dc_offset": stats.dc_offset,
+                            "zero_crossings": stats.zero_crossings,
+                            "clipping_events": stats.clipping_events,
+                        })
+                    })
+                    .collect(),
+                Analyzer::Loudness => {
+                    let loudness = signal.loudness();
+                    json!({
+                        "integrated_lufs": loudness.integrated,
+                        "true_peak_dbtp": loudness.true_peak_dbtp,
+                    })
+                }
+                Analyzer::Metrics => {
+                    let metrics = signal.metrics();
+                    json!({
+                        "centroid_hz": metrics.centroid,
+                        "noise_floor_dbfs": metrics.noise_floor_dbfs,
+                    })
+                }
+                Analyzer::Speech => {
+                    let regions = first.detect_speech(settings.speech.margin_db);
+                    json!(regions.iter().map(|r| [r.start, r.end]).collect::<Vec<_>>())
+                }
+                Analyzer::Silence => {
+                    let regions = first.detect_silence(
+                        settings.silence.threshold_db,
+                        settings.silence.min_duration,
+                    );
+                    json!(regions.iter().map(|r| [r.start, r.end]).collect::<Vec<_>>())
+                }
+            };
+            report.insert(analyzer.name().to_owned(), value);
+        }
+
+        Value::Object(report)
+    }
+}
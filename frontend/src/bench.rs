@@ -1,9 +1,63 @@
-#[cfg(debug_assertions)]
+// Timings are only recorded in the browser, so that the model can also be exercised by native
+// tests. Debug builds also log them to the console, while all builds keep the most recent ones for
+// the status line.
+#[cfg(target_arch = "wasm32")]
 thread_local! {
     pub static TIMESTAMPS: std::sync::Mutex<Vec<(f64, String, bool)>> = std::sync::Mutex::new(Vec::with_capacity(16));
+    pub static TIMINGS: std::cell::RefCell<std::collections::VecDeque<Timing>> = Default::default();
 }
 
-#[cfg(debug_assertions)]
+// Completed timings kept for the status line, enough to cover everything done in one update.
+#[cfg(target_arch = "wasm32")]
+const MAX_TIMINGS: usize = 256;
+// Stages this close to one another are taken to be part of the same update, e.g. the app and the
+// components it renders, which are timed separately.
+#[cfg(target_arch = "wasm32")]
+const UPDATE_GAP_MS: f64 = 50.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timing {
+    pub label: String,
+    // In milliseconds, `end` since the page was loaded.
+    pub end: f64,
+    pub duration: f64,
+    // Number of stages this one was nested within.
+    pub depth: usize,
+}
+
+// Formats a duration in milliseconds.
+pub fn format_duration(duration: f64) -> String {
+    if duration >= 1000.0 {
+        format!("{:.3} s", duration / 1000.0)
+    } else {
+        format!("{duration:.1} ms")
+    }
+}
+
+// The stages of the most recent update, in the order they completed, so that nested stages come
+// before the stage they are part of.
+#[cfg(target_arch = "wasm32")]
+pub fn last_update() -> Vec<Timing> {
+    TIMINGS.with(|timings| {
+        let timings = timings.borrow();
+        let mut start = timings.len();
+        while start > 1 {
+            let (previous, next) = (&timings[start - 2], &timings[start - 1]);
+            if next.end - next.duration - previous.end > UPDATE_GAP_MS {
+                break;
+            }
+            start -= 1;
+        }
+        timings.range(start.saturating_sub(1)..).cloned().collect()
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn last_update() -> Vec<Timing> {
+    Vec::new()
+}
+
+#[cfg(target_arch = "wasm32")]
 pub fn now() -> f64 {
     thread_local! {
         static PERFORMANCE: web_sys::Performance = {
@@ -17,7 +71,18 @@ pub fn now() -> f64 {
     PERFORMANCE.with(|p| p.now())
 }
 
-#[cfg(debug_assertions)]
+#[cfg(target_arch = "wasm32")]
+pub fn record(timing: Timing) {
+    TIMINGS.with(|timings| {
+        let mut timings = timings.borrow_mut();
+        if timings.len() == MAX_TIMINGS {
+            timings.pop_front();
+        }
+        timings.push_back(timing);
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
 macro_rules! bench {
     ([$($fmt:tt)*] => $e:expr) => {{
         bench_start!($($fmt)*);
@@ -27,7 +92,7 @@ macro_rules! bench {
     }};
 }
 
-#[cfg(debug_assertions)]
+#[cfg(target_arch = "wasm32")]
 macro_rules! bench_start {
     ($($fmt:tt)*) => {{
         let label = format!($($fmt)*);
@@ -36,7 +101,9 @@ macro_rules! bench_start {
             let start = $crate::bench::now();
             lock.iter_mut().for_each(|(_, label, is_non_nested)| {
                 if *is_non_nested {
-                    $crate::log(&format!("[START] {label}"));
+                    if cfg!(debug_assertions) {
+                        $crate::log(&format!("[START] {label}"));
+                    }
                     *is_non_nested = false;
                 }
             });
@@ -45,43 +112,46 @@ macro_rules! bench_start {
     }};
 }
 
-#[cfg(debug_assertions)]
+#[cfg(target_arch = "wasm32")]
 macro_rules! bench_end {
     () => {{
         let end = $crate::bench::now();
         $crate::bench::TIMESTAMPS.with(|t| {
             let mut lock = t.lock().expect("should have exclusive access");
             let (start, label, is_non_nested) = lock.pop().expect("should be non-empty");
-            let duration = end - start;
-
-            let duration_fmt = if duration >= 1000.0 {
-                format!("{:.3} s", duration / 1000.0)
-            } else {
-                format!("{duration:.1} ms")
+            let timing = $crate::bench::Timing {
+                label,
+                end,
+                duration: end - start,
+                depth: lock.len(),
             };
 
-            if is_non_nested {
-                $crate::log(&format!("{label} = {duration_fmt}",));
-            } else {
-                $crate::log(&format!("[ END ] {label} = {duration_fmt}"));
+            if cfg!(debug_assertions) {
+                let duration_fmt = $crate::bench::format_duration(timing.duration);
+                if is_non_nested {
+                    $crate::log(&format!("{} = {duration_fmt}", timing.label));
+                } else {
+                    $crate::log(&format!("[ END ] {} = {duration_fmt}", timing.label));
+                }
             }
+            $crate::bench::record(timing);
         });
     }};
 }
 
-#[cfg(not(debug_assertions))]
+#[cfg(not(target_arch = "wasm32"))]
 macro_rules! bench {
     ([$($t:tt)*] => $e:expr) => {
         $e
     };
 }
 
-#[cfg(not(debug_assertions))]
+#[cfg(not(target_arch = "wasm32"))]
 macro_rules! bench_start {
     ($($t:tt)*) => {};
 }
 
-#[cfg(not(debug_assertions))]
+#[cfg(not(target_arch = "wasm32"))]
 macro_rules! bench_end {
     ($($t:tt)*) => {};
 }
@@ -0,0 +1,187 @@
+use std::ops::Range;
+
+use wasm_bindgen::{prelude::*, Clamped, JsCast};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
+
+use crate::model::Channel;
+
+// Signals longer than this are drawn on a canvas instead of as SVG paths, which take too long to
+// lay out and paint once they reach hundreds of thousands of points.
+pub const MIN_SAMPLES: usize = 100_000;
+
+// Colours from low to high intensity, evenly spaced, for heatmaps.
+const COLOR_MAP: [[f64; 3]; 5] = [
+    [11.0, 11.0, 30.0],
+    [63.0, 167.0, 214.0],
+    [59.0, 206.0, 172.0],
+    [255.0, 210.0, 63.0],
+    [238.0, 66.0, 102.0],
+];
+
+pub struct Trace<'a> {
+    pub channel: &'a Channel,
+    // Factor applied to each sample, to bring it onto the amplitude axis of the plot.
+    pub scale: f64,
+    pub color: &'static str,
+}
+
+// Draws the part of each trace within `view`. `x` and `y` map a sample position and a scaled
+// amplitude to a fraction of the canvas width and height. When there are several samples per
+// pixel, each column is drawn as a vertical line between its extremes, so that no peaks are lost.
+pub fn draw_waveform(
+    canvas: &HtmlCanvasElement,
+    traces: &[Trace],
+    view: Range<usize>,
+    x: impl Fn(f64) -> f64,
+    y: impl Fn(f64) -> f64,
+) -> Result<(), JsValue> {
+    let ratio = gloo::utils::window().device_pixel_ratio();
+    let width = (f64::from(canvas.client_width()) * ratio).round();
+    let height = (f64::from(canvas.client_height()) * ratio).round();
+    canvas.set_width(width as u32);
+    canvas.set_height(height as u32);
+
+    let context: CanvasRenderingContext2d = canvas
+        .get_context("2d")?
+        .ok_or("canvas has no 2D context")?
+        .unchecked_into();
+    context.clear_rect(0.0, 0.0, width, height);
+    context.set_line_width(ratio);
+
+    let samples_per_pixel = ((x(view.start as f64 + 1.0) - x(view.start as f64)) * width).recip();
+    for Trace {
+        channel,
+        scale,
+        color,
+    } in traces
+    {
+        context.set_stroke_style_str(color);
+        context.begin_path();
+
+        let slice = channel.slice(view.clone());
+        let samples = slice
+            .iter()
+            .enumerate()
+            .map(|(i, sample)| (view.start + i, y(f64::from(sample) * scale) * height));
+        if samples_per_pixel <= 1.0 {
+            for (i, sample_y) in samples {
+                context.line_to(x(i as f64) * width, sample_y);
+            }
+        } else {
+            let mut column = None;
+            let mut extremes = (f64::INFINITY, f64::NEG_INFINITY);
+            for (i, sample_y) in samples {
+                let sample_column = (x(i as f64) * width).floor();
+                if column != Some(sample_column) {
+                    if let Some(column) = column {
+                        context.line_to(column, extremes.0);
+                        context.line_to(column, extremes.1);
+                    }
+                    column = Some(sample_column);
+                    extremes = (f64::INFINITY, f64::NEG_INFINITY);
+                }
+                extremes = (extremes.0.min(sample_y), extremes.1.max(sample_y));
+            }
+            if let Some(column) = column {
+                context.line_to(column, extremes.0);
+                context.line_to(column, extremes.1);
+            }
+        }
+
+        context.stroke();
+    }
+
+    Ok(())
+}
+
+// Plots points, given as fractions of the canvas width and height, as translucent dots, so that
+// where they overlap stands out.
+pub fn draw_points(
+    canvas: &HtmlCanvasElement,
+    points: impl IntoIterator<Item = (f64, f64)>,
+    color: &str,
+) -> Result<(), JsValue> {
+    let ratio = gloo::utils::window().device_pixel_ratio();
+    let width = (f64::from(canvas.client_width()) * ratio).round();
+    let height = (f64::from(canvas.client_height()) * ratio).round();
+    canvas.set_width(width as u32);
+    canvas.set_height(height as u32);
+
+    let context: CanvasRenderingContext2d = canvas
+        .get_context("2d")?
+        .ok_or("canvas has no 2D context")?
+        .unchecked_into();
+    context.clear_rect(0.0, 0.0, width, height);
+    context.set_fill_style_str(color);
+    context.set_global_alpha(0.4);
+    for (x, y) in points {
+        context.fill_rect(x * width, y * height, ratio, ratio);
+    }
+
+    Ok(())
+}
+
+// Draws a curve downsampled to ranges, each given as a fraction of the canvas width and the
+// fractions of its height between which the curve went, as vertical lines joined end to end so that
+// no peaks are lost.
+#[cfg_attr(not(feature = "server"), allow(dead_code))]
+pub fn draw_ranges(
+    canvas: &HtmlCanvasElement,
+    ranges: impl IntoIterator<Item = (f64, f64, f64)>,
+    color: &str,
+) -> Result<(), JsValue> {
+    let ratio = gloo::utils::window().device_pixel_ratio();
+    let width = (f64::from(canvas.client_width()) * ratio).round();
+    let height = (f64::from(canvas.client_height()) * ratio).round();
+    canvas.set_width(width as u32);
+    canvas.set_height(height as u32);
+
+    let context: CanvasRenderingContext2d = canvas
+        .get_context("2d")?
+        .ok_or("canvas has no 2D context")?
+        .unchecked_into();
+    context.clear_rect(0.0, 0.0, width, height);
+    context.set_line_width(ratio);
+    context.set_stroke_style_str(color);
+    context.begin_path();
+    for (x, low, high) in ranges {
+        context.line_to(x * width, low * height);
+        context.line_to(x * width, high * height);
+    }
+    context.stroke();
+
+    Ok(())
+}
+
+// Draws a `width` by `height` grid of cells, coloured by an intensity between 0 and 1. The canvas
+// gets one pixel per cell and is left for the stylesheet to stretch.
+pub fn draw_heatmap(
+    canvas: &HtmlCanvasElement,
+    width: usize,
+    height: usize,
+    intensity: impl Fn(usize, usize) -> f64,
+) -> Result<(), JsValue> {
+    canvas.set_width(width as u32);
+    canvas.set_height(height as u32);
+
+    let context: CanvasRenderingContext2d = canvas
+        .get_context("2d")?
+        .ok_or("canvas has no 2D context")?
+        .unchecked_into();
+
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        for x in 0..width {
+            let position = intensity(x, y).clamp(0.0, 1.0) * (COLOR_MAP.len() - 1) as f64;
+            let low = (position.floor() as usize).min(COLOR_MAP.len() - 2);
+            let fraction = position - low as f64;
+            let (from, to) = (COLOR_MAP[low], COLOR_MAP[low + 1]);
+            pixels.extend((0..3).map(|i| (from[i] + (to[i] - from[i]) * fraction).round() as u8));
+            pixels.push(u8::MAX);
+        }
+    }
+
+    let image =
+        ImageData::new_with_u8_clamped_array_and_sh(Clamped(&pixels), width as u32, height as u32)?;
+    context.put_image_data(&image, 0.0, 0.0)
+}
@@ -0,0 +1,117 @@
+use std::{cell::RefCell, rc::Rc};
+
+use js_sys::{Object, Promise, Reflect};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    AudioContext, AudioContextOptions, AudioProcessingEvent, AudioScheduledSourceNode, MediaStream,
+    MediaStreamConstraints, MediaStreamTrack, ScriptProcessorNode,
+};
+
+use crate::model::Channel;
+
+// Samples recorded per callback.
+const BUFFER_LEN: u32 = 4096;
+
+// The microphone and the audio graph recording from it, released when dropped.
+struct Session {
+    context: AudioContext,
+    stream: MediaStream,
+    processor: ScriptProcessorNode,
+    recorded: Rc<RefCell<Vec<f32>>>,
+    _on_audio: Closure<dyn FnMut(AudioProcessingEvent)>,
+}
+
+impl Session {
+    fn new(stream: MediaStream, sample_rate: u32) -> Result<Self, JsValue> {
+        let options = AudioContextOptions::new();
+        options.set_sample_rate(sample_rate as f32);
+        let context = AudioContext::new_with_context_options(&options)?;
+
+        let processor = context
+            .create_script_processor_with_buffer_size_and_number_of_input_channels_and_number_of_output_channels(
+                BUFFER_LEN, 1, 1,
+            )?;
+        let recorded = Rc::new(RefCell::new(Vec::new()));
+        let on_audio = {
+            let recorded = recorded.clone();
+            Closure::<dyn FnMut(AudioProcessingEvent)>::new(move |event: AudioProcessingEvent| {
+                if let Ok(samples) = event
+                    .input_buffer()
+                    .and_then(|buffer| buffer.get_channel_data(0))
+                {
+                    recorded.borrow_mut().extend(samples);
+                }
+            })
+        };
+        processor.set_onaudioprocess(Some(on_audio.as_ref().unchecked_ref()));
+
+        context
+            .create_media_stream_source(&stream)?
+            .connect_with_audio_node(&processor)?;
+        // The processor only runs while connected to the output, to which it writes silence.
+        processor.connect_with_audio_node(&context.destination())?;
+
+        Ok(Self {
+            context,
+            stream,
+            processor,
+            recorded,
+            _on_audio: on_audio,
+        })
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.processor.set_onaudioprocess(None);
+        for track in self.stream.get_tracks() {
+            track.unchecked_into::<MediaStreamTrack>().stop();
+        }
+        let _ = self.context.close();
+    }
+}
+
+// Asks for the raw input, without the processing meant for voice calls, which would distort the
+// measurement.
+fn audio_constraints() -> Result<MediaStreamConstraints, JsValue> {
+    let audio = Object::new();
+    for name in ["echoCancellation", "noiseSuppression", "autoGainControl"] {
+        Reflect::set(&audio, &name.into(), &false.into())?;
+    }
+    let constraints = MediaStreamConstraints::new();
+    constraints.set_audio(&audio);
+    Ok(constraints)
+}
+
+// Plays `stimulus` while recording the default input device, both through the same audio context
+// so that they run off the same clock. Recording continues for `tail` seconds after the stimulus,
+// to capture the latency and decay of the system. Dropping the future before it completes stops
+// the measurement and releases the microphone.
+pub async fn measure(stimulus: &Channel, tail: f64) -> Result<Channel, JsValue> {
+    let sample_rate = stimulus.sample_rate();
+    let devices = gloo::utils::window().navigator().media_devices()?;
+    let stream: MediaStream =
+        JsFuture::from(devices.get_user_media_with_constraints(&audio_constraints()?)?)
+            .await?
+            .dyn_into()?;
+    let session = Session::new(stream, sample_rate)?;
+    let context = &session.context;
+
+    let len = stimulus.count() + (tail * f64::from(sample_rate)) as usize;
+    let buffer = context.create_buffer(1, len.max(1) as u32, sample_rate as f32)?;
+    let samples: Vec<f32> = stimulus.iter_normalized().map(|x| x as f32).collect();
+    buffer.copy_to_channel(&samples, 0)?;
+
+    let source = context.create_buffer_source()?;
+    source.set_buffer(Some(&buffer));
+    source.connect_with_audio_node(&context.destination())?;
+    let ended = Promise::new(&mut |resolve, _| {
+        AudioScheduledSourceNode::set_onended(&source, Some(&resolve));
+    });
+    source.start()?;
+    JsFuture::from(ended).await?;
+
+    let recorded = session.recorded.take();
+    Ok(Channel::from_samples_f32(recorded, 32, sample_rate))
+}
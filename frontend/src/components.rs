@@ -0,0 +1,122 @@
+use std::str::FromStr;
+
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::{model::Signal, source, task::TaskSlot};
+
+pub mod bands;
+pub mod batch;
+pub mod bits;
+pub mod calibration;
+pub mod changelog;
+pub mod comparison;
+pub mod decay;
+pub mod decimation;
+pub mod detector;
+pub mod dynamics;
+pub mod filter;
+pub mod gain_staging;
+pub mod generator;
+pub mod goniometer;
+pub mod layout;
+pub mod levels;
+#[cfg(feature = "server")]
+pub mod login;
+pub mod ltas;
+pub mod markers;
+pub mod measurement;
+pub mod metadata;
+pub mod palette;
+pub mod pitch;
+pub mod playback;
+pub mod presets;
+pub mod processing;
+pub mod raw;
+pub mod selection_export;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "server")]
+pub mod sessions;
+pub mod silence;
+pub mod sound_level;
+pub mod spectrum;
+pub mod speech;
+pub mod statistics;
+pub mod telemetry;
+pub mod transfer;
+pub mod waterfall;
+
+pub use bands::BandEnergyPanel;
+pub use batch::BatchPanel;
+pub use bits::BitDepthPanel;
+pub use calibration::CalibrationPanel;
+pub use changelog::ChangeLogPanel;
+pub use comparison::{ComparisonMode, ComparisonPanel};
+pub use decay::{DecayPanel, DecayView};
+pub use decimation::LargeFilePrompt;
+pub use detector::PatternDetector;
+pub use dynamics::DynamicsPanel;
+pub use filter::FilterPanel;
+pub use gain_staging::GainStagingPanel;
+pub use generator::GeneratorPanel;
+pub use goniometer::GoniometerPanel;
+pub use layout::SampleLayoutSelect;
+pub use levels::{LevelView, LevelsPanel};
+#[cfg(feature = "server")]
+pub use login::LoginScreen;
+pub use ltas::LtasView;
+pub use markers::MarkersPanel;
+pub use measurement::MeasurementPanel;
+pub use metadata::MetadataPanel;
+pub use palette::CommandPalette;
+pub use pitch::{PitchPanel, PitchTrackView};
+pub use playback::PlaybackPanel;
+pub use presets::PresetsPanel;
+pub use processing::ProcessingPanel;
+pub use raw::RawImportPrompt;
+pub use selection_export::SelectionExportPanel;
+#[cfg(feature = "server")]
+pub use server::ServerJobNotice;
+#[cfg(feature = "server")]
+pub use sessions::SessionsPanel;
+pub use silence::SilencePanel;
+pub use sound_level::SoundLevelPanel;
+pub use spectrum::SpectrumPanel;
+pub use speech::SpeechPanel;
+pub use statistics::StatisticsPanel;
+pub use telemetry::StatusLine;
+pub use transfer::{TransferPanel, TransferView};
+pub use waterfall::WaterfallView;
+
+// Reads the file picked in an input, replacing any read still in progress in `task`.
+fn read_signal(event: &Event, task: &TaskSlot, on_loaded: Callback<Signal>) {
+    let Some(file) = event
+        .target_unchecked_into::<HtmlInputElement>()
+        .files()
+        .and_then(|files| files.get(0))
+    else {
+        return;
+    };
+    task.spawn(async move {
+        match source::read_file(&file).await {
+            Ok(signal) => on_loaded.emit(signal),
+            Err(error) => crate::log(&error),
+        }
+    });
+}
+
+fn input_value<T: FromStr>(event: &Event) -> Option<T> {
+    event
+        .target_unchecked_into::<HtmlInputElement>()
+        .value()
+        .parse()
+        .ok()
+}
+
+fn select_index(event: &Event) -> usize {
+    event
+        .target_unchecked_into::<HtmlSelectElement>()
+        .selected_index()
+        .max(0) as usize
+}
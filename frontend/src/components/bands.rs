@@ -0,0 +1,167 @@
+use std::ops::Range;
+
+use web_sys::{HtmlDetailsElement, HtmlInputElement};
+use yew::prelude::*;
+
+use crate::{model::Channel, settings::BandSettings};
+
+#[derive(Properties, PartialEq)]
+pub struct BandEnergyPanelProps {
+    pub channel: Channel,
+    // The selection, or else the visible part of the channel.
+    pub region: Range<usize>,
+    pub settings: BandSettings,
+    pub on_settings: Callback<BandSettings>,
+}
+
+// Edge frequencies in Hz, with "k" for kHz, e.g. "20, 60, 250, 2k".
+fn parse_edges(text: &str) -> Option<Vec<f64>> {
+    let edges = text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|edge| !edge.is_empty())
+        .map(|edge| match edge.strip_suffix(['k', 'K']) {
+            Some(khz) => khz.parse::<f64>().ok().map(|f| f * 1000.0),
+            None => edge.parse().ok(),
+        })
+        .collect::<Option<Vec<f64>>>()?;
+    let ascending = edges.windows(2).all(|pair| pair[0] < pair[1]);
+    (edges.len() >= 2 && ascending && edges[0] >= 0.0).then_some(edges)
+}
+
+fn format_edge(frequency: f64) -> String {
+    if frequency >= 1000.0 {
+        format!("{}k", frequency / 1000.0)
+    } else {
+        frequency.to_string()
+    }
+}
+
+// Share of the energy and level in each of a set of frequency bands for the selected region, for
+// quick checks of the tonal balance, and the RMS level within a single band, such as how much there
+// is below 40 Hz.
+#[function_component(BandEnergyPanel)]
+pub fn band_energy_panel(
+    BandEnergyPanelProps {
+        channel,
+        region,
+        settings,
+        on_settings,
+    }: &BandEnergyPanelProps,
+) -> Html {
+    let open = use_state(|| false);
+
+    let bands = use_memo(
+        |(channel, region, settings, open)| {
+            open.then(|| {
+                bench!(["Calculating band energies"] => {
+                    channel.slice(region.clone()).band_energies(&settings.edges)
+                })
+            })
+        },
+        (channel.clone(), region.clone(), settings.clone(), *open),
+    );
+    let band_rms = use_memo(
+        |(channel, region, lower, upper, open)| {
+            open.then(|| {
+                bench!(["Calculating band RMS"] => {
+                    channel.slice(region.clone()).band_rms_dbfs(*lower, *upper)
+                })
+            })
+        },
+        (
+            channel.clone(),
+            region.clone(),
+            settings.rms_lower,
+            settings.rms_upper,
+            *open,
+        ),
+    );
+
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |event: Event| {
+            open.set(event.target_unchecked_into::<HtmlDetailsElement>().open())
+        })
+    };
+    let on_edges = {
+        let (settings, on_settings) = (settings.clone(), on_settings.clone());
+        Callback::from(move |event: Event| {
+            let text = event.target_unchecked_into::<HtmlInputElement>().value();
+            if let Some(edges) = parse_edges(&text) {
+                on_settings.emit(BandSettings {
+                    edges,
+                    ..settings.clone()
+                });
+            }
+        })
+    };
+    let on_rms_band = |upper: bool| {
+        let (settings, on_settings) = (settings.clone(), on_settings.clone());
+        Callback::from(move |event: Event| {
+            let text = event.target_unchecked_into::<HtmlInputElement>().value();
+            let Some(frequency) = text.parse::<f64>().ok().filter(|f| *f >= 0.0) else {
+                return;
+            };
+            let mut settings = settings.clone();
+            if upper {
+                settings.rms_upper = frequency.max(settings.rms_lower);
+            } else {
+                settings.rms_lower = frequency.min(settings.rms_upper);
+            }
+            on_settings.emit(settings);
+        })
+    };
+
+    let edges = settings
+        .edges
+        .iter()
+        .map(|&edge| format_edge(edge))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let table = (*bands).as_ref().map(|bands| {
+        html! {
+            <table class="statistics">
+                { for bands.iter().map(|band| html! {
+                    <tr>
+                        <td>{format!("{}–{} Hz", format_edge(band.lower), format_edge(band.upper))}</td>
+                        <td><meter value={band.share.to_string()} /></td>
+                        <td>{format!("{:.1} %", band.share * 100.0)}</td>
+                        <td>{format!("{:.1} dBFS", band.level_db)}</td>
+                    </tr>
+                }) }
+            </table>
+        }
+    });
+
+    html! {
+        <details class="panel" ontoggle={on_toggle}>
+            <summary>{"Band energy"}</summary>
+            <label>
+                {"Band edges (Hz)"}
+                <input class="edges" type="text" value={edges} onchange={on_edges} />
+            </label>
+            {table}
+            <label>
+                {"RMS from (Hz)"}
+                <input type="number" min="0" step="any"
+                    value={settings.rms_lower.to_string()}
+                    onchange={on_rms_band(false)} />
+            </label>
+            <label>
+                {"RMS to (Hz)"}
+                <input type="number" min="0" step="any"
+                    value={settings.rms_upper.to_string()}
+                    onchange={on_rms_band(true)} />
+            </label>
+            if let Some(level) = *band_rms {
+                <p>
+                    {format!(
+                        "RMS between {} and {} Hz: {level:.1} dBFS",
+                        format_edge(settings.rms_lower),
+                        format_edge(settings.rms_upper),
+                    )}
+                </p>
+            }
+        </details>
+    }
+}
@@ -0,0 +1,199 @@
+use std::{cmp::Ordering, rc::Rc};
+
+use gloo::file::{callbacks::FileReader, File};
+use web_sys::{HtmlDetailsElement, HtmlInputElement};
+use yew::prelude::*;
+
+use crate::model::{metrics::Metrics, Signal};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Name,
+    Duration,
+    Loudness,
+    TruePeak,
+    Centroid,
+    NoiseFloor,
+}
+
+impl Column {
+    const ALL: [Self; 6] = [
+        Self::Name,
+        Self::Duration,
+        Self::Loudness,
+        Self::TruePeak,
+        Self::Centroid,
+        Self::NoiseFloor,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Name => "File",
+            Self::Duration => "Duration (s)",
+            Self::Loudness => "Loudness (LUFS)",
+            Self::TruePeak => "True peak (dBTP)",
+            Self::Centroid => "Centroid (Hz)",
+            Self::NoiseFloor => "Noise floor (dBFS)",
+        }
+    }
+
+    fn compare(self, (a_name, a): &(String, Metrics), (b_name, b): &(String, Metrics)) -> Ordering {
+        let value = |metrics: &Metrics| match self {
+            Self::Name => 0.0,
+            Self::Duration => metrics.duration,
+            // Files too quiet to be measured sort as the quietest.
+            Self::Loudness => metrics.loudness.unwrap_or(f64::NEG_INFINITY),
+            Self::TruePeak => metrics.true_peak_dbtp,
+            Self::Centroid => metrics.centroid,
+            Self::NoiseFloor => metrics.noise_floor_dbfs,
+        };
+        match self {
+            Self::Name => a_name.cmp(b_name),
+            _ => value(a).total_cmp(&value(b)),
+        }
+    }
+}
+
+#[derive(Default, PartialEq)]
+struct Batch {
+    files: Vec<(String, Metrics)>,
+}
+
+enum BatchAction {
+    Add(String, Metrics),
+    Clear,
+}
+
+impl Reducible for Batch {
+    type Action = BatchAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        match action {
+            BatchAction::Add(name, metrics) => {
+                let mut files = self.files.clone();
+                files.push((name, metrics));
+                Rc::new(Self { files })
+            }
+            BatchAction::Clear => Rc::new(Self::default()),
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct BatchPanelProps {
+    pub signal: Signal,
+    pub name: Option<String>,
+}
+
+// Key metrics of the current signal and any number of additional files side by side, so that a
+// whole delivery batch can be validated at once.
+#[function_component(BatchPanel)]
+pub fn batch_panel(BatchPanelProps { signal, name }: &BatchPanelProps) -> Html {
+    let open = use_state(|| false);
+    let batch = use_reducer(Batch::default);
+    let file_readers = use_mut_ref(Vec::<FileReader>::new);
+    let sort = use_state(|| (Column::Name, true));
+
+    let current = use_memo(
+        |(signal, open)| open.then(|| bench!(["Calculating batch metrics"] => signal.metrics())),
+        (signal.clone(), *open),
+    );
+
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |event: Event| {
+            open.set(event.target_unchecked_into::<HtmlDetailsElement>().open())
+        })
+    };
+    let on_add = {
+        let batch = batch.clone();
+        let file_readers = file_readers.clone();
+        Callback::from(move |event: Event| {
+            let Some(files) = event.target_unchecked_into::<HtmlInputElement>().files() else {
+                return;
+            };
+            for file in (0..files.length()).filter_map(|i| files.get(i)) {
+                let name = file.name();
+                let batch = batch.clone();
+                let reader = gloo::file::callbacks::read_as_bytes(&File::from(file), move |res| {
+                    match res.map(Signal::from_wav) {
+                        Ok(Ok(signal)) => batch.dispatch(BatchAction::Add(
+                            name,
+                            bench!(["Calculating batch metrics"] => signal.metrics()),
+                        )),
+                        Ok(Err(error)) => crate::log(&format!("failed to decode {name}: {error}")),
+                        Err(error) => crate::log(&format!("failed to read {name}: {error}")),
+                    }
+                });
+                file_readers.borrow_mut().push(reader);
+            }
+        })
+    };
+    let on_clear = {
+        let batch = batch.clone();
+        Callback::from(move |_| {
+            file_readers.borrow_mut().clear();
+            batch.dispatch(BatchAction::Clear);
+        })
+    };
+    let on_sort = |column: Column| {
+        let sort = sort.clone();
+        Callback::from(move |_| {
+            let (sorted_by, ascending) = *sort;
+            sort.set((column, sorted_by != column || !ascending));
+        })
+    };
+
+    let mut rows: Vec<(String, Metrics)> = (*current)
+        .map(|metrics| {
+            let name = name.clone().unwrap_or_else(|| "Current signal".to_owned());
+            (name, metrics)
+        })
+        .into_iter()
+        .chain(batch.files.iter().cloned())
+        .collect();
+    let (sorted_by, ascending) = *sort;
+    rows.sort_by(|a, b| {
+        let ordering = sorted_by.compare(a, b);
+        if ascending {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    });
+
+    html! {
+        <details class="panel" ontoggle={on_toggle}>
+            <summary>{"Batch"}</summary>
+            <label class="button" for="add-batch-files">{"Add files"}</label>
+            <input id="add-batch-files" type="file" accept=".wav,.aif,.aiff,.aifc" multiple=true onchange={on_add} />
+            if !batch.files.is_empty() {
+                <button onclick={on_clear}>{"Clear"}</button>
+            }
+            if *open {
+                <table class="batch">
+                    <tr>
+                        { for Column::ALL.into_iter().map(|column| html! {
+                            <th onclick={on_sort(column)}>
+                                {column.name()}
+                                if column == sorted_by {
+                                    {if ascending { " ▲" } else { " ▼" }}
+                                }
+                            </th>
+                        }) }
+                    </tr>
+                    { for rows.iter().map(|(name, metrics)| html! {
+                        <tr>
+                            <td>{name}</td>
+                            <td>{format!("{:.2}", metrics.duration)}</td>
+                            <td>{metrics.loudness.map_or("–".to_owned(), |l| format!("{l:.1}"))}</td>
+                            <td>{format!("{:.1}", metrics.true_peak_dbtp)}</td>
+                            <td>{format!("{:.0}", metrics.centroid)}</td>
+                            <td>{format!("{:.1}", metrics.noise_floor_dbfs)}</td>
+                        </tr>
+                    }) }
+                </table>
+            }
+        </details>
+    }
+}
@@ -0,0 +1,74 @@
+use web_sys::HtmlDetailsElement;
+use yew::prelude::*;
+
+use crate::model::Channel;
+
+#[derive(Properties, PartialEq)]
+pub struct BitDepthPanelProps {
+    pub channel: Channel,
+}
+
+// Bar chart of how often each bit of the samples is set, from the most significant bit on the left
+// to the least significant on the right, along with the bit depth the content actually uses. Bits
+// that are never set at the bottom are padding, and a drop in activity above them suggests
+// truncated dither.
+#[function_component(BitDepthPanel)]
+pub fn bit_depth_panel(BitDepthPanelProps { channel }: &BitDepthPanelProps) -> Html {
+    let open = use_state(|| false);
+
+    let activity = use_memo(
+        |(channel, open)| {
+            open.then(|| bench!(["Calculating bit activity"] => channel.bit_activity()))
+                .flatten()
+        },
+        (channel.clone(), *open),
+    );
+
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |event: Event| {
+            open.set(event.target_unchecked_into::<HtmlDetailsElement>().open())
+        })
+    };
+
+    let chart = match &*activity {
+        Some(activity) => {
+            let bits = activity.activity.len();
+            let bars = activity
+                .activity
+                .iter()
+                .rev()
+                .enumerate()
+                .map(|(x, share)| {
+                    html! {
+                        <rect
+                            x={format!("{:.2}", x as f64 + 0.1)}
+                            y={format!("{:.4}", 1.0 - share)}
+                            width="0.8"
+                            height={format!("{share:.4}")} />
+                    }
+                });
+            html! {
+                <>
+                    <svg class="bits" viewBox={format!("0 0 {bits} 1")} preserveAspectRatio="none">
+                        <path vector-effect="non-scaling-stroke" d={format!("M 0 0.5 L {bits} 0.5")} />
+                        { for bars }
+                    </svg>
+                    <p>{format!("Bits set, most significant first ({bits} bits)")}</p>
+                    <p class={classes!((usize::from(activity.effective_bits) < bits).then_some("warning"))}>
+                        {format!("Effective bit depth: {} bits", activity.effective_bits)}
+                    </p>
+                </>
+            }
+        }
+        None if *open => html! { <p>{"Floating-point samples have no fixed bit depth."}</p> },
+        None => html!(),
+    };
+
+    html! {
+        <details class="panel" ontoggle={on_toggle}>
+            <summary>{"Bit depth"}</summary>
+            {chart}
+        </details>
+    }
+}
@@ -0,0 +1,76 @@
+use gloo::file::{callbacks::FileReader, File};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::model::calibration::Calibration;
+
+#[derive(Properties, PartialEq)]
+pub struct CalibrationPanelProps {
+    pub calibration: Option<Calibration>,
+    pub on_calibration: Callback<Option<Calibration>>,
+}
+
+// Loads the calibration file of a measurement microphone, which the drawn spectra and exported
+// responses are corrected by.
+#[function_component(CalibrationPanel)]
+pub fn calibration_panel(
+    CalibrationPanelProps {
+        calibration,
+        on_calibration,
+    }: &CalibrationPanelProps,
+) -> Html {
+    let file_reader = use_mut_ref(|| None::<FileReader>);
+
+    let on_load = {
+        let on_calibration = on_calibration.clone();
+        Callback::from(move |event: Event| {
+            let Some(file) = event
+                .target_unchecked_into::<HtmlInputElement>()
+                .files()
+                .and_then(|files| files.get(0))
+            else {
+                return;
+            };
+            let name = file.name();
+            let on_calibration = on_calibration.clone();
+            let reader =
+                gloo::file::callbacks::read_as_text(&File::from(file), move |res| {
+                    match res.map(|text| Calibration::parse(&name, &text)) {
+                        Ok(Some(calibration)) => on_calibration.emit(Some(calibration)),
+                        Ok(None) => crate::log(&format!("no calibration points in {name}")),
+                        Err(error) => crate::log(&format!("failed to read {name}: {error}")),
+                    }
+                });
+            *file_reader.borrow_mut() = Some(reader);
+        })
+    };
+
+    let summary = calibration.as_ref().map(|calibration| {
+        let (low, _) = calibration.points[0];
+        let (high, _) = calibration.points[calibration.points.len() - 1];
+        html! {
+            <>
+                <p>{format!(
+                    "{}: {} points from {low:.0} to {high:.0} Hz",
+                    calibration.name,
+                    calibration.points.len(),
+                )}</p>
+                if let Some(sensitivity) = calibration.sensitivity_db {
+                    <p>{format!("Sensitivity factor {sensitivity:+.2} dB")}</p>
+                }
+            </>
+        }
+    });
+
+    html! {
+        <details class="panel">
+            <summary>{"Microphone calibration"}</summary>
+            <label class="button" for="load-calibration-file">{"Load calibration file"}</label>
+            <input id="load-calibration-file" type="file" accept=".txt,.cal,.frd,.csv" onchange={on_load} />
+            {summary}
+            if calibration.is_some() {
+                <button onclick={on_calibration.reform(|_| None)}>{"Remove"}</button>
+            }
+        </details>
+    }
+}
@@ -0,0 +1,61 @@
+use web_sys::HtmlDetailsElement;
+use yew::prelude::*;
+
+use crate::session::{ChangeLogEntry, SessionState};
+
+#[derive(Properties, PartialEq)]
+pub struct ChangeLogPanelProps {
+    pub change_log: Vec<ChangeLogEntry>,
+    pub session: SessionState,
+    // The session as last saved, if it has been.
+    pub saved: Option<SessionState>,
+}
+
+// What changed each time the session was exported, and what has changed since, for tracing how
+// the results in a measurement report were arrived at.
+#[function_component(ChangeLogPanel)]
+pub fn change_log_panel(
+    ChangeLogPanelProps {
+        change_log,
+        session,
+        saved,
+    }: &ChangeLogPanelProps,
+) -> Html {
+    let open = use_state(|| false);
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |event: Event| {
+            open.set(event.target_unchecked_into::<HtmlDetailsElement>().open())
+        })
+    };
+    let unsaved = use_memo(
+        |(open, session, saved)| match saved {
+            Some(saved) if *open => session.changes_since(saved),
+            _ => Vec::new(),
+        },
+        (*open, session.clone(), saved.clone()),
+    );
+
+    html! {
+        <details class="panel" ontoggle={on_toggle}>
+            <summary>{"Change log"}</summary>
+            if change_log.is_empty() {
+                <p>{"Changes are logged each time the session is exported."}</p>
+            }
+            if !unsaved.is_empty() {
+                <p>{"Not yet saved"}</p>
+                <ul>
+                    { for unsaved.iter().map(|change| html! { <li>{change}</li> }) }
+                </ul>
+            }
+            { for change_log.iter().rev().map(|entry| html! {
+                <>
+                    <p>{format!("Saved {}", entry.saved_at)}</p>
+                    <ul>
+                        { for entry.changes.iter().map(|change| html! { <li>{change}</li> }) }
+                    </ul>
+                </>
+            }) }
+        </details>
+    }
+}
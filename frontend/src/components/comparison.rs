@@ -0,0 +1,115 @@
+use yew::prelude::*;
+
+use super::{read_signal, select_index};
+use crate::{
+    hooks::use_task_slot,
+    model::{null_test::NullTest, Signal},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonMode {
+    Overlay,
+    Difference,
+    NullTest,
+}
+
+impl ComparisonMode {
+    pub const ALL: [Self; 3] = [Self::Overlay, Self::Difference, Self::NullTest];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Overlay => "Overlay",
+            Self::Difference => "Difference (A − B)",
+            Self::NullTest => "Null test (aligned A − B)",
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ComparisonPanelProps {
+    pub signals: Vec<Signal>,
+    pub mode: ComparisonMode,
+    pub null_test: Option<NullTest>,
+    // Why the difference is partial or cannot be taken, in difference mode.
+    pub difference_notice: Option<String>,
+    pub on_compare: Callback<Option<Signal>>,
+    pub on_mode: Callback<ComparisonMode>,
+}
+
+#[function_component(ComparisonPanel)]
+pub fn comparison_panel(
+    ComparisonPanelProps {
+        signals,
+        mode,
+        null_test,
+        difference_notice,
+        on_compare,
+        on_mode,
+    }: &ComparisonPanelProps,
+) -> Html {
+    let loading = use_task_slot();
+
+    let on_load = {
+        let on_compare = on_compare.clone();
+        Callback::from(move |event: Event| {
+            let on_loaded = on_compare.reform(Some);
+            read_signal(&event, &loading, on_loaded);
+        })
+    };
+    let on_clear = on_compare.reform(|_| None);
+    let on_select_mode = on_mode.reform(|event: Event| ComparisonMode::ALL[select_index(&event)]);
+
+    let legend = signals
+        .iter()
+        .zip(["a", "b"])
+        .map(|(signal, name)| {
+            let channel = signal.channel(0);
+            html! {
+                <li class={classes!("legend", name)}>
+                    {format!(
+                        "{} — {} Hz, {:.2} s",
+                        name.to_uppercase(),
+                        channel.sample_rate(),
+                        channel.count() as f64 / channel.sample_rate() as f64,
+                    )}
+                </li>
+            }
+        })
+        .collect::<Html>();
+
+    let sample_rate = signals[0].channel(0).sample_rate() as f64;
+
+    html! {
+        <details class="panel" open=true>
+            <summary>{"Comparison"}</summary>
+            <ul class="legend">{legend}</ul>
+            <label class="button" for="load-comparison-file">{"Load comparison file"}</label>
+            <input id="load-comparison-file" type="file" accept=".wav,.aif,.aiff,.aifc" onchange={on_load} />
+            if signals.len() > 1 {
+                <label>
+                    {"Mode"}
+                    <select onchange={on_select_mode}>
+                        { for ComparisonMode::ALL.into_iter().map(|m| html! {
+                            <option selected={m == *mode}>{m.name()}</option>
+                        }) }
+                    </select>
+                </label>
+                if let Some(notice) = difference_notice {
+                    <div class="notice">{notice}</div>
+                }
+                if let Some(null_test) = null_test {
+                    <ul>
+                        <li>{format!(
+                            "Lag: {} samples ({:.2} ms)",
+                            null_test.lag,
+                            null_test.lag as f64 / sample_rate * 1000.0,
+                        )}</li>
+                        <li>{format!("Residual RMS: {:.1} dBFS", null_test.residual_dbfs)}</li>
+                        <li>{format!("Null depth: {:.1} dB", null_test.rejection_db)}</li>
+                    </ul>
+                }
+                <button onclick={on_clear}>{"Remove B"}</button>
+            }
+        </details>
+    }
+}
@@ -0,0 +1,179 @@
+use yew::prelude::*;
+
+use super::input_value;
+use crate::{
+    model::{decay::RoomDecay, Channel},
+    settings::DecaySettings,
+};
+
+// The most points each decay curve is drawn with.
+const MAX_POINTS: usize = 1000;
+
+#[derive(Properties, PartialEq)]
+pub struct DecayPanelProps {
+    // The impulse response, which is the selection when there is one.
+    pub channel: Channel,
+    pub settings: DecaySettings,
+    pub on_settings: Callback<DecaySettings>,
+    pub on_decay: Callback<Option<RoomDecay>>,
+}
+
+// Impulse response analysis: the reverberation times of the response in each octave band, with
+// its energy decay curves drawn in place of the spectrum.
+#[function_component(DecayPanel)]
+pub fn decay_panel(
+    DecayPanelProps {
+        channel,
+        settings,
+        on_settings,
+        on_decay,
+    }: &DecayPanelProps,
+) -> Html {
+    let decay = use_memo(
+        |(channel, enabled)| {
+            enabled.then(|| bench!(["Calculating energy decay"] => channel.room_decay()))
+        },
+        (channel.clone(), settings.enabled),
+    );
+
+    {
+        let on_decay = on_decay.clone();
+        use_effect_with_deps(move |decay| on_decay.emit((**decay).clone()), decay.clone());
+    }
+
+    let settings = *settings;
+    let on_toggle = on_settings.reform(move |_| DecaySettings {
+        enabled: !settings.enabled,
+        ..settings
+    });
+    let on_range = on_settings.reform(move |event: Event| DecaySettings {
+        range_db: input_value(&event).map_or(settings.range_db, |r: f64| r.clamp(10.0, 120.0)),
+        ..settings
+    });
+
+    let seconds = |time: Option<f64>| time.map_or_else(|| "–".to_owned(), |t| format!("{t:.2} s"));
+    let table = (*decay).as_ref().map(|decay| {
+        html! {
+            <table class="statistics">
+                <tr>
+                    <th>{"Band"}</th>
+                    <th>{"EDT"}</th>
+                    <th>{"T20"}</th>
+                    <th>{"T30"}</th>
+                    <th>{"RT60"}</th>
+                </tr>
+                { for decay.bands.iter().map(|band| html! {
+                    <tr>
+                        <td>{band.center.map_or_else(|| "All".to_owned(), |c| format!("{c:.0} Hz"))}</td>
+                        <td>{seconds(band.times.edt)}</td>
+                        <td>{seconds(band.times.t20)}</td>
+                        <td>{seconds(band.times.t30)}</td>
+                        <td>{seconds(band.times.rt60())}</td>
+                    </tr>
+                }) }
+            </table>
+        }
+    });
+
+    html! {
+        <details class="panel">
+            <summary>{"Impulse response"}</summary>
+            <label>
+                <input type="checkbox" checked={settings.enabled} onchange={on_toggle} />
+                {"Analyze as impulse response"}
+            </label>
+            <label>
+                {"Decay range (dB)"}
+                <input type="number" min="10" max="120" step="10"
+                    value={settings.range_db.to_string()}
+                    onchange={on_range} />
+            </label>
+            {table}
+        </details>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct DecayViewProps {
+    pub decay: RoomDecay,
+    pub range_db: f64,
+}
+
+// Energy decay curves of an impulse response, in place of the frequency view: that of the whole
+// band and, fainter, those of the octave bands.
+#[function_component(DecayView)]
+pub fn decay_view(DecayViewProps { decay, range_db }: &DecayViewProps) -> Html {
+    let range_db = *range_db;
+    let len = decay
+        .bands
+        .iter()
+        .map(|band| band.curve_db.len())
+        .max()
+        .unwrap_or_default()
+        .max(2);
+    let duration = len as f64 / decay.sample_rate as f64;
+    let step = len.div_ceil(MAX_POINTS);
+
+    let format_lines = |curve: &[f64]| {
+        curve
+            .iter()
+            .enumerate()
+            .step_by(step)
+            .map(|(n, db)| format!("{n} {:.4} ", -db.max(-range_db)))
+            .collect::<String>()
+    };
+    let curves = decay.bands.iter().map(|band| {
+        let class = if band.center.is_some() {
+            "decay-band"
+        } else {
+            "decay"
+        };
+        let name = band
+            .center
+            .map_or_else(|| "All".to_owned(), |c| format!("{c:.0} Hz"));
+        html! {
+            <path class={class} vector-effect="non-scaling-stroke"
+                d={format!("M {}", format_lines(&band.curve_db))}>
+                <title>{name}</title>
+            </path>
+        }
+    });
+
+    let grid = (0..=(range_db / 10.0) as i64)
+        .map(|step| format!("M 0 {0} L {len} {0} ", 10 * step))
+        .collect::<String>();
+    let y_labels = (0..=(range_db / 10.0) as i64).map(|step| {
+        let db = -10.0 * step as f64;
+        html! {
+            <p class="unit decibel" style={format!("top: {:.4}%", -db / range_db * 100.0)}>{db}</p>
+        }
+    });
+    let time_step = 10_f64.powf((duration / 2.0).log10().floor()).max(1e-3);
+    let decimals = (-time_step.log10()).round().max(0.0) as usize;
+    let x_labels = (0..=(duration / time_step) as i64).map(|step| {
+        let time = step as f64 * time_step;
+        html! {
+            <p class="unit second" style={format!("left: {:.4}%", time / duration * 100.0)}>
+                {format!("{time:.decimals$}")}
+            </p>
+        }
+    });
+
+    html! {
+        <>
+            <div class="plot spectrum-view decay-view">
+                <svg xmlns="http://www.w3.org/2000/svg">
+                    <svg
+                        viewBox={format!("0 0 {len} {range_db:.4}")}
+                        preserveAspectRatio="none">
+                        <path vector-effect="non-scaling-stroke" d={grid} />
+                        { for curves }
+                    </svg>
+                </svg>
+            </div>
+            <div class="x-labels">{ for x_labels }</div>
+            <div class="y-labels">{ for y_labels }</div>
+            <div class="empty-box" />
+        </>
+    }
+}
@@ -0,0 +1,62 @@
+use yew::prelude::*;
+
+use super::select_index;
+use crate::model::decimation::Decimation;
+
+#[derive(Properties, PartialEq)]
+pub struct LargeFilePromptProps {
+    pub name: String,
+    // In bytes.
+    pub size: usize,
+    pub sample_rate: u32,
+    // Emits `None` to open the file in full.
+    pub on_open: Callback<Option<Decimation>>,
+    // Uploads the file for the backend to analyse instead, if there is one.
+    #[prop_or_default]
+    pub on_analyse_on_server: Option<Callback<()>>,
+    pub on_cancel: Callback<()>,
+}
+
+// Asks how to open a file that may be too large to decode in full.
+#[function_component(LargeFilePrompt)]
+pub fn large_file_prompt(
+    LargeFilePromptProps {
+        name,
+        size,
+        sample_rate,
+        on_open,
+        on_analyse_on_server,
+        on_cancel,
+    }: &LargeFilePromptProps,
+) -> Html {
+    let options = Decimation::options(*sample_rate);
+    let selected = use_state(|| 0);
+
+    let on_select = {
+        let selected = selected.clone();
+        Callback::from(move |event: Event| selected.set(select_index(&event)))
+    };
+    let on_decimated = {
+        let decimation = options.get(*selected).copied();
+        on_open.reform(move |_| decimation)
+    };
+
+    html! {
+        <div class="notice">
+            {format!("{name} is {:.0} MB and may not fit in memory.", *size as f64 / 1e6)}
+            if !options.is_empty() {
+                <select onchange={on_select}>
+                    { for options.iter().enumerate().map(|(n, decimation)| html! {
+                        <option selected={n == *selected}>{decimation.name(*sample_rate)}</option>
+                    }) }
+                </select>
+                <button onclick={on_decimated}>{"Open decimated"}</button>
+            }
+            <button onclick={on_open.reform(|_| None)}>{"Open in full"}</button>
+            if let Some(on_analyse_on_server) = on_analyse_on_server {
+                <button onclick={on_analyse_on_server.reform(|_| ())}>{"Analyse on the server"}</button>
+            }
+            <button onclick={on_cancel.reform(|_| ())}>{"Cancel"}</button>
+        </div>
+    }
+}
@@ -0,0 +1,93 @@
+use std::ops::Range;
+
+use yew::prelude::*;
+
+use super::{input_value, read_signal};
+use crate::{
+    hooks::use_task_slot,
+    model::{Channel, Signal},
+};
+
+#[derive(Properties, PartialEq)]
+pub struct PatternDetectorProps {
+    pub channel: Channel,
+    pub on_detected: Callback<Vec<Range<usize>>>,
+}
+
+#[function_component(PatternDetector)]
+pub fn pattern_detector(
+    PatternDetectorProps {
+        channel,
+        on_detected,
+    }: &PatternDetectorProps,
+) -> Html {
+    let reference = use_state(|| None::<Channel>);
+    let threshold = use_state(|| 0.6);
+    let loading = use_task_slot();
+
+    let detections = use_memo(
+        |(channel, reference, threshold)| {
+            reference.as_ref().map_or_else(Vec::new, |reference| {
+                bench!(["Detecting pattern"] => channel.find_pattern(reference, *threshold))
+            })
+        },
+        (channel.clone(), (*reference).clone(), *threshold),
+    );
+
+    {
+        let on_detected = on_detected.clone();
+        use_effect_with_deps(
+            move |detections| {
+                on_detected.emit(detections.iter().map(|d| d.range()).collect());
+            },
+            detections.clone(),
+        );
+    }
+
+    let on_reference = {
+        let reference = reference.clone();
+        Callback::from(move |event: Event| {
+            let reference = reference.clone();
+            let on_loaded = Callback::from(move |signal: Signal| {
+                reference.set(Some(signal.channel(0).clone()))
+            });
+            read_signal(&event, &loading, on_loaded);
+        })
+    };
+    let on_threshold = {
+        let threshold = threshold.clone();
+        Callback::from(move |event: Event| {
+            if let Some(value) = input_value::<f64>(&event) {
+                threshold.set(value.clamp(0.0, 1.0));
+            }
+        })
+    };
+
+    let sample_rate = channel.sample_rate() as f64;
+
+    html! {
+        <details class="panel" open=true>
+            <summary>{"Pattern detector"}</summary>
+            <label class="button" for="load-reference-file">{"Load reference pattern"}</label>
+            <input id="load-reference-file" type="file" accept=".wav,.aif,.aiff,.aifc" onchange={on_reference} />
+            <label>
+                {"Threshold"}
+                <input type="number" min="0" max="1" step="0.05"
+                    value={threshold.to_string()}
+                    onchange={on_threshold} />
+            </label>
+            if reference.is_some() {
+                <p>{format!("{} occurrences", detections.len())}</p>
+                <ul>
+                    { for detections.iter().map(|d| html! {
+                        <li>{format!(
+                            "{:.3} s (score {:.2})",
+                            d.position as f64 / sample_rate,
+                            d.score,
+                        )}</li>
+                    }) }
+                </ul>
+            }
+        </details>
+    }
+}
@@ -0,0 +1,90 @@
+use web_sys::HtmlDetailsElement;
+use yew::prelude::*;
+
+use super::input_value;
+use crate::model::Channel;
+
+const RANGE_DB: f64 = 60.0;
+
+#[derive(Properties, PartialEq)]
+pub struct DynamicsPanelProps {
+    pub channel: Channel,
+}
+
+// Scatter plot of short-term peak against RMS level. Heavily compressed or limited material
+// gathers in a narrow band close to the diagonal, while dynamic material spreads out.
+#[function_component(DynamicsPanel)]
+pub fn dynamics_panel(DynamicsPanelProps { channel }: &DynamicsPanelProps) -> Html {
+    let open = use_state(|| false);
+    let frame_ms = use_state(|| 100.0);
+
+    let curve = use_memo(
+        |(channel, open, frame_ms)| {
+            open.then(|| {
+                let frame = (frame_ms / 1000.0 * channel.sample_rate() as f64) as usize;
+                bench!(["Calculating dynamics"] => channel.levels(frame, frame))
+            })
+        },
+        (channel.clone(), *open, *frame_ms),
+    );
+
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |event: Event| {
+            open.set(event.target_unchecked_into::<HtmlDetailsElement>().open())
+        })
+    };
+    let on_frame_ms = {
+        let frame_ms = frame_ms.clone();
+        Callback::from(move |event: Event| {
+            if let Some(value) = input_value::<f64>(&event).filter(|&v| v > 0.0) {
+                frame_ms.set(value);
+            }
+        })
+    };
+
+    let plot = (*curve).as_ref().map(|curve| {
+        let points = curve
+            .rms_db
+            .iter()
+            .zip(&curve.peak_db)
+            .filter(|(&rms, _)| rms > -RANGE_DB)
+            .map(|(rms, peak)| {
+                html! {
+                    <circle cx={format!("{:.2}", rms + RANGE_DB)} cy={format!("{:.2}", -peak)} r="0.6" />
+                }
+            });
+        let grid = (0..=RANGE_DB as i64)
+            .step_by(10)
+            .map(|db| format!("M {db} 0 L {db} {RANGE_DB} M 0 {db} L {RANGE_DB} {db} "))
+            .collect::<String>();
+
+        html! {
+            <>
+                <svg class="dynamics" viewBox={format!("0 0 {RANGE_DB} {RANGE_DB}")}>
+                    <path class="grid" vector-effect="non-scaling-stroke" d={grid} />
+                    <path class="diagonal" vector-effect="non-scaling-stroke"
+                        d={format!("M 0 {RANGE_DB} L {RANGE_DB} 0")} />
+                    { for points }
+                </svg>
+                <p>{format!("Peak vs. RMS, 0 to −{RANGE_DB} dBFS")}</p>
+                if let Some(crest_factor) = curve.median_crest_factor(-RANGE_DB) {
+                    <p>{format!("Median crest factor: {crest_factor:.1} dB")}</p>
+                }
+            </>
+        }
+    });
+
+    html! {
+        <details class="panel" ontoggle={on_toggle}>
+            <summary>{"Dynamics"}</summary>
+            <label>
+                {"Frame (ms)"}
+                <input type="number" min="1" step="any"
+                    value={frame_ms.to_string()}
+                    onchange={on_frame_ms} />
+            </label>
+            {plot}
+        </details>
+    }
+}
@@ -0,0 +1,93 @@
+use yew::prelude::*;
+
+use super::{input_value, select_index};
+use crate::model::biquad::{FilterDesign, FilterType};
+
+#[derive(Properties, PartialEq)]
+pub struct FilterPanelProps {
+    pub on_add: Callback<FilterDesign>,
+    pub on_preview: Callback<Option<FilterDesign>>,
+}
+
+#[function_component(FilterPanel)]
+pub fn filter_panel(FilterPanelProps { on_add, on_preview }: &FilterPanelProps) -> Html {
+    let design = use_state(FilterDesign::default);
+    let show_response = use_state(|| false);
+
+    {
+        let on_preview = on_preview.clone();
+        use_effect_with_deps(
+            move |(design, show_response)| {
+                on_preview.emit(show_response.then_some(*design));
+                let on_preview = on_preview.clone();
+                move || on_preview.emit(None)
+            },
+            (*design, *show_response),
+        );
+    }
+
+    let update = |f: fn(&mut FilterDesign, &Event)| {
+        let design = design.clone();
+        Callback::from(move |event: Event| {
+            let mut new_design = *design;
+            f(&mut new_design, &event);
+            design.set(new_design);
+        })
+    };
+
+    let on_type = update(|d, e| d.filter_type = FilterType::ALL[select_index(e)]);
+    let on_frequency = update(|d, e| {
+        d.frequency = input_value(e)
+            .filter(|&f: &f64| f > 0.0)
+            .unwrap_or(d.frequency)
+    });
+    let on_q = update(|d, e| d.q = input_value(e).filter(|&q: &f64| q > 0.0).unwrap_or(d.q));
+    let on_gain = update(|d, e| d.gain_db = input_value(e).unwrap_or(d.gain_db));
+    let on_show_response = {
+        let show_response = show_response.clone();
+        Callback::from(move |_| show_response.set(!*show_response))
+    };
+    let on_add = {
+        let design = *design;
+        on_add.reform(move |_| design)
+    };
+
+    html! {
+        <details class="panel">
+            <summary>{"Filter designer"}</summary>
+            <label>
+                {"Type"}
+                <select onchange={on_type}>
+                    { for FilterType::ALL.into_iter().map(|t| html! {
+                        <option selected={t == design.filter_type}>{t.name()}</option>
+                    }) }
+                </select>
+            </label>
+            <label>
+                {"Frequency (Hz)"}
+                <input type="number" min="1" step="any"
+                    value={design.frequency.to_string()}
+                    onchange={on_frequency} />
+            </label>
+            <label>
+                {"Q"}
+                <input type="number" min="0.01" step="any"
+                    value={design.q.to_string()}
+                    onchange={on_q} />
+            </label>
+            if design.filter_type.has_gain() {
+                <label>
+                    {"Gain (dB)"}
+                    <input type="number" step="any"
+                        value={design.gain_db.to_string()}
+                        onchange={on_gain} />
+                </label>
+            }
+            <label>
+                <input type="checkbox" checked={*show_response} onchange={on_show_response} />
+                {"Show response on spectrum"}
+            </label>
+            <button onclick={on_add}>{"Add to processing chain"}</button>
+        </details>
+    }
+}
@@ -0,0 +1,121 @@
+use web_sys::HtmlDetailsElement;
+use yew::prelude::*;
+
+use super::input_value;
+use crate::{model::Signal, settings::GainStagingSettings};
+
+// Recommended gains smaller than this are considered to be on target.
+const TOLERANCE_DB: f64 = 0.05;
+
+#[derive(Properties, PartialEq)]
+pub struct GainStagingPanelProps {
+    pub signal: Signal,
+    pub settings: GainStagingSettings,
+    pub on_settings: Callback<GainStagingSettings>,
+    pub on_apply: Callback<f64>,
+}
+
+// Recommends the gain needed to reach a loudness target without exceeding a true-peak ceiling.
+// Applied gain ends up in the processing chain, so the processed signal is measured again right
+// away to verify the result.
+#[function_component(GainStagingPanel)]
+pub fn gain_staging_panel(
+    GainStagingPanelProps {
+        signal,
+        settings,
+        on_settings,
+        on_apply,
+    }: &GainStagingPanelProps,
+) -> Html {
+    let open = use_state(|| false);
+
+    let loudness = use_memo(
+        |(signal, open)| open.then(|| bench!(["Measuring loudness"] => signal.loudness())),
+        (signal.clone(), *open),
+    );
+
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |event: Event| {
+            open.set(event.target_unchecked_into::<HtmlDetailsElement>().open())
+        })
+    };
+    let settings = *settings;
+    let on_target = {
+        let on_settings = on_settings.clone();
+        Callback::from(move |event: Event| {
+            if let Some(value) = input_value::<f64>(&event) {
+                on_settings.emit(GainStagingSettings {
+                    target_lufs: value.min(0.0),
+                    ..settings
+                });
+            }
+        })
+    };
+    let on_ceiling = {
+        let on_settings = on_settings.clone();
+        Callback::from(move |event: Event| {
+            if let Some(value) = input_value::<f64>(&event) {
+                on_settings.emit(GainStagingSettings {
+                    ceiling_dbtp: value.min(0.0),
+                    ..settings
+                });
+            }
+        })
+    };
+
+    let result = (*loudness).map(|loudness| {
+        let (gain, limited_by_peak) =
+            loudness.recommended_gain(settings.target_lufs, settings.ceiling_dbtp);
+        let on_target = gain.abs() < TOLERANCE_DB;
+
+        html! {
+            <>
+                <table class="statistics">
+                    <tr>
+                        <td>{"Integrated loudness"}</td>
+                        <td>{match loudness.integrated {
+                            Some(integrated) => format!("{integrated:.1} LUFS"),
+                            None => "–".to_owned(),
+                        }}</td>
+                    </tr>
+                    <tr>
+                        <td>{"True peak"}</td>
+                        <td>{format!("{:.1} dBTP", loudness.true_peak_dbtp)}</td>
+                    </tr>
+                    <tr class={classes!(on_target.then_some("ok"))}>
+                        <td>{"Recommended gain"}</td>
+                        <td>{format!("{gain:+.1} dB")}</td>
+                    </tr>
+                </table>
+                if on_target {
+                    <p>{"On target."}</p>
+                } else {
+                    if limited_by_peak {
+                        <p>{"Limited by the true-peak ceiling."}</p>
+                    }
+                    <button onclick={on_apply.reform(move |_| gain)}>{"Apply gain"}</button>
+                }
+            </>
+        }
+    });
+
+    html! {
+        <details class="panel" ontoggle={on_toggle}>
+            <summary>{"Gain staging"}</summary>
+            <label>
+                {"Target (LUFS)"}
+                <input type="number" max="0" step="any"
+                    value={settings.target_lufs.to_string()}
+                    onchange={on_target} />
+            </label>
+            <label>
+                {"Ceiling (dBTP)"}
+                <input type="number" max="0" step="any"
+                    value={settings.ceiling_dbtp.to_string()}
+                    onchange={on_ceiling} />
+            </label>
+            {result}
+        </details>
+    }
+}
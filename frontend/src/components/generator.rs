@@ -0,0 +1,139 @@
+use yew::prelude::*;
+
+use super::{input_value, select_index};
+use crate::model::{
+    generator::{BitDepth, Generator, Waveform},
+    Signal, WavLimits,
+};
+
+const SAMPLE_RATES: [u32; 6] = [8000, 16000, 22050, 44100, 48000, 96000];
+
+type TestSignal = (&'static str, fn() -> Generator);
+
+// Common measurement signals, which take on the chosen duration, sample rate and bit depth.
+const TEST_SIGNALS: [TestSignal; 6] = [
+    ("1 kHz sine, −20 dBFS", || Generator::sine(1000.0, 0.1)),
+    ("Sweep 20 Hz–20 kHz, −6 dBFS", || {
+        Generator::sweep(20.0, 20_000.0, 0.5)
+    }),
+    ("White noise, −6 dBFS peak", || {
+        Generator::white_noise(0.5)
+    }),
+    ("Pink noise", || Generator::pink_noise(1.0)),
+    ("DC, −6 dBFS", || Generator::dc(0.5)),
+    ("Impulse, 0 dBFS", || Generator::impulse(1.0)),
+];
+
+#[derive(Properties, PartialEq)]
+pub struct GeneratorPanelProps {
+    pub on_generated: Callback<Signal>,
+}
+
+#[function_component(GeneratorPanel)]
+pub fn generator_panel(GeneratorPanelProps { on_generated }: &GeneratorPanelProps) -> Html {
+    let generator = use_state(Generator::default);
+
+    let update = |f: fn(&mut Generator, &Event)| {
+        let generator = generator.clone();
+        Callback::from(move |event: Event| {
+            let mut new_generator = (*generator).clone();
+            f(&mut new_generator, &event);
+            generator.set(new_generator);
+        })
+    };
+
+    let on_test_signal = update(|g, e| {
+        // The first option is a placeholder.
+        if let Some((_, test_signal)) = select_index(e).checked_sub(1).map(|i| TEST_SIGNALS[i]) {
+            *g = test_signal()
+                .with_duration(g.duration)
+                .with_sample_rate(g.sample_rate)
+                .with_bit_depth(g.bit_depth);
+        }
+    });
+    let on_waveform = update(|g, e| g.waveform = Waveform::ALL[select_index(e)]);
+    let on_frequency = update(|g, e| {
+        g.frequency = input_value(e)
+            .filter(|&f: &f64| f > 0.0)
+            .unwrap_or(g.frequency)
+    });
+    let on_end_frequency = update(|g, e| {
+        g.end_frequency = input_value(e)
+            .filter(|&f: &f64| f > 0.0)
+            .unwrap_or(g.end_frequency)
+    });
+    let on_amplitude =
+        update(|g, e| g.amplitude = input_value(e).map_or(g.amplitude, |a: f64| a / 100.0));
+    // No longer than a loaded file may be, as every sample is generated at once.
+    let on_duration = update(|g, e| {
+        g.duration = input_value(e)
+            .filter(|&d: &f64| d > 0.0)
+            .map_or(g.duration, |d| d.min(WavLimits::default().max_duration))
+    });
+    let on_sample_rate = update(|g, e| g.sample_rate = SAMPLE_RATES[select_index(e)]);
+    let on_bit_depth = update(|g, e| g.bit_depth = BitDepth::ALL[select_index(e)]);
+
+    let on_generate = {
+        let generator = generator.clone();
+        let on_generated = on_generated.clone();
+        Callback::from(move |_| {
+            on_generated.emit(bench!(["Generating signal"] => generator.generate()))
+        })
+    };
+
+    html! {
+        <div class="generator">
+            <select onchange={on_test_signal}>
+                <option selected=true disabled=true>{"Test signal…"}</option>
+                { for TEST_SIGNALS.into_iter().map(|(name, _)| html! { <option>{name}</option> }) }
+            </select>
+            <select onchange={on_waveform}>
+                { for Waveform::ALL.into_iter().map(|w| html! {
+                    <option selected={w == generator.waveform}>{w.name()}</option>
+                }) }
+            </select>
+            if generator.waveform.has_frequency() {
+                <label>
+                    {"f"}
+                    <input type="number" min="0" step="any"
+                        value={generator.frequency.to_string()}
+                        onchange={on_frequency} />
+                    {"Hz"}
+                </label>
+            }
+            if generator.waveform == Waveform::Chirp {
+                <label>
+                    {"to"}
+                    <input type="number" min="0" step="any"
+                        value={generator.end_frequency.to_string()}
+                        onchange={on_end_frequency} />
+                    {"Hz"}
+                </label>
+            }
+            <label>
+                {"A"}
+                <input type="number" min="0" max="100" step="any"
+                    value={(generator.amplitude * 100.0).to_string()}
+                    onchange={on_amplitude} />
+                {"%"}
+            </label>
+            <label>
+                <input type="number" min="0" max={WavLimits::default().max_duration.to_string()} step="any"
+                    value={generator.duration.to_string()}
+                    onchange={on_duration} />
+                {"s"}
+            </label>
+            <select onchange={on_sample_rate}>
+                { for SAMPLE_RATES.into_iter().map(|r| html! {
+                    <option selected={r == generator.sample_rate}>{format!("{r} Hz")}</option>
+                }) }
+            </select>
+            <select onchange={on_bit_depth}>
+                { for BitDepth::ALL.into_iter().map(|b| html! {
+                    <option selected={b == generator.bit_depth}>{b.name()}</option>
+                }) }
+            </select>
+            <button onclick={on_generate}>{"Generate"}</button>
+        </div>
+    }
+}
@@ -0,0 +1,112 @@
+use std::ops::Range;
+
+use web_sys::{HtmlCanvasElement, HtmlDetailsElement};
+use yew::prelude::*;
+
+use crate::{
+    canvas,
+    hooks::use_playhead,
+    model::{stereo::phase_correlation, Channel},
+    theme::Theme,
+};
+
+// Length of the window that follows the playhead during playback.
+const WINDOW_MS: f64 = 100.0;
+// Longer windows are plotted with every nth frame only.
+const MAX_POINTS: usize = 8192;
+
+#[derive(Properties, PartialEq)]
+pub struct GoniometerPanelProps {
+    pub left: Channel,
+    pub right: Channel,
+    pub view: Range<usize>,
+    // The sample being played, if any.
+    pub playhead: Callback<(), Option<usize>>,
+    pub theme: Theme,
+}
+
+// Lissajous plot of the two channels, with mid (in phase) content along the vertical and side (out
+// of phase) content along the horizontal, and their correlation. Both cover the window just played
+// during playback, and the visible part of the signal otherwise.
+#[function_component(GoniometerPanel)]
+pub fn goniometer_panel(
+    GoniometerPanelProps {
+        left,
+        right,
+        view,
+        playhead,
+        theme,
+    }: &GoniometerPanelProps,
+) -> Html {
+    let open = use_state(|| false);
+    let window_len = (WINDOW_MS / 1000.0 * left.sample_rate() as f64) as usize;
+    let window = match use_playhead(playhead.clone()) {
+        Some(position) => position.saturating_sub(window_len)..position,
+        None => view.clone(),
+    };
+
+    let correlation = use_memo(
+        |(left, right, window, open)| {
+            open.then(|| {
+                phase_correlation(&left.slice(window.clone()), &right.slice(window.clone()))
+            })
+        },
+        (left.clone(), right.clone(), window.clone(), *open),
+    );
+
+    let canvas_ref = use_node_ref();
+    {
+        let canvas_ref = canvas_ref.clone();
+        use_effect_with_deps(
+            move |(left, right, window, _, theme)| {
+                let Some(element) = canvas_ref.cast::<HtmlCanvasElement>() else {
+                    return;
+                };
+                let step = window.len().div_ceil(MAX_POINTS).max(1);
+                let left = left.slice(window.clone());
+                let right = right.slice(window.clone());
+                let points = left
+                    .iter_normalized()
+                    .zip(right.iter_normalized())
+                    .step_by(step)
+                    .map(|(l, r)| (0.5 + (r - l) / 4.0, 0.5 - (l + r) / 4.0));
+                let result = bench!(["Drawing goniometer"] => {
+                    canvas::draw_points(&element, points, theme.overlay)
+                });
+                if let Err(error) = result {
+                    crate::log(&format!("failed to draw goniometer: {error:?}"));
+                }
+            },
+            (left.clone(), right.clone(), window, *open, *theme),
+        );
+    }
+
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |event: Event| {
+            open.set(event.target_unchecked_into::<HtmlDetailsElement>().open())
+        })
+    };
+
+    html! {
+        <details class="panel" ontoggle={on_toggle}>
+            <summary>{"Goniometer"}</summary>
+            if let Some(correlation) = *correlation {
+                <div class="goniometer">
+                    <canvas ref={canvas_ref} />
+                    <svg viewBox="0 0 2 2" preserveAspectRatio="none">
+                        <path vector-effect="non-scaling-stroke" d="M 0 0 L 2 2 M 2 0 L 0 2 M 1 0 L 1 2" />
+                    </svg>
+                    <p style="left: 4%; top: 4%">{"L"}</p>
+                    <p style="right: 4%; top: 4%">{"R"}</p>
+                    <p style="left: 52%; top: 4%">{"M"}</p>
+                </div>
+                <label>
+                    {"Correlation"}
+                    <meter min="-1" max="1" low="0" optimum="1" value={correlation.to_string()} />
+                    {format!("{correlation:+.2}")}
+                </label>
+            }
+        </details>
+    }
+}
@@ -0,0 +1,39 @@
+use yew::prelude::*;
+
+use super::select_index;
+use crate::model::layout::SampleLayout;
+
+#[derive(Properties, PartialEq)]
+pub struct SampleLayoutSelectProps {
+    pub layout: SampleLayout,
+    // The layout in the header, if the samples are read differently.
+    pub declared: Option<SampleLayout>,
+    pub on_layout: Callback<SampleLayout>,
+}
+
+// How the samples of a file with 32-bit samples are read, for overriding a header that does not
+// match them or a wrong guess.
+#[function_component(SampleLayoutSelect)]
+pub fn sample_layout_select(
+    SampleLayoutSelectProps {
+        layout,
+        declared,
+        on_layout,
+    }: &SampleLayoutSelectProps,
+) -> Html {
+    let on_change = on_layout.reform(|event: Event| SampleLayout::ALL[select_index(&event)]);
+
+    html! {
+        <div class={classes!(declared.is_some().then_some("notice"))}>
+            {"Samples read as"}
+            <select onchange={on_change}>
+                { for SampleLayout::ALL.into_iter().map(|l| html! {
+                    <option selected={l == *layout}>{l.name()}</option>
+                }) }
+            </select>
+            if let Some(declared) = declared {
+                {format!("although the header declares {}.", declared.name())}
+            }
+        </div>
+    }
+}
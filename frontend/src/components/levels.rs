@@ -0,0 +1,133 @@
+use std::ops::Range;
+
+use yew::prelude::*;
+
+use super::input_value;
+use crate::{
+    model::{levels::LevelCurve, Channel},
+    settings::LevelSettings,
+};
+
+const RANGE_DB: f64 = 60.0;
+
+#[derive(Properties, PartialEq)]
+pub struct LevelsPanelProps {
+    pub channel: Channel,
+    pub settings: LevelSettings,
+    pub on_settings: Callback<LevelSettings>,
+    pub on_levels: Callback<Option<LevelCurve>>,
+}
+
+#[function_component(LevelsPanel)]
+pub fn levels_panel(
+    LevelsPanelProps {
+        channel,
+        settings,
+        on_settings,
+        on_levels,
+    }: &LevelsPanelProps,
+) -> Html {
+    let curve = use_memo(
+        |(channel, settings)| {
+            settings.enabled.then(|| {
+                let to_samples = |ms: f64| (ms / 1000.0 * channel.sample_rate() as f64) as usize;
+                bench!(["Calculating levels"] => {
+                    channel.levels(to_samples(settings.window_ms), to_samples(settings.hop_ms))
+                })
+            })
+        },
+        (channel.clone(), *settings),
+    );
+
+    {
+        let on_levels = on_levels.clone();
+        use_effect_with_deps(move |curve| on_levels.emit((**curve).clone()), curve);
+    }
+
+    let settings = *settings;
+    let on_toggle = on_settings.reform(move |_| LevelSettings {
+        enabled: !settings.enabled,
+        ..settings
+    });
+    let on_ms = |f: fn(&mut LevelSettings, f64)| {
+        let on_settings = on_settings.clone();
+        Callback::from(move |event: Event| {
+            if let Some(value) = input_value::<f64>(&event).filter(|&v| v > 0.0) {
+                let mut new_settings = settings;
+                f(&mut new_settings, value);
+                on_settings.emit(new_settings);
+            }
+        })
+    };
+
+    html! {
+        <details class="panel">
+            <summary>{"Levels"}</summary>
+            <label>
+                <input type="checkbox" checked={settings.enabled} onchange={on_toggle} />
+                {"Show RMS / peak over time"}
+            </label>
+            <label>
+                {"Window (ms)"}
+                <input type="number" min="1" step="any"
+                    value={settings.window_ms.to_string()}
+                    onchange={on_ms(|s, v| s.window_ms = v)} />
+            </label>
+            <label>
+                {"Hop (ms)"}
+                <input type="number" min="1" step="any"
+                    value={settings.hop_ms.to_string()}
+                    onchange={on_ms(|s, v| s.hop_ms = v)} />
+            </label>
+        </details>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct LevelViewProps {
+    pub curve: LevelCurve,
+    pub view: Range<usize>,
+}
+
+#[function_component(LevelView)]
+pub fn level_view(LevelViewProps { curve, view }: &LevelViewProps) -> Html {
+    const Y_SCALE: f64 = 1.0125;
+
+    let view_len = view.len().max(1);
+
+    let format_lines = |levels: &[f64]| {
+        levels
+            .iter()
+            .enumerate()
+            .map(|(frame, db)| {
+                // Relative to the start of the view, to stay within the precision of SVG.
+                let x = curve.frame_center(frame) as f64 - view.start as f64;
+                format!("{x} {:.4} ", -db.max(-RANGE_DB))
+            })
+            .collect::<String>()
+    };
+
+    let rms_lines = bench!(["Formatting RMS lines"] => format_lines(&curve.rms_db));
+    let peak_lines = bench!(["Formatting peak lines"] => format_lines(&curve.peak_db));
+
+    html! {
+        <div class="plot levels-view">
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <svg
+                    viewBox={format!("0 0 {:.4} {RANGE_DB}", Y_SCALE * view_len as f64)}
+                    preserveAspectRatio="none">
+                    if !curve.rms_db.is_empty() {
+                        <path class="peak" vector-effect="non-scaling-stroke"
+                            d={format!("M {peak_lines}")} />
+                        <path class="rms" vector-effect="non-scaling-stroke"
+                            d={format!("M {rms_lines}")} />
+                    }
+                    <rect vector-effect="non-scaling-stroke"
+                        width={view_len.to_string()}
+                        height={RANGE_DB.to_string()} />
+                </svg>
+            </svg>
+            <p class="unit decibel">{format!("−{RANGE_DB}…0 ")}</p>
+        </div>
+    }
+}
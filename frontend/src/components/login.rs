@@ -0,0 +1,62 @@
+use yew::prelude::*;
+
+use super::input_value;
+use crate::{hooks::use_task_slot, server};
+
+#[derive(Properties, PartialEq)]
+pub struct LoginScreenProps {
+    pub on_signed_in: Callback<()>,
+}
+
+// Asks for a token when the backend requires one, shown in place of the app until a valid one is
+// given. The token is kept in local storage and sent with every request to the backend.
+#[function_component(LoginScreen)]
+pub fn login_screen(LoginScreenProps { on_signed_in }: &LoginScreenProps) -> Html {
+    let token = use_state(String::new);
+    let rejected = use_state(|| false);
+    let task = use_task_slot();
+
+    let on_token = {
+        let token = token.clone();
+        Callback::from(move |event: Event| {
+            token.set(
+                input_value::<String>(&event)
+                    .unwrap_or_default()
+                    .trim()
+                    .to_owned(),
+            )
+        })
+    };
+    let on_submit = {
+        let token = token.clone();
+        let rejected = rejected.clone();
+        let on_signed_in = on_signed_in.clone();
+        Callback::from(move |event: SubmitEvent| {
+            event.prevent_default();
+            server::set_token(&token);
+            let rejected = rejected.clone();
+            let on_signed_in = on_signed_in.clone();
+            task.spawn(async move {
+                if server::is_authorized().await {
+                    on_signed_in.emit(());
+                } else {
+                    rejected.set(true);
+                }
+            });
+        })
+    };
+
+    html! {
+        <form class="login" onsubmit={on_submit}>
+            <h1>{"Signal Inspector"}</h1>
+            <label>
+                {"Access token"}
+                <input type="password" autocomplete="current-password" onchange={on_token} />
+            </label>
+            if *rejected {
+                <div class="notice">{"The token was not accepted."}</div>
+            }
+            <button type="submit" disabled={token.is_empty()}>{"Sign in"}</button>
+        </form>
+    }
+}
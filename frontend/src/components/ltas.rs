@@ -0,0 +1,92 @@
+use yew::prelude::*;
+
+use crate::model::ltas::Ltas;
+
+const RANGE_DB: f64 = 90.0;
+
+#[derive(Properties, PartialEq)]
+pub struct LtasViewProps {
+    pub ltas: Ltas,
+}
+
+// Bar chart of a long-term average spectrum, in place of the frequency view.
+#[function_component(LtasView)]
+pub fn ltas_view(LtasViewProps { ltas }: &LtasViewProps) -> Html {
+    let num_bands = ltas.bands.len().max(1) as f64;
+    let floor = ltas
+        .bands
+        .iter()
+        .map(|band| band.level_db)
+        .fold(0.0, f64::max)
+        - RANGE_DB;
+    let floor = 10.0 * (floor / 10.0).floor();
+    let to_top = |db: f64| -db / -floor * 100.0;
+
+    let bars = ltas.bands.iter().enumerate().map(|(i, band)| {
+        let level = band.level_db.max(floor);
+        html! {
+            <rect class="band"
+                x={format!("{:.4}", i as f64 + 0.1)}
+                y={format!("{:.4}", -level)}
+                width="0.8"
+                height={format!("{:.4}", level - floor)}>
+                <title>{format!(
+                    "{:.0} Hz ({:.0}–{:.0} Hz): {:.1} dBFS",
+                    band.nominal_center(),
+                    band.lower,
+                    band.upper,
+                    band.level_db,
+                )}</title>
+            </rect>
+        }
+    });
+
+    // Octave centres (31.5 Hz, 63 Hz, ...) are labelled.
+    let octave_offset = usize::from(ltas.bands_per_octave == 3);
+    let x_labels = ltas
+        .bands
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| i % ltas.bands_per_octave as usize == octave_offset)
+        .map(|(i, band)| {
+            let center = band.nominal_center();
+            let (value, unit) = if center < 1000.0 {
+                (center, "hertz")
+            } else {
+                (center / 1000.0, "kilohertz")
+            };
+            html! {
+                <p class={format!("unit {unit}")}
+                    style={format!("left: {:.4}%", (i as f64 + 0.5) / num_bands * 100.0)}>
+                    {value}
+                </p>
+            }
+        });
+    let y_labels = (0..=(-floor / 10.0) as i64).map(|step| {
+        let db = -10.0 * step as f64;
+        html! {
+            <p class="unit decibel" style={format!("top: {:.4}%", to_top(db))}>{db}</p>
+        }
+    });
+    let grid = (0..=(-floor / 10.0) as i64)
+        .map(|step| format!("M 0 {0} L {num_bands} {0} ", 10 * step))
+        .collect::<String>();
+
+    html! {
+        <>
+            <div class="plot spectrum-view ltas">
+                <svg xmlns="http://www.w3.org/2000/svg">
+                    <svg
+                        viewBox={format!("0 0 {num_bands} {:.4}", -floor)}
+                        preserveAspectRatio="none">
+                        <path vector-effect="non-scaling-stroke" d={grid} />
+                        { for bars }
+                    </svg>
+                </svg>
+            </div>
+            <div class="x-labels">{ for x_labels }</div>
+            <div class="y-labels">{ for y_labels }</div>
+            <div class="empty-box" />
+        </>
+    }
+}
@@ -0,0 +1,66 @@
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+use crate::markers::Marker;
+
+#[derive(Properties, PartialEq)]
+pub struct MarkersPanelProps {
+    pub markers: Vec<Marker>,
+    pub sample_rate: u32,
+    pub on_change: Callback<Vec<Marker>>,
+    pub on_jump: Callback<usize>,
+}
+
+#[function_component(MarkersPanel)]
+pub fn markers_panel(
+    MarkersPanelProps {
+        markers,
+        sample_rate,
+        on_change,
+        on_jump,
+    }: &MarkersPanelProps,
+) -> Html {
+    let items = markers.iter().enumerate().map(|(n, marker)| {
+        let on_label = {
+            let markers = markers.clone();
+            let on_change = on_change.clone();
+            Callback::from(move |event: Event| {
+                let mut new_markers = markers.clone();
+                new_markers[n].label = event.target_unchecked_into::<HtmlInputElement>().value();
+                on_change.emit(new_markers);
+            })
+        };
+        let on_remove = {
+            let markers = markers.clone();
+            let on_change = on_change.clone();
+            Callback::from(move |_| {
+                let mut new_markers = markers.clone();
+                new_markers.remove(n);
+                on_change.emit(new_markers);
+            })
+        };
+        let position = marker.position;
+
+        html! {
+            <li>
+                <button onclick={on_jump.reform(move |_| position)}>{
+                    format!("{:.3} s", position as f64 / *sample_rate as f64)
+                }</button>
+                <input type="text" value={marker.label.clone()} onchange={on_label} />
+                <button onclick={on_remove}>{"×"}</button>
+            </li>
+        }
+    });
+
+    html! {
+        <details class="panel">
+            <summary>{"Markers"}</summary>
+            if markers.is_empty() {
+                <p>{"Double-click the waveform to add a marker."}</p>
+            } else {
+                <ul class="markers">{ for items }</ul>
+                <button onclick={on_change.reform(|_| Vec::new())}>{"Remove all"}</button>
+            }
+        </details>
+    }
+}
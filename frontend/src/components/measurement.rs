@@ -0,0 +1,169 @@
+use web_sys::HtmlDetailsElement;
+use yew::prelude::*;
+
+use super::input_value;
+use crate::{
+    capture,
+    hooks::use_task_slot,
+    model::{generator::Generator, Signal},
+};
+
+// Recording continues for this long after the sweep, which is also the length of the impulse
+// response, so that the latency and decay of the system are captured.
+const TAIL_SECONDS: f64 = 1.0;
+
+#[derive(Clone, PartialEq)]
+enum Status {
+    Idle,
+    Measuring,
+    // The recording, kept for inspecting it instead of the impulse response.
+    Done(Signal),
+    Failed(String),
+}
+
+#[derive(Properties, PartialEq)]
+pub struct MeasurementPanelProps {
+    // Emits the impulse response of the device, to be shown with its frequency response.
+    pub on_measured: Callback<Signal>,
+    pub on_loaded: Callback<Signal>,
+}
+
+// Plays a sweep through the default output while recording the default input, and derives the
+// impulse response of whatever is in between.
+#[function_component(MeasurementPanel)]
+pub fn measurement_panel(
+    MeasurementPanelProps {
+        on_measured,
+        on_loaded,
+    }: &MeasurementPanelProps,
+) -> Html {
+    let sweep = use_state(|| Generator::sweep(20.0, 20_000.0, 0.25).with_duration(5.0));
+    let status = use_state(|| Status::Idle);
+    let task = use_task_slot();
+
+    let update = |f: fn(&mut Generator, &Event)| {
+        let sweep = sweep.clone();
+        Callback::from(move |event: Event| {
+            let mut new_sweep = (*sweep).clone();
+            f(&mut new_sweep, &event);
+            sweep.set(new_sweep);
+        })
+    };
+    let on_frequency = update(|g, e| {
+        g.frequency = input_value(e)
+            .filter(|&f: &f64| f > 0.0)
+            .unwrap_or(g.frequency)
+    });
+    let on_end_frequency = update(|g, e| {
+        g.end_frequency = input_value(e)
+            .filter(|&f: &f64| f > 0.0)
+            .unwrap_or(g.end_frequency)
+    });
+    let on_level = update(|g, e| {
+        g.amplitude = input_value(e)
+            .filter(|&db: &f64| db <= 0.0)
+            .map_or(g.amplitude, |db| 10_f64.powf(db / 20.0))
+    });
+    let on_duration = update(|g, e| {
+        g.duration = input_value(e)
+            .filter(|&d: &f64| d > 0.0)
+            .unwrap_or(g.duration)
+    });
+
+    let on_measure = {
+        let sweep = sweep.clone();
+        let status = status.clone();
+        let task = task.clone();
+        let on_measured = on_measured.clone();
+        Callback::from(move |_| {
+            let stimulus = sweep.channel();
+            let status = status.clone();
+            let on_measured = on_measured.clone();
+            status.set(Status::Measuring);
+            task.spawn(async move {
+                match capture::measure(&stimulus, TAIL_SECONDS).await {
+                    Ok(recording) => {
+                        let len = (TAIL_SECONDS * f64::from(stimulus.sample_rate())) as usize;
+                        let response = recording.impulse_response(&stimulus, len);
+                        on_measured.emit(Signal::Mono(response));
+                        status.set(Status::Done(Signal::Mono(recording)));
+                    }
+                    Err(error) => status.set(Status::Failed(format!("{error:?}"))),
+                }
+            })
+        })
+    };
+    let on_cancel = {
+        let status = status.clone();
+        let task = task.clone();
+        Callback::from(move |_| {
+            task.abort();
+            status.set(Status::Idle);
+        })
+    };
+    // Releases the microphone when the panel is closed mid-measurement.
+    let on_toggle = {
+        let on_cancel = on_cancel.clone();
+        Callback::from(move |event: Event| {
+            if !event.target_unchecked_into::<HtmlDetailsElement>().open() {
+                on_cancel.emit(());
+            }
+        })
+    };
+
+    let level_db = 20.0 * sweep.amplitude.log10();
+    html! {
+        <details class="panel" ontoggle={on_toggle}>
+            <summary>{"Measurement"}</summary>
+            <p>{"Connect the output to the device under test and the device to the input."}</p>
+            <label>
+                {"Sweep from"}
+                <input type="number" min="0" step="any"
+                    value={sweep.frequency.to_string()}
+                    onchange={on_frequency} />
+                {" to"}
+                <input type="number" min="0" step="any"
+                    value={sweep.end_frequency.to_string()}
+                    onchange={on_end_frequency} />
+                {" Hz"}
+            </label>
+            <label>
+                {"Level (dBFS)"}
+                <input type="number" max="0" step="any"
+                    value={format!("{level_db:.1}")}
+                    onchange={on_level} />
+            </label>
+            <label>
+                {"Duration (s)"}
+                <input type="number" min="0" step="any"
+                    value={sweep.duration.to_string()}
+                    onchange={on_duration} />
+            </label>
+            {
+                match &*status {
+                    Status::Measuring => html! {
+                        <>
+                            <p>{"Measuring…"}</p>
+                            <button onclick={on_cancel.reform(|_| ())}>{"Cancel"}</button>
+                        </>
+                    },
+                    status => html! {
+                        <>
+                            <button onclick={on_measure}>{"Measure"}</button>
+                            if let Status::Done(recording) = status {
+                                <p>{"The impulse response is open, with its frequency response."}</p>
+                                <button onclick={on_loaded.reform({
+                                    let recording = recording.clone();
+                                    move |_| recording.clone()
+                                })}>{"Open recording"}</button>
+                            }
+                            if let Status::Failed(error) = status {
+                                <p class="warning">{format!("Measurement failed: {error}")}</p>
+                            }
+                        </>
+                    },
+                }
+            }
+        </details>
+    }
+}
@@ -0,0 +1,160 @@
+use yew::prelude::*;
+
+use crate::model::riff::Metadata;
+
+// Names of the common LIST/INFO tags.
+pub const INFO_NAMES: [(&str, &str); 10] = [
+    ("INAM", "Title"),
+    ("IART", "Artist"),
+    ("IPRD", "Album"),
+    ("ICMT", "Comment"),
+    ("ICRD", "Date"),
+    ("IGNR", "Genre"),
+    ("ICOP", "Copyright"),
+    ("IENG", "Engineer"),
+    ("ISFT", "Software"),
+    ("ITRK", "Track"),
+];
+const LOOP_TYPES: [&str; 3] = ["forward", "alternating", "backward"];
+
+#[derive(Properties, PartialEq)]
+pub struct MetadataPanelProps {
+    pub metadata: Option<Metadata>,
+}
+
+// The chunks of the opened WAV file other than the samples.
+#[function_component(MetadataPanel)]
+pub fn metadata_panel(MetadataPanelProps { metadata }: &MetadataPanelProps) -> Html {
+    let Some(metadata) = metadata else {
+        return html! {
+            <details class="panel">
+                <summary>{"Metadata"}</summary>
+                <p>{"Only opened files have metadata."}</p>
+            </details>
+        };
+    };
+    let sample_rate = metadata
+        .format
+        .as_ref()
+        .map_or(0, |format| format.sample_rate);
+    let time_reference = |time_reference: u64| {
+        if sample_rate == 0 {
+            format!("sample {time_reference}")
+        } else {
+            format_time_of_day(time_reference as f64 / f64::from(sample_rate))
+        }
+    };
+    let seconds = |frames: u32| {
+        if sample_rate == 0 {
+            format!("sample {frames}")
+        } else {
+            format!("{:.3} s", f64::from(frames) / f64::from(sample_rate))
+        }
+    };
+
+    html! {
+        <details class="panel">
+            <summary>{"Metadata"}</summary>
+            if let Some(format) = &metadata.format {
+                <table class="statistics">
+                    <tr><th colspan="2">{"Format"}</th></tr>
+                    <tr><td>{"Encoding"}</td><td>{format.name()}</td></tr>
+                    <tr><td>{"Channels"}</td><td>{format.channels}</td></tr>
+                    <tr><td>{"Sample rate"}</td><td>{format!("{} Hz", format.sample_rate)}</td></tr>
+                    <tr><td>{"Byte rate"}</td><td>{format!("{} B/s", format.byte_rate)}</td></tr>
+                    <tr><td>{"Block align"}</td><td>{format!("{} B", format.block_align)}</td></tr>
+                    <tr><td>{"Bits per sample"}</td><td>{format.bits_per_sample}</td></tr>
+                    if let Some(valid_bits) = format.valid_bits {
+                        <tr><td>{"Valid bits"}</td><td>{valid_bits}</td></tr>
+                    }
+                    if let Some(channel_mask) = format.channel_mask {
+                        <tr><td>{"Channel mask"}</td><td>{format!("0x{channel_mask:x}")}</td></tr>
+                    }
+                </table>
+            }
+            if !metadata.info.is_empty() {
+                <table class="statistics">
+                    <tr><th colspan="2">{"Tags"}</th></tr>
+                    { for metadata.info.iter().map(|(id, value)| {
+                        let name = INFO_NAMES
+                            .iter()
+                            .find(|(info_id, _)| info_id == id)
+                            .map_or(id.as_str(), |(_, name)| name);
+                        html! { <tr><td>{name}</td><td>{value}</td></tr> }
+                    }) }
+                </table>
+            }
+            if let Some(broadcast) = &metadata.broadcast {
+                <table class="statistics">
+                    <tr><th colspan="2">{format!("Broadcast extension v{}", broadcast.version)}</th></tr>
+                    <tr><td>{"Description"}</td><td>{&broadcast.description}</td></tr>
+                    <tr><td>{"Originator"}</td><td>{&broadcast.originator}</td></tr>
+                    <tr><td>{"Reference"}</td><td>{&broadcast.originator_reference}</td></tr>
+                    <tr>
+                        <td>{"Originated"}</td>
+                        <td>{format!("{} {}", broadcast.origination_date, broadcast.origination_time)}</td>
+                    </tr>
+                    <tr><td>{"Time reference"}</td><td>{time_reference(broadcast.time_reference)}</td></tr>
+                    if !broadcast.coding_history.is_empty() {
+                        <tr><td colspan="2"><pre>{&broadcast.coding_history}</pre></td></tr>
+                    }
+                </table>
+            }
+            if !metadata.cue_points.is_empty() {
+                <table class="statistics">
+                    <tr><th colspan="2">{"Cue points"}</th></tr>
+                    { for metadata.cue_points.iter().map(|cue_point| html! {
+                        <tr>
+                            <td>{cue_point.label.clone().unwrap_or_else(|| format!("Cue {}", cue_point.id))}</td>
+                            <td>{seconds(cue_point.position)}</td>
+                        </tr>
+                    }) }
+                </table>
+            }
+            if let Some(sampler) = &metadata.sampler {
+                <table class="statistics">
+                    <tr><th colspan="2">{"Sampler"}</th></tr>
+                    <tr>
+                        <td>{"Unity note"}</td>
+                        <td>{format!(
+                            "{} {:+.0} cents",
+                            sampler.midi_unity_note,
+                            f64::from(sampler.midi_pitch_fraction) / f64::from(u32::MAX) * 100.0,
+                        )}</td>
+                    </tr>
+                    { for sampler.loops.iter().map(|sample_loop| html! {
+                        <tr>
+                            <td>{format!(
+                                "Loop {}, {}{}",
+                                sample_loop.id,
+                                LOOP_TYPES.get(sample_loop.loop_type as usize).unwrap_or(&"custom"),
+                                match sample_loop.play_count {
+                                    0 => String::new(),
+                                    count => format!(" ×{count}"),
+                                },
+                            )}</td>
+                            <td>{format!("{}–{}", seconds(sample_loop.start), seconds(sample_loop.end))}</td>
+                        </tr>
+                    }) }
+                </table>
+            }
+            <table class="statistics">
+                <tr><th colspan="2">{"Chunks"}</th></tr>
+                { for metadata.chunks.iter().map(|chunk| html! {
+                    <tr><td>{format!("{:?}", chunk.id)}</td><td>{format!("{} B", chunk.size)}</td></tr>
+                }) }
+            </table>
+        </details>
+    }
+}
+
+// Seconds since midnight as hh:mm:ss.sss.
+fn format_time_of_day(seconds: f64) -> String {
+    let minutes = (seconds / 60.0).floor();
+    format!(
+        "{:02}:{:02}:{:06.3}",
+        (minutes / 60.0).floor(),
+        minutes % 60.0,
+        seconds % 60.0
+    )
+}
@@ -0,0 +1,112 @@
+use web_sys::{HtmlInputElement, KeyboardEvent};
+use yew::prelude::*;
+
+use crate::actions::AppAction;
+
+#[derive(Properties, PartialEq)]
+pub struct CommandPaletteProps {
+    pub on_action: Callback<AppAction>,
+    pub on_close: Callback<()>,
+}
+
+// Searchable list of every action, opened with Ctrl+K.
+#[function_component(CommandPalette)]
+pub fn command_palette(
+    CommandPaletteProps {
+        on_action,
+        on_close,
+    }: &CommandPaletteProps,
+) -> Html {
+    let query = use_state(String::new);
+    let selected = use_state(|| 0);
+    let input = use_node_ref();
+
+    {
+        let input = input.clone();
+        use_effect_with_deps(
+            move |_| {
+                if let Some(input) = input.cast::<HtmlInputElement>() {
+                    let _ = input.focus();
+                }
+            },
+            (),
+        );
+    }
+
+    let matches: Vec<AppAction> = AppAction::ALL
+        .into_iter()
+        .filter(|action| {
+            action
+                .name()
+                .to_lowercase()
+                .contains(&query.trim().to_lowercase())
+        })
+        .collect();
+    let selected_index = (*selected).min(matches.len().saturating_sub(1));
+
+    let run = {
+        let on_action = on_action.clone();
+        let on_close = on_close.clone();
+        move |action: AppAction| {
+            on_close.emit(());
+            on_action.emit(action);
+        }
+    };
+
+    let on_input = {
+        let query = query.clone();
+        let selected = selected.clone();
+        Callback::from(move |event: InputEvent| {
+            query.set(event.target_unchecked_into::<HtmlInputElement>().value());
+            selected.set(0);
+        })
+    };
+    let on_keydown = {
+        let matches = matches.clone();
+        let selected = selected.clone();
+        let on_close = on_close.clone();
+        let run = run.clone();
+        Callback::from(move |event: KeyboardEvent| match event.key().as_str() {
+            "Escape" => on_close.emit(()),
+            "Enter" => {
+                if let Some(&action) = matches.get(selected_index) {
+                    run(action);
+                }
+            }
+            "ArrowDown" => {
+                event.prevent_default();
+                selected.set((selected_index + 1).min(matches.len().saturating_sub(1)));
+            }
+            "ArrowUp" => {
+                event.prevent_default();
+                selected.set(selected_index.saturating_sub(1));
+            }
+            _ => (),
+        })
+    };
+
+    html! {
+        <div class="palette-backdrop" onclick={on_close.reform(|_| ())}>
+            <div class="palette" onclick={Callback::from(|event: MouseEvent| event.stop_propagation())}>
+                <input ref={input} type="text" placeholder="Type a command"
+                    value={(*query).clone()}
+                    oninput={on_input}
+                    onkeydown={on_keydown} />
+                <ul>
+                    { for matches.iter().enumerate().map(|(n, &action)| {
+                        let run = run.clone();
+                        html! {
+                            <li class={classes!((n == selected_index).then_some("selected"))}
+                                onclick={Callback::from(move |_| run(action))}>
+                                {action.name()}
+                                if let Some(shortcut) = action.shortcut() {
+                                    <kbd>{shortcut}</kbd>
+                                }
+                            </li>
+                        }
+                    }) }
+                </ul>
+            </div>
+        </div>
+    }
+}
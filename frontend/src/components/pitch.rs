@@ -0,0 +1,199 @@
+use std::ops::Range;
+
+use web_sys::HtmlDetailsElement;
+use yew::prelude::*;
+
+use super::{input_value, select_index};
+use crate::{
+    model::{
+        pitch::{PitchMethod, PitchTrack},
+        Channel,
+    },
+    settings::PitchSettings,
+};
+
+// Tracks of longer views are computed with a longer hop, to bound the work.
+const MAX_FRAMES: usize = 2000;
+// Frames with a lower confidence are taken to be unvoiced and left out of the track.
+const MIN_CONFIDENCE: f64 = 0.5;
+// Range of the track as MIDI note numbers, G1 (49 Hz) to C7 (2.1 kHz).
+const LOWEST_NOTE: f64 = 31.0;
+const HIGHEST_NOTE: f64 = 96.0;
+
+#[derive(Properties, PartialEq)]
+pub struct PitchPanelProps {
+    pub channel: Channel,
+    // The selection, or else the visible part of the channel.
+    pub region: Range<usize>,
+    pub view: Range<usize>,
+    pub settings: PitchSettings,
+    pub on_settings: Callback<PitchSettings>,
+    pub on_track: Callback<Option<PitchTrack>>,
+}
+
+// Fundamental frequency of the selected region, with the nearest note, and optionally a track of
+// it over the visible part of the channel.
+#[function_component(PitchPanel)]
+pub fn pitch_panel(
+    PitchPanelProps {
+        channel,
+        region,
+        view,
+        settings,
+        on_settings,
+        on_track,
+    }: &PitchPanelProps,
+) -> Html {
+    let open = use_state(|| false);
+    let method = use_state(|| PitchMethod::Autocorrelation);
+
+    let pitch = use_memo(
+        |(channel, region, method, open)| {
+            open.then(|| channel.slice(region.clone()).pitch(*method))
+                .flatten()
+        },
+        (channel.clone(), region.clone(), *method, *open),
+    );
+
+    let track = use_memo(
+        |(channel, view, settings)| {
+            settings.track.then(|| {
+                let hop = (settings.hop_ms / 1000.0 * channel.sample_rate() as f64) as usize;
+                channel.pitch_track(view.clone(), hop.max(view.len() / MAX_FRAMES))
+            })
+        },
+        (channel.clone(), view.clone(), *settings),
+    );
+    {
+        let on_track = on_track.clone();
+        use_effect_with_deps(move |track| on_track.emit((**track).clone()), track);
+    }
+
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |event: Event| {
+            open.set(event.target_unchecked_into::<HtmlDetailsElement>().open())
+        })
+    };
+    let on_method = {
+        let method = method.clone();
+        Callback::from(move |event: Event| method.set(PitchMethod::ALL[select_index(&event)]))
+    };
+    let settings = *settings;
+    let on_track_toggle = on_settings.reform(move |_| PitchSettings {
+        track: !settings.track,
+        ..settings
+    });
+    let on_hop_ms = {
+        let on_settings = on_settings.clone();
+        Callback::from(move |event: Event| {
+            if let Some(hop_ms) = input_value::<f64>(&event).filter(|&v| v > 0.0) {
+                on_settings.emit(PitchSettings { hop_ms, ..settings });
+            }
+        })
+    };
+
+    let result = match *pitch {
+        Some(pitch) => {
+            let (note, cents) = pitch.note();
+            html! {
+                <table class="statistics">
+                    <tr><td>{"Fundamental"}</td><td>{format!("{:.1} Hz", pitch.frequency)}</td></tr>
+                    <tr><td>{"Note"}</td><td>{format!("{note} {cents:+.0} cents")}</td></tr>
+                    <tr><td>{"Confidence"}</td><td>{format!("{:.0} %", pitch.confidence * 100.0)}</td></tr>
+                </table>
+            }
+        }
+        None if *open => html! { <p>{"No pitch detected."}</p> },
+        None => html!(),
+    };
+
+    html! {
+        <details class="panel" ontoggle={on_toggle}>
+            <summary>{"Pitch"}</summary>
+            <select onchange={on_method}>
+                { for PitchMethod::ALL.into_iter().map(|m| html! {
+                    <option selected={m == *method}>{m.name()}</option>
+                }) }
+            </select>
+            {result}
+            <label>
+                <input type="checkbox" checked={settings.track} onchange={on_track_toggle} />
+                {"Show pitch over time"}
+            </label>
+            <label>
+                {"Hop (ms)"}
+                <input type="number" min="1" step="any"
+                    value={settings.hop_ms.to_string()}
+                    onchange={on_hop_ms} />
+            </label>
+        </details>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct PitchTrackViewProps {
+    pub track: PitchTrack,
+    pub view: Range<usize>,
+}
+
+// Pitch contour beneath the waveform on a semitone grid, with the octaves of C labelled.
+#[function_component(PitchTrackView)]
+pub fn pitch_track_view(PitchTrackViewProps { track, view }: &PitchTrackViewProps) -> Html {
+    const Y_SCALE: f64 = 1.0125;
+
+    let view_len = view.len().max(1);
+    let height = HIGHEST_NOTE - LOWEST_NOTE;
+
+    // Unvoiced frames break the contour into separate segments.
+    let contour = bench!(["Formatting pitch track"] => {
+        let mut connected = false;
+        let mut path = String::new();
+        for (frame, pitch) in track.pitches.iter().enumerate() {
+            match pitch.filter(|pitch| pitch.confidence >= MIN_CONFIDENCE) {
+                Some(pitch) => {
+                    let x = track.frame_center(frame) as f64 - view.start as f64;
+                    let note = 69.0 + 12.0 * (pitch.frequency / 440.0).log2();
+                    let command = if connected { "L" } else { "M" };
+                    path.push_str(&format!("{command} {x} {:.4} ", HIGHEST_NOTE - note));
+                    connected = true;
+                }
+                None => connected = false,
+            }
+        }
+        path
+    });
+
+    let grid = |octaves: bool| {
+        (LOWEST_NOTE as i64..=HIGHEST_NOTE as i64)
+            .filter(|note| (note % 12 == 0) == octaves)
+            .map(|note| format!("M 0 {0} L {view_len} {0} ", HIGHEST_NOTE - note as f64))
+            .collect::<String>()
+    };
+    let labels = (LOWEST_NOTE as i64..=HIGHEST_NOTE as i64)
+        .filter(|note| note % 12 == 0)
+        .map(|note| {
+            let top = (HIGHEST_NOTE - note as f64) / height * 100.0;
+            html! {
+                <p style={format!("top: {top:.4}%")}>{format!("C{}", note / 12 - 1)}</p>
+            }
+        });
+
+    html! {
+        <div class="plot pitch-view">
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <svg
+                    viewBox={format!("0 0 {:.4} {height}", Y_SCALE * view_len as f64)}
+                    preserveAspectRatio="none">
+                    <path class="semitones" vector-effect="non-scaling-stroke" d={grid(false)} />
+                    <path class="octaves" vector-effect="non-scaling-stroke" d={grid(true)} />
+                    <path class="contour" vector-effect="non-scaling-stroke" d={contour} />
+                    <rect vector-effect="non-scaling-stroke"
+                        width={view_len.to_string()}
+                        height={height.to_string()} />
+                </svg>
+            </svg>
+            { for labels }
+        </div>
+    }
+}
@@ -0,0 +1,109 @@
+use std::ops::Range;
+
+use web_sys::HtmlDetailsElement;
+use yew::prelude::*;
+
+use super::input_value;
+use crate::{model::Signal, settings::PlaybackSettings};
+
+#[derive(Properties, PartialEq)]
+pub struct PlaybackPanelProps {
+    pub signal: Signal,
+    // The selection, or else the visible part of the signal, which is what gets played.
+    pub region: Range<usize>,
+    pub settings: PlaybackSettings,
+    pub on_settings: Callback<PlaybackSettings>,
+}
+
+// The gain and limiter on the way to the speakers, along with the peak that is played once they
+// are applied.
+#[function_component(PlaybackPanel)]
+pub fn playback_panel(
+    PlaybackPanelProps {
+        signal,
+        region,
+        settings,
+        on_settings,
+    }: &PlaybackPanelProps,
+) -> Html {
+    let open = use_state(|| false);
+
+    let peak_dbfs = use_memo(
+        |(signal, region, open)| {
+            open.then(|| {
+                bench!(["Measuring playback peak"] => (0..signal.num_channels())
+                    .map(|n| signal.channel(n).slice(region.clone()).statistics().peak_dbfs)
+                    .fold(f64::NEG_INFINITY, f64::max))
+            })
+        },
+        (signal.clone(), region.clone(), *open),
+    );
+
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |event: Event| {
+            open.set(event.target_unchecked_into::<HtmlDetailsElement>().open())
+        })
+    };
+    let settings = *settings;
+    let update = |f: fn(&mut PlaybackSettings, &Event)| {
+        let on_settings = on_settings.clone();
+        Callback::from(move |event: Event| {
+            let mut new_settings = settings;
+            f(&mut new_settings, &event);
+            on_settings.emit(new_settings);
+        })
+    };
+    let on_gain = update(|s, e| s.gain_db = input_value(e).map_or(s.gain_db, |g: f64| g.min(24.0)));
+    let on_limiter = update(|s, _| s.limiter = !s.limiter);
+    let on_ceiling =
+        update(|s, e| s.ceiling_dbfs = input_value(e).map_or(s.ceiling_dbfs, |c: f64| c.min(0.0)));
+
+    let meter = (*peak_dbfs).map(|peak_dbfs| {
+        let (output_dbfs, reduction) = settings.output_peak(peak_dbfs);
+        html! {
+            <table class="statistics">
+                <tr>
+                    <td>{"Peak played"}</td>
+                    <td>
+                        <meter min="-60" max="0" high="-6" optimum="-20"
+                            value={output_dbfs.to_string()} />
+                    </td>
+                    <td>{format!("{output_dbfs:.1} dBFS")}</td>
+                </tr>
+                if reduction > 0.0 {
+                    <tr>
+                        <td>{"Limited by"}</td>
+                        <td />
+                        <td>{format!("{reduction:.1} dB")}</td>
+                    </tr>
+                }
+            </table>
+        }
+    });
+
+    html! {
+        <details class="panel" ontoggle={on_toggle}>
+            <summary>{"Playback"}</summary>
+            <label>
+                {"Gain (dB)"}
+                <input type="number" max="24" step="any"
+                    value={settings.gain_db.to_string()}
+                    onchange={on_gain} />
+            </label>
+            <label>
+                <input type="checkbox" checked={settings.limiter} onchange={on_limiter} />
+                {"Limiter"}
+            </label>
+            if settings.limiter {
+                <label>
+                    {"Ceiling (dBFS)"}
+                    <input type="number" max="0" step="any"
+                        value={settings.ceiling_dbfs.to_string()}
+                        onchange={on_ceiling} />
+                </label>
+            }
+            {meter}
+        </details>
+    }
+}
@@ -0,0 +1,121 @@
+use yew::prelude::*;
+
+use super::input_value;
+use crate::{presets::Presets, settings::AnalysisSettings};
+
+#[derive(Properties, PartialEq)]
+pub struct PresetsPanelProps {
+    pub settings: AnalysisSettings,
+    // Name of the open file, if it is known.
+    pub file: Option<String>,
+    pub on_apply: Callback<AnalysisSettings>,
+}
+
+#[function_component(PresetsPanel)]
+pub fn presets_panel(
+    PresetsPanelProps {
+        settings,
+        file,
+        on_apply,
+    }: &PresetsPanelProps,
+) -> Html {
+    let presets = use_state(Presets::load);
+    let name = use_state(String::new);
+
+    let edit = |f: Box<dyn Fn(&mut Presets)>| {
+        let presets = presets.clone();
+        Callback::from(move |_| {
+            let mut new_presets = (*presets).clone();
+            f(&mut new_presets);
+            new_presets.store();
+            presets.set(new_presets);
+        })
+    };
+
+    let on_name = {
+        let name = name.clone();
+        Callback::from(move |event: Event| {
+            name.set(
+                input_value::<String>(&event)
+                    .unwrap_or_default()
+                    .trim()
+                    .to_owned(),
+            )
+        })
+    };
+    let on_save = {
+        let name = (*name).clone();
+        let settings = settings.clone();
+        edit(Box::new(move |presets| {
+            if !name.is_empty() {
+                presets.presets.insert(name.clone(), settings.clone());
+            }
+        }))
+    };
+
+    let items = presets.presets.iter().map(|(preset, preset_settings)| {
+        let on_apply = on_apply.reform({
+            let preset_settings = preset_settings.clone();
+            move |_| preset_settings.clone()
+        });
+        let is_default = presets.default.as_ref() == Some(preset);
+        let on_default = {
+            let preset = preset.clone();
+            edit(Box::new(move |presets| {
+                presets.default = (!is_default).then(|| preset.clone());
+            }))
+        };
+        let for_file = file.as_ref().map(|file| {
+            let is_assigned = presets.files.get(file) == Some(preset);
+            let on_assign = {
+                let preset = preset.clone();
+                let file = file.clone();
+                edit(Box::new(move |presets| {
+                    if is_assigned {
+                        presets.files.remove(&file);
+                    } else {
+                        presets.files.insert(file.clone(), preset.clone());
+                    }
+                }))
+            };
+            html! {
+                <label title={format!("Apply when opening {file}")}>
+                    <input type="checkbox" checked={is_assigned} onchange={on_assign} />
+                    {"File"}
+                </label>
+            }
+        });
+        let on_remove = {
+            let preset = preset.clone();
+            edit(Box::new(move |presets| presets.remove(&preset)))
+        };
+
+        html! {
+            <li>
+                <span>{preset}</span>
+                <button onclick={on_apply}>{"Apply"}</button>
+                <label title="Apply on startup and to files without a preset">
+                    <input type="checkbox" checked={is_default} onchange={on_default} />
+                    {"Default"}
+                </label>
+                {for_file}
+                <button onclick={on_remove.reform(Event::from)}>{"×"}</button>
+            </li>
+        }
+    });
+
+    html! {
+        <details class="panel">
+            <summary>{"Presets"}</summary>
+            <ul class="presets">{ for items }</ul>
+            <label>
+                <input type="text" placeholder="Preset name"
+                    value={(*name).clone()}
+                    onchange={on_name} />
+                <button disabled={name.is_empty()} onclick={on_save.reform(Event::from)}>
+                    {"Save"}
+                </button>
+            </label>
+        </details>
+    }
+}
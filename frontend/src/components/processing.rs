@@ -0,0 +1,102 @@
+use yew::prelude::*;
+
+use super::{input_value, select_index};
+use crate::model::{processing::Operation, resample::ResampleQuality};
+
+type Edit = Box<dyn Fn(&mut Vec<Operation>, &Event)>;
+
+#[derive(Properties, PartialEq)]
+pub struct ProcessingPanelProps {
+    pub chain: Vec<Operation>,
+    pub on_change: Callback<Vec<Operation>>,
+}
+
+#[function_component(ProcessingPanel)]
+pub fn processing_panel(ProcessingPanelProps { chain, on_change }: &ProcessingPanelProps) -> Html {
+    let selected = use_state(|| 0);
+
+    let edit = |f: Edit| {
+        let chain = chain.clone();
+        let on_change = on_change.clone();
+        Callback::from(move |event: Event| {
+            let mut new_chain = chain.clone();
+            f(&mut new_chain, &event);
+            on_change.emit(new_chain);
+        })
+    };
+
+    let on_select = {
+        let selected = selected.clone();
+        Callback::from(move |event: Event| selected.set(select_index(&event)))
+    };
+    let on_add = {
+        let selected = *selected;
+        edit(Box::new(move |chain, _| {
+            chain.push(Operation::DEFAULTS[selected])
+        }))
+    };
+    let on_clear = on_change.reform(|_| Vec::new());
+
+    let operations = chain.iter().enumerate().map(|(i, operation)| {
+        let on_parameter = edit(Box::new(move |chain, event| {
+            if let Some(value) = input_value(event) {
+                chain[i] = chain[i].with_parameter(value);
+            }
+        }));
+        let on_quality = edit(Box::new(move |chain, event| {
+            if let Operation::Resample { rate, .. } = chain[i] {
+                chain[i] = Operation::Resample {
+                    rate,
+                    quality: ResampleQuality::ALL[select_index(event)],
+                };
+            }
+        }));
+        let on_up = edit(Box::new(move |chain, _| chain.swap(i - 1, i)));
+        let on_down = edit(Box::new(move |chain, _| chain.swap(i, i + 1)));
+        let on_remove = edit(Box::new(move |chain, _| {
+            chain.remove(i);
+        }));
+
+        html! {
+            <li>
+                <span>{operation.name()}</span>
+                if let Some((value, unit)) = operation.parameter() {
+                    <input type="number" step="any"
+                        value={value.to_string()}
+                        onchange={on_parameter} />
+                    <span>{unit}</span>
+                }
+                if let Operation::Resample { quality, .. } = operation {
+                    <select onchange={on_quality}>
+                        { for ResampleQuality::ALL.into_iter().map(|q| html! {
+                            <option selected={q == *quality}>{q.name()}</option>
+                        }) }
+                    </select>
+                }
+                <button disabled={i == 0} onclick={on_up.reform(Event::from)}>{"↑"}</button>
+                <button disabled={i + 1 == chain.len()} onclick={on_down.reform(Event::from)}>
+                    {"↓"}
+                </button>
+                <button onclick={on_remove.reform(Event::from)}>{"×"}</button>
+            </li>
+        }
+    });
+
+    html! {
+        <details class="panel">
+            <summary>{"Processing"}</summary>
+            <ol class="processing">{ for operations }</ol>
+            <label>
+                <select onchange={on_select}>
+                    { for Operation::DEFAULTS.into_iter().enumerate().map(|(i, o)| html! {
+                        <option selected={i == *selected}>{o.name()}</option>
+                    }) }
+                </select>
+                <button onclick={on_add.reform(Event::from)}>{"Add"}</button>
+            </label>
+            if !chain.is_empty() {
+                <button onclick={on_clear}>{"Clear"}</button>
+            }
+        </details>
+    }
+}
@@ -0,0 +1,84 @@
+use yew::prelude::*;
+
+use super::{input_value, select_index};
+use crate::model::raw::{Endianness, RawFormat, RawSpec};
+
+#[derive(Properties, PartialEq)]
+pub struct RawImportPromptProps {
+    pub name: String,
+    // In bytes.
+    pub size: usize,
+    // The spec last used, to start from.
+    pub spec: RawSpec,
+    pub on_open: Callback<RawSpec>,
+    pub on_cancel: Callback<()>,
+}
+
+// Asks how to read a file without a WAV or AIFF header as bare samples.
+#[function_component(RawImportPrompt)]
+pub fn raw_import_prompt(
+    RawImportPromptProps {
+        name,
+        size,
+        spec,
+        on_open,
+        on_cancel,
+    }: &RawImportPromptProps,
+) -> Html {
+    let spec = use_state_eq({
+        let spec = *spec;
+        move || spec
+    });
+
+    let update = |f: fn(&mut RawSpec, &Event)| {
+        let spec = spec.clone();
+        Callback::from(move |event: Event| {
+            let mut new_spec = *spec;
+            f(&mut new_spec, &event);
+            spec.set(new_spec);
+        })
+    };
+    let on_format = update(|s, e| s.format = RawFormat::ALL[select_index(e)]);
+    let on_endianness = update(|s, e| s.endianness = Endianness::ALL[select_index(e)]);
+    let on_channels = update(|s, e| s.channels = select_index(e) as u16 + 1);
+    let on_sample_rate =
+        update(|s, e| s.sample_rate = input_value(e).filter(|&r| r > 0).unwrap_or(s.sample_rate));
+    let on_read = {
+        let spec = *spec;
+        on_open.reform(move |_| spec)
+    };
+
+    let frames = size / (spec.format.bytes_per_sample() * usize::from(spec.channels));
+    let duration = frames as f64 / f64::from(spec.sample_rate);
+
+    html! {
+        <div class="notice">
+            {format!("{name} has no WAV or AIFF header. Read it as")}
+            <select onchange={on_format}>
+                { for RawFormat::ALL.into_iter().map(|f| html! {
+                    <option selected={f == spec.format}>{f.name()}</option>
+                }) }
+            </select>
+            if spec.format.bytes_per_sample() > 1 {
+                <select onchange={on_endianness}>
+                    { for Endianness::ALL.into_iter().map(|e| html! {
+                        <option selected={e == spec.endianness}>{e.name()}</option>
+                    }) }
+                </select>
+            }
+            <select onchange={on_channels}>
+                <option selected={spec.channels == 1}>{"Mono"}</option>
+                <option selected={spec.channels == 2}>{"Stereo"}</option>
+            </select>
+            <label>
+                {"Sample rate (Hz)"}
+                <input type="number" min="1" step="1"
+                    value={spec.sample_rate.to_string()}
+                    onchange={on_sample_rate} />
+            </label>
+            {format!("({duration:.2} s)")}
+            <button onclick={on_read}>{"Read samples"}</button>
+            <button onclick={on_cancel.reform(|_| ())}>{"Cancel"}</button>
+        </div>
+    }
+}
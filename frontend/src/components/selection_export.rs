@@ -0,0 +1,62 @@
+use yew::prelude::*;
+
+use super::{input_value, select_index};
+use crate::settings::{ExportSettings, SelectionEdges};
+
+#[derive(Properties, PartialEq)]
+pub struct SelectionExportPanelProps {
+    pub settings: ExportSettings,
+    pub on_settings: Callback<ExportSettings>,
+    pub selected: bool,
+    pub on_export: Callback<()>,
+}
+
+// Exports the selection as a WAV file of its own, with its edges faded or crossfaded so that the
+// snippet does not click where it was cut.
+#[function_component(SelectionExportPanel)]
+pub fn selection_export_panel(
+    SelectionExportPanelProps {
+        settings,
+        on_settings,
+        selected,
+        on_export,
+    }: &SelectionExportPanelProps,
+) -> Html {
+    let settings = *settings;
+    let update = |f: fn(&mut ExportSettings, &Event)| {
+        let on_settings = on_settings.clone();
+        Callback::from(move |event: Event| {
+            let mut new_settings = settings;
+            f(&mut new_settings, &event);
+            on_settings.emit(new_settings);
+        })
+    };
+    let on_edges = update(|s, e| s.edges = SelectionEdges::ALL[select_index(e)]);
+    let on_fade =
+        update(|s, e| s.fade_ms = input_value(e).map_or(s.fade_ms, |ms: f64| ms.max(0.0)));
+
+    html! {
+        <details class="panel">
+            <summary>{"Selection export"}</summary>
+            <label>
+                {"Edges"}
+                <select onchange={on_edges}>
+                    { for SelectionEdges::ALL.into_iter().map(|edges| html! {
+                        <option selected={edges == settings.edges}>{edges.name()}</option>
+                    }) }
+                </select>
+            </label>
+            if settings.edges != SelectionEdges::Cut {
+                <label>
+                    {"Length (ms)"}
+                    <input type="number" min="0" step="any"
+                        value={settings.fade_ms.to_string()}
+                        onchange={on_fade} />
+                </label>
+            }
+            <button disabled={!*selected} onclick={on_export.reform(|_| ())}>
+                {"Export selection as WAV"}
+            </button>
+        </details>
+    }
+}
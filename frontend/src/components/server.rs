@@ -0,0 +1,313 @@
+use std::{cell::RefCell, collections::HashMap, ops::Range, rc::Rc};
+
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+
+use crate::{
+    canvas,
+    hooks::use_task_slot,
+    server::{self, JobState, Series, SeriesPage, ServerJob, SpectrogramTiles},
+    theme::Theme,
+};
+
+// Time columns and logarithmically spaced frequency rows of the spectrogram.
+const COLUMNS: usize = 1024;
+const ROWS: usize = 256;
+const RANGE_DB: f64 = 100.0;
+// The fewest frames of level 0 to zoom in to.
+const MIN_SPAN: usize = 64;
+// The momentary loudness shown below the spectrogram, and the range of it shown.
+const LOUDNESS_SERIES: &str = "momentary_loudness";
+const LOUDNESS_RANGE_LU: f64 = 60.0;
+
+// Fetched tiles by zoom and index.
+type TileCache = HashMap<(usize, usize), Rc<Vec<f32>>>;
+
+#[derive(Properties, PartialEq)]
+pub struct ServerJobNoticeProps {
+    pub job: ServerJob,
+    pub on_dismiss: Callback<()>,
+    pub theme: Theme,
+}
+
+// The progress and results of a file analysed by the backend rather than opened in the browser.
+#[function_component(ServerJobNotice)]
+pub fn server_job_notice(
+    ServerJobNoticeProps {
+        job,
+        on_dismiss,
+        theme,
+    }: &ServerJobNoticeProps,
+) -> Html {
+    let name = &job.name;
+    let status = match &job.state {
+        JobState::Running { progress, tiles: 0 } => {
+            format!("Analysing {name} on the server: {:.0}%", progress * 100.0)
+        }
+        JobState::Running { progress, tiles } => format!(
+            "Analysing {name} on the server: {:.0}%, {tiles} spectrogram tiles ready",
+            progress * 100.0
+        ),
+        JobState::Done { analysis } => {
+            let loudness = match analysis.integrated_lufs {
+                Some(integrated) => format!("{integrated:.1} LUFS"),
+                None => "–".to_owned(),
+            };
+            format!(
+                "{name}: {:.1} s at {} Hz, integrated loudness {loudness}, true peak {:.1} dBTP, \
+                 spectrogram of {} frames",
+                analysis.duration_seconds,
+                analysis.sample_rate,
+                analysis.true_peak_dbtp,
+                analysis.spectrogram.frames,
+            )
+        }
+        JobState::Failed { error } => format!("Failed to analyse {name} on the server: {error}"),
+    };
+
+    html! {
+        <>
+            <div class="notice">
+                {status}
+                <button onclick={on_dismiss.reform(|_| ())}>{"Dismiss"}</button>
+            </div>
+            if let (Some(id), JobState::Done { analysis }) = (job.id, &job.state) {
+                <ServerSpectrogramView
+                    id={id}
+                    spectrogram={analysis.spectrogram.clone()}
+                    series={analysis.series.clone()}
+                    sample_rate={analysis.sample_rate}
+                    theme={*theme} />
+            }
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ServerSpectrogramViewProps {
+    pub id: u64,
+    pub spectrogram: SpectrogramTiles,
+    pub series: Vec<Series>,
+    pub sample_rate: u32,
+    pub theme: Theme,
+}
+
+// The spectrogram computed by the backend, fetching only the tiles in view from the coarsest level
+// of the pyramid that still has a frame for each column, above the momentary loudness downsampled
+// by the backend to a point for each column.
+#[function_component(ServerSpectrogramView)]
+pub fn server_spectrogram_view(
+    ServerSpectrogramViewProps {
+        id,
+        spectrogram,
+        series,
+        sample_rate,
+        theme,
+    }: &ServerSpectrogramViewProps,
+) -> Html {
+    let frames = spectrogram.frames;
+    // The frames of level 0 in view.
+    let view = use_state(|| 0..frames);
+    let zoom = (view.len() / COLUMNS)
+        .max(1)
+        .ilog2()
+        .min(spectrogram.zooms.saturating_sub(1) as u32) as usize;
+
+    let tiles = use_mut_ref(TileCache::new);
+    // The number of tiles fetched, to redraw as they arrive.
+    let fetched = use_state(|| 0);
+    let fetch_task = use_task_slot();
+    {
+        let tiles = tiles.clone();
+        let fetched = fetched.clone();
+        use_effect_with_deps(
+            move |&(id, zoom, ref visible)| {
+                let missing: Vec<_> = visible
+                    .clone()
+                    .filter(|&tile| !tiles.borrow().contains_key(&(zoom, tile)))
+                    .collect();
+                fetch_task.spawn(async move {
+                    for tile in missing {
+                        match server::spectrogram_tile(id, zoom, tile).await {
+                            Ok(levels) => {
+                                tiles.borrow_mut().insert((zoom, tile), Rc::new(levels));
+                                fetched.set(tiles.borrow().len());
+                            }
+                            Err(error) => {
+                                crate::log(&error);
+                                return;
+                            }
+                        }
+                    }
+                });
+            },
+            (*id, zoom, visible_tiles(spectrogram, &view, zoom)),
+        );
+    }
+
+    let bins = spectrogram.frame_len / 2;
+    let nyquist_log = (*sample_rate as f64 / 2.0).log10();
+    let canvas_ref = use_node_ref();
+    {
+        let canvas_ref = canvas_ref.clone();
+        let tiles = tiles.clone();
+        use_effect_with_deps(
+            move |(spectrogram, view, _)| {
+                let Some(element) = canvas_ref.cast::<HtmlCanvasElement>() else {
+                    return;
+                };
+                draw(&element, spectrogram, view, zoom, &tiles, nyquist_log, bins);
+            },
+            (spectrogram.clone(), (*view).clone(), *fetched),
+        );
+    }
+
+    let seconds = |frame: usize| (frame * spectrogram.hop) as f64 / *sample_rate as f64;
+    let view_seconds = seconds(view.start)..seconds(view.end);
+    let has_loudness = series.iter().any(|series| series.name == LOUDNESS_SERIES);
+    let loudness = use_state(|| None::<SeriesPage>);
+    let loudness_task = use_task_slot();
+    {
+        let loudness = loudness.clone();
+        use_effect_with_deps(
+            move |&(id, ref view_seconds, has_loudness)| {
+                if !has_loudness {
+                    return;
+                }
+                let view_seconds = view_seconds.clone();
+                loudness_task.spawn(async move {
+                    match server::series(id, LOUDNESS_SERIES, view_seconds, COLUMNS).await {
+                        Ok(page) => loudness.set(Some(page)),
+                        Err(error) => crate::log(&error),
+                    }
+                });
+            },
+            (*id, view_seconds.clone(), has_loudness),
+        );
+    }
+    let loudness_ref = use_node_ref();
+    {
+        let loudness_ref = loudness_ref.clone();
+        use_effect_with_deps(
+            move |(loudness, view_seconds, theme)| {
+                let (Some(element), Some(page)) =
+                    (loudness_ref.cast::<HtmlCanvasElement>(), loudness.as_ref())
+                else {
+                    return;
+                };
+                let span = view_seconds.end - view_seconds.start;
+                let y = |level: f32| (-f64::from(level) / LOUDNESS_RANGE_LU).clamp(0.0, 1.0);
+                let ranges = page
+                    .min
+                    .iter()
+                    .zip(&page.max)
+                    .enumerate()
+                    .map(|(n, (&min, &max))| {
+                        let time = page.start_seconds + (n as f64 + 0.5) * page.step_seconds;
+                        ((time - view_seconds.start) / span, y(min), y(max))
+                    });
+                if let Err(error) = canvas::draw_ranges(&element, ranges, theme.overlay) {
+                    crate::log(&format!("failed to draw loudness: {error:?}"));
+                }
+            },
+            ((*loudness).clone(), view_seconds.clone(), *theme),
+        );
+    }
+
+    let set_view = |change: fn(Range<usize>, usize) -> Range<usize>| {
+        let view = view.clone();
+        Callback::from(move |_| {
+            let span = view.len();
+            let new_view = change((*view).clone(), span);
+            let span = new_view.len().clamp(MIN_SPAN.min(frames), frames);
+            let start = new_view.start.min(frames - span);
+            view.set(start..start + span);
+        })
+    };
+    let on_zoom_in = set_view(|view, span| {
+        let center = view.start + span / 2;
+        center.saturating_sub(span / 4)..center + span / 4
+    });
+    let on_zoom_out = set_view(|view, span| {
+        let center = view.start + span / 2;
+        center.saturating_sub(span)..center + span
+    });
+    let on_back = set_view(|view, span| {
+        let start = view.start.saturating_sub(span / 2);
+        start..start + span
+    });
+    let on_forward = set_view(|view, span| view.start + span / 2..view.end + span / 2);
+    let on_reset = {
+        let view = view.clone();
+        Callback::from(move |_| view.set(0..frames))
+    };
+
+    html! {
+        <div class="server-spectrogram">
+            <canvas ref={canvas_ref} />
+            if has_loudness {
+                <canvas class="loudness" ref={loudness_ref} />
+            }
+            <div class="controls">
+                {format!("{:.1}–{:.1} s", view_seconds.start, view_seconds.end)}
+                <button onclick={on_zoom_in}>{"+"}</button>
+                <button onclick={on_zoom_out}>{"−"}</button>
+                <button onclick={on_back}>{"←"}</button>
+                <button onclick={on_forward}>{"→"}</button>
+                <button onclick={on_reset}>{"Reset"}</button>
+            </div>
+        </div>
+    }
+}
+
+// The indices of the tiles of a level that overlap the view.
+fn visible_tiles(spectrogram: &SpectrogramTiles, view: &Range<usize>, zoom: usize) -> Range<usize> {
+    if view.is_empty() {
+        return 0..0;
+    }
+    let first = (view.start >> zoom) / spectrogram.tile_frames;
+    let last = ((view.end - 1) >> zoom).min(spectrogram.frames_at_zoom(zoom) - 1)
+        / spectrogram.tile_frames;
+    first..last + 1
+}
+
+fn draw(
+    element: &HtmlCanvasElement,
+    spectrogram: &SpectrogramTiles,
+    view: &Range<usize>,
+    zoom: usize,
+    tiles: &RefCell<TileCache>,
+    nyquist_log: f64,
+    bins: usize,
+) {
+    let tiles = tiles.borrow();
+    let rows: Vec<_> = (0..ROWS)
+        .map(|row| {
+            let frequency_log = nyquist_log * (1.0 - (row as f64 + 0.5) / ROWS as f64);
+            let bin = 10_f64.powf(frequency_log) / 10_f64.powf(nyquist_log) * bins as f64;
+            (bin as usize).min(bins - 1)
+        })
+        .collect();
+    let columns: Vec<_> = (0..COLUMNS)
+        .map(|column| {
+            let frame = view.start + (column * 2 + 1) * view.len() / (COLUMNS * 2);
+            let frame = frame >> zoom;
+            let tile = tiles.get(&(zoom, frame / spectrogram.tile_frames))?;
+            let offset = frame % spectrogram.tile_frames * bins;
+            tile.get(offset..offset + bins)
+        })
+        .collect();
+
+    let result = bench!(["Drawing server spectrogram"] => canvas::draw_heatmap(
+        element,
+        COLUMNS,
+        ROWS,
+        |column, row| match columns[column] {
+            Some(levels) => 1.0 + levels[rows[row]] as f64 / RANGE_DB,
+            None => 0.0,
+        },
+    ));
+    if let Err(error) = result {
+        crate::log(&format!("failed to draw server spectrogram: {error:?}"));
+    }
+}
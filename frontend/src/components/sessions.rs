@@ -0,0 +1,142 @@
+use yew::prelude::*;
+
+use super::input_value;
+use crate::{
+    hooks::use_task_slot,
+    server::{self, SessionSummary},
+    session::SessionState,
+};
+
+#[derive(Properties, PartialEq)]
+pub struct SessionsPanelProps {
+    pub session: SessionState,
+    // Name of the open file, if it is known.
+    pub file: Option<String>,
+    pub on_open: Callback<SessionState>,
+}
+
+// Sessions saved on the backend, to be opened again later or elsewhere. Like exported session
+// files, they hold the settings, view and markers, but not the signal itself.
+#[function_component(SessionsPanel)]
+pub fn sessions_panel(
+    SessionsPanelProps {
+        session,
+        file,
+        on_open,
+    }: &SessionsPanelProps,
+) -> Html {
+    let sessions = use_state(Vec::<SessionSummary>::new);
+    let name = use_state(String::new);
+    let notes = use_state(String::new);
+    let task = use_task_slot();
+
+    // The list is fetched when the panel is first opened and after each save.
+    let refresh = {
+        let sessions = sessions.clone();
+        let task = task.clone();
+        Callback::from(move |_| {
+            let sessions = sessions.clone();
+            task.spawn(async move {
+                match server::sessions().await {
+                    Ok(list) => sessions.set(list),
+                    Err(error) => crate::log(&error),
+                }
+            });
+        })
+    };
+    let loaded = use_state(|| false);
+    let on_toggle = {
+        let refresh = refresh.clone();
+        Callback::from(move |_| {
+            if !*loaded {
+                loaded.set(true);
+                refresh.emit(());
+            }
+        })
+    };
+
+    let on_name = {
+        let name = name.clone();
+        Callback::from(move |event: Event| {
+            name.set(
+                input_value::<String>(&event)
+                    .unwrap_or_default()
+                    .trim()
+                    .to_owned(),
+            )
+        })
+    };
+    let on_notes = {
+        let notes = notes.clone();
+        Callback::from(move |event: Event| notes.set(input_value(&event).unwrap_or_default()))
+    };
+    let on_save = {
+        let name = name.clone();
+        let notes = notes.clone();
+        let session = session.clone();
+        let file = file.clone();
+        let task = task.clone();
+        Callback::from(move |_| {
+            let (name, notes, session, file) =
+                (name.clone(), notes.clone(), session.clone(), file.clone());
+            let refresh = refresh.clone();
+            task.spawn(async move {
+                let result = server::save_session(&name, file.as_deref(), &notes, &session).await;
+                match result {
+                    Ok(_) => {
+                        name.set(String::new());
+                        notes.set(String::new());
+                        refresh.emit(());
+                    }
+                    Err(error) => crate::log(&error),
+                }
+            });
+        })
+    };
+
+    let items = sessions.iter().map(|summary| {
+        let on_open = {
+            let on_open = on_open.clone();
+            let task = task.clone();
+            let id = summary.id;
+            Callback::from(move |_| {
+                let on_open = on_open.clone();
+                task.spawn(async move {
+                    match server::session(id).await {
+                        Ok(state) => on_open.emit(state),
+                        Err(error) => crate::log(&error),
+                    }
+                });
+            })
+        };
+        let file = summary
+            .file_name
+            .as_ref()
+            .map_or_else(String::new, |file| format!(" of {file}"));
+
+        html! {
+            <li title={summary.notes.clone()}>
+                <span>{format!("{}{file}, saved {}", summary.name, summary.saved_at)}</span>
+                <button onclick={on_open}>{"Open"}</button>
+            </li>
+        }
+    });
+
+    html! {
+        <details class="panel" ontoggle={on_toggle}>
+            <summary>{"Server sessions"}</summary>
+            <ul class="presets">{ for items }</ul>
+            <label>
+                <input type="text" placeholder="Session name"
+                    value={(*name).clone()}
+                    onchange={on_name} />
+            </label>
+            <label>
+                <input type="text" placeholder="Notes"
+                    value={(*notes).clone()}
+                    onchange={on_notes} />
+                <button disabled={name.is_empty()} onclick={on_save}>{"Save session"}</button>
+            </label>
+        </details>
+    }
+}
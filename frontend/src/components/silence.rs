@@ -0,0 +1,120 @@
+use std::ops::Range;
+
+use yew::prelude::*;
+
+use super::input_value;
+use crate::{model::Channel, settings::SilenceSettings};
+
+#[derive(Properties, PartialEq)]
+pub struct SilencePanelProps {
+    pub channel: Channel,
+    pub settings: SilenceSettings,
+    pub on_settings: Callback<SilenceSettings>,
+    pub on_detected: Callback<Vec<Range<usize>>>,
+    pub on_zoom: Callback<Range<usize>>,
+}
+
+#[function_component(SilencePanel)]
+pub fn silence_panel(
+    SilencePanelProps {
+        channel,
+        settings,
+        on_settings,
+        on_detected,
+        on_zoom,
+    }: &SilencePanelProps,
+) -> Html {
+    let regions = use_memo(
+        |(channel, settings)| {
+            if settings.enabled {
+                bench!(["Detecting silence"] => {
+                    channel.detect_silence(settings.threshold_db, settings.min_duration)
+                })
+            } else {
+                Vec::new()
+            }
+        },
+        (channel.clone(), *settings),
+    );
+
+    {
+        let on_detected = on_detected.clone();
+        use_effect_with_deps(
+            move |regions| on_detected.emit((**regions).clone()),
+            regions.clone(),
+        );
+    }
+
+    let settings = *settings;
+    let on_toggle = on_settings.reform(move |_| SilenceSettings {
+        enabled: !settings.enabled,
+        ..settings
+    });
+    let on_threshold = {
+        let on_settings = on_settings.clone();
+        Callback::from(move |event: Event| {
+            if let Some(value) = input_value::<f64>(&event) {
+                on_settings.emit(SilenceSettings {
+                    threshold_db: value.min(0.0),
+                    ..settings
+                });
+            }
+        })
+    };
+    let on_min_duration = {
+        let on_settings = on_settings.clone();
+        Callback::from(move |event: Event| {
+            if let Some(value) = input_value::<f64>(&event).filter(|&v| v > 0.0) {
+                on_settings.emit(SilenceSettings {
+                    min_duration: value,
+                    ..settings
+                });
+            }
+        })
+    };
+
+    let sample_rate = channel.sample_rate() as f64;
+    let count = channel.count();
+
+    html! {
+        <details class="panel">
+            <summary>{"Silence"}</summary>
+            <label>
+                <input type="checkbox" checked={settings.enabled} onchange={on_toggle} />
+                {"Detect silence"}
+            </label>
+            <label>
+                {"Threshold (dBFS)"}
+                <input type="number" max="0" step="1"
+                    value={settings.threshold_db.to_string()}
+                    onchange={on_threshold} />
+            </label>
+            <label>
+                {"Minimum duration (s)"}
+                <input type="number" min="0" step="any"
+                    value={settings.min_duration.to_string()}
+                    onchange={on_min_duration} />
+            </label>
+            if settings.enabled {
+                <p>{format!("{} silent regions", regions.len())}</p>
+                <ul class="regions">
+                    { for regions.iter().map(|range| {
+                        // Zoom to the region with some context on either side.
+                        let padding = range.len() / 4;
+                        let zoom = range.start.saturating_sub(padding)
+                            ..(range.end + padding).min(count);
+                        let onclick = on_zoom.reform(move |_| zoom.clone());
+                        html! {
+                            <li {onclick}>{format!(
+                                "{:.3} – {:.3} s ({:.3} s)",
+                                range.start as f64 / sample_rate,
+                                range.end as f64 / sample_rate,
+                                range.len() as f64 / sample_rate,
+                            )}</li>
+                        }
+                    }) }
+                </ul>
+            }
+        </details>
+    }
+}
@@ -0,0 +1,149 @@
+use web_sys::HtmlDetailsElement;
+use yew::prelude::*;
+
+use super::{input_value, select_index};
+use crate::{
+    model::{
+        sound_level::{FrequencyWeighting, TimeWeighting},
+        Channel,
+    },
+    settings::SoundLevelSettings,
+};
+
+#[derive(Properties, PartialEq)]
+pub struct SoundLevelPanelProps {
+    // The selection, or else the whole channel, which is what is measured.
+    pub channel: Channel,
+    pub selected: bool,
+    pub settings: SoundLevelSettings,
+    pub on_settings: Callback<SoundLevelSettings>,
+}
+
+// A sound level meter, reading in dB SPL once calibrated with a recording of a calibrator and in
+// dBFS until then.
+#[function_component(SoundLevelPanel)]
+pub fn sound_level_panel(
+    SoundLevelPanelProps {
+        channel,
+        selected,
+        settings,
+        on_settings,
+    }: &SoundLevelPanelProps,
+) -> Html {
+    let open = use_state(|| false);
+
+    let levels = use_memo(
+        |(channel, settings, open)| {
+            open.then(|| {
+                bench!(["Measuring sound levels"] => channel.sound_levels(
+                    settings.frequency_weighting,
+                    settings.time_weighting,
+                    settings.spl_offset_db.unwrap_or_default(),
+                ))
+            })
+        },
+        (channel.clone(), *settings, *open),
+    );
+
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |event: Event| {
+            open.set(event.target_unchecked_into::<HtmlDetailsElement>().open())
+        })
+    };
+    let settings = *settings;
+    let update = |f: fn(&mut SoundLevelSettings, &Event)| {
+        let on_settings = on_settings.clone();
+        Callback::from(move |event: Event| {
+            let mut new_settings = settings;
+            f(&mut new_settings, &event);
+            on_settings.emit(new_settings);
+        })
+    };
+    let on_frequency_weighting =
+        update(|s, e| s.frequency_weighting = FrequencyWeighting::ALL[select_index(e)]);
+    let on_time_weighting = update(|s, e| s.time_weighting = TimeWeighting::ALL[select_index(e)]);
+    let on_calibrator = update(|s, e| s.calibrator_db = input_value(e).unwrap_or(s.calibrator_db));
+    // The selection is taken to be the tone of the calibrator, whose level is the same through
+    // either weighting at 1 kHz.
+    let on_calibrate = {
+        let on_settings = on_settings.clone();
+        let channel = channel.clone();
+        Callback::from(move |_| {
+            on_settings.emit(SoundLevelSettings {
+                spl_offset_db: Some(settings.calibrator_db - channel.statistics().rms_dbfs),
+                ..settings
+            })
+        })
+    };
+    let on_uncalibrate = on_settings.reform(move |_| SoundLevelSettings {
+        spl_offset_db: None,
+        ..settings
+    });
+
+    let unit = if settings.spl_offset_db.is_some() {
+        "dB SPL"
+    } else {
+        "dBFS"
+    };
+    let table = (*levels).map(|levels| {
+        let row = |name: &str, level: f64| {
+            html! {
+                <tr>
+                    <td>{name}</td>
+                    <td>{format!("{level:.1} {unit}")}</td>
+                </tr>
+            }
+        };
+        html! {
+            <table class="statistics">
+                {row("Leq", levels.leq)}
+                {row("Lmax", levels.lmax)}
+                {row("Lmin", levels.lmin)}
+                {row("L10", levels.l10)}
+                {row("L50", levels.l50)}
+                {row("L90", levels.l90)}
+            </table>
+        }
+    });
+
+    html! {
+        <details class="panel" ontoggle={on_toggle}>
+            <summary>{"Sound level meter"}</summary>
+            <label>
+                {"Frequency weighting"}
+                <select onchange={on_frequency_weighting}>
+                    { for FrequencyWeighting::ALL.into_iter().map(|weighting| html! {
+                        <option selected={weighting == settings.frequency_weighting}>
+                            {weighting.name()}
+                        </option>
+                    }) }
+                </select>
+            </label>
+            <label>
+                {"Time weighting"}
+                <select onchange={on_time_weighting}>
+                    { for TimeWeighting::ALL.into_iter().map(|weighting| html! {
+                        <option selected={weighting == settings.time_weighting}>
+                            {weighting.name()}
+                        </option>
+                    }) }
+                </select>
+            </label>
+            <label>
+                {"Calibrator (dB SPL)"}
+                <input type="number" step="any"
+                    value={settings.calibrator_db.to_string()}
+                    onchange={on_calibrator} />
+            </label>
+            <button disabled={!*selected} onclick={on_calibrate}>
+                {"Calibrate with selection"}
+            </button>
+            if let Some(offset) = settings.spl_offset_db {
+                <p>{format!("0 dBFS is {offset:.1} dB SPL")}</p>
+                <button onclick={on_uncalibrate}>{"Remove SPL calibration"}</button>
+            }
+            {table}
+        </details>
+    }
+}
@@ -0,0 +1,328 @@
+use yew::prelude::*;
+
+use super::{input_value, select_index};
+use crate::{
+    model::{
+        peaks::PeakFormat,
+        recommendation::{SignalCharacter, WindowRecommendation},
+        response::ResponseFormat,
+        smoothing::FRACTIONS,
+        Channel,
+    },
+    settings::{SpectrumHold, SpectrumSettings, StereoSpectra},
+};
+
+const BANDS_PER_OCTAVE: [(u32, &str); 2] = [(1, "Octave"), (3, "Third-octave")];
+const SEGMENT_LENGTHS: [usize; 9] = [256, 512, 1024, 2048, 4096, 8192, 16384, 32768, 65536];
+const MAX_POINTS: [Option<usize>; 6] = [
+    None,
+    Some(8192),
+    Some(4096),
+    Some(2048),
+    Some(1024),
+    Some(512),
+];
+
+#[derive(Properties, PartialEq)]
+pub struct SpectrumPanelProps {
+    pub settings: SpectrumSettings,
+    pub on_settings: Callback<SpectrumSettings>,
+    // The selection, or else the whole channel, which settings are recommended for.
+    pub channel: Channel,
+    pub selected: bool,
+    // Number of averaged segments, when averaging.
+    pub segments: Option<usize>,
+    // The selected channel of a stereo signal, for choosing which spectra to draw.
+    pub stereo_channel: Option<usize>,
+    // In dB per octave, over the range of the settings.
+    pub tilt: Option<f64>,
+    // Starts holding or averaging over from the spectrum as it is now.
+    pub on_reset_hold: Callback<()>,
+    // Exports the spectrum of the channel as a frequency response.
+    pub on_export: Callback<ResponseFormat>,
+    // Exports the peaks of the spectrum of the channel, grouped into harmonics.
+    pub on_export_peaks: Callback<PeakFormat>,
+}
+
+#[function_component(SpectrumPanel)]
+pub fn spectrum_panel(
+    SpectrumPanelProps {
+        settings,
+        on_settings,
+        channel,
+        selected,
+        segments,
+        stereo_channel,
+        tilt,
+        on_reset_hold,
+        on_export,
+        on_export_peaks,
+    }: &SpectrumPanelProps,
+) -> Html {
+    let recommendation = use_state(|| None::<WindowRecommendation>);
+    {
+        let recommendation = recommendation.clone();
+        use_effect_with_deps(move |_| recommendation.set(None), channel.clone());
+    }
+
+    let settings = *settings;
+    let update = |f: fn(&mut SpectrumSettings, &Event)| {
+        let on_settings = on_settings.clone();
+        Callback::from(move |event: Event| {
+            let mut new_settings = settings;
+            f(&mut new_settings, &event);
+            on_settings.emit(new_settings);
+        })
+    };
+
+    let on_ltas = update(|s, _| s.ltas = !s.ltas);
+    let on_bands_per_octave =
+        update(|s, e| s.bands_per_octave = BANDS_PER_OCTAVE[select_index(e)].0);
+    let on_waterfall = update(|s, _| s.waterfall = !s.waterfall);
+    let on_gpu = update(|s, _| s.gpu = !s.gpu);
+    let on_worker = update(|s, _| s.worker = !s.worker);
+    let on_welch = update(|s, _| s.welch = !s.welch);
+    let on_segment_len = update(|s, e| s.segment_len = SEGMENT_LENGTHS[select_index(e)]);
+    let on_overlap = update(|s, e| {
+        s.overlap = input_value(e).map_or(s.overlap, |o: f64| o.clamp(0.0, 95.0) / 100.0)
+    });
+    let on_show_confidence = update(|s, _| s.show_confidence = !s.show_confidence);
+    let on_smoothing =
+        update(|s, e| s.smoothing = select_index(e).checked_sub(1).map(|n| FRACTIONS[n]));
+    let on_cursors = update(|s, _| s.cursors = !s.cursors);
+    let on_harmonics = update(|s, _| s.harmonics = !s.harmonics);
+    let on_harmonic_count = update(|s, e| {
+        s.harmonic_count = input_value(e).map_or(s.harmonic_count, |n: u32| n.clamp(2, 50))
+    });
+    let on_hold = update(|s, e| s.hold = SpectrumHold::ALL[select_index(e)]);
+    let on_max_points = update(|s, e| s.max_points = MAX_POINTS[select_index(e)]);
+    let on_stereo = update(|s, e| s.stereo = StereoSpectra::ALL[select_index(e)]);
+    let on_tilt_low = update(|s, e| {
+        s.tilt_low = input_value(e).map_or(s.tilt_low, |f: f64| f.clamp(1.0, s.tilt_high))
+    });
+    let on_tilt_high =
+        update(|s, e| s.tilt_high = input_value(e).map_or(s.tilt_high, |f: f64| f.max(s.tilt_low)));
+
+    let on_recommend = {
+        let recommendation = recommendation.clone();
+        let channel = channel.clone();
+        Callback::from(move |_| {
+            recommendation.set(Some(
+                bench!(["Recommending window"] => channel.recommend_window()),
+            ))
+        })
+    };
+    let recommended = (*recommendation).map(|recommendation| {
+        let character = match recommendation.character {
+            SignalCharacter::Tonal => "Tonal",
+            SignalCharacter::Broadband => "Broadband",
+        };
+        let advice = match (recommendation.character, recommendation.segment_len) {
+            (_, None) => "too short to average, so the whole of it is best transformed at once, \
+                          without a window"
+                .to_owned(),
+            (SignalCharacter::Tonal, Some(len)) => format!(
+                "Welch averaging over Hann-windowed segments of {len} samples, {:.1} Hz apart, \
+                 long enough to resolve the tones while the window keeps their leakage from \
+                 hiding what lies between them",
+                channel.sample_rate() as f64 / len as f64,
+            ),
+            (SignalCharacter::Broadband, Some(len)) => format!(
+                "Welch averaging over {} segments of {len} samples, short enough for the average \
+                 to settle",
+                recommendation.segments,
+            ),
+        };
+        let on_apply = on_settings.reform(move |_| SpectrumSettings {
+            welch: recommendation.segment_len.is_some(),
+            segment_len: recommendation.segment_len.unwrap_or(settings.segment_len),
+            overlap: recommendation.overlap,
+            ..settings
+        });
+        html! {
+            <>
+                <p>{format!("{character}: {advice}.")}</p>
+                <button onclick={on_apply}>{"Apply"}</button>
+            </>
+        }
+    });
+
+    // The selected channel is drawn in the color of the waveform and the other in that of overlays.
+    let legend = stereo_channel
+        .filter(|_| settings.stereo != StereoSpectra::Selected)
+        .map(|channel| {
+            let names = ["Left", "Right"];
+            html! {
+                <ul class="legend">
+                    <li class="legend a">{names[channel.min(1)]}</li>
+                    <li class="legend b">{names[1 - channel.min(1)]}</li>
+                    if settings.stereo == StereoSpectra::BothAndDifference {
+                        <li class="legend difference">{"Left − right"}</li>
+                    }
+                </ul>
+            }
+        });
+
+    html! {
+        <details class="panel">
+            <summary>{"Spectrum"}</summary>
+            <label>
+                <input type="checkbox" checked={settings.ltas} onchange={on_ltas} />
+                {"Octave-band spectrum"}
+            </label>
+            if settings.ltas {
+                <label>
+                    {"Bands"}
+                    <select onchange={on_bands_per_octave}>
+                        { for BANDS_PER_OCTAVE.into_iter().map(|(n, name)| html! {
+                            <option selected={n == settings.bands_per_octave}>{name}</option>
+                        }) }
+                    </select>
+                </label>
+            }
+            <label>
+                <input type="checkbox" checked={settings.waterfall} onchange={on_waterfall} />
+                {"Waterfall (spectrum over time)"}
+            </label>
+            if settings.waterfall {
+                <label>
+                    <input type="checkbox" checked={settings.gpu} onchange={on_gpu} />
+                    {"Transform on the GPU (experimental)"}
+                </label>
+                <label>
+                    <input type="checkbox" checked={settings.worker} onchange={on_worker} />
+                    {"Transform in a background worker"}
+                </label>
+            }
+            <button onclick={on_recommend}>{
+                if *selected {
+                    "Recommend settings for selection"
+                } else {
+                    "Recommend settings"
+                }
+            }</button>
+            {recommended}
+            <label>
+                <input type="checkbox" checked={settings.welch} onchange={on_welch} />
+                {"Welch averaging"}
+            </label>
+            if settings.welch {
+                <label>
+                    {"Segment length"}
+                    <select onchange={on_segment_len}>
+                        { for SEGMENT_LENGTHS.into_iter().map(|n| html! {
+                            <option selected={n == settings.segment_len}>{n}</option>
+                        }) }
+                    </select>
+                </label>
+                <label>
+                    {"Overlap (%)"}
+                    <input type="number" min="0" max="95" step="any"
+                        value={(settings.overlap * 100.0).to_string()}
+                        onchange={on_overlap} />
+                </label>
+                <label>
+                    <input type="checkbox"
+                        checked={settings.show_confidence}
+                        onchange={on_show_confidence} />
+                    {"Shade 95 % confidence band"}
+                </label>
+                if let Some(segments) = segments {
+                    <p>{format!("{segments} segments averaged")}</p>
+                }
+            }
+            if stereo_channel.is_some() {
+                <label>
+                    {"Channels"}
+                    <select onchange={on_stereo}>
+                        { for StereoSpectra::ALL.into_iter().map(|stereo| html! {
+                            <option selected={stereo == settings.stereo}>{stereo.name()}</option>
+                        }) }
+                    </select>
+                </label>
+                {legend.unwrap_or_default()}
+            }
+            <label>
+                {"Smoothing"}
+                <select onchange={on_smoothing}>
+                    <option selected={settings.smoothing.is_none()}>{"None"}</option>
+                    { for FRACTIONS.into_iter().map(|fraction| html! {
+                        <option selected={settings.smoothing == Some(fraction)}>
+                            {format!("1/{fraction} octave")}
+                        </option>
+                    }) }
+                </select>
+            </label>
+            <label>
+                <input type="checkbox" checked={settings.cursors} onchange={on_cursors} />
+                {"Measurement cursors"}
+            </label>
+            <label>
+                <input type="checkbox" checked={settings.harmonics} onchange={on_harmonics} />
+                {"Harmonic markers"}
+            </label>
+            if settings.harmonics {
+                <label>
+                    {"Harmonics"}
+                    <input type="number" min="2" max="50"
+                        value={settings.harmonic_count.to_string()}
+                        onchange={on_harmonic_count} />
+                </label>
+                if settings.cursors {
+                    <p>{"Each measurement cursor is taken for a fundamental."}</p>
+                } else {
+                    <p>{"Click the spectrum to pick the fundamental."}</p>
+                }
+            }
+            <label>
+                {"Hold"}
+                <select onchange={on_hold}>
+                    { for SpectrumHold::ALL.into_iter().map(|hold| html! {
+                        <option selected={hold == settings.hold}>{hold.name()}</option>
+                    }) }
+                </select>
+            </label>
+            if settings.hold.accumulates() {
+                <button onclick={on_reset_hold.reform(|_| ())}>{"Reset"}</button>
+            }
+            <label>
+                {"Tilt from (Hz)"}
+                <input type="number" min="1" step="any"
+                    value={settings.tilt_low.to_string()}
+                    onchange={on_tilt_low} />
+            </label>
+            <label>
+                {"Tilt to (Hz)"}
+                <input type="number" min="1" step="any"
+                    value={settings.tilt_high.to_string()}
+                    onchange={on_tilt_high} />
+            </label>
+            <p>
+                {match tilt {
+                    Some(tilt) => format!("Spectral tilt: {tilt:+.1} dB/octave"),
+                    None => "Spectral tilt: no bins in range".to_owned(),
+                }}
+            </p>
+            <label>
+                {"Points drawn"}
+                <select onchange={on_max_points}>
+                    { for MAX_POINTS.into_iter().map(|max_points| html! {
+                        <option selected={max_points == settings.max_points}>
+                            {max_points.map_or_else(|| "One per bin".to_owned(), |n| format!("At most {n}"))}
+                        </option>
+                    }) }
+                </select>
+            </label>
+            { for ResponseFormat::ALL.into_iter().map(|format| html! {
+                <button onclick={on_export.reform(move |_| format)}>
+                    {format!("Export {}", format.name())}
+                </button>
+            }) }
+            { for PeakFormat::ALL.into_iter().map(|format| html! {
+                <button onclick={on_export_peaks.reform(move |_| format)}>
+                    {format!("Export peaks ({})", format.name())}
+                </button>
+            }) }
+        </details>
+    }
+}
@@ -0,0 +1,87 @@
+use std::ops::Range;
+
+use yew::prelude::*;
+
+use super::input_value;
+use crate::{model::Channel, settings::SpeechSettings};
+
+#[derive(Properties, PartialEq)]
+pub struct SpeechPanelProps {
+    pub channel: Channel,
+    pub settings: SpeechSettings,
+    pub on_settings: Callback<SpeechSettings>,
+    pub on_detected: Callback<Vec<Range<usize>>>,
+}
+
+#[function_component(SpeechPanel)]
+pub fn speech_panel(
+    SpeechPanelProps {
+        channel,
+        settings,
+        on_settings,
+        on_detected,
+    }: &SpeechPanelProps,
+) -> Html {
+    let regions = use_memo(
+        |(channel, settings)| {
+            if settings.enabled {
+                bench!(["Detecting speech"] => channel.detect_speech(settings.margin_db))
+            } else {
+                Vec::new()
+            }
+        },
+        (channel.clone(), *settings),
+    );
+
+    {
+        let on_detected = on_detected.clone();
+        use_effect_with_deps(
+            move |regions| on_detected.emit((**regions).clone()),
+            regions.clone(),
+        );
+    }
+
+    let settings = *settings;
+    let on_toggle = on_settings.reform(move |_| SpeechSettings {
+        enabled: !settings.enabled,
+        ..settings
+    });
+    let on_margin = {
+        let on_settings = on_settings.clone();
+        Callback::from(move |event: Event| {
+            if let Some(value) = input_value::<f64>(&event) {
+                on_settings.emit(SpeechSettings {
+                    margin_db: value.max(0.0),
+                    ..settings
+                });
+            }
+        })
+    };
+
+    let sample_rate = channel.sample_rate() as f64;
+    let total = channel.count() as f64 / sample_rate;
+    let speech = regions.iter().map(Range::len).sum::<usize>() as f64 / sample_rate;
+
+    html! {
+        <details class="panel">
+            <summary>{"Speech activity"}</summary>
+            <label>
+                <input type="checkbox" checked={settings.enabled} onchange={on_toggle} />
+                {"Detect speech"}
+            </label>
+            <label>
+                {"Margin above noise floor (dB)"}
+                <input type="number" min="0" step="1"
+                    value={settings.margin_db.to_string()}
+                    onchange={on_margin} />
+            </label>
+            if settings.enabled {
+                <p>{format!(
+                    "Speech: {speech:.2} s of {total:.2} s ({:.0}%) in {} segments",
+                    if total > 0.0 { 100.0 * speech / total } else { 0.0 },
+                    regions.len(),
+                )}</p>
+            }
+        </details>
+    }
+}
@@ -0,0 +1,216 @@
+use std::ops::Range;
+
+use web_sys::HtmlDetailsElement;
+use yew::prelude::*;
+
+use super::{input_value, select_index};
+use crate::{
+    model::{Channel, Signal},
+    settings::{NoiseFloorMethod, TruePeakSettings},
+};
+
+// DC offsets above this share of full scale (-60 dBFS) are flagged.
+const DC_OFFSET_WARNING: f64 = 0.001;
+
+#[derive(Properties, PartialEq)]
+pub struct StatisticsPanelProps {
+    pub signal: Signal,
+    pub settings: TruePeakSettings,
+    pub on_settings: Callback<TruePeakSettings>,
+    pub noise_floor: NoiseFloorMethod,
+    pub on_noise_floor: Callback<NoiseFloorMethod>,
+    pub selection: Option<Range<usize>>,
+    // The region the noise floor is measured in, when it is taken from one.
+    pub noise_region: Option<Range<usize>>,
+    pub on_noise_region: Callback<Option<Range<usize>>>,
+    pub on_remove_dc: Callback<()>,
+}
+
+#[function_component(StatisticsPanel)]
+pub fn statistics_panel(
+    StatisticsPanelProps {
+        signal,
+        settings,
+        on_settings,
+        noise_floor,
+        on_noise_floor,
+        selection,
+        noise_region,
+        on_noise_region,
+        on_remove_dc,
+    }: &StatisticsPanelProps,
+) -> Html {
+    let open = use_state(|| false);
+
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |event: Event| {
+            open.set(event.target_unchecked_into::<HtmlDetailsElement>().open())
+        })
+    };
+
+    let correlation = use_memo(
+        |(signal, open)| {
+            open.then(|| {
+                bench!(["Calculating phase correlation"] => signal.phase_correlation(0..usize::MAX))
+            })
+            .flatten()
+        },
+        (signal.clone(), *open),
+    );
+
+    let settings = *settings;
+    let on_mark_overs = on_settings.reform(move |_| TruePeakSettings {
+        mark_overs: !settings.mark_overs,
+        ..settings
+    });
+    let on_ceiling = {
+        let on_settings = on_settings.clone();
+        Callback::from(move |event: Event| {
+            if let Some(value) = input_value::<f64>(&event) {
+                on_settings.emit(TruePeakSettings {
+                    ceiling_dbtp: value.min(0.0),
+                    ..settings
+                });
+            }
+        })
+    };
+
+    let on_noise_floor =
+        on_noise_floor.reform(|event: Event| NoiseFloorMethod::ALL[select_index(&event)]);
+    let on_use_selection = {
+        let selection = selection.clone();
+        on_noise_region.reform(move |_| selection.clone())
+    };
+
+    let names: &[&str] = match signal {
+        Signal::Mono(_) => &["Mono"],
+        Signal::Stereo(..) => &["Left", "Right"],
+    };
+
+    html! {
+        <details class="panel" ontoggle={on_toggle}>
+            <summary>{"Statistics"}</summary>
+            // Statistics are only computed while the panel is open.
+            if *open {
+                { for names.iter().enumerate().map(|(n, name)| html! {
+                    <ChannelStatistics
+                        name={*name}
+                        channel={signal.channel(n).clone()}
+                        ceiling_dbtp={settings.ceiling_dbtp}
+                        noise_floor={*noise_floor}
+                        noise_region={noise_region.clone()} />
+                }) }
+            }
+            if let Some(correlation) = *correlation {
+                <table class="statistics">
+                    <tr><th colspan="2">{"Stereo"}</th></tr>
+                    <tr class={classes!((correlation < 0.0).then_some("warning"))}>
+                        <td>{"Phase correlation"}</td>
+                        <td>{format!("{correlation:+.2}")}</td>
+                    </tr>
+                </table>
+            }
+            <label>
+                {"Noise floor from"}
+                <select onchange={on_noise_floor}>
+                    { for NoiseFloorMethod::ALL.into_iter().map(|method| html! {
+                        <option selected={method == *noise_floor}>{method.name()}</option>
+                    }) }
+                </select>
+            </label>
+            if *noise_floor == NoiseFloorMethod::Region {
+                <p>
+                    {match noise_region {
+                        Some(region) => format!("Samples {}–{}", region.start, region.end),
+                        None => "No region chosen".to_owned(),
+                    }}
+                </p>
+                <button disabled={selection.is_none()} onclick={on_use_selection}>
+                    {"Use selection as silence"}
+                </button>
+            }
+            <label>
+                <input type="checkbox" checked={settings.mark_overs} onchange={on_mark_overs} />
+                {"Mark true peaks above (dBTP)"}
+                <input type="number" max="0" step="any"
+                    value={settings.ceiling_dbtp.to_string()}
+                    onchange={on_ceiling} />
+            </label>
+            <button onclick={on_remove_dc.reform(|_| ())}>{"Remove DC"}</button>
+        </details>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct ChannelStatisticsProps {
+    name: &'static str,
+    channel: Channel,
+    ceiling_dbtp: f64,
+    noise_floor: NoiseFloorMethod,
+    noise_region: Option<Range<usize>>,
+}
+
+#[function_component(ChannelStatistics)]
+fn channel_statistics(
+    ChannelStatisticsProps {
+        name,
+        channel,
+        ceiling_dbtp,
+        noise_floor,
+        noise_region,
+    }: &ChannelStatisticsProps,
+) -> Html {
+    let stats = use_memo(
+        |channel| bench!(["Calculating statistics"] => channel.statistics()),
+        channel.clone(),
+    );
+    let true_peak = use_memo(
+        |channel| bench!(["Calculating true peak"] => channel.true_peak_dbtp()),
+        channel.clone(),
+    );
+    let noise = use_memo(
+        |(channel, method, region)| {
+            let floor_dbfs = match method {
+                NoiseFloorMethod::QuietestFrames => Some(channel.noise_floor_dbfs()),
+                NoiseFloorMethod::Spectrum => Some(channel.spectral_noise_floor_dbfs()),
+                NoiseFloorMethod::Region => region.as_ref().map(|region| {
+                    let count = channel.count();
+                    let region = region.start.min(count)..region.end.min(count);
+                    channel.slice(region).statistics().rms_dbfs
+                }),
+            };
+            floor_dbfs.map(|floor_dbfs| bench!(["Measuring noise"] => channel.noise(floor_dbfs)))
+        },
+        (channel.clone(), *noise_floor, noise_region.clone()),
+    );
+
+    html! {
+        <table class="statistics">
+            <tr><th colspan="2">{*name}</th></tr>
+            <tr><td>{"Samples"}</td><td>{stats.count}</td></tr>
+            <tr><td>{"Duration"}</td><td>{format!("{:.3} s", stats.duration)}</td></tr>
+            <tr><td>{"Peak"}</td><td>{format!("{:.1} dBFS", stats.peak_dbfs)}</td></tr>
+            <tr class={classes!((*true_peak > *ceiling_dbtp).then_some("warning"))}>
+                <td>{"True peak"}</td>
+                <td>{format!("{:.1} dBTP", *true_peak)}</td>
+            </tr>
+            <tr><td>{"RMS"}</td><td>{format!("{:.1} dBFS", stats.rms_dbfs)}</td></tr>
+            <tr><td>{"Crest factor"}</td><td>{format!("{:.1} dB", stats.crest_factor_db)}</td></tr>
+            <tr class={classes!((stats.dc_offset.abs() > DC_OFFSET_WARNING).then_some("warning"))}>
+                <td>{"DC offset"}</td>
+                <td>{format!("{:.4} %", stats.dc_offset * 100.0)}</td>
+            </tr>
+            <tr><td>{"Zero crossings"}</td><td>{stats.zero_crossings}</td></tr>
+            <tr><td>{"Clipping"}</td><td>{stats.clipping_events}</td></tr>
+            if let Some(noise) = *noise {
+                <tr><td>{"Noise floor"}</td><td>{format!("{:.1} dBFS", noise.floor_dbfs)}</td></tr>
+                <tr><td>{"SNR"}</td><td>{format!("{:.1} dB", noise.snr_db)}</td></tr>
+                <tr>
+                    <td>{"Dynamic range"}</td>
+                    <td>{format!("{:.1} dB", noise.dynamic_range_db)}</td>
+                </tr>
+            }
+        </table>
+    }
+}
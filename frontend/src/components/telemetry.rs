@@ -0,0 +1,79 @@
+use gloo::timers::callback::Interval;
+use yew::prelude::*;
+
+use crate::{
+    bench::{self, format_duration, Timing},
+    hooks::page_hidden,
+};
+
+const POLL_INTERVAL_MS: u32 = 500;
+// Stages named in the summary, the slowest first.
+const SLOWEST: usize = 3;
+
+// How long the last update took, naming the slowest stages, with the full breakdown on expanding.
+#[function_component(StatusLine)]
+pub fn status_line() -> Html {
+    let stages = use_state_eq(bench::last_update);
+    {
+        let stages = stages.clone();
+        use_effect_with_deps(
+            move |_| {
+                let interval = Interval::new(POLL_INTERVAL_MS, move || {
+                    if !page_hidden() {
+                        stages.set(bench::last_update())
+                    }
+                });
+                move || drop(interval)
+            },
+            (),
+        );
+    }
+
+    if stages.is_empty() {
+        return html!();
+    }
+
+    let top_level = || stages.iter().filter(|timing| timing.depth == 0);
+    let total: f64 = top_level().map(|timing| timing.duration).sum();
+    let mut slowest: Vec<&Timing> = top_level().collect();
+    slowest.sort_by(|a, b| b.duration.total_cmp(&a.duration));
+    let slowest = slowest
+        .iter()
+        .take(SLOWEST)
+        .map(|timing| {
+            format!(
+                "{} {}",
+                timing.label.to_lowercase(),
+                format_duration(timing.duration)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    // Listed in the order they started, so that nested stages follow the stage they are part of.
+    let mut ordered = (*stages).clone();
+    ordered.sort_by(|a, b| (a.end - a.duration).total_cmp(&(b.end - b.duration)));
+    let simd = if cfg!(target_feature = "simd128") {
+        "enabled"
+    } else {
+        "disabled"
+    };
+
+    html! {
+        <details class="status-line">
+            <summary>
+                {format!("Last update took {}", format_duration(total))}
+                if !slowest.is_empty() {
+                    {format!(": {slowest}")}
+                }
+            </summary>
+            <ul>
+                { for ordered.iter().map(|timing| html! {
+                    <li style={format!("margin-left: {}em", timing.depth)}>
+                        {format!("{} {}", timing.label, format_duration(timing.duration))}
+                    </li>
+                }) }
+            </ul>
+            <p>{format!("Analysis runs on the main thread, with SIMD {simd}.")}</p>
+        </details>
+    }
+}
@@ -0,0 +1,212 @@
+use yew::prelude::*;
+
+use crate::{
+    model::{calibration::Calibration, transfer::TransferFunction, Channel},
+    settings::TransferSettings,
+};
+
+// Range of the magnitude plot below its top.
+const RANGE_DB: f64 = 60.0;
+
+#[derive(Properties, PartialEq)]
+pub struct TransferPanelProps {
+    // The reference and the output of the device, if there are two channels or files to measure
+    // between, and what they are.
+    pub channels: Option<(Channel, Channel, &'static str)>,
+    pub segment_len: usize,
+    pub overlap: f64,
+    pub calibration: Option<Calibration>,
+    pub settings: TransferSettings,
+    pub on_settings: Callback<TransferSettings>,
+    pub on_transfer: Callback<Option<TransferFunction>>,
+}
+
+// Measures the transfer function from a reference to the output of a device, such as the sweep or
+// noise sent to a loudspeaker and what a microphone picked up from it.
+#[function_component(TransferPanel)]
+pub fn transfer_panel(
+    TransferPanelProps {
+        channels,
+        segment_len,
+        overlap,
+        calibration,
+        settings,
+        on_settings,
+        on_transfer,
+    }: &TransferPanelProps,
+) -> Html {
+    let transfer = use_memo(
+        |(channels, segment_len, overlap, calibration, enabled)| {
+            let (reference, output, _) = channels.as_ref().filter(|_| *enabled)?;
+            let transfer = bench!(["Calculating transfer function"] => {
+                reference.transfer_function(output, *segment_len, *overlap)
+            });
+            Some(match calibration {
+                Some(calibration) => transfer.calibrated(calibration),
+                None => transfer,
+            })
+        },
+        (
+            channels.clone(),
+            *segment_len,
+            *overlap,
+            calibration.clone(),
+            settings.enabled,
+        ),
+    );
+
+    {
+        let on_transfer = on_transfer.clone();
+        use_effect_with_deps(
+            move |transfer| on_transfer.emit((**transfer).clone()),
+            transfer.clone(),
+        );
+    }
+
+    let settings = *settings;
+    let on_toggle = on_settings.reform(move |_| TransferSettings {
+        enabled: !settings.enabled,
+        ..settings
+    });
+    let on_phase = on_settings.reform(move |_| TransferSettings {
+        show_phase: !settings.show_phase,
+        ..settings
+    });
+    let on_coherence = on_settings.reform(move |_| TransferSettings {
+        show_coherence: !settings.show_coherence,
+        ..settings
+    });
+
+    html! {
+        <details class="panel">
+            <summary>{"Transfer function"}</summary>
+            if let Some((_, _, description)) = channels {
+                <label>
+                    <input type="checkbox" checked={settings.enabled} onchange={on_toggle} />
+                    {"Measure transfer function"}
+                </label>
+                <p>{format!("From the {description}")}</p>
+                <label>
+                    <input type="checkbox" checked={settings.show_phase} onchange={on_phase} />
+                    {"Phase"}
+                </label>
+                <label>
+                    <input type="checkbox" checked={settings.show_coherence} onchange={on_coherence} />
+                    {"Coherence"}
+                </label>
+                if let Some(transfer) = &*transfer {
+                    <p>{format!("{} segments of {} samples", transfer.segments, transfer.segment_len)}</p>
+                }
+            } else {
+                <p>{"Load a stereo file, or a comparison file, to measure between."}</p>
+            }
+        </details>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TransferViewProps {
+    pub transfer: TransferFunction,
+    pub show_phase: bool,
+    pub show_coherence: bool,
+}
+
+// Magnitude of a transfer function over a logarithmic frequency axis, in place of the spectrum.
+// The phase, from -180° to 180°, and the coherence, from 0 to 1, are drawn over the height of the
+// plot.
+#[function_component(TransferView)]
+pub fn transfer_view(
+    TransferViewProps {
+        transfer,
+        show_phase,
+        show_coherence,
+    }: &TransferViewProps,
+) -> Html {
+    let bins = 1..transfer.response.len();
+    let lowest_log = transfer.bin_to_frequency(1).log10();
+    let highest_log = (transfer.sample_rate as f64 / 2.0).log10();
+    let top = bins
+        .clone()
+        .map(|bin| transfer.magnitude_db(bin))
+        .filter(|db| db.is_finite())
+        .fold(f64::NEG_INFINITY, f64::max);
+    let top = if top.is_finite() {
+        10.0 * (top / 10.0).ceil()
+    } else {
+        0.0
+    };
+
+    // Heights are given from 0 at the top to 1 at the bottom. Where the height jumps by more than
+    // half of the plot, as the phase does where it wraps, the line is broken rather than drawn
+    // across.
+    let format_lines = |height: &dyn Fn(usize) -> f64| {
+        let mut previous = None;
+        bins.clone()
+            .map(|bin| {
+                let y = height(bin).clamp(0.0, 1.0);
+                let jump = previous.is_some_and(|previous: f64| (y - previous).abs() > 0.5);
+                previous = Some(y);
+                format!(
+                    "{}{:.4} {:.4} ",
+                    if jump { "M " } else { "" },
+                    transfer.bin_to_frequency(bin).log10(),
+                    y * RANGE_DB,
+                )
+            })
+            .collect::<String>()
+    };
+    let magnitude = format_lines(&|bin| (top - transfer.magnitude_db(bin)) / RANGE_DB);
+    let phase = show_phase.then(|| format_lines(&|bin| 0.5 - transfer.phase_degrees(bin) / 360.0));
+    let coherence = show_coherence.then(|| format_lines(&|bin| 1.0 - transfer.coherence[bin]));
+
+    let width = highest_log - lowest_log;
+    let grid = (0..=(RANGE_DB / 10.0) as i64)
+        .map(|step| format!("M {lowest_log:.4} {0} L {highest_log:.4} {0} ", 10 * step))
+        .collect::<String>();
+    let y_labels = (0..=(RANGE_DB / 10.0) as i64).map(|step| {
+        let db = top - 10.0 * step as f64;
+        html! {
+            <p class="unit decibel" style={format!("top: {:.4}%", step as f64 * 10.0 / RANGE_DB * 100.0)}>
+                {db}
+            </p>
+        }
+    });
+    let x_labels = (0..=highest_log.floor() as u32)
+        .filter(|&order| f64::from(order) >= lowest_log)
+        .map(|order| {
+            let left = (f64::from(order) - lowest_log) / width * 100.0;
+            let unit = if order < 3 { "hertz" } else { "kilohertz" };
+            html! {
+                <p class={format!("unit {unit}")} style={format!("left: {left:.4}%")}>
+                    {format!("{}", 10_u32.pow(order % 3))}
+                </p>
+            }
+        });
+
+    html! {
+        <>
+            <div class="plot spectrum-view transfer-view">
+                <svg xmlns="http://www.w3.org/2000/svg">
+                    <svg
+                        viewBox={format!("{lowest_log:.4} 0 {width:.4} {RANGE_DB}")}
+                        preserveAspectRatio="none">
+                        <path vector-effect="non-scaling-stroke" d={grid} />
+                        if let Some(coherence) = coherence {
+                            <path class="coherence" vector-effect="non-scaling-stroke"
+                                d={format!("M {coherence}")} />
+                        }
+                        if let Some(phase) = phase {
+                            <path class="phase" vector-effect="non-scaling-stroke"
+                                d={format!("M {phase}")} />
+                        }
+                        <path class="magnitude" vector-effect="non-scaling-stroke"
+                            d={format!("M {magnitude}")} />
+                    </svg>
+                </svg>
+            </div>
+            <div class="x-labels">{ for x_labels }</div>
+            <div class="y-labels">{ for y_labels }</div>
+            <div class="empty-box" />
+        </>
+    }
+}
@@ -0,0 +1,193 @@
+use std::{ops::Range, rc::Rc};
+
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+
+use crate::{
+    canvas, gpu,
+    hooks::{use_playhead, use_task_slot},
+    model::{waterfall::Waterfall, Channel},
+    worker,
+};
+
+const NUM_FRAMES: usize = 128;
+// Frequency columns, spaced logarithmically like the frequency view.
+const COLUMNS: usize = 512;
+const RANGE_DB: f64 = 100.0;
+
+#[derive(Properties, PartialEq)]
+pub struct WaterfallViewProps {
+    pub channel: Channel,
+    pub view: Range<usize>,
+    // The sample being played, if any.
+    pub playhead: Callback<(), Option<usize>>,
+    // Whether to transform the frames on the GPU where supported.
+    pub gpu: bool,
+    // Whether to otherwise transform the frames in a background worker.
+    pub worker: bool,
+}
+
+// Where frames are transformed when not on the main thread.
+#[derive(Clone, Copy, PartialEq)]
+enum Offload {
+    Gpu,
+    Worker,
+}
+
+// Successive short-time spectra stacked with the most recent at the top, in place of the frequency
+// view. The history spans the view, ending at the playhead during playback and at the end of the
+// view otherwise, with the frame length following the zoom.
+#[function_component(WaterfallView)]
+pub fn waterfall_view(
+    WaterfallViewProps {
+        channel,
+        view,
+        playhead,
+        gpu,
+        worker,
+    }: &WaterfallViewProps,
+) -> Html {
+    let end = use_playhead(playhead.clone()).unwrap_or(view.end);
+    let spectrogram = use_memo(|channel| channel.spectrogram(), channel.clone());
+
+    // Frames are transformed on the GPU or in a worker in the background, showing the previous
+    // waterfall until they are done. Should that fail, it falls back to the main thread for good.
+    let gpu_failed = use_state(|| false);
+    let worker_failed = use_state(|| false);
+    let offload = if *gpu && !*gpu_failed && gpu::is_supported() {
+        Some(Offload::Gpu)
+    } else if *worker && !*worker_failed {
+        Some(Offload::Worker)
+    } else {
+        None
+    };
+    let offloaded_waterfall = use_state(|| None::<Rc<Waterfall>>);
+    let offload_task = use_task_slot();
+    {
+        let gpu_failed = gpu_failed.clone();
+        let worker_failed = worker_failed.clone();
+        let offloaded_waterfall = offloaded_waterfall.clone();
+        use_effect_with_deps(
+            move |(spectrogram, end, span, offload)| {
+                let Some(offload) = *offload else {
+                    offload_task.abort();
+                    return;
+                };
+                let (spectrogram, end, span) = ((**spectrogram).clone(), *end, *span);
+                offload_task.spawn(async move {
+                    for tile in spectrogram.uncached_tiles(end, span, NUM_FRAMES) {
+                        let spectra = match offload {
+                            Offload::Gpu => gpu::fft(tile.samples(), tile.frame_len())
+                                .await
+                                .map_err(|error| format!("on the GPU: {error:?}")),
+                            Offload::Worker => worker::fft(tile.samples(), tile.frame_len())
+                                .await
+                                .map_err(|error| format!("in a worker: {error}")),
+                        };
+                        match spectra {
+                            Ok(spectra) => tile.finish(&spectrogram, &spectra),
+                            Err(error) => {
+                                crate::log(&format!("failed to transform {error}"));
+                                match offload {
+                                    Offload::Gpu => gpu_failed.set(true),
+                                    Offload::Worker => worker_failed.set(true),
+                                }
+                                return;
+                            }
+                        }
+                    }
+                    // Every tile is cached by now.
+                    let waterfall = spectrogram.waterfall(end, span, NUM_FRAMES);
+                    offloaded_waterfall.set(Some(Rc::new(waterfall)));
+                });
+            },
+            (spectrogram.clone(), end, view.len(), offload),
+        );
+    }
+    let main_thread_waterfall = use_memo(
+        |(spectrogram, end, span, offloaded)| {
+            (!offloaded).then(|| Rc::new(spectrogram.waterfall(*end, *span, NUM_FRAMES)))
+        },
+        ((*spectrogram).clone(), end, view.len(), offload.is_some()),
+    );
+    let waterfall = if offload.is_some() {
+        (*offloaded_waterfall).clone()
+    } else {
+        (*main_thread_waterfall).clone()
+    };
+
+    let nyquist_log = (channel.sample_rate() as f64 / 2.0).log10();
+    let canvas_ref = use_node_ref();
+    {
+        let canvas_ref = canvas_ref.clone();
+        use_effect_with_deps(
+            move |waterfall| {
+                let (Some(element), Some(waterfall)) =
+                    (canvas_ref.cast::<HtmlCanvasElement>(), waterfall)
+                else {
+                    return;
+                };
+                let bins: Vec<_> = (0..COLUMNS)
+                    .map(|column| {
+                        let frequency_log = nyquist_log * (column as f64 + 0.5) / COLUMNS as f64;
+                        waterfall.frequency_to_bin(10_f64.powf(frequency_log))
+                    })
+                    .collect();
+                let num_frames = waterfall.frames.len();
+                let result = bench!(["Drawing waterfall"] => canvas::draw_heatmap(
+                    &element,
+                    COLUMNS,
+                    NUM_FRAMES,
+                    |column, row| {
+                        let Some(frame) = num_frames
+                            .checked_sub(row + 1)
+                            .and_then(|frame| waterfall.frames.get(frame))
+                        else {
+                            return 0.0;
+                        };
+                        let level = frame.get(bins[column]).copied().unwrap_or(-RANGE_DB);
+                        1.0 + level / RANGE_DB
+                    },
+                ));
+                if let Err(error) = result {
+                    crate::log(&format!("failed to draw waterfall: {error:?}"));
+                }
+            },
+            waterfall.clone(),
+        );
+    }
+
+    let order_of_magnitude = (channel.sample_rate() as f32).log10().floor() as u32;
+    let x_labels = (0..=order_of_magnitude)
+        .filter(|&order| f64::from(10_u32.pow(order)).log10() <= nyquist_log)
+        .map(|order| {
+            let left = (10_u32.pow(order) as f64).log10() / nyquist_log * 100.0;
+            let unit = if order < 3 { "hertz" } else { "kilohertz" };
+            html! {
+                <p class={format!("unit {unit}")} style={format!("left: {left:.4}%")}>
+                    {format!("{}", 10_u32.pow(order % 3))}
+                </p>
+            }
+        });
+    let hop = waterfall.as_ref().map_or(0, |waterfall| waterfall.hop);
+    let seconds = (NUM_FRAMES * hop) as f64 / channel.sample_rate() as f64;
+    let y_labels = (0..=4).filter(|_| hop > 0).map(|step| {
+        let fraction = step as f64 / 4.0;
+        html! {
+            <p class="unit second" style={format!("top: {:.4}%", fraction * 100.0)}>
+                {format!("{:.1}", -fraction * seconds)}
+            </p>
+        }
+    });
+
+    html! {
+        <>
+            <div class="plot spectrum-view waterfall">
+                <canvas ref={canvas_ref} />
+            </div>
+            <div class="x-labels">{ for x_labels }</div>
+            <div class="y-labels">{ for y_labels }</div>
+            <div class="empty-box" />
+        </>
+    }
+}
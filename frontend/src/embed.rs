@@ -0,0 +1,129 @@
+use gloo::net::http::Request;
+use wasm_bindgen::JsCast;
+use web_sys::Element;
+use yew::prelude::*;
+
+use crate::{
+    model::{Signal, WavLimits},
+    theme::ThemeName,
+    SignalView, SpectrumView,
+};
+
+// Elements carrying this attribute get a read-only viewer mounted into them instead of the full
+// app, configured through the data attributes parsed below.
+pub const ATTRIBUTE: &str = "data-signal-inspector";
+
+#[derive(Properties, PartialEq)]
+pub struct EmbedProps {
+    // URL of the WAV file to show.
+    pub src: String,
+    pub show_spectrum: bool,
+    pub channel: usize,
+    pub limits: WavLimits,
+    pub theme: ThemeName,
+}
+
+impl EmbedProps {
+    // Reads `data-src`, `data-view` (`waveform` or `spectrum`), `data-channel`, `data-theme`
+    // (`dark` or `light`), and the optional limits `data-max-size` (in bytes) and
+    // `data-max-duration` (in seconds).
+    pub fn from_element(element: &Element) -> Self {
+        let number = |name| {
+            element
+                .get_attribute(name)
+                .and_then(|value| value.parse::<f64>().ok())
+                .filter(|value| *value > 0.0)
+        };
+        let defaults = WavLimits::default();
+        Self {
+            src: element.get_attribute("data-src").unwrap_or_default(),
+            show_spectrum: element.get_attribute("data-view").as_deref() == Some("spectrum"),
+            channel: element
+                .get_attribute("data-channel")
+                .and_then(|channel| channel.parse().ok())
+                .unwrap_or(0),
+            limits: WavLimits {
+                max_bytes: number("data-max-size").map_or(defaults.max_bytes, |n| n as usize),
+                max_duration: number("data-max-duration").unwrap_or(defaults.max_duration),
+            },
+            theme: element
+                .get_attribute("data-theme")
+                .and_then(|name| ThemeName::from_name(&name))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+// All elements to mount viewers into.
+pub fn elements() -> Vec<Element> {
+    let Ok(nodes) = gloo::utils::document().query_selector_all(&format!("[{ATTRIBUTE}]")) else {
+        return Vec::new();
+    };
+    (0..nodes.length())
+        .filter_map(|i| nodes.get(i)?.dyn_into().ok())
+        .collect()
+}
+
+async fn fetch(src: &str, limits: WavLimits) -> Result<Signal, String> {
+    let response = Request::get(src)
+        .send()
+        .await
+        .map_err(|error| format!("failed to fetch {src}: {error}"))?;
+    if !response.ok() {
+        return Err(format!("failed to fetch {src}: {}", response.status()));
+    }
+    let data = response
+        .binary()
+        .await
+        .map_err(|error| format!("failed to fetch {src}: {error}"))?;
+    Signal::from_wav_with_limits(data, limits)
+        .map_err(|error| format!("failed to decode {src}: {error}"))
+}
+
+#[function_component(Embed)]
+pub fn embed(
+    EmbedProps {
+        src,
+        show_spectrum,
+        channel,
+        limits,
+        theme,
+    }: &EmbedProps,
+) -> Html {
+    let signal = use_state(|| None::<Result<Signal, String>>);
+
+    {
+        let signal = signal.clone();
+        use_effect_with_deps(
+            move |(src, limits)| {
+                let (src, limits) = (src.clone(), *limits);
+                wasm_bindgen_futures::spawn_local(async move {
+                    signal.set(Some(fetch(&src, limits).await));
+                })
+            },
+            (src.clone(), *limits),
+        );
+    }
+
+    let content = match &*signal {
+        None => html! { <p class="status">{"Loading…"}</p> },
+        Some(Err(error)) => html! { <p class="status">{error}</p> },
+        Some(Ok(signal)) => {
+            let channel = signal
+                .channel((*channel).min(signal.num_channels() - 1))
+                .clone();
+            html! {
+                <>
+                    <SignalView channel={channel.clone()} mini={*show_spectrum} theme={theme.theme()} />
+                    <SpectrumView spectrum={channel.spectrum()} show={*show_spectrum} />
+                </>
+            }
+        }
+    };
+
+    html! {
+        <div class={classes!("embed", show_spectrum.then_some("split"))}>
+            {content}
+        </div>
+    }
+}
@@ -0,0 +1,253 @@
+// Exports the plot in view as a standalone image for reports. The plot is an SVG drawn with styles
+// from the stylesheet, with HTML labels laid out around it and, for long signals, a canvas beneath
+// it, so the image is put together from what is on screen: the SVG with its computed styles
+// inlined, the labels as text at the positions they are shown at, and the canvas as an embedded
+// bitmap.
+
+use std::fmt::Write;
+
+use js_sys::Promise;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    Blob, CanvasRenderingContext2d, DomRect, Element, HtmlCanvasElement, HtmlImageElement,
+    XmlSerializer,
+};
+
+use crate::fs_access;
+
+// Space around the plot and its labels.
+const PADDING: f64 = 10.0;
+// The resolution of PNG images relative to the plot on screen.
+const PNG_SCALE: f64 = 2.0;
+// The properties set by the stylesheet on the elements of a plot.
+const SVG_PROPERTIES: [&str; 7] = [
+    "fill",
+    "fill-opacity",
+    "stroke",
+    "stroke-width",
+    "stroke-opacity",
+    "stroke-dasharray",
+    "opacity",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Svg,
+    Png,
+}
+
+impl ImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Png => "png",
+        }
+    }
+}
+
+// Downloads the spectrum if it is shown, or otherwise the signal.
+pub async fn export_plot(format: ImageFormat) -> Result<(), JsValue> {
+    let document = gloo::utils::document();
+    let (plot, name) = match document.query_selector(".plot.spectrum-view")? {
+        Some(plot) => (plot, "spectrum"),
+        None => (
+            document
+                .query_selector(".plot.signal-view:not(.mini)")?
+                .ok_or("no plot to export")?,
+            "signal",
+        ),
+    };
+    let (svg, width, height) = plot_svg(&plot)?;
+    let file_name = format!("{name}.{}", format.extension());
+    match format {
+        ImageFormat::Svg => fs_access::download(&file_name, "image/svg+xml", svg.as_bytes()),
+        ImageFormat::Png => {
+            let png = rasterize(&svg, width, height).await?;
+            fs_access::download_blob(&file_name, &png)
+        }
+    }
+}
+
+// The plot along with the labels following it, up to the empty box closing its grid area, as an
+// SVG document, along with its width and height.
+pub fn plot_svg(plot: &Element) -> Result<(String, f64, f64), JsValue> {
+    let window = gloo::utils::window();
+    let mut label_boxes = Vec::new();
+    let mut sibling = plot.next_element_sibling();
+    while let Some(element) = sibling.filter(|element| !element.class_list().contains("empty-box"))
+    {
+        sibling = element.next_element_sibling();
+        label_boxes.push(element);
+    }
+    let labels = std::iter::once(plot)
+        .chain(&label_boxes)
+        .map(|element| element.query_selector_all("p"))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flat_map(|list| (0..list.length()).filter_map(move |n| list.get(n)))
+        .filter_map(|node| node.dyn_into::<Element>().ok())
+        .collect::<Vec<_>>();
+
+    let plot_rect = plot.get_bounding_client_rect();
+    let (mut left, mut top) = (plot_rect.left(), plot_rect.top());
+    let (mut right, mut bottom) = (plot_rect.right(), plot_rect.bottom());
+    for rect in labels.iter().map(Element::get_bounding_client_rect) {
+        left = left.min(rect.left());
+        top = top.min(rect.top());
+        right = right.max(rect.right());
+        bottom = bottom.max(rect.bottom());
+    }
+    let (x0, y0) = (left - PADDING, top - PADDING);
+    let (width, height) = (right - left + 2.0 * PADDING, bottom - top + 2.0 * PADDING);
+    let position = |rect: &DomRect| (rect.left() - x0, rect.top() - y0);
+
+    let background = window
+        .get_computed_style(&gloo::utils::body())?
+        .ok_or("body has no style")?
+        .get_property_value("background-color")?;
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.0}" height="{height:.0}" viewBox="0 0 {width:.2} {height:.2}"><rect width="100%" height="100%" fill="{background}" />"#,
+    );
+
+    // Canvases are drawn beneath the SVG, at the same size.
+    let canvases = plot.query_selector_all("canvas")?;
+    for canvas in (0..canvases.length()).filter_map(|n| canvases.get(n)) {
+        let canvas: HtmlCanvasElement = canvas.unchecked_into();
+        let rect = canvas.get_bounding_client_rect();
+        let (x, y) = position(&rect);
+        let _ = write!(
+            svg,
+            r#"<image x="{x:.2}" y="{y:.2}" width="{:.2}" height="{:.2}" preserveAspectRatio="none" href="{}" />"#,
+            rect.width(),
+            rect.height(),
+            canvas.to_data_url()?,
+        );
+    }
+
+    let serializer = XmlSerializer::new()?;
+    let plots = plot.query_selector_all(":scope > svg")?;
+    for original in (0..plots.length()).filter_map(|n| plots.get(n)) {
+        let original: Element = original.unchecked_into();
+        let copy: Element = original.clone_node_with_deep(true)?.unchecked_into();
+        inline_styles(&original, &copy)?;
+        let rect = original.get_bounding_client_rect();
+        let (x, y) = position(&rect);
+        copy.set_attribute("x", &format!("{x:.2}"))?;
+        copy.set_attribute("y", &format!("{y:.2}"))?;
+        copy.set_attribute("width", &format!("{:.2}", rect.width()))?;
+        copy.set_attribute("height", &format!("{:.2}", rect.height()))?;
+        svg.push_str(&serializer.serialize_to_string(&copy)?);
+    }
+
+    for label in labels {
+        let style = window
+            .get_computed_style(&label)?
+            .ok_or("label has no style")?;
+        // Units are added by the stylesheet after the number.
+        let unit = window
+            .get_computed_style_with_pseudo_elt(&label, "::after")?
+            .map(|style| style.get_property_value("content"))
+            .transpose()?
+            .map(|content| content.trim_matches('"').to_owned())
+            .filter(|content| !content.is_empty() && content != "none" && content != "normal");
+        let mut text = label.text_content().unwrap_or_default();
+        if let Some(unit) = unit {
+            text = format!("{text} {unit}");
+        }
+        let rect = label.get_bounding_client_rect();
+        let (x, y) = position(&rect);
+        let _ = write!(
+            svg,
+            r#"<text x="{x:.2}" y="{:.2}" dominant-baseline="central" font-family="{}" font-size="{}" fill="{}">{}</text>"#,
+            y + rect.height() / 2.0,
+            escape(&style.get_property_value("font-family")?),
+            style.get_property_value("font-size")?,
+            style.get_property_value("color")?,
+            escape(&text),
+        );
+    }
+
+    svg.push_str("</svg>");
+    Ok((svg, width, height))
+}
+
+// Sets the properties the stylesheet gives each element of `original` on the same element of its
+// copy, so that the copy looks the same without the stylesheet.
+fn inline_styles(original: &Element, copy: &Element) -> Result<(), JsValue> {
+    let window = gloo::utils::window();
+    let originals = original.query_selector_all("*")?;
+    let copies = copy.query_selector_all("*")?;
+    for n in 0..originals.length() {
+        let (Some(original), Some(copy)) = (originals.get(n), copies.get(n)) else {
+            continue;
+        };
+        let (original, copy): (Element, Element) =
+            (original.unchecked_into(), copy.unchecked_into());
+        let Some(style) = window.get_computed_style(&original)? else {
+            continue;
+        };
+        let mut inline = String::new();
+        for property in SVG_PROPERTIES {
+            let value = style.get_property_value(property)?;
+            if !value.is_empty() {
+                let _ = write!(inline, "{property}: {value}; ");
+            }
+        }
+        copy.set_attribute("style", &inline)?;
+        copy.remove_attribute("class")?;
+    }
+    Ok(())
+}
+
+// Draws an SVG document on a canvas and encodes it as PNG.
+async fn rasterize(svg: &str, width: f64, height: f64) -> Result<Blob, JsValue> {
+    let image = HtmlImageElement::new()?;
+    let loaded = Promise::new(&mut |resolve, reject| {
+        image.set_onload(Some(&resolve));
+        image.set_onerror(Some(&reject));
+    });
+    // A data URL rather than an object URL, so that the canvas is not tainted by it.
+    let url = format!(
+        "data:image/svg+xml;charset=utf-8,{}",
+        js_sys::encode_uri_component(svg)
+    );
+    image.set_src(&url);
+    JsFuture::from(loaded).await?;
+
+    let canvas: HtmlCanvasElement = gloo::utils::document()
+        .create_element("canvas")?
+        .unchecked_into();
+    canvas.set_width((width * PNG_SCALE).round() as u32);
+    canvas.set_height((height * PNG_SCALE).round() as u32);
+    let context: CanvasRenderingContext2d = canvas
+        .get_context("2d")?
+        .ok_or("canvas has no 2D context")?
+        .unchecked_into();
+    context.draw_image_with_html_image_element_and_dw_and_dh(
+        &image,
+        0.0,
+        0.0,
+        f64::from(canvas.width()),
+        f64::from(canvas.height()),
+    )?;
+
+    let encoded = Promise::new(&mut |resolve, reject| {
+        if let Err(error) = canvas.to_blob(&resolve) {
+            let _ = reject.call1(&JsValue::UNDEFINED, &error);
+        }
+    });
+    JsFuture::from(encoded)
+        .await?
+        .dyn_into::<Blob>()
+        .map_err(|_| "failed to encode PNG".into())
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
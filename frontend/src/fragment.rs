@@ -0,0 +1,172 @@
+use std::ops::Range;
+
+use wasm_bindgen::JsValue;
+
+use crate::{
+    markers::Marker,
+    settings::{AnalysisSettings, SpectrumSettings},
+};
+
+// The part of the state that describes what is being looked at, shared by encoding it into the
+// URL fragment, e.g. `#mode=spectrum&zoom=4800-9600&channel=1&welch=4096,0.5&markers=4800:Intro`.
+// Applying a link
+// only makes sense to someone who has the same file open.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewState {
+    pub show_spectrum: bool,
+    pub view: Option<Range<usize>>,
+    pub channel: usize,
+    pub spectrum: SpectrumSettings,
+    pub markers: Vec<Marker>,
+}
+
+impl ViewState {
+    pub fn new(
+        settings: &AnalysisSettings,
+        view: Option<Range<usize>>,
+        channel: usize,
+        markers: Vec<Marker>,
+    ) -> Self {
+        Self {
+            show_spectrum: settings.show_spectrum,
+            view,
+            channel,
+            spectrum: settings.spectrum,
+            markers,
+        }
+    }
+
+    pub fn apply(&self, settings: &mut AnalysisSettings) {
+        settings.show_spectrum = self.show_spectrum;
+        settings.spectrum = self.spectrum;
+    }
+
+    // Parses a fragment on top of `base`, leaving out anything that is missing or malformed.
+    pub fn parse(fragment: &str, base: Self) -> Self {
+        let mut state = Self {
+            spectrum: SpectrumSettings {
+                ltas: false,
+                waterfall: false,
+                welch: false,
+                ..base.spectrum
+            },
+            markers: Vec::new(),
+            ..base
+        };
+
+        for (key, value) in fragment
+            .trim_start_matches('#')
+            .split('&')
+            .filter_map(|pair| pair.split_once('=').or(Some((pair, ""))))
+        {
+            match key {
+                "mode" => state.show_spectrum = value == "spectrum",
+                "zoom" => {
+                    state.view = value
+                        .split_once('-')
+                        .and_then(|(start, end)| Some(start.parse().ok()?..end.parse().ok()?))
+                        .filter(|range: &Range<usize>| !range.is_empty())
+                }
+                "channel" => state.channel = value.parse().unwrap_or(state.channel),
+                "ltas" => {
+                    state.spectrum.ltas = true;
+                    state.spectrum.bands_per_octave = match value {
+                        "1" => 1,
+                        _ => 3,
+                    };
+                }
+                "waterfall" => state.spectrum.waterfall = true,
+                "welch" => {
+                    state.spectrum.welch = true;
+                    if let Some((segment_len, overlap)) = value.split_once(',') {
+                        state.spectrum.segment_len =
+                            segment_len.parse().unwrap_or(state.spectrum.segment_len);
+                        state.spectrum.overlap = overlap
+                            .parse()
+                            .ok()
+                            .filter(|overlap| (0.0..1.0).contains(overlap))
+                            .unwrap_or(state.spectrum.overlap);
+                    }
+                }
+                "confidence" => state.spectrum.show_confidence = value != "0",
+                "markers" => {
+                    state.markers = value
+                        .split(',')
+                        .filter_map(|marker| {
+                            let (position, label) = marker.split_once(':')?;
+                            Some(Marker {
+                                position: position.parse().ok()?,
+                                label: js_sys::decode_uri_component(label).ok()?.into(),
+                            })
+                        })
+                        .fold(Vec::new(), |markers, marker| {
+                            crate::markers::insert(&markers, marker)
+                        })
+                }
+                _ => (),
+            }
+        }
+
+        state
+    }
+
+    pub fn to_fragment(&self) -> String {
+        let mut pairs = vec![format!(
+            "mode={}",
+            if self.show_spectrum {
+                "spectrum"
+            } else {
+                "waveform"
+            }
+        )];
+        if let Some(view) = &self.view {
+            pairs.push(format!("zoom={}-{}", view.start, view.end));
+        }
+        if self.channel > 0 {
+            pairs.push(format!("channel={}", self.channel));
+        }
+        if self.spectrum.ltas {
+            pairs.push(match self.spectrum.bands_per_octave {
+                1 => "ltas=1".to_owned(),
+                _ => "ltas".to_owned(),
+            });
+        }
+        if self.spectrum.waterfall {
+            pairs.push("waterfall".to_owned());
+        }
+        if self.spectrum.welch {
+            pairs.push(format!(
+                "welch={},{}",
+                self.spectrum.segment_len, self.spectrum.overlap
+            ));
+            if !self.spectrum.show_confidence {
+                pairs.push("confidence=0".to_owned());
+            }
+        }
+        if !self.markers.is_empty() {
+            let markers = self
+                .markers
+                .iter()
+                .map(|marker| {
+                    let label = js_sys::encode_uri_component(&marker.label);
+                    format!("{}:{label}", marker.position)
+                })
+                .collect::<Vec<_>>();
+            pairs.push(format!("markers={}", markers.join(",")));
+        }
+        format!("#{}", pairs.join("&"))
+    }
+}
+
+// The current fragment, if there is one.
+pub fn current() -> Option<String> {
+    let hash = gloo::utils::window().location().hash().ok()?;
+    (hash.len() > 1).then_some(hash)
+}
+
+// Replaces the fragment without adding a history entry or triggering a `hashchange` event.
+pub fn replace(fragment: &str) -> Result<(), JsValue> {
+    gloo::utils::window()
+        .history()?
+        .replace_state_with_url(&JsValue::NULL, "", Some(fragment))
+}
@@ -0,0 +1,176 @@
+use js_sys::{Array, Object, Reflect, Uint8Array};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::JsFuture;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = showOpenFilePicker, catch)]
+    async fn show_open_file_picker(options: &JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_name = showSaveFilePicker, catch)]
+    async fn show_save_file_picker(options: &JsValue) -> Result<JsValue, JsValue>;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub type FileHandle;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn name(this: &FileHandle) -> String;
+
+    #[wasm_bindgen(method, js_name = getFile, catch)]
+    async fn get_file(this: &FileHandle) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, js_name = createWritable, catch)]
+    async fn create_writable(this: &FileHandle) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, js_name = queryPermission, catch)]
+    async fn query_permission(this: &FileHandle, descriptor: &JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, js_name = requestPermission, catch)]
+    async fn request_permission(
+        this: &FileHandle,
+        descriptor: &JsValue,
+    ) -> Result<JsValue, JsValue>;
+
+    type WritableStream;
+
+    #[wasm_bindgen(method, catch)]
+    async fn write(this: &WritableStream, data: &JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    async fn close(this: &WritableStream) -> Result<JsValue, JsValue>;
+}
+
+pub fn is_supported() -> bool {
+    web_sys::window()
+        .is_some_and(|window| Reflect::has(&window, &"showOpenFilePicker".into()).unwrap_or(false))
+}
+
+// Pickers without a suggested name are for opening files, which may also be AIFF. Files are only
+// ever saved as WAV.
+fn wav_options(suggested_name: Option<&str>) -> JsValue {
+    let accept = Object::new();
+    let _ = Reflect::set(&accept, &"audio/wav".into(), &Array::of1(&".wav".into()));
+    if suggested_name.is_none() {
+        let extensions = Array::of3(&".aif".into(), &".aiff".into(), &".aifc".into());
+        let _ = Reflect::set(&accept, &"audio/aiff".into(), &extensions);
+    }
+    let description = if suggested_name.is_none() {
+        "WAV or AIFF audio"
+    } else {
+        "WAV audio"
+    };
+    let wav_type = Object::new();
+    let _ = Reflect::set(&wav_type, &"description".into(), &description.into());
+    let _ = Reflect::set(&wav_type, &"accept".into(), &accept);
+
+    let options = Object::new();
+    let _ = Reflect::set(&options, &"types".into(), &Array::of1(&wav_type));
+    let _ = Reflect::set(&options, &"id".into(), &"signal-inspector".into());
+    if let Some(name) = suggested_name {
+        let _ = Reflect::set(&options, &"suggestedName".into(), &name.into());
+    }
+    options.into()
+}
+
+// Permissions granted to a handle last for the rest of the session, so retained handles can be
+// written to again without prompting. Handles restored after a reload prompt once more, which
+// browsers only allow in response to a click.
+async fn verify_permission(handle: &FileHandle, writable: bool) -> Result<bool, JsValue> {
+    let descriptor = Object::new();
+    let mode = if writable { "readwrite" } else { "read" };
+    Reflect::set(&descriptor, &"mode".into(), &mode.into())?;
+
+    if handle.query_permission(&descriptor).await? == "granted" {
+        return Ok(true);
+    }
+    Ok(handle.request_permission(&descriptor).await? == "granted")
+}
+
+pub async fn read(handle: &FileHandle) -> Result<Vec<u8>, JsValue> {
+    if !verify_permission(handle, false).await? {
+        return Err("permission to read file was denied".into());
+    }
+
+    let file: web_sys::File = handle.get_file().await?.dyn_into()?;
+    let buffer = JsFuture::from(file.array_buffer()).await?;
+    Ok(Uint8Array::new(&buffer).to_vec())
+}
+
+// Does not prompt for permission, as it is meant to be polled in the background.
+pub async fn last_modified(handle: &FileHandle) -> Result<f64, JsValue> {
+    let file: web_sys::File = handle.get_file().await?.dyn_into()?;
+    Ok(file.last_modified())
+}
+
+pub async fn open() -> Result<(FileHandle, Vec<u8>), JsValue> {
+    let handles: Array = show_open_file_picker(&wav_options(None))
+        .await?
+        .dyn_into()?;
+    let handle: FileHandle = handles.get(0).unchecked_into();
+    let data = read(&handle).await?;
+    Ok((handle, data))
+}
+
+pub async fn save(
+    handle: Option<FileHandle>,
+    suggested_name: &str,
+    data: &[u8],
+) -> Result<FileHandle, JsValue> {
+    let handle = match handle {
+        Some(handle) => handle,
+        None => show_save_file_picker(&wav_options(Some(suggested_name)))
+            .await?
+            .unchecked_into(),
+    };
+
+    if !verify_permission(&handle, true).await? {
+        return Err("permission to write file was denied".into());
+    }
+
+    let stream: WritableStream = handle.create_writable().await?.unchecked_into();
+    stream.write(&Uint8Array::from(data).into()).await?;
+    stream.close().await?;
+
+    Ok(handle)
+}
+
+pub fn download(name: &str, mime_type: &str, data: &[u8]) -> Result<(), JsValue> {
+    let parts = Array::of1(&Uint8Array::from(data));
+    let properties = web_sys::BlobPropertyBag::new();
+    properties.set_type(mime_type);
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &properties)?;
+    download_blob(name, &blob)
+}
+
+// Opens `data` in a new window, as if it had been downloaded and opened from there.
+pub fn open_in_window(mime_type: &str, data: &[u8]) -> Result<(), JsValue> {
+    let parts = Array::of1(&Uint8Array::from(data));
+    let properties = web_sys::BlobPropertyBag::new();
+    properties.set_type(mime_type);
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &properties)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+    gloo::utils::window()
+        .open_with_url(&url)?
+        .ok_or("the window was blocked")?;
+    // The new window loads the URL after this returns, so it is only revoked once it has had time
+    // to.
+    gloo::timers::callback::Timeout::new(60_000, move || {
+        let _ = web_sys::Url::revoke_object_url(&url);
+    })
+    .forget();
+    Ok(())
+}
+
+pub fn download_blob(name: &str, blob: &web_sys::Blob) -> Result<(), JsValue> {
+    let url = web_sys::Url::create_object_url_with_blob(blob)?;
+
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("no document")?;
+    let anchor: web_sys::HtmlAnchorElement = document.create_element("a")?.unchecked_into();
+    anchor.set_href(&url);
+    anchor.set_download(name);
+    anchor.click();
+
+    web_sys::Url::revoke_object_url(&url)
+}
@@ -0,0 +1,202 @@
+// An experimental FFT on the GPU through WebGPU, used for waterfall frames where available. The
+// API is reached through reflection, as the bindings for it are not yet stable.
+
+use std::{cell::RefCell, rc::Rc};
+
+use js_sys::{Array, Float32Array, Function, Object, Promise, Reflect, Uint32Array};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+// Buffer usage and map mode flags.
+const MAP_READ: u32 = 0x01;
+const COPY_SRC: u32 = 0x04;
+const COPY_DST: u32 = 0x08;
+const UNIFORM: u32 = 0x40;
+const STORAGE: u32 = 0x80;
+const MAP_MODE_READ: u32 = 0x01;
+
+const WORKGROUP_SIZE: usize = 64;
+
+// One radix-2 stage of a Stockham FFT over consecutive frames, combining pairs of transforms of
+// length `span` into transforms of twice that. The output is in natural order after the last stage.
+const SHADER: &str = "
+struct Stage {
+    frame_len: u32,
+    span: u32,
+    frames: u32,
+    padding: u32,
+}
+
+@group(0) @binding(0) var<storage, read> src: array<vec2<f32>>;
+@group(0) @binding(1) var<storage, read_write> dst: array<vec2<f32>>;
+@group(0) @binding(2) var<uniform> stage: Stage;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let half = stage.frame_len / 2u;
+    if (id.x >= half * stage.frames) {
+        return;
+    }
+    let base = id.x / half * stage.frame_len;
+    let j = id.x % half;
+    let k = j % stage.span;
+    let angle = -3.141592653589793 * f32(k) / f32(stage.span);
+    let w = vec2<f32>(cos(angle), sin(angle));
+    let a = src[base + j];
+    let c = src[base + j + half];
+    let b = vec2<f32>(c.x * w.x - c.y * w.y, c.x * w.y + c.y * w.x);
+    let out = base + (j - k) * 2u + k;
+    dst[out] = a + b;
+    dst[out + stage.span] = a - b;
+}
+";
+
+thread_local! {
+    static GPU: RefCell<Option<Rc<Gpu>>> = const { RefCell::new(None) };
+}
+
+struct Gpu {
+    device: JsValue,
+    pipeline: JsValue,
+}
+
+impl Gpu {
+    // The device and pipeline, set up on first use.
+    async fn get() -> Result<Rc<Self>, JsValue> {
+        if let Some(gpu) = GPU.with(|gpu| gpu.borrow().clone()) {
+            return Ok(gpu);
+        }
+
+        let navigator = gloo::utils::window().navigator();
+        let gpu = Reflect::get(&navigator, &"gpu".into())?;
+        let adapter = call_async(&gpu, "requestAdapter", &[]).await?;
+        if adapter.is_null() {
+            return Err("no WebGPU adapter".into());
+        }
+        let device = call_async(&adapter, "requestDevice", &[]).await?;
+        let module = call(
+            &device,
+            "createShaderModule",
+            &[&object(&[("code", &SHADER.into())])],
+        )?;
+        let compute = object(&[("module", &module), ("entryPoint", &"main".into())]);
+        let pipeline = call(
+            &device,
+            "createComputePipeline",
+            &[&object(&[
+                ("layout", &"auto".into()),
+                ("compute", &compute),
+            ])],
+        )?;
+
+        let gpu = Rc::new(Self { device, pipeline });
+        GPU.with(|cell| *cell.borrow_mut() = Some(gpu.clone()));
+        Ok(gpu)
+    }
+
+    fn buffer(&self, size: usize, usage: u32) -> Result<JsValue, JsValue> {
+        let descriptor = object(&[("size", &(size as f64).into()), ("usage", &usage.into())]);
+        call(&self.device, "createBuffer", &[&descriptor])
+    }
+
+    fn write(&self, buffer: &JsValue, data: &JsValue) -> Result<(), JsValue> {
+        let queue = Reflect::get(&self.device, &"queue".into())?;
+        call(&queue, "writeBuffer", &[buffer, &0.into(), data])?;
+        Ok(())
+    }
+}
+
+pub fn is_supported() -> bool {
+    Reflect::has(&gloo::utils::window().navigator(), &"gpu".into()).unwrap_or(false)
+}
+
+// The complex spectra of consecutive frames of `frame_len` real samples, a power of two, as
+// interleaved real and imaginary parts.
+pub async fn fft(samples: &[f32], frame_len: usize) -> Result<Vec<f32>, JsValue> {
+    let gpu = Gpu::get().await?;
+    let frames = samples.len() / frame_len;
+    let size = frames * frame_len * 2 * std::mem::size_of::<f32>();
+    if size == 0 {
+        return Ok(Vec::new());
+    }
+
+    let complex: Vec<f32> = samples[..frames * frame_len]
+        .iter()
+        .flat_map(|&x| [x, 0.0])
+        .collect();
+    let buffers = [
+        gpu.buffer(size, STORAGE | COPY_SRC | COPY_DST)?,
+        gpu.buffer(size, STORAGE | COPY_SRC | COPY_DST)?,
+    ];
+    gpu.write(&buffers[0], &Float32Array::from(&complex[..]))?;
+    let read = gpu.buffer(size, MAP_READ | COPY_DST)?;
+
+    let encoder = call(&gpu.device, "createCommandEncoder", &[])?;
+    let layout = call(&gpu.pipeline, "getBindGroupLayout", &[&0.into()])?;
+    let workgroups = (frames * frame_len / 2).div_ceil(WORKGROUP_SIZE) as u32;
+    let stages = frame_len.trailing_zeros() as usize;
+    let mut uniforms = Vec::with_capacity(stages);
+    for stage in 0..stages {
+        let uniform = gpu.buffer(16, UNIFORM | COPY_DST)?;
+        let params = [frame_len as u32, 1 << stage, frames as u32, 0];
+        gpu.write(&uniform, &Uint32Array::from(&params[..]))?;
+
+        let entry = |binding: u32, buffer: &JsValue| {
+            let resource = object(&[("buffer", buffer)]);
+            object(&[("binding", &binding.into()), ("resource", &resource)]).into()
+        };
+        let entries = Array::of3(
+            &entry(0, &buffers[stage % 2]),
+            &entry(1, &buffers[1 - stage % 2]),
+            &entry(2, &uniform),
+        );
+        let bind_group = call(
+            &gpu.device,
+            "createBindGroup",
+            &[&object(&[("layout", &layout), ("entries", &entries)])],
+        )?;
+
+        let pass = call(&encoder, "beginComputePass", &[])?;
+        call(&pass, "setPipeline", &[&gpu.pipeline])?;
+        call(&pass, "setBindGroup", &[&0.into(), &bind_group])?;
+        call(&pass, "dispatchWorkgroups", &[&workgroups.into()])?;
+        call(&pass, "end", &[])?;
+        uniforms.push(uniform);
+    }
+    let result = &buffers[stages % 2];
+    let size = JsValue::from(size as f64);
+    call(
+        &encoder,
+        "copyBufferToBuffer",
+        &[result, &0.into(), &read, &0.into(), &size],
+    )?;
+    let commands = call(&encoder, "finish", &[])?;
+    let queue = Reflect::get(&gpu.device, &"queue".into())?;
+    call(&queue, "submit", &[&Array::of1(&commands)])?;
+
+    call_async(&read, "mapAsync", &[&MAP_MODE_READ.into()]).await?;
+    let spectra = Float32Array::new(&call(&read, "getMappedRange", &[])?).to_vec();
+    call(&read, "unmap", &[])?;
+    for buffer in buffers.iter().chain(&uniforms).chain([&read]) {
+        call(buffer, "destroy", &[])?;
+    }
+    Ok(spectra)
+}
+
+fn call(target: &JsValue, method: &str, args: &[&JsValue]) -> Result<JsValue, JsValue> {
+    let function: Function = Reflect::get(target, &method.into())?.dyn_into()?;
+    function.apply(target, &args.iter().copied().collect())
+}
+
+async fn call_async(target: &JsValue, method: &str, args: &[&JsValue]) -> Result<JsValue, JsValue> {
+    let promise: Promise = call(target, method, args)?.dyn_into()?;
+    JsFuture::from(promise).await
+}
+
+pub fn object(properties: &[(&str, &JsValue)]) -> Object {
+    let object = Object::new();
+    for (name, value) in properties {
+        let _ = Reflect::set(&object, &(*name).into(), value);
+    }
+    object
+}
@@ -0,0 +1,239 @@
+use std::{cell::Cell, rc::Rc};
+
+use gloo::{events::EventListener, timers::callback::Interval};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{ClipboardEvent, Element, KeyboardEvent};
+use yew::prelude::*;
+
+use crate::{actions::AppAction, fs_access, source::Source, task::TaskSlot};
+
+// Whether the page is in a background tab or a minimized window. Polling loops skip their work
+// while it is, and pick up where things are on the first poll after it is shown again.
+pub fn page_hidden() -> bool {
+    gloo::utils::document().hidden()
+}
+
+// Reports audio files pasted anywhere in the document, e.g. copied from the OS file manager.
+#[hook]
+pub fn use_paste(on_file: Callback<web_sys::File>) {
+    let latest_on_file = use_mut_ref(|| on_file.clone());
+    *latest_on_file.borrow_mut() = on_file;
+
+    use_effect_with_deps(
+        move |_| {
+            let document = gloo::utils::document();
+
+            let listener = EventListener::new(&document, "paste", move |event| {
+                let Some(files) = event
+                    .dyn_ref::<ClipboardEvent>()
+                    .and_then(ClipboardEvent::clipboard_data)
+                    .and_then(|data| data.files())
+                else {
+                    return;
+                };
+
+                let Some(file) = (0..files.length())
+                    .filter_map(|i| files.get(i))
+                    .find(|file| {
+                        file.type_().starts_with("audio/") || file.name().ends_with(".wav")
+                    })
+                else {
+                    return;
+                };
+
+                latest_on_file.borrow().emit(file);
+            });
+
+            move || drop(listener)
+        },
+        (),
+    );
+}
+
+// Reports the names of new or changed files in the backend watch folder, if the backend has one
+// configured.
+#[cfg(feature = "server")]
+#[hook]
+pub fn use_watch_folder(on_file: Callback<String>) {
+    use web_sys::{EventSource, MessageEvent};
+
+    use crate::server;
+
+    let latest_on_file = use_mut_ref(|| on_file.clone());
+    *latest_on_file.borrow_mut() = on_file;
+
+    use_effect_with_deps(
+        move |_| {
+            // The event stream fails for good if the backend does not watch a folder.
+            let event_source =
+                EventSource::new(&server::event_source_url("/api/watch/events")).ok();
+            let listener = event_source.as_ref().map(|event_source| {
+                EventListener::new(event_source, "file", move |event| {
+                    if let Some(name) = event
+                        .dyn_ref::<MessageEvent>()
+                        .and_then(|event| event.data().as_string())
+                    {
+                        latest_on_file.borrow().emit(name);
+                    }
+                })
+            });
+
+            move || {
+                drop(listener);
+                if let Some(event_source) = event_source {
+                    event_source.close();
+                }
+            }
+        },
+        (),
+    );
+}
+
+// Polls the modification time of a file opened through the File System Access API, emitting once
+// for every change.
+#[hook]
+pub fn use_file_changes(source: Option<Source>, on_changed: Callback<()>) {
+    const POLL_INTERVAL_MS: u32 = 2000;
+
+    let latest_on_changed = use_mut_ref(|| on_changed.clone());
+    *latest_on_changed.borrow_mut() = on_changed;
+
+    use_effect_with_deps(
+        move |source| {
+            let interval = match source.clone() {
+                Some(Source::File(handle)) => {
+                    let baseline = Rc::new(Cell::new(None));
+                    let poll = move || {
+                        // A change made while hidden is found on the first poll once shown.
+                        if page_hidden() {
+                            return;
+                        }
+                        let handle = handle.clone();
+                        let baseline = baseline.clone();
+                        let latest_on_changed = latest_on_changed.clone();
+                        spawn_local(async move {
+                            // The file may be unreadable while being written, so errors are ignored.
+                            let Ok(modified) = fs_access::last_modified(&handle).await else {
+                                return;
+                            };
+                            match baseline.replace(Some(modified)) {
+                                Some(previous) if previous != modified => {
+                                    latest_on_changed.borrow().emit(())
+                                }
+                                _ => {}
+                            }
+                        })
+                    };
+                    poll();
+                    Some(Interval::new(POLL_INTERVAL_MS, poll))
+                }
+                _ => None,
+            };
+
+            move || drop(interval)
+        },
+        source,
+    );
+}
+
+// Calls `on_change` with the new fragment whenever it is changed by the user, e.g. by pasting a
+// link into the address bar of an open tab.
+#[hook]
+pub fn use_fragment_change(on_change: Callback<String>) {
+    let latest_on_change = use_mut_ref(|| on_change.clone());
+    *latest_on_change.borrow_mut() = on_change;
+
+    use_effect_with_deps(
+        move |_| {
+            let window = gloo::utils::window();
+            let listener = EventListener::new(&window, "hashchange", move |_| {
+                if let Some(fragment) = crate::fragment::current() {
+                    latest_on_change.borrow().emit(fragment);
+                }
+            });
+            move || drop(listener)
+        },
+        (),
+    );
+}
+
+// Dispatches keyboard shortcuts, except while typing into a form field, and opens the command
+// palette on Ctrl+K.
+#[hook]
+pub fn use_keyboard(on_action: Callback<AppAction>, on_palette: Callback<()>) {
+    let latest = use_mut_ref(|| (on_action.clone(), on_palette.clone()));
+    *latest.borrow_mut() = (on_action, on_palette);
+
+    use_effect_with_deps(
+        move |_| {
+            let document = gloo::utils::document();
+            let listener = EventListener::new(&document, "keydown", move |event| {
+                let Some(event) = event.dyn_ref::<KeyboardEvent>() else {
+                    return;
+                };
+                let (on_action, on_palette) = latest.borrow().clone();
+
+                if (event.ctrl_key() || event.meta_key()) && event.key().eq_ignore_ascii_case("k") {
+                    event.prevent_default();
+                    return on_palette.emit(());
+                }
+
+                let in_form_field = event
+                    .target()
+                    .and_then(|target| target.dyn_into::<Element>().ok())
+                    .is_some_and(|element| {
+                        matches!(element.tag_name().as_str(), "INPUT" | "SELECT" | "TEXTAREA")
+                    });
+                if in_form_field {
+                    return;
+                }
+
+                if let Some(action) = AppAction::from_key(event) {
+                    event.prevent_default();
+                    on_action.emit(action);
+                }
+            });
+            move || drop(listener)
+        },
+        (),
+    );
+}
+
+// The sample being played, polled from `playhead` often enough for views that follow it.
+#[hook]
+pub fn use_playhead(playhead: Callback<(), Option<usize>>) -> Option<usize> {
+    const POLL_INTERVAL_MS: u32 = 50;
+
+    let latest_playhead = use_mut_ref(|| playhead.clone());
+    *latest_playhead.borrow_mut() = playhead;
+
+    let position = use_state_eq(|| None::<usize>);
+    {
+        let position = position.clone();
+        use_effect_with_deps(
+            move |_| {
+                let interval = Interval::new(POLL_INTERVAL_MS, move || {
+                    // Views following the playhead are not redrawn in the background.
+                    if !page_hidden() {
+                        position.set(latest_playhead.borrow().emit(()))
+                    }
+                });
+                move || drop(interval)
+            },
+            (),
+        );
+    }
+    *position
+}
+
+// A task slot owned by the component, whose task is aborted when the component goes away.
+#[hook]
+pub fn use_task_slot() -> TaskSlot {
+    let slot = (*use_state(TaskSlot::default)).clone();
+    {
+        let slot = slot.clone();
+        use_effect_with_deps(move |_| move || slot.abort(), ());
+    }
+    slot
+}
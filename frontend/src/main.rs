@@ -1,16 +1,84 @@
-use std::{cmp::Ordering, f64::consts::PI};
+use std::{cmp::Ordering, ops::Range};
 
-use gloo::file::File;
+use canvas::Trace;
+use gloo::{events::EventListener, file::File};
 use wasm_bindgen::prelude::*;
-use web_sys::HtmlInputElement;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{Element, HtmlCanvasElement, HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 
-use model::{Channel, Signal, Spectrum};
+use actions::AppAction;
+use automation::Automation;
+use components::{
+    BandEnergyPanel, BatchPanel, BitDepthPanel, CalibrationPanel, ChangeLogPanel, CommandPalette,
+    ComparisonMode, ComparisonPanel, DecayPanel, DecayView, DynamicsPanel, FilterPanel,
+    GainStagingPanel, GeneratorPanel, GoniometerPanel, LargeFilePrompt, LevelView, LevelsPanel,
+    LtasView, MarkersPanel, MeasurementPanel, MetadataPanel, PatternDetector, PitchPanel,
+    PitchTrackView, PlaybackPanel, PresetsPanel, ProcessingPanel, RawImportPrompt,
+    SampleLayoutSelect, SelectionExportPanel, SilencePanel, SoundLevelPanel, SpectrumPanel,
+    SpeechPanel, StatisticsPanel, StatusLine, TransferPanel, TransferView, WaterfallView,
+};
+#[cfg(feature = "server")]
+use components::{LoginScreen, ServerJobNotice, SessionsPanel};
+use export::ImageFormat;
+use fragment::ViewState;
+use fs_access::FileHandle;
+#[cfg(feature = "server")]
+use hooks::use_watch_folder;
+use hooks::{use_file_changes, use_fragment_change, use_keyboard, use_paste, use_task_slot};
+use markers::Marker;
+use model::{
+    biquad::{Biquad, FilterDesign},
+    cursors::Interval,
+    decimation::Decimation,
+    peaks::{self, Harmonic, PeakFormat},
+    processing::{self, Operation},
+    raw::RawSpec,
+    response::ResponseFormat,
+    riff,
+    welch::ConfidenceBand,
+    Channel, Signal, Spectrum,
+};
+use playback::Player;
+#[cfg(feature = "server")]
+use server::{JobEvents, JobState, ServerJob};
+use session::SessionState;
+use settings::{
+    AmplitudeUnit, AnalysisSettings, ExportSettings, SelectionEdges, SpectrumHold, StereoSpectra,
+    TimeFormat,
+};
+use source::{Source, Undecoded};
+use store::{Store, StoreAction};
+use theme::{Theme, ThemeName};
 
 #[macro_use]
 mod bench;
 
+mod actions;
+mod automation;
+mod canvas;
+mod capture;
+mod components;
+mod embed;
+mod export;
+mod fragment;
+mod fs_access;
+mod gpu;
+mod hooks;
+mod markers;
 mod model;
+mod playback;
+mod presets;
+mod report;
+#[cfg(feature = "server")]
+mod server;
+mod session;
+mod settings;
+mod source;
+mod store;
+mod task;
+mod theme;
+mod worker;
 
 #[wasm_bindgen]
 extern "C" {
@@ -18,6 +86,10 @@ extern "C" {
     fn log(s: &str);
 }
 
+// Whole seconds within `view`, as their offsets in samples from the start of the view along with
+// the seconds themselves. Positions in long recordings at high sample rates are too large for the
+// single-precision coordinates of SVG, so plots are drawn relative to the start of the view, and
+// the boundaries are found with integer arithmetic to keep them exact.
 fn map_range<T: Into<f64>>(value: T, from_min: T, from_max: T, to_min: f64, to_max: f64) -> f64 {
     let from_min = from_min.into();
     to_min + (value.into() - from_min) / (from_max.into() - from_min) * (to_max - to_min)
@@ -25,39 +97,169 @@ fn map_range<T: Into<f64>>(value: T, from_min: T, from_max: T, to_min: f64, to_m
 
 #[derive(Properties, PartialEq)]
 struct ControlBoardProps {
+    signal: Signal,
     on_loaded: Callback<Signal>,
+    on_file: Callback<web_sys::File>,
+    on_open: Callback<()>,
+    changed: Option<String>,
+    on_reload: Callback<()>,
+    // What was given up in opening the signal decimated, if it was.
+    decimated: Option<String>,
     on_spectrum: Callback<()>,
     show_spectrum: bool,
+    zoomed: bool,
+    on_reset_zoom: Callback<()>,
+    can_undo: bool,
+    can_redo: bool,
+    on_undo: Callback<()>,
+    on_redo: Callback<()>,
+    on_clear_session: Callback<()>,
+    channel: usize,
+    on_channel: Callback<usize>,
+    session: SessionState,
+    on_export_session: Callback<()>,
+    on_import_session: Callback<SessionState>,
+    playing: bool,
+    on_play: Callback<()>,
+    selected: bool,
+    looping: bool,
+    on_loop: Callback<()>,
+    amplitude_unit: AmplitudeUnit,
+    on_amplitude_unit: Callback<AmplitudeUnit>,
+    time_format: TimeFormat,
+    on_time_format: Callback<TimeFormat>,
+    theme: ThemeName,
+    on_theme: Callback<ThemeName>,
+    #[prop_or_default]
+    children: Children,
 }
 
 #[function_component(ControlBoard)]
 fn control_board(
     ControlBoardProps {
+        signal,
         on_loaded,
+        on_file,
+        on_open,
+        changed,
+        on_reload,
+        decimated,
         on_spectrum,
         show_spectrum,
+        zoomed,
+        on_reset_zoom,
+        can_undo,
+        can_redo,
+        on_undo,
+        on_redo,
+        on_clear_session,
+        channel,
+        on_channel,
+        session,
+        on_export_session,
+        on_import_session,
+        playing,
+        on_play,
+        selected,
+        looping,
+        on_loop,
+        amplitude_unit,
+        on_amplitude_unit,
+        time_format,
+        on_time_format,
+        theme,
+        on_theme,
+        children,
     }: &ControlBoardProps,
 ) -> Html {
-    let file_reader = use_state(|| None);
+    let export_handle = use_state(|| None::<FileHandle>);
+    let session_reader = use_state(|| None);
+
+    // Picks up saving to the file the signal was last exported to before a reload.
+    {
+        let export_handle = export_handle.clone();
+        use_effect_with_deps(
+            move |_| {
+                if fs_access::is_supported() {
+                    spawn_local(async move {
+                        match session::load_export_handle().await {
+                            Ok(handle) => export_handle.set(handle),
+                            Err(error) => log(&format!("failed to load file handle: {error:?}")),
+                        }
+                    });
+                }
+            },
+            (),
+        );
+    }
     let on_change = {
-        let on_loaded = on_loaded.clone();
+        let on_file = on_file.clone();
         Callback::from(move |event: Event| {
-            bench!(["Reading file"] => {
-                let file: web_sys::File = event
-                    .target_unchecked_into::<HtmlInputElement>()
-                    .files()
-                    .unwrap()
-                    .get(0)
-                    .unwrap();
-                let file = File::from(file);
-                let on_loaded = on_loaded.clone();
-                let reader = gloo::file::callbacks::read_as_bytes(&file, move |res| {
-                    on_loaded.emit(Signal::from_wav(res.unwrap()).unwrap());
-                });
-                file_reader.set(Some(reader));
+            if let Some(file) = event
+                .target_unchecked_into::<HtmlInputElement>()
+                .files()
+                .and_then(|files| files.get(0))
+            {
+                on_file.emit(file);
+            }
+        })
+    };
+    let on_export = {
+        let signal = signal.clone();
+        let cue_points = markers::to_cue_points(&session.markers);
+        let export_handle = export_handle.clone();
+        Callback::from(move |reuse_handle: bool| {
+            let mut data = match signal.to_wav() {
+                Ok(data) => data,
+                Err(error) => return log(&format!("failed to encode file: {error}")),
+            };
+            riff::append_cue_points(&mut data, &cue_points);
+
+            if !fs_access::is_supported() {
+                if let Err(error) = fs_access::download("signal.wav", "audio/wav", &data) {
+                    log(&format!("failed to download file: {error:?}"));
+                }
+                return;
+            }
+
+            let handle = (*export_handle).clone().filter(|_| reuse_handle);
+            let export_handle = export_handle.clone();
+            spawn_local(async move {
+                match fs_access::save(handle, "signal.wav", &data).await {
+                    Ok(handle) => {
+                        if let Err(error) = session::save_export_handle(&handle).await {
+                            log(&format!("failed to store file handle: {error:?}"));
+                        }
+                        export_handle.set(Some(handle));
+                    }
+                    Err(error) => log(&format!("failed to save file: {error:?}")),
+                }
             })
         })
     };
+    let on_import = {
+        let on_import_session = on_import_session.clone();
+        let session_reader = session_reader.clone();
+        Callback::from(move |event: Event| {
+            let Some(file) = event
+                .target_unchecked_into::<HtmlInputElement>()
+                .files()
+                .and_then(|files| files.get(0))
+            else {
+                return;
+            };
+            let on_import_session = on_import_session.clone();
+            let reader =
+                gloo::file::callbacks::read_as_text(&File::from(file), move |res| {
+                    match res.map(|text| serde_json::from_str(&text)) {
+                        Ok(Ok(state)) => on_import_session.emit(state),
+                        Ok(Err(error)) => log(&format!("failed to parse session: {error}")),
+                        Err(error) => log(&format!("failed to read session: {error}")),
+                    }
+                });
+            session_reader.set(Some(reader));
+        })
+    };
     let on_click = {
         let on_spectrum = on_spectrum.clone();
         Callback::from(move |_| on_spectrum.emit(()))
@@ -66,9 +268,95 @@ fn control_board(
     html! {
         <div class="control-board">
             <div>
-                <label for="load-sample-file">{"Load sample file"}</label>
-                <input id="load-sample-file" type="file" accept=".wav" onchange={on_change} />
+                if fs_access::is_supported() {
+                    <button onclick={on_open.reform(|_| ())}>{"Load sample file"}</button>
+                } else {
+                    <label for="load-sample-file">{"Load sample file"}</label>
+                    <input id="load-sample-file" type="file" accept=".wav,.aif,.aiff,.aifc,.raw,.pcm,.bin" onchange={on_change} />
+                }
+                <button onclick={on_export.reform(|_| true)}>{
+                    match &*export_handle {
+                        Some(handle) => format!("Save to {}", handle.name()),
+                        None => "Export WAV".to_owned(),
+                    }
+                }</button>
+                if export_handle.is_some() {
+                    <button onclick={on_export.reform(|_| false)}>{"Export as…"}</button>
+                }
+                if *zoomed {
+                    <button onclick={on_reset_zoom.reform(|_| ())}>{"Reset zoom"}</button>
+                }
+                <button title="Ctrl+Z" disabled={!*can_undo} onclick={on_undo.reform(|_| ())}>
+                    {"Undo"}
+                </button>
+                <button title="Ctrl+Shift+Z" disabled={!*can_redo} onclick={on_redo.reform(|_| ())}>
+                    {"Redo"}
+                </button>
+                <button onclick={on_play.reform(|_| ())}>{
+                    if *playing {
+                        "Pause"
+                    } else {
+                        "Play"
+                    }
+                }</button>
+                if *selected || *looping {
+                    <button onclick={on_loop.reform(|_| ())}>{
+                        if *looping {
+                            "Stop loop"
+                        } else {
+                            "Loop selection"
+                        }
+                    }</button>
+                }
+                <button onclick={on_clear_session.reform(|_| ())}>{"Clear session"}</button>
+                <button onclick={on_export_session.reform(|_| ())}>{"Export session"}</button>
+                <label for="import-session">{"Import session"}</label>
+                <input id="import-session" type="file" accept=".json" onchange={on_import} />
+                if let Signal::Stereo(..) = signal {
+                    <select onchange={on_channel.reform(|event: Event| {
+                        event.target_unchecked_into::<HtmlSelectElement>().selected_index().max(0) as usize
+                    })}>
+                        { for ["Left", "Right"].into_iter().enumerate().map(|(n, name)| html! {
+                            <option selected={n == *channel}>{name}</option>
+                        }) }
+                    </select>
+                }
+                <select title="Amplitude unit" onchange={on_amplitude_unit.reform(|event: Event| {
+                    let index = event.target_unchecked_into::<HtmlSelectElement>().selected_index();
+                    AmplitudeUnit::ALL[(index.max(0) as usize).min(AmplitudeUnit::ALL.len() - 1)]
+                })}>
+                    { for AmplitudeUnit::ALL.into_iter().map(|unit| html! {
+                        <option selected={unit == *amplitude_unit}>{unit.name()}</option>
+                    }) }
+                </select>
+                <select title="Time format" onchange={on_time_format.reform(|event: Event| {
+                    let index = event.target_unchecked_into::<HtmlSelectElement>().selected_index();
+                    TimeFormat::ALL[(index.max(0) as usize).min(TimeFormat::ALL.len() - 1)]
+                })}>
+                    { for TimeFormat::ALL.into_iter().map(|format| html! {
+                        <option selected={format == *time_format}>{format.name()}</option>
+                    }) }
+                </select>
+                <select title="Theme" onchange={on_theme.reform(|event: Event| {
+                    let index = event.target_unchecked_into::<HtmlSelectElement>().selected_index();
+                    ThemeName::ALL[(index.max(0) as usize).min(ThemeName::ALL.len() - 1)]
+                })}>
+                    { for ThemeName::ALL.into_iter().map(|name| html! {
+                        <option selected={name == *theme}>{name.name()}</option>
+                    }) }
+                </select>
             </div>
+            if let Some(name) = changed {
+                <div class="changed">
+                    {format!("{name} changed on disk")}
+                    <button onclick={on_reload.reform(|_| ())}>{"Reload"}</button>
+                </div>
+            }
+            if let Some(notice) = decimated {
+                <div class="notice">{notice}</div>
+            }
+            { children.clone() }
+            <GeneratorPanel on_generated={on_loaded.clone()} />
             <div>
                 <button style="width: 250px" onclick={on_click}>{
                     if *show_spectrum {
@@ -78,74 +366,219 @@ fn control_board(
                     }
                 }</button>
             </div>
+            <StatusLine />
         </div>
     }
 }
 
+#[derive(Clone, PartialEq)]
+struct Region {
+    range: Range<usize>,
+    class: &'static str,
+}
+
 #[derive(Properties, PartialEq)]
 struct SignalViewProps {
     channel: Channel,
     mini: bool,
+    #[prop_or_default]
+    regions: Vec<Region>,
+    #[prop_or_default]
+    overlays: Vec<Channel>,
+    #[prop_or_default]
+    view: Option<Range<usize>>,
+    #[prop_or_default]
+    markers: Vec<Marker>,
+    #[prop_or_default]
+    on_add_marker: Callback<usize>,
+    #[prop_or_default]
+    selection: Option<Range<usize>>,
+    #[prop_or_default]
+    on_select: Callback<Option<Range<usize>>>,
+    #[prop_or_default]
+    theme: Theme,
+    #[prop_or_default]
+    unit: AmplitudeUnit,
+    #[prop_or_default]
+    time_format: TimeFormat,
 }
 
 #[function_component(SignalView)]
-fn signal_view(SignalViewProps { channel, mini }: &SignalViewProps) -> Html {
+fn signal_view(
+    SignalViewProps {
+        channel,
+        mini,
+        regions,
+        overlays,
+        view,
+        markers,
+        on_add_marker,
+        selection,
+        on_select,
+        theme,
+        unit,
+        time_format,
+    }: &SignalViewProps,
+) -> Html {
     const X_SCALE: f64 = 1.025;
     const Y_SCALE: f64 = 1.0125;
+    const HANDLE_PIXELS: f64 = 5.0;
 
-    let num_samples = channel.count();
+    let num_samples = overlays
+        .iter()
+        .map(Channel::count)
+        .fold(channel.count(), usize::max);
+    let use_canvas = num_samples >= canvas::MIN_SAMPLES;
+    let view = view.clone().unwrap_or(0..num_samples);
+    let view_len = view.len().max(1);
+    // Coordinates of the plot, relative to the start of the view.
+    let offset = |position: usize| position as f64 - view.start as f64;
 
     bench_start!("Preparing sample view");
 
-    let sample_lower_bound = channel.lower_bound();
-    let sample_upper_bound = channel.upper_bound();
+    let sample_upper_bound = channel.full_scale();
 
+    // Overlaid channels are rescaled to the full scale of the primary channel, so that all traces
+    // share the same amplitude axis.
     let min_amplitude = *use_memo(
-        |_| bench!(["Calculating min amplitude"] => channel.min()),
-        channel.clone(),
+        |(channel, overlays)| {
+            bench!(["Calculating min amplitude"] => overlays
+                .iter()
+                .map(|o| f64::from(o.min()) * channel.full_scale() / o.full_scale())
+                .fold(f64::from(channel.min()), f64::min))
+        },
+        (channel.clone(), overlays.clone()),
     );
     let max_amplitude = *use_memo(
-        |_| bench!(["Calculating max amplitude"] => channel.max()),
-        channel.clone(),
+        |(channel, overlays)| {
+            bench!(["Calculating max amplitude"] => overlays
+                .iter()
+                .map(|o| f64::from(o.max()) * channel.full_scale() / o.full_scale())
+                .fold(f64::from(channel.max()), f64::max))
+        },
+        (channel.clone(), overlays.clone()),
     );
+    // Only the samples within the view are turned into points, so that zooming in on a long signal
+    // makes it cheaper to draw rather than re-rendering all of it.
+    let format_lines = move |channel: &Channel, view: &Range<usize>, scale: f64| {
+        let slice = channel.slice(view.clone());
+        let points = slice
+            .iter()
+            .enumerate()
+            .map(|(i, amplitude)| {
+                let amplitude = f64::from(amplitude) * scale;
+                let percentage = map_range(amplitude, max_amplitude, min_amplitude, -100.0, 100.0);
+                format!("{i} {percentage:.4} ")
+            })
+            .collect::<String>();
+        format!("M 0 0 L {points}{} 0", slice.count())
+    };
     let lines = use_memo(
-        |_| {
-            bench!(["Formatting sample lines"] => channel
+        |(channel, view, _, _, use_canvas)| {
+            if *use_canvas {
+                return String::new();
+            }
+            bench!(["Formatting sample lines"] => format_lines(channel, view, 1.0))
+        },
+        (
+            channel.clone(),
+            view.clone(),
+            min_amplitude,
+            max_amplitude,
+            use_canvas,
+        ),
+    );
+    let overlay_lines = use_memo(
+        |(overlays, view, _, _, use_canvas)| {
+            if *use_canvas {
+                return Vec::new();
+            }
+            bench!(["Formatting overlay lines"] => overlays
                 .iter()
-                .enumerate()
-                .map(|(i, amplitude)| {
-                    let percentage = map_range(amplitude, max_amplitude, min_amplitude, -100.0, 100.0);
-                    format!("{i} {percentage:.4} ")
-                })
-                .collect::<String>())
+                .map(|o| format_lines(o, view, sample_upper_bound / o.full_scale()))
+                .collect::<Vec<_>>())
         },
-        channel.clone(),
+        (
+            overlays.clone(),
+            view.clone(),
+            min_amplitude,
+            max_amplitude,
+            use_canvas,
+        ),
     );
 
+    // Large signals are drawn on a canvas underneath the SVG, which keeps the axes and overlays.
+    // The canvas is redrawn when the window is resized, as its resolution follows its size.
+    let canvas_ref = use_node_ref();
+    {
+        let canvas_ref = canvas_ref.clone();
+        use_effect_with_deps(
+            move |deps| {
+                let (channel, overlays, view, _, use_canvas, theme) = deps.clone();
+                let draw = move || {
+                    let Some(element) = canvas_ref.cast::<HtmlCanvasElement>() else {
+                        return;
+                    };
+                    let traces: Vec<_> = std::iter::once(Trace {
+                        channel: &channel,
+                        scale: 1.0,
+                        color: theme.waveform,
+                    })
+                    .chain(overlays.iter().map(|o| Trace {
+                        channel: o,
+                        scale: sample_upper_bound / o.full_scale(),
+                        color: theme.overlay,
+                    }))
+                    .collect();
+                    let x = |position: f64| {
+                        (position - view.start as f64) / (Y_SCALE * view.len().max(1) as f64)
+                    };
+                    let y = |amplitude: f64| {
+                        let percentage =
+                            map_range(amplitude, max_amplitude, min_amplitude, -100.0, 100.0);
+                        (percentage + 100.0) / (X_SCALE * 200.0)
+                    };
+                    let result = bench!(["Drawing waveform"] =>
+                        canvas::draw_waveform(&element, &traces, view.clone(), x, y));
+                    if let Err(error) = result {
+                        log(&format!("failed to draw waveform: {error:?}"));
+                    }
+                };
+                let listener = use_canvas.then(|| {
+                    draw();
+                    EventListener::new(&gloo::utils::window(), "resize", move |_| draw())
+                });
+                move || drop(listener)
+            },
+            (
+                channel.clone(),
+                overlays.clone(),
+                view.clone(),
+                (min_amplitude, max_amplitude, *mini),
+                use_canvas,
+                *theme,
+            ),
+        );
+    }
+
     let tick_paths = if !*mini {
-        let x_ticks = bench!(["Formatting X ticks"] => (0..=num_samples)
-            .step_by(channel.sample_rate() as usize)
-            .map(|sample| {
-                format!(
-                    "M {sample} -100 L {sample} {:.4} ",
-                    X_SCALE * 200.0,
-                )
+        let x_ticks = bench!(["Formatting X ticks"] => time_format.ticks(&view, channel.sample_rate())
+            .into_iter()
+            .map(|(position, _)| {
+                let x = position - view.start as f64;
+                format!("M {x:.4} -100 L {x:.4} {:.4} ", X_SCALE * 200.0)
             })
             .collect::<String>());
 
         let y_ticks = bench!(["Formatting Y ticks"] =>
-            [
-                min_amplitude,
-                min_amplitude.into_zero(),
-                max_amplitude,
-            ]
+            unit.ticks(min_amplitude, max_amplitude, sample_upper_bound)
             .into_iter()
             .map(|amplitude| {
                 let percentage = map_range(amplitude, max_amplitude, min_amplitude, -100.0, 100.0);
                 format!(
                     "M 0 {0:.4} L {1} {0:.4} ",
                     percentage,
-                    X_SCALE * num_samples as f64
+                    X_SCALE * view_len as f64,
                 )
             })
             .collect::<String>());
@@ -160,34 +593,44 @@ fn signal_view(SignalViewProps { channel, mini }: &SignalViewProps) -> Html {
         None
     };
 
-    let tick_labels = if !*mini {
-        let x_tick_labels = bench!(["Rendering X tick labels"] => (0..=num_samples)
-            .step_by(channel.sample_rate() as usize)
-            .map(|sample| {
+    let marker_labels = (!*mini).then(|| {
+        markers
+            .iter()
+            .filter(|marker| view.contains(&marker.position))
+            .map(|marker| {
                 let left = map_range(
-                    sample as f64,
+                    offset(marker.position),
                     0.0,
-                    (num_samples) as f64,
+                    view_len as f64,
                     0.0,
                     100.0 / Y_SCALE,
                 );
+                html! {
+                    <p class="marker" style={format!("left: {left:.4}%")}>{&marker.label}</p>
+                }
+            })
+            .collect::<Html>()
+    });
+
+    let tick_labels = if !*mini {
+        let x_tick_labels = bench!(["Rendering X tick labels"] => time_format.ticks(&view, channel.sample_rate())
+            .into_iter()
+            .map(|(position, label)| {
+                let x = position - view.start as f64;
+                let left = map_range(x, 0.0, view_len as f64, 0.0, 100.0 / Y_SCALE);
 
                 html! {
                     <p
-                        class="unit second"
+                        class={classes!("unit", time_format.class())}
                         style={format!("left: {left:.4}%")}>
-                        {format!("{}", sample / channel.sample_rate() as usize)}
+                        {label}
                     </p>
                 }
             })
             .collect::<Html>());
 
         let y_tick_labels = bench!(["Rendering Y tick labels"] =>
-            [
-                min_amplitude,
-                min_amplitude.into_zero(),
-                max_amplitude,
-            ]
+            unit.ticks(min_amplitude, max_amplitude, sample_upper_bound)
             .into_iter()
             .map(|amplitude| {
                 let top = map_range(
@@ -197,23 +640,11 @@ fn signal_view(SignalViewProps { channel, mini }: &SignalViewProps) -> Html {
                     0.0,
                     100.0 / X_SCALE,
                 );
-                let display = if amplitude.is_zero() {
-                    0.0
-                } else {
-                    map_range(
-                        amplitude,
-                        sample_lower_bound,
-                        sample_upper_bound,
-                        -100.0,
-                        100.0,
-                    )
-                };
-
                 html! {
                     <p
-                        class="unit percentage"
+                        class={classes!("unit", unit.class())}
                         style={format!("top: {top:.4}%")}>
-                        {format!("{display:.0}")}
+                        {unit.format(amplitude, sample_upper_bound)}
                     </p>
                 }
             })
@@ -223,6 +654,7 @@ fn signal_view(SignalViewProps { channel, mini }: &SignalViewProps) -> Html {
             <>
                 <div class="x-labels">
                     {x_tick_labels}
+                    {marker_labels}
                 </div>
                 <div class="y-labels">
                     {y_tick_labels}
@@ -233,27 +665,174 @@ fn signal_view(SignalViewProps { channel, mini }: &SignalViewProps) -> Html {
         None
     };
 
+    // Maps the pointer to a sample position, along with the number of samples per pixel.
+    let view_start = view.start;
+    let pointer = move |event: &MouseEvent| {
+        let rect = event
+            .current_target()
+            .unwrap()
+            .unchecked_into::<Element>()
+            .get_bounding_client_rect();
+        let samples_per_pixel = Y_SCALE * view_len as f64 / rect.width();
+        let position =
+            view_start as f64 + (event.client_x() as f64 - rect.left()) * samples_per_pixel;
+        (position, samples_per_pixel)
+    };
+
+    // The time and amplitude under the pointer are written to the readout directly rather than
+    // through a render, which would compare the whole trace on every movement.
+    let readout_ref = use_node_ref();
+    let show_readout = {
+        let readout_ref = readout_ref.clone();
+        let channel = channel.clone();
+        let unit = *unit;
+        let time_format = *time_format;
+        move |position: Option<f64>| {
+            let Some(readout) = readout_ref.cast::<Element>() else {
+                return;
+            };
+            let text = position
+                .filter(|position| (0.0..channel.count() as f64).contains(position))
+                .and_then(|position| {
+                    let index = position as usize;
+                    let sample = channel.slice(index..index + 1).iter().next()?;
+                    Some(format!(
+                        "{}{}, {}{}",
+                        time_format.format_precise(index as f64, channel.sample_rate()),
+                        time_format.suffix(),
+                        unit.format(f64::from(sample), channel.full_scale()),
+                        unit.suffix(),
+                    ))
+                });
+            readout.set_text_content(text.as_deref());
+        }
+    };
+
+    let on_double_click = {
+        let on_add_marker = on_add_marker.clone();
+        Callback::from(move |event: MouseEvent| {
+            let (position, _) = pointer(&event);
+            if (0.0..num_samples as f64).contains(&position) {
+                on_add_marker.emit(position as usize);
+            }
+        })
+    };
+
+    // Dragging keeps one edge of the selection fixed: the opposite edge when grabbing a handle, or
+    // the starting point when drawing a new selection.
+    let anchor = use_mut_ref(|| None::<usize>);
+    let on_mouse_down = {
+        let anchor = anchor.clone();
+        let selection = selection.clone();
+        Callback::from(move |event: MouseEvent| {
+            if event.button() != 0 {
+                return;
+            }
+            event.prevent_default();
+            let (position, samples_per_pixel) = pointer(&event);
+            let position = position.clamp(0.0, num_samples as f64);
+            let reach = HANDLE_PIXELS * samples_per_pixel;
+            *anchor.borrow_mut() = Some(match &selection {
+                Some(selection) if (position - selection.start as f64).abs() <= reach => {
+                    selection.end
+                }
+                Some(selection) if (position - selection.end as f64).abs() <= reach => {
+                    selection.start
+                }
+                _ => position as usize,
+            });
+        })
+    };
+    let on_mouse_move = {
+        let anchor = anchor.clone();
+        let on_select = on_select.clone();
+        let show_readout = show_readout.clone();
+        Callback::from(move |event: MouseEvent| {
+            show_readout(Some(pointer(&event).0));
+            let Some(anchor) = *anchor.borrow() else {
+                return;
+            };
+            let position = pointer(&event).0.clamp(0.0, num_samples as f64) as usize;
+            if position != anchor {
+                on_select.emit(Some(anchor.min(position)..anchor.max(position)));
+            }
+        })
+    };
+    let on_mouse_up = {
+        let anchor = anchor.clone();
+        Callback::from(move |_: MouseEvent| *anchor.borrow_mut() = None)
+    };
+    let on_mouse_leave = Callback::from(move |_: MouseEvent| {
+        *anchor.borrow_mut() = None;
+        show_readout(None);
+    });
     bench_end!();
 
     html! {
         <>
             <div class={classes!("plot", mini.then_some("mini"), "signal-view")}>
-                <svg xmlns="http://www.w3.org/2000/svg">
+                if use_canvas {
+                    <canvas ref={canvas_ref} />
+                }
+                <svg xmlns="http://www.w3.org/2000/svg"
+                    ondblclick={on_double_click}
+                    onmousedown={on_mouse_down}
+                    onmousemove={on_mouse_move}
+                    onmouseup={on_mouse_up}
+                    onmouseleave={on_mouse_leave}>
                     <svg
                         viewBox={format!("0 -100 {:.4} {:.4}",
-                            Y_SCALE * num_samples as f64,
+                            Y_SCALE * view_len as f64,
                             X_SCALE * 200.0,
                         )}
                         preserveAspectRatio="none">
                         {tick_paths}
-                        <path vector-effect="non-scaling-stroke"
-                            d={format!("M 0 0 L {lines} {num_samples} 0")} />
+                        if !use_canvas {
+                            <path vector-effect="non-scaling-stroke" d={(*lines).clone()} />
+                            { for overlay_lines.iter().map(|lines| html! {
+                                <path class="overlay" vector-effect="non-scaling-stroke"
+                                    d={lines.clone()} />
+                            }) }
+                        }
+                        { for regions.iter().map(|Region { range, class }| html! {
+                            <rect class={classes!("region", *class)}
+                                vector-effect="non-scaling-stroke"
+                                x={offset(range.start).to_string()}
+                                y="-100"
+                                width={range.len().to_string()}
+                                height="200" />
+                        }) }
+                        { for markers.iter().map(|marker| html! {
+                            <line class="marker" vector-effect="non-scaling-stroke"
+                                x1={offset(marker.position).to_string()}
+                                x2={offset(marker.position).to_string()}
+                                y1="-100"
+                                y2="100" />
+                        }) }
+                        if let Some(selection) = selection {
+                            <rect class="selection"
+                                vector-effect="non-scaling-stroke"
+                                x={offset(selection.start).to_string()}
+                                y="-100"
+                                width={selection.len().to_string()}
+                                height="200" />
+                            { for [selection.start, selection.end].into_iter().map(|edge| html! {
+                                <line class="handle" vector-effect="non-scaling-stroke"
+                                    x1={offset(edge).to_string()}
+                                    x2={offset(edge).to_string()}
+                                    y1="-100"
+                                    y2="100" />
+                            }) }
+                        }
                         <rect vector-effect="non-scaling-stroke"
                             y="-100"
-                            width={num_samples.to_string()}
+                            width={view_len.to_string()}
                             height="200" />
                     </svg>
                 </svg>
+                if !*mini {
+                    <p ref={readout_ref} class="readout" />
+                }
             </div>
             {tick_labels}
             <div class="empty-box" />
@@ -265,15 +844,92 @@ fn signal_view(SignalViewProps { channel, mini }: &SignalViewProps) -> Html {
 struct SpectrumViewProps {
     spectrum: Spectrum,
     show: bool,
+    #[prop_or_default]
+    overlays: Vec<Spectrum>,
+    // Theoretical filter response, drawn with 0 dB at the top of the plot.
+    #[prop_or_default]
+    response: Option<Biquad>,
+    #[prop_or_default]
+    confidence: Option<ConfidenceBand>,
+    // The most points each trace is drawn with, or `None` for one per bin.
+    #[prop_or_default]
+    max_points: Option<usize>,
+    // Drawn like an overlay, in a color of its own.
+    #[prop_or_default]
+    difference: Option<Spectrum>,
+    #[prop_or_default]
+    hold: SpectrumHold,
+    // Changed to start holding or averaging over.
+    #[prop_or_default]
+    hold_reset: u32,
+    // The frequencies of the two measurement cursors, when they are shown.
+    #[prop_or_default]
+    cursors: Option<[f64; 2]>,
+    #[prop_or_default]
+    on_cursors: Callback<[f64; 2]>,
+    // The RMS level of the channel between the cursors.
+    #[prop_or_default]
+    cursor_band_dbfs: Option<f64>,
+    // How many harmonics of the picked fundamental to mark, when they are marked.
+    #[prop_or_default]
+    harmonics: Option<u32>,
+    #[prop_or_default]
+    fundamental: Option<f64>,
+    #[prop_or_default]
+    on_fundamental: Callback<f64>,
 }
 
 #[function_component(SpectrumView)]
-fn spectrum_view(SpectrumViewProps { spectrum, show }: &SpectrumViewProps) -> Html {
+fn spectrum_view(
+    SpectrumViewProps {
+        spectrum,
+        show,
+        overlays,
+        response,
+        confidence,
+        max_points,
+        difference,
+        hold,
+        hold_reset,
+        cursors,
+        on_cursors,
+        cursor_band_dbfs,
+        harmonics,
+        fundamental,
+        on_fundamental,
+    }: &SpectrumViewProps,
+) -> Html {
     const X_SCALE: f64 = 1.025;
     const Y_SCALE: f64 = 1.0125;
+    // How much each new spectrum counts in the exponential average.
+    const AVERAGE_WEIGHT: f64 = 0.25;
 
     bench_start!("Preparing frequency view");
 
+    // Each new spectrum is held or averaged with those before it, which are let go when the mode is
+    // changed, it is reset, or the bins change.
+    let held = use_mut_ref(|| None::<(SpectrumHold, u32, Spectrum)>);
+    let spectrum = &(*use_memo(
+        |(spectrum, hold, reset)| {
+            let mut held = held.borrow_mut();
+            let earlier = held
+                .as_ref()
+                .filter(|(h, r, earlier)| h == hold && r == reset && earlier.same_bins(spectrum))
+                .map(|(_, _, earlier)| earlier);
+            let current = match (hold, earlier) {
+                (SpectrumHold::PeakHold, Some(earlier)) => spectrum.peak_hold(earlier),
+                (SpectrumHold::Average, Some(earlier)) => {
+                    spectrum.exponential_average(earlier, AVERAGE_WEIGHT)
+                }
+                _ => spectrum.clone(),
+            };
+            *held = Some((*hold, *reset, current.clone()));
+            current
+        },
+        (spectrum.clone(), *hold, *hold_reset),
+    ))
+    .clone();
+
     let num_usable_samples = spectrum.len();
     let half_sample_rate_log = (spectrum.sample_rate() as f64 / 2.0).log10();
 
@@ -281,8 +937,7 @@ fn spectrum_view(SpectrumViewProps { spectrum, show }: &SpectrumViewProps) -> Ht
         |_| {
             bench!(["Calculating RMS"] => {
                 let square_sum = spectrum
-                    .iter()
-                    .map(|c| c.norm())
+                    .magnitudes()
                     .map(|f| f * f)
                     .sum::<f64>();
 
@@ -293,24 +948,7 @@ fn spectrum_view(SpectrumViewProps { spectrum, show }: &SpectrumViewProps) -> Ht
     );
 
     let centroid = *use_memo(
-        |_| {
-            bench!(["Calculating centroid"] => {
-                let numerator: f64 = spectrum
-                    .iter()
-                    .enumerate()
-                    .map(|(n, c)| {
-                        let frequency = spectrum.bin_to_frequency(n);
-                        let magnitude = c.norm();
-                        frequency * magnitude
-                    })
-                    .sum();
-                let denominator: f64 = spectrum
-                    .iter()
-                    .map(|c| c.norm())
-                    .sum();
-                numerator / denominator
-            })
-        },
+        |_| bench!(["Calculating centroid"] => spectrum.centroid()),
         spectrum.clone(),
     );
     let centroid_log = centroid.log10();
@@ -343,10 +981,11 @@ fn spectrum_view(SpectrumViewProps { spectrum, show }: &SpectrumViewProps) -> Ht
     });
 
     let max_volume = *use_memo(
-        |_| {
+        |(spectrum, overlays, difference)| {
             bench!(["Calculating max volume"] => spectrum
-            .iter()
-            .map(|c| Spectrum::decibel(c.norm(), rms))
+            .magnitudes()
+            .chain(overlays.iter().chain(difference).flat_map(Spectrum::magnitudes))
+            .map(|magnitude| Spectrum::decibel(magnitude, rms))
             .max_by(|x, y| {
                 x.partial_cmp(y).unwrap_or_else(|| {
                     if !x.is_nan() {
@@ -358,25 +997,103 @@ fn spectrum_view(SpectrumViewProps { spectrum, show }: &SpectrumViewProps) -> Ht
             })
             .unwrap_or(0.0))
         },
-        spectrum.clone(),
+        (spectrum.clone(), overlays.clone(), difference.clone()),
     );
     let min_volume = 0.0;
-    let lines = use_memo(
-        |_| {
-            bench!(["Formatting frequency lines"] => spectrum
-            .iter()
-            .enumerate()
-            .skip(1)
-            .map(|(n, &amplitude)| {
+    let max_points = *max_points;
+    // Bins drawn as one point are drawn at the loudest of them, so that no peaks are lost.
+    let format_lines = move |spectrum: &Spectrum| {
+        let magnitudes: Vec<_> = spectrum.magnitudes().collect();
+        spectrum
+            .point_ranges(max_points)
+            .into_iter()
+            .map(|range| {
+                let (n, magnitude) = range
+                    .map(|n| (n, magnitudes[n]))
+                    .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .unwrap_or_default();
                 let frequency_log = spectrum.bin_to_frequency(n).log10();
-                let volume = Spectrum::decibel(amplitude.norm(), rms).max(min_volume);
+                let volume = Spectrum::decibel(magnitude, rms).max(min_volume);
                 format!("{frequency_log:.4} {:.4} ", -volume)
             })
-            .collect::<String>())
+            .collect::<String>()
+    };
+    let lines = use_memo(
+        |(spectrum, _)| bench!(["Formatting frequency lines"] => format_lines(spectrum)),
+        (spectrum.clone(), max_points),
+    );
+    let overlay_lines = use_memo(
+        |(overlays, difference, _, _)| {
+            bench!(["Formatting overlay frequency lines"] => overlays
+                .iter()
+                .map(|o| (o, "overlay"))
+                .chain(difference.iter().map(|d| (d, "difference")))
+                .map(|(o, class)| (format_lines(o), (o.sample_rate() as f64 / 2.0).log10(), class))
+                .collect::<Vec<_>>())
         },
-        spectrum.clone(),
+        (overlays.clone(), difference.clone(), rms, max_points),
+    );
+    // Drawing fewer points than there are bins is pointed out, as narrow peaks may be merged.
+    let density_warning = max_points
+        .filter(|&max_points| spectrum.len() > max_points + 1)
+        .map(|max_points| {
+            format!(
+                "{} bins drawn as at most {max_points} points",
+                spectrum.len() - 1
+            )
+        });
+
+    let response_line = use_memo(
+        |(response, sample_rate, max_volume)| {
+            response.map(|biquad| {
+                const POINTS: usize = 512;
+                let nyquist_log = (*sample_rate as f64 / 2.0).log10();
+                bench!(["Formatting filter response"] => (0..=POINTS)
+                    .map(|i| {
+                        let frequency_log = nyquist_log * i as f64 / POINTS as f64;
+                        let gain = biquad.magnitude_db(10_f64.powf(frequency_log), *sample_rate);
+                        let volume = (max_volume + gain).clamp(min_volume, *max_volume);
+                        format!("{frequency_log:.4} {:.4} ", -volume)
+                    })
+                    .collect::<String>())
+            })
+        },
+        (*response, spectrum.sample_rate(), max_volume),
+    );
+
+    // The band is drawn as a closed outline, along the upper bounds and back along the lower ones,
+    // widened to the extremes of the bins drawn as one point.
+    let confidence_area = use_memo(
+        |(spectrum, confidence, _, max_points)| {
+            confidence.as_ref().map(|band| {
+                let point = |n: usize, magnitude: f64| {
+                    let frequency_log = spectrum.bin_to_frequency(n).log10();
+                    let volume = Spectrum::decibel(magnitude, rms).max(min_volume);
+                    format!("{frequency_log:.4} {:.4} ", -volume)
+                };
+                bench!(["Formatting confidence band"] => {
+                    let ranges: Vec<_> = spectrum
+                        .point_ranges(*max_points)
+                        .into_iter()
+                        .map(|range| range.start..range.end.min(band.upper.len()))
+                        .filter(|range| !range.is_empty())
+                        .collect();
+                    let upper = ranges.iter().map(|range| {
+                        point(range.start, band.upper[range.clone()].iter().copied().fold(0.0, f64::max))
+                    });
+                    let lower = ranges.iter().rev().map(|range| {
+                        point(range.start, band.lower[range.clone()].iter().copied().fold(f64::INFINITY, f64::min))
+                    });
+                    upper.chain(lower).collect::<String>()
+                })
+            })
+        },
+        (spectrum.clone(), confidence.clone(), rms, max_points),
     );
 
+    // Dragging moves the cursor nearest to where it started.
+    let dragged = use_mut_ref(|| None::<usize>);
+
     if !*show {
         return html!();
     }
@@ -454,12 +1171,149 @@ fn spectrum_view(SpectrumViewProps { spectrum, show }: &SpectrumViewProps) -> Ht
             })
             .collect::<Html>());
 
+    // Maps the pointer to a frequency on the logarithmic axis, within the bins above DC.
+    let lowest_frequency = spectrum.bin_to_frequency(1);
+    let pointer_frequency = move |event: &MouseEvent| {
+        let rect = event
+            .current_target()
+            .unwrap()
+            .unchecked_into::<Element>()
+            .get_bounding_client_rect();
+        let position = (event.client_x() as f64 - rect.left()) / rect.width();
+        10_f64
+            .powf(position * Y_SCALE * half_sample_rate_log)
+            .clamp(lowest_frequency, 10_f64.powf(half_sample_rate_log))
+    };
+    let cursors = *cursors;
+    let harmonics = *harmonics;
+    let on_mouse_down = {
+        let dragged = dragged.clone();
+        let on_cursors = on_cursors.clone();
+        let on_fundamental = on_fundamental.clone();
+        Callback::from(move |event: MouseEvent| {
+            if event.button() != 0 {
+                return;
+            }
+            // Without cursors to move, a click picks the fundamental whose harmonics are marked.
+            let Some(mut cursors) = cursors else {
+                if harmonics.is_some() {
+                    event.prevent_default();
+                    on_fundamental.emit(pointer_frequency(&event));
+                }
+                return;
+            };
+            event.prevent_default();
+            let frequency = pointer_frequency(&event);
+            let distance = |cursor: f64| (cursor / frequency).log2().abs();
+            let nearest = usize::from(distance(cursors[1]) < distance(cursors[0]));
+            *dragged.borrow_mut() = Some(nearest);
+            cursors[nearest] = frequency;
+            on_cursors.emit(cursors);
+        })
+    };
+    let on_mouse_move = {
+        let dragged = dragged.clone();
+        let on_cursors = on_cursors.clone();
+        Callback::from(move |event: MouseEvent| {
+            if let (Some(n), Some(mut cursors)) = (*dragged.borrow(), cursors) {
+                cursors[n] = pointer_frequency(&event);
+                on_cursors.emit(cursors);
+            }
+        })
+    };
+    let on_mouse_up = {
+        let dragged = dragged.clone();
+        Callback::from(move |_: MouseEvent| *dragged.borrow_mut() = None)
+    };
+    let on_mouse_leave = Callback::from(move |_: MouseEvent| *dragged.borrow_mut() = None);
+
+    // With the measurement cursors shown, each of them is taken for a fundamental, so that the
+    // harmonics of two tones can be read at once. Otherwise, the fundamental is the one picked.
+    let fundamentals: Vec<(f64, Option<&str>)> = match cursors {
+        Some([a, b]) => vec![(a, Some("a")), (b, Some("b"))],
+        None => fundamental.iter().map(|&f| (f, None)).collect(),
+    };
+    let harmonic_sets: Vec<(Option<&str>, Vec<Harmonic>)> = harmonics
+        .map(|count| {
+            fundamentals
+                .iter()
+                .map(|&(f, cursor)| (cursor, spectrum.harmonics_of(f, count)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // The level at each cursor, as drawn, and the interval and RMS level between them, along with
+    // the distortion of each when their harmonics are marked.
+    let cursor_readout = cursors.map(|[a, b]| {
+        let interval = Interval::between(a, b);
+        let mut text = format!(
+            "A {a:.1} Hz, {:.1} dBFS; B {b:.1} Hz, {:.1} dBFS; Δ {:+.1} Hz, {:+.2} octaves, {:+.0} cents",
+            spectrum.level_at(a),
+            spectrum.level_at(b),
+            interval.hertz,
+            interval.octaves,
+            interval.cents,
+        );
+        if let Some(level) = cursor_band_dbfs {
+            text.push_str(&format!("; {level:.1} dBFS RMS between"));
+        }
+        for (cursor, harmonics) in &harmonic_sets {
+            if let (Some(cursor), Some(thd)) = (cursor, peaks::total_harmonic_distortion(harmonics)) {
+                text.push_str(&format!(
+                    "; THD {} {:.3} % ({:.1} dB)",
+                    cursor.to_uppercase(),
+                    100.0 * thd,
+                    20.0 * thd.log10(),
+                ));
+            }
+        }
+        text
+    });
+
+    // Each harmonic is labeled where its level is drawn, the fundamental in dBFS and the others
+    // relative to it, after the letter of the cursor it belongs to, if any.
+    let harmonic_labels = bench!(["Rendering harmonic labels"] => harmonic_sets
+            .iter()
+            .flat_map(|(cursor, harmonics)| harmonics.iter().map(move |harmonic| (*cursor, harmonic)))
+            .map(|(cursor, harmonic)| {
+                let left = map_range(
+                    harmonic.frequency.log10(),
+                    0.0,
+                    half_sample_rate_log,
+                    0.0,
+                    100.0 / Y_SCALE,
+                );
+                let volume = (harmonic.level_db - 20.0 * (2.0 * rms).log10())
+                    .clamp(min_volume, max_volume);
+                let top = map_range(volume, max_volume, min_volume, 0.0, 100.0 / X_SCALE);
+                let letter = cursor.map(str::to_uppercase).unwrap_or_default();
+                let text = if harmonic.number > 1 {
+                    format!("{letter}{}: {:+.1} dB", harmonic.number, harmonic.relative_db)
+                } else if let Some(cursor) = cursor {
+                    format!("{}: {:.1} dBFS", cursor.to_uppercase(), harmonic.level_db)
+                } else {
+                    format!("{:.1} dBFS", harmonic.level_db)
+                };
+
+                html! {
+                    <p class={classes!("harmonic", cursor)}
+                        style={format!("top: {top:.4}%; left: {left:.4}%")}>
+                        {text}
+                    </p>
+                }
+            })
+            .collect::<Html>());
+
     bench_end!();
 
     html! {
         <>
             <div class="plot spectrum-view">
-                <svg xmlns="http://www.w3.org/2000/svg">
+                <svg xmlns="http://www.w3.org/2000/svg"
+                    onmousedown={on_mouse_down}
+                    onmousemove={on_mouse_move}
+                    onmouseup={on_mouse_up}
+                    onmouseleave={on_mouse_leave}>
                     <svg
                         viewBox={format!("0 {:.4} {:.4} {:.4}",
                             -max_volume,
@@ -471,12 +1325,48 @@ fn spectrum_view(SpectrumViewProps { spectrum, show }: &SpectrumViewProps) -> Ht
                         <path vector-effect="non-scaling-stroke" d={y_ticks} />
                         <path vector-effect="non-scaling-stroke"
                             d={format!("M 0 0 L {lines} {half_sample_rate_log:.4} 0")} />
+                        { for overlay_lines.iter().map(|(lines, end, class)| html! {
+                            <path class={*class} vector-effect="non-scaling-stroke"
+                                d={format!("M 0 0 L {lines} {end:.4} 0")} />
+                        }) }
+                        if let Some(area) = &*confidence_area {
+                            <path class="confidence" vector-effect="non-scaling-stroke"
+                                d={format!("M {area} Z")} />
+                        }
+                        if let Some(line) = &*response_line {
+                            <path class="response" vector-effect="non-scaling-stroke"
+                                d={format!("M {line}")} />
+                        }
                         <path vector-effect="non-scaling-stroke"
                             d={format!("M {0:.4} {1:.4} L {0:.4} {2:.4}",
                                 centroid_log,
                                 -min_volume,
                                 -(max_volume - min_volume) / 2.0,
                             )} />
+                        if let Some([a, b]) = cursors {
+                            <rect class="cursor-band" vector-effect="non-scaling-stroke"
+                                x={format!("{:.4}", a.min(b).log10())}
+                                y={format!("{:.4}", -max_volume)}
+                                width={format!("{:.4}", (a.max(b) / a.min(b)).log10())}
+                                height={format!("{:.4}", max_volume - min_volume)} />
+                            { for [a, b].into_iter().map(|cursor| html! {
+                                <line class="cursor" vector-effect="non-scaling-stroke"
+                                    x1={format!("{:.4}", cursor.log10())}
+                                    x2={format!("{:.4}", cursor.log10())}
+                                    y1={format!("{:.4}", -max_volume)}
+                                    y2={format!("{:.4}", -min_volume)} />
+                            }) }
+                        }
+                        { for harmonic_sets
+                            .iter()
+                            .flat_map(|(cursor, harmonics)| harmonics.iter().map(move |h| (*cursor, h)))
+                            .map(|(cursor, harmonic)| html! {
+                            <line class={classes!("harmonic", cursor)} vector-effect="non-scaling-stroke"
+                                x1={format!("{:.4}", harmonic.frequency.log10())}
+                                x2={format!("{:.4}", harmonic.frequency.log10())}
+                                y1={format!("{:.4}", -max_volume)}
+                                y2={format!("{:.4}", -min_volume)} />
+                        }) }
                         <rect vector-effect="non-scaling-stroke"
                             y={format!("{:.4}", -max_volume)}
                             width={format!("{half_sample_rate_log:.4}")}
@@ -484,6 +1374,13 @@ fn spectrum_view(SpectrumViewProps { spectrum, show }: &SpectrumViewProps) -> Ht
                     </svg>
                 </svg>
                 {centroid_label}
+                {harmonic_labels}
+                if let Some(readout) = cursor_readout {
+                    <p class="readout">{readout}</p>
+                }
+                if let Some(warning) = density_warning {
+                    <p class="density-warning">{warning}</p>
+                }
             </div>
             <div class="x-labels">
                 {x_tick_labels}
@@ -496,61 +1393,1747 @@ fn spectrum_view(SpectrumViewProps { spectrum, show }: &SpectrumViewProps) -> Ht
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Playback {
+    Stopped,
+    Playing,
+    Paused,
+}
+
+// The app, once the backend, if it requires a token, has been given a valid one. Nothing is shown
+// while that is checked, so that the app does not start only to be replaced.
+#[cfg(feature = "server")]
+#[function_component(Root)]
+fn root() -> Html {
+    let authorized = use_state(|| None::<bool>);
+    {
+        let authorized = authorized.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move { authorized.set(Some(server::is_authorized().await)) })
+            },
+            (),
+        );
+    }
+    let on_signed_in = {
+        let authorized = authorized.clone();
+        Callback::from(move |_| authorized.set(Some(true)))
+    };
+
+    match *authorized {
+        Some(true) => html! { <App /> },
+        Some(false) => html! { <LoginScreen on_signed_in={on_signed_in} /> },
+        None => html! {},
+    }
+}
+
+// Without a backend there is nothing to sign in to.
+#[cfg(not(feature = "server"))]
+#[function_component(Root)]
+fn root() -> Html {
+    html! { <App /> }
+}
+
 #[function_component(App)]
 fn app() -> Html {
     bench_start!("Preparing app");
 
-    let signal = use_state(|| {
-        bench!(["Generating default stereo signal"] => {
-            let frequency = 5;
-            let sample_rate = 44100;
-            let wave = (0..sample_rate)
-                .map(|i| {
-                    map_range(
-                        (2.0 * PI * frequency as f64 * i as f64 / sample_rate as f64).sin(),
-                        -1.0,
-                        1.0,
-                        f32::MIN as f64,
-                        f32::MAX as f64,
-                    ) as f32
-                });
-            Signal::Mono(Channel::from_samples_f32(wave, 32, sample_rate))
-        })
+    let store = use_reducer(Store::new);
+    let filter_preview = use_state(|| None::<FilterDesign>);
+    let hold_reset = use_state(|| 0_u32);
+    let spectrum_cursors = use_state(|| [100.0_f64, 1000.0]);
+    let harmonic_fundamental = use_state(|| None::<f64>);
+    let noise_region = use_state(|| None::<Range<usize>>);
+    let processed = use_memo(
+        |(signal, chain)| bench!(["Processing signal"] => processing::process(signal, chain)),
+        (store.signal().clone(), store.chain.clone()),
+    );
+    let channel_index = store.channel.min(processed.num_channels() - 1);
+    let channel = processed.channel(channel_index);
+    let comparison = store.signals.get(1).map(|signal| {
+        signal
+            .channel(channel_index.min(signal.num_channels() - 1))
+            .clone()
     });
-    let channel = signal.channel(0);
-    let spectrum = use_memo(|_| channel.spectrum(), channel.clone());
+    let settings = &store.settings;
+    let spectrum_settings = settings.spectrum;
+    let welch = use_memo(
+        |(channel, s)| {
+            s.welch.then(|| {
+                bench!(["Calculating Welch spectrum"] => channel.welch(s.segment_len, s.overlap))
+            })
+        },
+        (channel.clone(), spectrum_settings),
+    );
+    let spectrum = {
+        let welch = welch.clone();
+        use_memo(
+            move |(channel, _)| match &*welch {
+                Some(welch) => welch.spectrum.clone(),
+                None => channel.spectrum(),
+            },
+            (channel.clone(), spectrum_settings),
+        )
+    };
+    // The spectra of the other channel of a stereo signal and of the left minus the right one,
+    // averaged like that of the selected channel.
+    let stereo_spectra = use_memo(
+        |(signal, channel_index, s)| {
+            let Signal::Stereo(left, right) = &**signal else {
+                return (None, None);
+            };
+            let spectrum = |channel: &Channel| {
+                if s.welch {
+                    channel.welch(s.segment_len, s.overlap).spectrum
+                } else {
+                    channel.spectrum()
+                }
+            };
+            let other = (s.stereo != StereoSpectra::Selected).then(|| {
+                let other = if *channel_index == 0 { right } else { left };
+                bench!(["Calculating spectrum of the other channel"] => spectrum(other))
+            });
+            let difference = (s.stereo == StereoSpectra::BothAndDifference)
+                .then(|| left.difference(right))
+                .flatten()
+                .map(|difference| {
+                    bench!(["Calculating spectrum of the channel difference"] => spectrum(&difference))
+                });
+            (other, difference)
+        },
+        (processed.clone(), channel_index, spectrum_settings),
+    );
+    // The loudest each bin is over the segments of the selection, or else of the whole channel,
+    // with those of Welch averaging.
+    let max_over_selection = use_memo(
+        |(channel, selection, s)| {
+            (s.hold == SpectrumHold::MaxOverSelection).then(|| {
+                let region = selection.clone().unwrap_or(0..channel.count());
+                bench!(["Calculating maximum spectrum"] => {
+                    channel.slice(region).welch(s.segment_len, s.overlap).maximum
+                })
+            })
+        },
+        (channel.clone(), store.selection.clone(), spectrum_settings),
+    );
+    let tilt = *use_memo(
+        |(spectrum, low, high)| bench!(["Fitting spectral tilt"] => spectrum.tilt(*low..*high)),
+        (
+            (*spectrum).clone(),
+            spectrum_settings.tilt_low,
+            spectrum_settings.tilt_high,
+        ),
+    );
+    let ltas = use_memo(
+        |(channel, bands_per_octave, enabled)| {
+            enabled.then(|| bench!(["Calculating LTAS"] => channel.ltas(*bands_per_octave)))
+        },
+        (
+            channel.clone(),
+            spectrum_settings.bands_per_octave,
+            spectrum_settings.ltas && settings.show_spectrum,
+        ),
+    );
+    let overs = use_memo(
+        |(channel, s)| {
+            if s.mark_overs {
+                bench!(["Finding true-peak overs"] => channel.true_peak_overs(s.ceiling_dbtp))
+            } else {
+                Vec::new()
+            }
+        },
+        (channel.clone(), settings.true_peak),
+    );
+    let comparison_spectrum = use_memo(
+        |(comparison, s)| {
+            comparison.as_ref().map(|comparison| {
+                if s.welch {
+                    comparison.welch(s.segment_len, s.overlap).spectrum
+                } else {
+                    comparison.spectrum()
+                }
+            })
+        },
+        (comparison.clone(), spectrum_settings),
+    );
 
-    let show_spectrum = use_state(|| false);
+    let comparison_mode = use_state(|| ComparisonMode::Overlay);
+    let detections = use_state(Vec::new);
+    let speech = use_state(Vec::new);
+    let levels = use_state(|| None);
+    let decay = use_state(|| None);
+    let transfer = use_state(|| None);
+    let pitch_track = use_state(|| None);
+    let silence = use_state(Vec::new);
+    // Saving is held off until the previous session has been restored, so that it is not
+    // overwritten by the defaults.
+    let restored = use_state(|| false);
+    let player = use_mut_ref(|| None::<Player>);
+    let playback = use_state(|| Playback::Stopped);
+    let palette_open = use_state(|| false);
+    let automation = use_memo(|_| Automation::from_query(), ());
+    let automated = use_mut_ref(|| false);
+    let automation_pending = use_state(|| false);
 
+    {
+        let store = store.clone();
+        let restored = restored.clone();
+        use_effect_with_deps(
+            move |_| {
+                spawn_local(async move {
+                    let mut signal = None;
+                    let mut state = SessionState {
+                        settings: store.settings.clone(),
+                        ..SessionState::default()
+                    };
+                    match session::load().await {
+                        Ok(Some((restored_signal, restored_state))) => {
+                            signal = Some(restored_signal);
+                            state = restored_state;
+                        }
+                        Ok(None) => (),
+                        Err(error) => log(&format!("failed to restore session: {error:?}")),
+                    }
+                    // A shared link takes precedence over how the session was left.
+                    if let Some(fragment) = fragment::current() {
+                        let linked = ViewState::parse(
+                            &fragment,
+                            ViewState::new(&state.settings, state.view, 0, state.markers),
+                        );
+                        linked.apply(&mut state.settings);
+                        state.view = linked.view;
+                        state.markers = linked.markers;
+                        store.dispatch(StoreAction::Channel(linked.channel));
+                    }
+                    store.dispatch(StoreAction::Restore(signal, state));
+                    restored.set(true);
+                })
+            },
+            (),
+        );
+    }
+    use_effect_with_deps(
+        |(signal, restored)| {
+            if *restored {
+                let signal = signal.clone();
+                spawn_local(async move {
+                    if let Err(error) = session::save_signal(&signal).await {
+                        log(&format!("failed to save session: {error:?}"));
+                    }
+                })
+            }
+        },
+        (store.signal().clone(), *restored),
+    );
+    use_effect_with_deps(
+        |(state, restored)| {
+            if *restored {
+                let state = state.clone();
+                spawn_local(async move {
+                    if let Err(error) = session::save_state(&state).await {
+                        log(&format!("failed to save session: {error:?}"));
+                    }
+                })
+            }
+        },
+        (store.session(), *restored),
+    );
+
+    let null_test = use_memo(
+        |(channel, comparison, mode)| {
+            comparison
+                .as_ref()
+                .filter(|_| *mode == ComparisonMode::NullTest)
+                .map(|comparison| bench!(["Running null test"] => channel.null_test(comparison)))
+        },
+        (channel.clone(), comparison.clone(), *comparison_mode),
+    );
+    let difference = use_memo(
+        |(channel, comparison, spectrum, comparison_spectrum, mode, null_test)| match (
+            mode,
+            comparison,
+            comparison_spectrum,
+            null_test,
+        ) {
+            (ComparisonMode::Difference, Some(comparison), Some(comparison_spectrum), _) => {
+                bench!(["Calculating difference"] => channel
+                    .difference(comparison)
+                    .map(|difference| (difference, spectrum.difference(comparison_spectrum))))
+            }
+            (ComparisonMode::NullTest, _, _, Some(null_test)) => {
+                Some((null_test.residual.clone(), null_test.residual.spectrum()))
+            }
+            _ => None,
+        },
+        (
+            channel.clone(),
+            comparison.clone(),
+            (*spectrum).clone(),
+            (*comparison_spectrum).clone(),
+            *comparison_mode,
+            (*null_test).clone(),
+        ),
+    );
+
+    // The difference is only taken between samples that line up, and where both files have them.
+    let difference_notice = comparison
+        .as_ref()
+        .filter(|_| *comparison_mode == ComparisonMode::Difference)
+        .and_then(|comparison| {
+            if channel.sample_rate() != comparison.sample_rate() {
+                Some(
+                    "The files have different sample rates, so their samples cannot be subtracted."
+                        .to_owned(),
+                )
+            } else if channel.count() != comparison.count() {
+                Some(format!(
+                    "Only the first {:.2} s, where both files have samples, are compared.",
+                    channel.count().min(comparison.count()) as f64
+                        / f64::from(channel.sample_rate()),
+                ))
+            } else {
+                None
+            }
+        });
+
+    // Loading a signal aborts any load still in progress, so that the most recently requested file
+    // is shown rather than whichever finishes decoding last.
+    let loading = use_task_slot();
     let on_loaded = {
-        let signal = signal.clone();
-        Callback::from(move |new_signal| {
-            signal.set(new_signal);
+        let store = store.clone();
+        let loading = loading.clone();
+        Callback::from(move |(signal, source)| {
+            loading.abort();
+            store.dispatch(StoreAction::Load(signal, source, None, None))
         })
     };
+    #[cfg(feature = "server")]
+    let on_opened = {
+        let store = store.clone();
+        Callback::from(move |(signal, source)| {
+            store.dispatch(StoreAction::Load(signal, Some(source), None, None))
+        })
+    };
+    let on_loaded = on_loaded.reform(|signal| (signal, None));
+    // Large files are only decoded once the user has chosen whether to decimate them. Reloading
+    // keeps the choice made when the file was opened.
+    let large_file = use_mut_ref(|| None::<Undecoded>);
+    let large_file_prompt = use_state(|| None::<(String, usize, u32)>);
+    let on_large_file = {
+        let store = store.clone();
+        let large_file = large_file.clone();
+        let large_file_prompt = large_file_prompt.clone();
+        Callback::from(move |decimation: Option<Decimation>| {
+            large_file_prompt.set(None);
+            let Some(file) = large_file.borrow_mut().take() else {
+                return;
+            };
+            let source = file.source.clone();
+            let metadata = file.metadata();
+            match file.decode(decimation) {
+                Ok(signal) => {
+                    store.dispatch(StoreAction::Load(signal, source, decimation, metadata))
+                }
+                Err(error) => log(&error),
+            }
+        })
+    };
+    let on_layout = {
+        let store = store.clone();
+        Callback::from(move |layout| store.dispatch(StoreAction::Layout(layout)))
+    };
+    // Large files may instead be uploaded for the backend to analyse, whose progress is pushed by
+    // it and shown until dismissed.
+    #[cfg(feature = "server")]
+    let server_job = use_state(|| None::<ServerJob>);
+    #[cfg(feature = "server")]
+    let server_events = use_mut_ref(|| None::<JobEvents>);
+    #[cfg(feature = "server")]
+    let server_task = use_task_slot();
+    #[cfg(feature = "server")]
+    let on_analyse_on_server = {
+        let large_file = large_file.clone();
+        let large_file_prompt = large_file_prompt.clone();
+        let server_job = server_job.clone();
+        let server_events = server_events.clone();
+        let server_task = server_task.clone();
+        let callback = Callback::from(move |_| {
+            large_file_prompt.set(None);
+            let Some(file) = large_file.borrow_mut().take() else {
+                return;
+            };
+            let running = JobState::Running {
+                progress: 0.0,
+                tiles: 0,
+            };
+            server_job.set(Some(ServerJob {
+                name: file.name.clone(),
+                id: None,
+                state: running,
+            }));
+            let server_job = server_job.clone();
+            let server_events = server_events.clone();
+            server_task.spawn(async move {
+                let name = file.name;
+                let events = server::upload(&name, file.data).await.and_then(|id| {
+                    let on_state = {
+                        let server_job = server_job.clone();
+                        Callback::from(move |state| {
+                            server_job.set(Some(ServerJob {
+                                name: name.clone(),
+                                id: Some(id),
+                                state,
+                            }))
+                        })
+                    };
+                    JobEvents::new(id, on_state, Callback::noop())
+                });
+                match events {
+                    Ok(events) => *server_events.borrow_mut() = Some(events),
+                    Err(error) => {
+                        log(&error);
+                        server_job.set(None);
+                    }
+                }
+            });
+        });
+        Some(callback)
+    };
+    #[cfg(not(feature = "server"))]
+    let on_analyse_on_server = None::<Callback<()>>;
+    #[cfg(feature = "server")]
+    let on_dismiss_server_job = {
+        let server_job = server_job.clone();
+        Callback::from(move |_| {
+            server_task.abort();
+            server_events.borrow_mut().take();
+            server_job.set(None);
+        })
+    };
+    let on_cancel_large_file = {
+        let large_file = large_file.clone();
+        let large_file_prompt = large_file_prompt.clone();
+        Callback::from(move |_| {
+            large_file.borrow_mut().take();
+            large_file_prompt.set(None);
+        })
+    };
+    // Files without a WAV header are only read once the user has described their samples.
+    // Reloading reads them the same way again.
+    let raw_file = use_mut_ref(|| None::<Undecoded>);
+    let raw_spec = use_mut_ref(|| None::<RawSpec>);
+    let raw_file_prompt = use_state(|| None::<(String, usize)>);
+    let on_raw_file = {
+        let store = store.clone();
+        let raw_file = raw_file.clone();
+        let raw_spec = raw_spec.clone();
+        let raw_file_prompt = raw_file_prompt.clone();
+        Callback::from(move |spec: RawSpec| {
+            raw_file_prompt.set(None);
+            let Some(file) = raw_file.borrow_mut().take() else {
+                return;
+            };
+            *raw_spec.borrow_mut() = Some(spec);
+            let source = file.source.clone();
+            match file.decode_raw(spec) {
+                Ok(signal) => store.dispatch(StoreAction::Load(signal, source, None, None)),
+                Err(error) => log(&error),
+            }
+        })
+    };
+    let on_cancel_raw_file = {
+        let raw_file = raw_file.clone();
+        let raw_file_prompt = raw_file_prompt.clone();
+        Callback::from(move |_| {
+            raw_file.borrow_mut().take();
+            raw_file_prompt.set(None);
+        })
+    };
+    let on_undecoded = {
+        let store = store.clone();
+        let large_file_prompt = large_file_prompt.clone();
+        let raw_spec = raw_spec.clone();
+        let raw_file_prompt = raw_file_prompt.clone();
+        Callback::from(move |file: Undecoded| {
+            let reloading = file.source.is_some() && file.source == store.source;
+            if !file.has_header() {
+                let spec = *raw_spec.borrow();
+                match spec.filter(|_| reloading) {
+                    Some(spec) => {
+                        let source = file.source.clone();
+                        match file.decode_raw(spec) {
+                            Ok(signal) => {
+                                store.dispatch(StoreAction::Load(signal, source, None, None))
+                            }
+                            Err(error) => log(&error),
+                        }
+                    }
+                    None => {
+                        raw_file_prompt.set(Some((file.name.clone(), file.data.len())));
+                        *raw_file.borrow_mut() = Some(file);
+                    }
+                }
+                return;
+            }
+            if !reloading && file.is_large() {
+                // Files without a readable header are decoded right away to report the error.
+                if let Ok(sample_rate) = Signal::peek_sample_rate(&file.data) {
+                    large_file_prompt.set(Some((file.name.clone(), file.data.len(), sample_rate)));
+                    *large_file.borrow_mut() = Some(file);
+                    return;
+                }
+            }
+            let decimation = store.decimation.filter(|_| reloading);
+            let source = file.source.clone();
+            let metadata = file.metadata();
+            match file.decode(decimation) {
+                Ok(signal) => {
+                    store.dispatch(StoreAction::Load(signal, source, decimation, metadata))
+                }
+                Err(error) => log(&error),
+            }
+        })
+    };
+    let on_file = {
+        let loading = loading.clone();
+        let on_undecoded = on_undecoded.clone();
+        Callback::from(move |file: web_sys::File| {
+            let on_undecoded = on_undecoded.clone();
+            loading.spawn(async move {
+                match source::read_file_undecoded(&file).await {
+                    Ok(file) => on_undecoded.emit(file),
+                    Err(error) => log(&error),
+                }
+            })
+        })
+    };
+    let on_source = {
+        let loading = loading.clone();
+        let on_undecoded = on_undecoded.clone();
+        Callback::from(move |source: Source| {
+            let on_undecoded = on_undecoded.clone();
+            loading.spawn(async move {
+                match source.read_undecoded().await {
+                    Ok(file) => on_undecoded.emit(file),
+                    Err(error) => log(&error),
+                }
+            })
+        })
+    };
+    let on_open = {
+        let loading = loading.clone();
+        let on_undecoded = on_undecoded.clone();
+        Callback::from(move |_| {
+            let on_undecoded = on_undecoded.clone();
+            loading.spawn(async move {
+                match fs_access::open().await {
+                    Ok((handle, data)) => on_undecoded.emit(Undecoded {
+                        name: handle.name(),
+                        data,
+                        source: Some(Source::File(handle)),
+                    }),
+                    Err(error) => log(&format!("failed to open file: {error:?}")),
+                }
+            })
+        })
+    };
+    let on_reload = {
+        let store = store.clone();
+        let on_source = on_source.clone();
+        Callback::from(move |_| {
+            if let Some(source) = store.source.clone() {
+                on_source.emit(source);
+            }
+        })
+    };
+    let on_changed = {
+        let store = store.clone();
+        Callback::from(move |_| store.dispatch(StoreAction::Changed))
+    };
+    // New files in the watch folder are opened right away, while changes to the open one are only
+    // offered for reloading.
+    #[cfg(feature = "server")]
+    let on_watched_file = {
+        let store = store.clone();
+        let on_source = on_source.clone();
+        let on_changed = on_changed.clone();
+        Callback::from(move |name: String| {
+            let watched = Source::WatchFolder(name);
+            if store.source.as_ref() == Some(&watched) {
+                return on_changed.emit(());
+            }
+            on_source.emit(watched);
+        })
+    };
+    let on_remove_dc = {
+        let store = store.clone();
+        Callback::from(move |_| store.dispatch(StoreAction::RemoveDc))
+    };
+    let on_fragment = {
+        let store = store.clone();
+        Callback::from(move |fragment: String| {
+            let linked = ViewState::parse(
+                &fragment,
+                ViewState::new(
+                    &store.settings,
+                    store.view.clone(),
+                    store.channel,
+                    store.markers.clone(),
+                ),
+            );
+            let mut settings = store.settings.clone();
+            linked.apply(&mut settings);
+            // Following a link changes what is shown rather than restoring a session, so that what
+            // has changed since the session was saved is still known.
+            store.dispatch(StoreAction::Channel(linked.channel));
+            store.dispatch(StoreAction::Settings(settings));
+            store.dispatch(StoreAction::View(linked.view));
+            store.dispatch(StoreAction::Markers(linked.markers));
+        })
+    };
+    let on_channel = {
+        let store = store.clone();
+        Callback::from(move |channel| store.dispatch(StoreAction::Channel(channel)))
+    };
+    use_effect_with_deps(
+        |(state, restored)| {
+            if *restored {
+                if let Err(error) = fragment::replace(&state.to_fragment()) {
+                    log(&format!("failed to update URL: {error:?}"));
+                }
+            }
+        },
+        (
+            ViewState::new(
+                settings,
+                store.view.clone(),
+                channel_index,
+                store.markers.clone(),
+            ),
+            *restored,
+        ),
+    );
+
+    use_paste(on_file.clone());
+    use_fragment_change(on_fragment);
+    #[cfg(feature = "server")]
+    {
+        use_watch_folder(on_watched_file);
+    }
+
+    // Query parameter automation runs once the previous session has been restored, opening the
+    // requested file first so that its preset is in place before the analyzers are enabled.
+    {
+        let automation = automation.clone();
+        let automated = automated.clone();
+        let automation_pending = automation_pending.clone();
+        let loading = loading.clone();
+        #[cfg(feature = "server")]
+        let on_opened = on_opened.clone();
+        use_effect_with_deps(
+            move |restored| {
+                if !*restored || automation.is_empty() || automated.replace(true) {
+                    return;
+                }
+                loading.spawn(async move {
+                    #[cfg(feature = "server")]
+                    if let Some(name) = automation.open.clone() {
+                        let source = Source::WatchFolder(name);
+                        match source.read().await {
+                            Ok(signal) => on_opened.emit((signal, source)),
+                            Err(error) => log(&error),
+                        }
+                    }
+                    automation_pending.set(true);
+                })
+            },
+            *restored,
+        );
+    }
+    {
+        let store = store.clone();
+        let deps = (*automation_pending, (*processed).clone());
+        use_effect_with_deps(
+            move |(pending, processed)| {
+                if !*pending {
+                    return;
+                }
+                automation_pending.set(false);
+
+                let mut new_settings = store.settings.clone();
+                automation.apply(&mut new_settings);
+                if automation.export_json {
+                    let name = store.source.as_ref().map(Source::name);
+                    let report = bench!(["Running automated analysis"] => {
+                        automation.report(processed, name.as_deref(), &store.markers, &new_settings)
+                    });
+                    if let Err(error) = export_report(&report) {
+                        log(&format!("failed to export analysis: {error:?}"));
+                    }
+                }
+                store.dispatch(StoreAction::Settings(new_settings));
+            },
+            deps,
+        );
+    }
+    use_file_changes(store.source.clone(), on_changed);
+
+    let on_compare = {
+        let store = store.clone();
+        Callback::from(move |signal| store.dispatch(StoreAction::Compare(signal)))
+    };
+    let on_comparison_mode = {
+        let comparison_mode = comparison_mode.clone();
+        Callback::from(move |mode| comparison_mode.set(mode))
+    };
     let on_spectrum = {
-        let show_spectrum = show_spectrum.clone();
+        let store = store.clone();
+        Callback::from(move |_| store.dispatch(StoreAction::ToggleSpectrum))
+    };
+    // Measured impulse responses are shown along with their frequency response.
+    let on_measured = {
+        let store = store.clone();
+        Callback::from(move |response| {
+            store.dispatch(StoreAction::Load(response, None, None, None));
+            if !store.settings.show_spectrum {
+                store.dispatch(StoreAction::ToggleSpectrum);
+            }
+        })
+    };
+    let on_apply_settings = {
+        let store = store.clone();
+        Callback::from(move |settings| store.dispatch(StoreAction::Settings(settings)))
+    };
+    let on_reset_hold = {
+        let hold_reset = hold_reset.clone();
+        Callback::from(move |()| hold_reset.set(hold_reset.wrapping_add(1)))
+    };
+    let on_spectrum_settings = {
+        let store = store.clone();
+        Callback::from(move |spectrum| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                spectrum,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    // The channel is taken to be an impulse response, so that a measured one exports as the
+    // transfer function of the system.
+    let on_export_response = {
+        let channel = channel.clone();
+        let calibration = settings.calibration.clone();
+        Callback::from(move |format: ResponseFormat| {
+            let spectrum = channel.spectrum();
+            let data = match &calibration {
+                Some(calibration) => spectrum.calibrated(calibration),
+                None => spectrum,
+            }
+            .export_response(format);
+            let result =
+                fs_access::download(format.file_name(), format.mime_type(), data.as_bytes());
+            if let Err(error) = result {
+                log(&format!("failed to export response: {error:?}"));
+            }
+        })
+    };
+    let on_export_peaks = {
+        const EXPORTED_PEAKS: usize = 32;
+
+        let channel = channel.clone();
+        Callback::from(move |format: PeakFormat| {
+            let peaks = channel.spectrum().peaks(EXPORTED_PEAKS);
+            let data = peaks::export_peaks(&peaks, format);
+            let result =
+                fs_access::download(format.file_name(), format.mime_type(), data.as_bytes());
+            if let Err(error) = result {
+                log(&format!("failed to export peaks: {error:?}"));
+            }
+        })
+    };
+    let on_transfer_settings = {
+        let store = store.clone();
+        Callback::from(move |transfer| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                transfer,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_sound_level_settings = {
+        let store = store.clone();
+        Callback::from(move |sound_level| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                sound_level,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_decay_settings = {
+        let store = store.clone();
+        Callback::from(move |decay| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                decay,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_level_settings = {
+        let store = store.clone();
+        Callback::from(move |levels| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                levels,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_pitch_settings = {
+        let store = store.clone();
+        Callback::from(move |pitch| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                pitch,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_speech_settings = {
+        let store = store.clone();
+        Callback::from(move |speech| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                speech,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_gain_staging_settings = {
+        let store = store.clone();
+        Callback::from(move |gain_staging| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                gain_staging,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_amplitude_unit = {
+        let store = store.clone();
+        Callback::from(move |amplitude_unit| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                amplitude_unit,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_time_format = {
+        let store = store.clone();
+        Callback::from(move |time_format| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                time_format,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_true_peak_settings = {
+        let store = store.clone();
+        Callback::from(move |true_peak| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                true_peak,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_calibration = {
+        let store = store.clone();
+        Callback::from(move |calibration| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                calibration,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_export_settings = {
+        let store = store.clone();
+        Callback::from(move |export| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                export,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_noise_floor = {
+        let store = store.clone();
+        Callback::from(move |noise_floor| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                noise_floor,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_noise_region = {
+        let noise_region = noise_region.clone();
+        Callback::from(move |region| noise_region.set(region))
+    };
+    let on_playback_settings = {
+        let store = store.clone();
+        Callback::from(move |playback| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                playback,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_band_settings = {
+        let store = store.clone();
+        Callback::from(move |bands| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                bands,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let on_silence_settings = {
+        let store = store.clone();
+        Callback::from(move |silence| {
+            store.dispatch(StoreAction::Settings(AnalysisSettings {
+                silence,
+                ..store.settings.clone()
+            }))
+        })
+    };
+    let show_spectrum = settings.show_spectrum;
+
+    let theme_name = use_state(ThemeName::load);
+    let theme = theme_name.theme();
+    let on_theme = {
+        let theme_name = theme_name.clone();
+        Callback::from(move |name: ThemeName| {
+            name.store();
+            if let Err(error) = name.theme().apply_to_document() {
+                log(&format!("failed to apply theme: {error:?}"));
+            }
+            theme_name.set(name);
+        })
+    };
+
+    let on_detected = {
+        let detections = detections.clone();
+        Callback::from(move |ranges: Vec<Range<usize>>| detections.set(ranges))
+    };
+
+    let on_speech = {
+        let speech = speech.clone();
+        Callback::from(move |ranges: Vec<Range<usize>>| speech.set(ranges))
+    };
+
+    let on_levels = {
+        let levels = levels.clone();
+        Callback::from(move |curve| levels.set(curve))
+    };
+    let on_transfer = {
+        let transfer = transfer.clone();
+        Callback::from(move |transfer_function| transfer.set(transfer_function))
+    };
+    let on_decay = {
+        let decay = decay.clone();
+        Callback::from(move |room_decay| decay.set(room_decay))
+    };
+
+    let on_pitch_track = {
+        let pitch_track = pitch_track.clone();
+        Callback::from(move |track| pitch_track.set(track))
+    };
+
+    let on_silence = {
+        let silence = silence.clone();
+        Callback::from(move |ranges: Vec<Range<usize>>| silence.set(ranges))
+    };
+    let on_chain = {
+        let store = store.clone();
+        Callback::from(move |chain| store.dispatch(StoreAction::Chain(chain)))
+    };
+    let on_add_filter = {
+        let store = store.clone();
+        Callback::from(move |design| {
+            store.dispatch(StoreAction::PushOperation(Operation::Filter(design)))
+        })
+    };
+    let on_apply_gain = {
+        let store = store.clone();
+        Callback::from(move |db| store.dispatch(StoreAction::PushOperation(Operation::Gain { db })))
+    };
+    let on_filter_preview = {
+        let filter_preview = filter_preview.clone();
+        Callback::from(move |design| filter_preview.set(design))
+    };
+    let on_zoom = {
+        let store = store.clone();
+        Callback::from(move |range: Range<usize>| store.dispatch(StoreAction::View(Some(range))))
+    };
+    let on_reset_zoom = {
+        let store = store.clone();
+        Callback::from(move |_| store.dispatch(StoreAction::View(None)))
+    };
+    let on_clear_session = {
+        let store = store.clone();
+        let restored = restored.clone();
         Callback::from(move |_| {
-            show_spectrum.set(!*show_spectrum);
+            // Saving is paused until the stored session is gone, so that the two do not race.
+            restored.set(false);
+            store.dispatch(StoreAction::Clear);
+            let restored = restored.clone();
+            spawn_local(async move {
+                if let Err(error) = session::clear().await {
+                    log(&format!("failed to clear session: {error:?}"));
+                }
+                restored.set(true);
+            })
         })
     };
 
+    let regions = silence
+        .iter()
+        .map(|range| Region {
+            range: range.clone(),
+            class: "silence",
+        })
+        .chain(speech.iter().map(|range| Region {
+            range: range.clone(),
+            class: "speech",
+        }))
+        .chain(detections.iter().map(|range| Region {
+            range: range.clone(),
+            class: "detection",
+        }))
+        .chain(overs.iter().map(|range| Region {
+            range: range.clone(),
+            class: "over",
+        }))
+        .collect::<Vec<_>>();
+
+    // The band is around the averaged spectrum, so it is left out when something else is drawn.
+    let confidence = (*welch)
+        .as_ref()
+        .filter(|_| {
+            difference.is_none()
+                && spectrum_settings.show_confidence
+                && spectrum_settings.hold == SpectrumHold::Off
+        })
+        .map(|welch| welch.confidence.clone());
+
+    let (displayed_channel, displayed_spectrum, channel_overlays, spectrum_overlays) =
+        match (&*difference, &comparison, &*comparison_spectrum) {
+            (Some((channel, spectrum)), _, _) => {
+                (channel.clone(), spectrum.clone(), Vec::new(), Vec::new())
+            }
+            (None, Some(comparison), Some(comparison_spectrum)) => (
+                channel.clone(),
+                (*spectrum).clone(),
+                vec![comparison.clone()],
+                vec![comparison_spectrum.clone()],
+            ),
+            _ => (channel.clone(), (*spectrum).clone(), Vec::new(), Vec::new()),
+        };
+    let (spectrum_overlays, stereo_difference) = match (&*difference, &*stereo_spectra) {
+        (None, (other, stereo_difference)) => (
+            spectrum_overlays.into_iter().chain(other.clone()).collect(),
+            stereo_difference.clone(),
+        ),
+        (Some(_), _) => (spectrum_overlays, None),
+    };
+    let displayed_spectrum = match (&*difference, &*max_over_selection) {
+        (None, Some(maximum)) => maximum.clone(),
+        _ => displayed_spectrum,
+    };
+    // The drawn spectra are corrected for the microphone they were recorded with.
+    let calibrated = use_memo(
+        |(spectrum, overlays, difference, confidence, calibration)| match calibration {
+            Some(calibration) => bench!(["Calibrating spectra"] => (
+                spectrum.calibrated(calibration),
+                overlays.iter().map(|o| o.calibrated(calibration)).collect::<Vec<_>>(),
+                difference.as_ref().map(|d| d.calibrated(calibration)),
+                confidence.as_ref().map(|c| c.calibrated(spectrum, calibration)),
+            )),
+            None => (
+                spectrum.clone(),
+                overlays.clone(),
+                difference.clone(),
+                confidence.clone(),
+            ),
+        },
+        (
+            displayed_spectrum,
+            spectrum_overlays,
+            stereo_difference,
+            confidence,
+            settings.calibration.clone(),
+        ),
+    );
+    let (displayed_spectrum, spectrum_overlays, stereo_difference, confidence) =
+        (*calibrated).clone();
+    // Smoothing only changes what is drawn, while exports and measurements use the spectrum as it
+    // is.
+    let smoothed = use_memo(
+        |(spectrum, overlays, difference, confidence, smoothing)| match smoothing {
+            Some(fraction) => bench!(["Smoothing spectra"] => (
+                spectrum.smoothed(*fraction),
+                overlays.iter().map(|o| o.smoothed(*fraction)).collect::<Vec<_>>(),
+                difference.as_ref().map(|d| d.smoothed(*fraction)),
+                confidence.as_ref().map(|c| c.smoothed(*fraction)),
+            )),
+            None => (
+                spectrum.clone(),
+                overlays.clone(),
+                difference.clone(),
+                confidence.clone(),
+            ),
+        },
+        (
+            displayed_spectrum,
+            spectrum_overlays,
+            stereo_difference,
+            confidence,
+            spectrum_settings.smoothing,
+        ),
+    );
+    let (displayed_spectrum, spectrum_overlays, stereo_difference, confidence) =
+        (*smoothed).clone();
+    let cursor_band_dbfs = *use_memo(
+        |(channel, [a, b], shown)| {
+            shown.then(|| {
+                bench!(["Calculating RMS between cursors"] => {
+                    channel.band_rms_dbfs(a.min(*b), a.max(*b))
+                })
+            })
+        },
+        (
+            displayed_channel.clone(),
+            *spectrum_cursors,
+            spectrum_settings.cursors && show_spectrum,
+        ),
+    );
+    let on_spectrum_cursors = {
+        let spectrum_cursors = spectrum_cursors.clone();
+        Callback::from(move |cursors| spectrum_cursors.set(cursors))
+    };
+    let on_harmonic_fundamental = {
+        let harmonic_fundamental = harmonic_fundamental.clone();
+        Callback::from(move |frequency| harmonic_fundamental.set(Some(frequency)))
+    };
+
+    let num_samples = channel_overlays
+        .iter()
+        .map(Channel::count)
+        .fold(displayed_channel.count(), usize::max);
+
+    let on_add_marker = {
+        let store = store.clone();
+        Callback::from(move |position| store.dispatch(StoreAction::AddMarker(position)))
+    };
+    let on_markers = {
+        let store = store.clone();
+        Callback::from(move |markers| store.dispatch(StoreAction::Markers(markers)))
+    };
+    let on_jump = {
+        let store = store.clone();
+        Callback::from(move |position| {
+            let view = actions::center(store.view.clone(), num_samples, position);
+            store.dispatch(StoreAction::View(view))
+        })
+    };
+    // Session files hold the settings, view, markers and change log, but not the signal itself.
+    let on_export_session = {
+        let store = store.clone();
+        Callback::from(move |_| {
+            let entry = store.change_log_entry(String::from(js_sys::Date::new_0().to_iso_string()));
+            let mut session = store.session();
+            session.change_log.push(entry.clone());
+            let result = serde_json::to_vec_pretty(&session)
+                .map_err(|error| JsValue::from(error.to_string()))
+                .and_then(|data| fs_access::download("session.json", "application/json", &data));
+            match result {
+                Ok(()) => store.dispatch(StoreAction::Saved(entry)),
+                Err(error) => log(&format!("failed to export session: {error:?}")),
+            }
+        })
+    };
+    let on_import_session = {
+        let store = store.clone();
+        Callback::from(move |state| store.dispatch(StoreAction::Restore(None, state)))
+    };
+
+    let on_action = {
+        let processed = processed.clone();
+        let store = store.clone();
+        let on_jump = on_jump.clone();
+        let player = player.clone();
+        let playback = playback.clone();
+        let on_spectrum = on_spectrum.clone();
+        let on_clear_session = on_clear_session.clone();
+        Callback::from(move |action| match action {
+            AppAction::TogglePlayback => {
+                let result = match (*playback, &*player.borrow()) {
+                    (Playback::Playing, Some(player)) => player.pause().map(|_| Playback::Paused),
+                    (Playback::Paused, Some(player)) => player.resume().map(|_| Playback::Playing),
+                    _ => {
+                        let range = store
+                            .view
+                            .clone()
+                            .unwrap_or(0..processed.channel(0).count());
+                        let on_ended = {
+                            let playback = playback.clone();
+                            move || playback.set(Playback::Stopped)
+                        };
+                        Player::play(&processed, range, store.settings.playback, on_ended).map(
+                            |new_player| {
+                                *player.borrow_mut() = Some(new_player);
+                                Playback::Playing
+                            },
+                        )
+                    }
+                };
+                match result {
+                    Ok(state) => playback.set(state),
+                    Err(error) => log(&format!("failed to play signal: {error:?}")),
+                }
+            }
+            AppAction::ToggleLoop => {
+                if player.borrow().as_ref().is_some_and(Player::is_looping) {
+                    *player.borrow_mut() = None;
+                    playback.set(Playback::Stopped);
+                } else if let Some(region) = store.selection.clone() {
+                    match Player::play_loop(&processed, region, store.settings.playback) {
+                        Ok(new_player) => {
+                            *player.borrow_mut() = Some(new_player);
+                            playback.set(Playback::Playing);
+                        }
+                        Err(error) => log(&format!("failed to loop selection: {error:?}")),
+                    }
+                }
+            }
+            AppAction::ClearSelection => store.dispatch(StoreAction::Select(None)),
+            AppAction::ToggleSpectrum => on_spectrum.emit(()),
+            AppAction::ZoomIn => store.dispatch(StoreAction::View(actions::zoom(
+                store.view.clone(),
+                num_samples,
+                0.5,
+            ))),
+            AppAction::ZoomOut => store.dispatch(StoreAction::View(actions::zoom(
+                store.view.clone(),
+                num_samples,
+                2.0,
+            ))),
+            AppAction::ResetZoom => store.dispatch(StoreAction::View(None)),
+            AppAction::PanLeft => store.dispatch(StoreAction::View(actions::pan(
+                store.view.clone(),
+                num_samples,
+                -0.25,
+            ))),
+            AppAction::PanRight => store.dispatch(StoreAction::View(actions::pan(
+                store.view.clone(),
+                num_samples,
+                0.25,
+            ))),
+            AppAction::SelectLeftChannel => store.dispatch(StoreAction::Channel(0)),
+            AppAction::SelectRightChannel => store.dispatch(StoreAction::Channel(
+                processed.num_channels().saturating_sub(1),
+            )),
+            AppAction::NextMarker | AppAction::PreviousMarker => {
+                let markers = &store.markers;
+                let marker = match (&store.view, action) {
+                    (None, AppAction::NextMarker) => markers.first(),
+                    (None, _) => markers.last(),
+                    (Some(view), AppAction::NextMarker) => {
+                        markers::next(markers, view.start + view.len() / 2)
+                    }
+                    (Some(view), _) => markers::previous(markers, view.start + view.len() / 2),
+                };
+                if let Some(marker) = marker {
+                    on_jump.emit(marker.position);
+                }
+            }
+            AppAction::ClearSession => on_clear_session.emit(()),
+            AppAction::ExportSvg | AppAction::ExportPng => {
+                let format = match action {
+                    AppAction::ExportSvg => ImageFormat::Svg,
+                    _ => ImageFormat::Png,
+                };
+                spawn_local(async move {
+                    if let Err(error) = export::export_plot(format).await {
+                        log(&format!("failed to export image: {error:?}"));
+                    }
+                });
+            }
+            AppAction::ExportSelection => {
+                let Some(region) = store.selection.clone() else {
+                    return;
+                };
+                let result =
+                    export_selection(&processed, region, &store.markers, store.settings.export);
+                if let Err(error) = result {
+                    log(&format!("failed to export selection: {error:?}"));
+                }
+            }
+            AppAction::ExportReport | AppAction::PrintReport => {
+                let print = action == AppAction::PrintReport;
+                let name = store
+                    .source
+                    .as_ref()
+                    .map_or_else(|| "Generated signal".to_owned(), Source::name);
+                let result = report::report_html(
+                    &processed,
+                    &name,
+                    store.metadata.as_ref(),
+                    &store.markers,
+                    print,
+                )
+                .and_then(|html| match print {
+                    true => fs_access::open_in_window("text/html", html.as_bytes()),
+                    false => fs_access::download("report.html", "text/html", html.as_bytes()),
+                });
+                if let Err(error) = result {
+                    log(&format!("failed to export report: {error:?}"));
+                }
+            }
+            AppAction::Undo => store.dispatch(StoreAction::Undo),
+            AppAction::Redo => store.dispatch(StoreAction::Redo),
+        })
+    };
+    let on_select = {
+        let store = store.clone();
+        Callback::from(move |region| store.dispatch(StoreAction::Select(region)))
+    };
+
+    // A running loop follows the handles of the selection, and stops once it is cleared.
+    {
+        let player = player.clone();
+        let playback = playback.clone();
+        use_effect_with_deps(
+            move |selection| {
+                let mut player = player.borrow_mut();
+                match (player.as_ref().filter(|p| p.is_looping()), selection) {
+                    (Some(looping), Some(region)) => looping.set_loop(region.clone()),
+                    (Some(_), None) => {
+                        *player = None;
+                        playback.set(Playback::Stopped);
+                    }
+                    _ => {}
+                }
+                || ()
+            },
+            store.selection.clone(),
+        );
+    }
+    // Changes to the output of playback are heard right away.
+    {
+        let player = player.clone();
+        use_effect_with_deps(
+            move |output| {
+                if let Some(player) = &*player.borrow() {
+                    player.set_output(*output);
+                }
+                || ()
+            },
+            settings.playback,
+        );
+    }
+
+    let on_palette = {
+        let palette_open = palette_open.clone();
+        Callback::from(move |_| palette_open.set(!*palette_open))
+    };
+    let on_close_palette = {
+        let palette_open = palette_open.clone();
+        Callback::from(move |_| palette_open.set(false))
+    };
+    use_keyboard(on_action.clone(), on_palette);
+
+    let playhead = {
+        let player = player.clone();
+        let playing = *playback != Playback::Stopped;
+        Callback::from(move |()| {
+            player
+                .borrow()
+                .as_ref()
+                .filter(|_| playing)
+                .map(Player::position)
+        })
+    };
+
+    // Parts of the page that need the backend.
+    #[cfg(feature = "server")]
+    let sessions_panel = html! {
+        <SessionsPanel
+            session={store.session()}
+            file={store.source.as_ref().map(Source::name)}
+            on_open={on_import_session.clone()} />
+    };
+    #[cfg(feature = "server")]
+    let server_job_notice = match (*server_job).clone() {
+        Some(job) => html! {
+            <ServerJobNotice
+                job={job}
+                theme={theme}
+                on_dismiss={on_dismiss_server_job} />
+        },
+        None => html! {},
+    };
+    #[cfg(not(feature = "server"))]
+    let (sessions_panel, server_job_notice) = (html! {}, html! {});
+
     bench_end!();
 
     html! {
         <div class={classes!("app", show_spectrum.then_some("split"))}>
+            <div class="sidebar">
+                <StatisticsPanel
+                    signal={(*processed).clone()}
+                    settings={settings.true_peak}
+                    on_settings={on_true_peak_settings}
+                    noise_floor={settings.noise_floor}
+                    on_noise_floor={on_noise_floor}
+                    selection={store.selection.clone()}
+                    noise_region={(*noise_region).clone()}
+                    on_noise_region={on_noise_region}
+                    on_remove_dc={on_remove_dc} />
+                <ComparisonPanel
+                    signals={store.signals.clone()}
+                    mode={*comparison_mode}
+                    null_test={(*null_test).clone()}
+                    difference_notice={difference_notice}
+                    on_compare={on_compare}
+                    on_mode={on_comparison_mode} />
+                <BatchPanel
+                    signal={(*processed).clone()}
+                    name={store.source.as_ref().map(Source::name)} />
+                <ProcessingPanel chain={store.chain.clone()} on_change={on_chain} />
+                <GainStagingPanel
+                    signal={(*processed).clone()}
+                    settings={settings.gain_staging}
+                    on_settings={on_gain_staging_settings}
+                    on_apply={on_apply_gain} />
+                <SelectionExportPanel
+                    settings={settings.export}
+                    on_settings={on_export_settings}
+                    selected={store.selection.is_some()}
+                    on_export={on_action.reform(|_| AppAction::ExportSelection)} />
+                <PlaybackPanel
+                    signal={(*processed).clone()}
+                    region={store
+                        .selection
+                        .clone()
+                        .or_else(|| store.view.clone())
+                        .unwrap_or(0..num_samples)}
+                    settings={settings.playback}
+                    on_settings={on_playback_settings} />
+                <SpectrumPanel
+                    settings={spectrum_settings}
+                    on_settings={on_spectrum_settings}
+                    channel={channel.slice(store.selection.clone().unwrap_or(0..channel.count()))}
+                    selected={store.selection.is_some()}
+                    segments={(*welch).as_ref().map(|welch| welch.segments)}
+                    stereo_channel={(processed.num_channels() > 1).then_some(channel_index)}
+                    tilt={tilt}
+                    on_reset_hold={on_reset_hold}
+                    on_export={on_export_response}
+                    on_export_peaks={on_export_peaks} />
+                <CalibrationPanel
+                    calibration={settings.calibration.clone()}
+                    on_calibration={on_calibration} />
+                <FilterPanel on_add={on_add_filter} on_preview={on_filter_preview} />
+                <PatternDetector channel={channel.clone()} on_detected={on_detected} />
+                <SpeechPanel
+                    channel={channel.clone()}
+                    settings={settings.speech}
+                    on_settings={on_speech_settings}
+                    on_detected={on_speech} />
+                <SilencePanel
+                    channel={channel.clone()}
+                    settings={settings.silence}
+                    on_settings={on_silence_settings}
+                    on_detected={on_silence}
+                    on_zoom={on_zoom} />
+                <DynamicsPanel channel={channel.clone()} />
+                <BitDepthPanel channel={channel.clone()} />
+                if let Signal::Stereo(left, right) = &*processed {
+                    <GoniometerPanel
+                        left={left.clone()}
+                        right={right.clone()}
+                        view={store.view.clone().unwrap_or(0..num_samples)}
+                        playhead={playhead.clone()}
+                        theme={theme} />
+                }
+                <PitchPanel
+                    channel={channel.clone()}
+                    region={store
+                        .selection
+                        .clone()
+                        .or_else(|| store.view.clone())
+                        .unwrap_or(0..channel.count())}
+                    view={store.view.clone().unwrap_or(0..channel.count())}
+                    settings={settings.pitch}
+                    on_settings={on_pitch_settings}
+                    on_track={on_pitch_track} />
+                <BandEnergyPanel
+                    channel={channel.clone()}
+                    region={store
+                        .selection
+                        .clone()
+                        .or_else(|| store.view.clone())
+                        .unwrap_or(0..channel.count())}
+                    settings={settings.bands.clone()}
+                    on_settings={on_band_settings} />
+                <LevelsPanel
+                    channel={channel.clone()}
+                    settings={settings.levels}
+                    on_settings={on_level_settings}
+                    on_levels={on_levels} />
+                <TransferPanel
+                    channels={match &*processed {
+                        Signal::Stereo(left, right) => {
+                            Some((left.clone(), right.clone(), "left channel to the right one"))
+                        }
+                        Signal::Mono(_) => comparison
+                            .clone()
+                            .map(|comparison| (channel.clone(), comparison, "file to the comparison file")),
+                    }}
+                    segment_len={spectrum_settings.segment_len}
+                    overlap={spectrum_settings.overlap}
+                    calibration={settings.calibration.clone()}
+                    settings={settings.transfer}
+                    on_settings={on_transfer_settings}
+                    on_transfer={on_transfer} />
+                <SoundLevelPanel
+                    channel={channel.slice(store.selection.clone().unwrap_or(0..channel.count()))}
+                    selected={store.selection.is_some()}
+                    settings={settings.sound_level}
+                    on_settings={on_sound_level_settings} />
+                <DecayPanel
+                    channel={channel.slice(store.selection.clone().unwrap_or(0..channel.count()))}
+                    settings={settings.decay}
+                    on_settings={on_decay_settings}
+                    on_decay={on_decay} />
+                <MetadataPanel metadata={store.metadata.clone()} />
+                <ChangeLogPanel
+                    change_log={store.change_log.clone()}
+                    session={store.session()}
+                    saved={store.saved.clone()} />
+                <MarkersPanel
+                    markers={store.markers.clone()}
+                    sample_rate={channel.sample_rate()}
+                    on_change={on_markers}
+                    on_jump={on_jump} />
+                <PresetsPanel
+                    settings={settings.clone()}
+                    file={store.source.as_ref().map(Source::name)}
+                    on_apply={on_apply_settings} />
+                { sessions_panel }
+                <MeasurementPanel on_measured={on_measured} on_loaded={on_loaded.clone()} />
+            </div>
             <ControlBoard
+                signal={(*processed).clone()}
                 on_loaded={on_loaded}
+                on_file={on_file}
+                on_open={on_open}
+                changed={store
+                    .changed
+                    .then(|| store.source.as_ref().map(Source::name))
+                    .flatten()}
+                on_reload={on_reload}
+                decimated={store
+                    .decimation
+                    .map(|decimation| decimation.notice(channel.sample_rate()))}
                 on_spectrum={on_spectrum}
-                show_spectrum={*show_spectrum} />
+                show_spectrum={show_spectrum}
+                zoomed={store.view.is_some()}
+                on_reset_zoom={on_reset_zoom}
+                can_undo={!store.undo.is_empty()}
+                can_redo={!store.redo.is_empty()}
+                on_undo={on_action.reform(|_| AppAction::Undo)}
+                on_redo={on_action.reform(|_| AppAction::Redo)}
+                channel={channel_index}
+                on_channel={on_channel}
+                session={store.session()}
+                on_export_session={on_export_session}
+                on_import_session={on_import_session}
+                playing={*playback == Playback::Playing}
+                on_play={on_action.reform(|_| AppAction::TogglePlayback)}
+                selected={store.selection.is_some()}
+                looping={player.borrow().as_ref().is_some_and(Player::is_looping)}
+                on_loop={on_action.reform(|_| AppAction::ToggleLoop)}
+                amplitude_unit={settings.amplitude_unit}
+                on_amplitude_unit={on_amplitude_unit}
+                time_format={settings.time_format}
+                on_time_format={on_time_format}
+                theme={*theme_name}
+                on_theme={on_theme}
+                on_clear_session={on_clear_session}>
+                if let Some((name, size, sample_rate)) = (*large_file_prompt).clone() {
+                    <LargeFilePrompt
+                        name={name}
+                        size={size}
+                        sample_rate={sample_rate}
+                        on_open={on_large_file}
+                        on_analyse_on_server={on_analyse_on_server}
+                        on_cancel={on_cancel_large_file} />
+                }
+                { server_job_notice }
+                if let Some((name, size)) = (*raw_file_prompt).clone() {
+                    <RawImportPrompt
+                        name={name}
+                        size={size}
+                        spec={raw_spec.borrow().unwrap_or_default()}
+                        on_open={on_raw_file}
+                        on_cancel={on_cancel_raw_file} />
+                }
+                if let Some(layout) = store.signal().layout() {
+                    <SampleLayoutSelect
+                        layout={layout}
+                        declared={store.declared.as_ref().and_then(Signal::layout)}
+                        on_layout={on_layout} />
+                }
+            </ControlBoard>
+            if let Some(curve) = (*levels).clone() {
+                <LevelView curve={curve} view={store.view.clone().unwrap_or(0..num_samples)} />
+            }
+            if let Some(track) = (*pitch_track).clone() {
+                <PitchTrackView
+                    track={track}
+                    view={store.view.clone().unwrap_or(0..num_samples)} />
+            }
             <SignalView
-                channel={channel.clone()}
-                mini={*show_spectrum} />
-            <SpectrumView spectrum={(*spectrum).clone()} show={*show_spectrum} />
+                channel={displayed_channel}
+                mini={show_spectrum}
+                regions={regions}
+                overlays={channel_overlays}
+                view={store.view.clone()}
+                markers={store.markers.clone()}
+                on_add_marker={on_add_marker}
+                selection={store.selection.clone()}
+                on_select={on_select}
+                theme={theme}
+                unit={settings.amplitude_unit}
+                time_format={settings.time_format} />
+            if let Some(ltas) = (*ltas).clone() {
+                <LtasView ltas={ltas} />
+            } else if let Some(transfer) = (*transfer).clone().filter(|_| show_spectrum) {
+                <TransferView
+                    transfer={transfer}
+                    show_phase={settings.transfer.show_phase}
+                    show_coherence={settings.transfer.show_coherence} />
+            } else if let Some(decay) = (*decay).clone().filter(|_| show_spectrum) {
+                <DecayView decay={decay} range_db={settings.decay.range_db} />
+            } else if show_spectrum && spectrum_settings.waterfall {
+                <WaterfallView
+                    channel={channel.clone()}
+                    view={store.view.clone().unwrap_or(0..num_samples)}
+                    playhead={playhead}
+                    gpu={spectrum_settings.gpu}
+                    worker={spectrum_settings.worker} />
+            } else {
+                <SpectrumView
+                    spectrum={displayed_spectrum}
+                    show={show_spectrum}
+                    overlays={spectrum_overlays}
+                    difference={stereo_difference}
+                    confidence={confidence}
+                    max_points={settings.spectrum.max_points}
+                    hold={spectrum_settings.hold}
+                    hold_reset={*hold_reset}
+                    cursors={spectrum_settings.cursors.then_some(*spectrum_cursors)}
+                    on_cursors={on_spectrum_cursors}
+                    cursor_band_dbfs={cursor_band_dbfs}
+                    harmonics={spectrum_settings.harmonics.then_some(spectrum_settings.harmonic_count)}
+                    fundamental={*harmonic_fundamental}
+                    on_fundamental={on_harmonic_fundamental}
+                    response={filter_preview.map(|design| design.coefficients(channel.sample_rate()))} />
+            }
+            if *palette_open {
+                <CommandPalette on_action={on_action} on_close={on_close_palette} />
+            }
         </div>
     }
 }
 
+// Downloads `region` of the signal as a WAV file, with its edges treated as the settings say and
+// with the markers within it.
+fn export_selection(
+    signal: &Signal,
+    region: Range<usize>,
+    markers: &[Marker],
+    settings: ExportSettings,
+) -> Result<(), JsValue> {
+    let sample_rate = f64::from(signal.channel(0).sample_rate());
+    let fade = (settings.fade_ms / 1000.0 * sample_rate).round() as usize;
+    let snippet = signal.map_channels(|channel| match settings.edges {
+        SelectionEdges::Cut => channel.slice(region.clone()),
+        SelectionEdges::Fade => channel.slice(region.clone()).faded(fade),
+        SelectionEdges::LoopCrossfade => channel.loop_crossfaded(region.clone(), fade),
+    });
+    let markers: Vec<Marker> = markers
+        .iter()
+        .filter(|marker| region.contains(&marker.position))
+        .map(|marker| Marker {
+            position: marker.position - region.start,
+            ..marker.clone()
+        })
+        .collect();
+
+    let mut data = snippet.to_wav().map_err(|error| error.to_string())?;
+    riff::append_cue_points(&mut data, &markers::to_cue_points(&markers));
+    fs_access::download("selection.wav", "audio/wav", &data)
+}
+
+// Downloads the report and also makes it available to test scripts as
+// `window.signalInspectorReport`.
+fn export_report(report: &serde_json::Value) -> Result<(), JsValue> {
+    let json = serde_json::to_string_pretty(report).map_err(|error| error.to_string())?;
+    js_sys::Reflect::set(
+        &gloo::utils::window(),
+        &"signalInspectorReport".into(),
+        &json.as_str().into(),
+    )?;
+    fs_access::download("analysis.json", "application/json", json.as_bytes())
+}
+
+fn register_service_worker() {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let navigator = window.navigator();
+    if !js_sys::Reflect::has(&navigator, &"serviceWorker".into()).unwrap_or(false) {
+        return;
+    }
+
+    let registration = navigator.service_worker().register("sw.js");
+    spawn_local(async move {
+        if let Err(error) = wasm_bindgen_futures::JsFuture::from(registration).await {
+            log(&format!("failed to register service worker: {error:?}"));
+        }
+    });
+}
+
 fn main() {
-    yew::Renderer::<App>::new().render();
+    // The same module runs in the background worker, where it only serves requests.
+    if worker::is_worker() {
+        worker::serve();
+        return;
+    }
+
+    // Pages embedding viewers only get those, without the app or its service worker.
+    let embeds = embed::elements();
+    if !embeds.is_empty() {
+        for element in embeds {
+            let props = embed::EmbedProps::from_element(&element);
+            if let Err(error) = props.theme.theme().apply(element.unchecked_ref()) {
+                log(&format!("failed to apply theme: {error:?}"));
+            }
+            yew::Renderer::<embed::Embed>::with_root_and_props(element, props).render();
+        }
+        return;
+    }
+
+    // Applied before rendering, so that the page does not flash in the default theme.
+    if let Err(error) = ThemeName::load().theme().apply_to_document() {
+        log(&format!("failed to apply theme: {error:?}"));
+    }
+    register_service_worker();
+    yew::Renderer::<Root>::new().render();
 }
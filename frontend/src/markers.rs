@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::riff::{CuePoint, Metadata};
+
+// A named position in the signal, in samples, so that it is independent of the zoom and of which
+// channel is shown.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Marker {
+    pub position: usize,
+    pub label: String,
+}
+
+// Markers at the cue points of a file, for a signal opened from it at `sample_rate`, which
+// differs from that of the file if it was opened decimated.
+pub fn from_cue_points(metadata: &Metadata, sample_rate: u32) -> Vec<Marker> {
+    let scale = metadata.format.as_ref().map_or(1.0, |format| {
+        f64::from(sample_rate) / f64::from(format.sample_rate.max(1))
+    });
+    let mut markers: Vec<Marker> = metadata
+        .cue_points
+        .iter()
+        .map(|cue_point| Marker {
+            position: (f64::from(cue_point.position) * scale).round() as usize,
+            label: cue_point
+                .label
+                .clone()
+                .unwrap_or_else(|| format!("Cue {}", cue_point.id)),
+        })
+        .collect();
+    markers.sort_by_key(|marker| marker.position);
+    markers
+}
+
+// Cue points for writing the markers into an exported file.
+pub fn to_cue_points(markers: &[Marker]) -> Vec<CuePoint> {
+    markers
+        .iter()
+        .zip(1..)
+        .map(|(marker, id)| CuePoint {
+            id,
+            position: marker.position.try_into().unwrap_or(u32::MAX),
+            label: Some(marker.label.clone()),
+        })
+        .collect()
+}
+
+// Inserts a marker, keeping the markers sorted by position.
+pub fn insert(markers: &[Marker], marker: Marker) -> Vec<Marker> {
+    let mut markers = markers.to_vec();
+    let index = markers.partition_point(|m| m.position <= marker.position);
+    markers.insert(index, marker);
+    markers
+}
+
+// The first marker after `position`.
+pub fn next(markers: &[Marker], position: usize) -> Option<&Marker> {
+    markers.iter().find(|marker| marker.position > position)
+}
+
+// The last marker before `position`.
+pub fn previous(markers: &[Marker], position: usize) -> Option<&Marker> {
+    markers
+        .iter()
+        .rev()
+        .find(|marker| marker.position < position)
+}
@@ -1,9 +1,78 @@
-use std::{io::Cursor, ops::Deref};
+use std::{
+    io::Cursor,
+    ops::{Deref, Range},
+};
 
-use hound::{SampleFormat, WavReader, WavSpec};
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
 use im::{vector::Iter, Vector};
 use rustfft::{num_complex::Complex, FftPlanner};
 
+mod aiff;
+pub mod band_energy;
+pub mod biquad;
+pub mod bits;
+mod cache;
+pub mod calibration;
+pub mod correlation;
+pub mod cursors;
+pub mod decay;
+pub mod decimation;
+pub mod fades;
+pub mod generator;
+#[cfg(test)]
+mod golden;
+mod hold;
+pub mod impulse_response;
+pub mod layout;
+pub mod levels;
+pub mod loudness;
+pub mod ltas;
+pub mod metrics;
+pub mod noise;
+pub mod null_test;
+pub mod peaks;
+pub mod pitch;
+pub mod processing;
+pub mod raw;
+pub mod recommendation;
+pub mod resample;
+pub mod response;
+pub mod riff;
+pub mod silence;
+pub mod smoothing;
+pub mod sound_level;
+pub mod stats;
+pub mod stereo;
+pub mod ticks;
+mod tilt;
+pub mod transfer;
+pub mod true_peak;
+pub mod vad;
+pub mod waterfall;
+pub mod welch;
+mod wide;
+
+// Sample rates above this are taken to be a corrupt header.
+const MAX_SAMPLE_RATE: u32 = 1_536_000;
+
+// Bounds on the files that are decoded, so that corrupt or hostile headers are rejected before
+// anything is allocated for them. Embedders can tighten them to the files they expect.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WavLimits {
+    pub max_bytes: usize,
+    // In seconds.
+    pub max_duration: f64,
+}
+
+impl Default for WavLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: 1 << 31,
+            max_duration: 4.0 * 60.0 * 60.0,
+        }
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub enum Signal {
     Mono(Channel),
@@ -11,16 +80,107 @@ pub enum Signal {
 }
 
 impl Signal {
+    // AIFF files are recognized by their header and decoded as well.
     pub fn from_wav(data: Vec<u8>) -> Result<Self, hound::Error> {
+        Self::from_wav_with_limits(data, WavLimits::default())
+    }
+
+    // Decodes a WAV file, returning an error rather than panicking for anything malformed.
+    pub fn from_wav_with_limits(data: Vec<u8>, limits: WavLimits) -> Result<Self, hound::Error> {
+        Self::decode(data, limits, 1)
+    }
+
+    // The sample rate from the header, without decoding the samples.
+    pub fn peek_sample_rate(data: &[u8]) -> Result<u32, hound::Error> {
+        if let Some(sample_rate) = wide::sample_rate(data) {
+            return Ok(sample_rate);
+        }
+        if aiff::is_aiff(data) {
+            return aiff::sample_rate(data).ok_or(hound::Error::FormatError("invalid AIFF header"));
+        }
+        Ok(WavReader::new(data)?.spec().sample_rate)
+    }
+
+    // Keeps every `step`th frame only. The sample rate is divided by `step`, so it should be a
+    // divisor of it for the time axis to stay exact.
+    fn decode(data: Vec<u8>, limits: WavLimits, step: usize) -> Result<Self, hound::Error> {
+        if data.len() > limits.max_bytes {
+            return Err(hound::Error::FormatError("file exceeds the maximum size"));
+        }
+        if wide::is_wide(&data) {
+            return wide::decode(&data, limits, step);
+        }
+        if aiff::is_aiff(&data) {
+            return aiff::decode(&data, limits, step);
+        }
+        let file_len = data.len();
         let reader = WavReader::new(Cursor::new(data))?;
         let spec = reader.spec();
 
-        if spec.channels == 1 {
-            Self::read_into_mono(reader, spec)
-        } else if spec.channels == 2 {
-            Self::read_into_stereo(reader, spec)
-        } else {
-            panic!("unsupported number of channels: {}", spec.channels);
+        if spec.sample_rate == 0 || spec.sample_rate > MAX_SAMPLE_RATE {
+            return Err(hound::Error::FormatError("invalid sample rate"));
+        }
+        // The declared size of the sample data is checked against what is actually there, as it
+        // is used to reserve memory for the samples.
+        let declared_bytes = u64::from(reader.len()) * u64::from(spec.bits_per_sample).div_ceil(8);
+        if declared_bytes > file_len as u64 {
+            return Err(hound::Error::FormatError("data chunk exceeds the file"));
+        }
+        if f64::from(reader.duration()) / f64::from(spec.sample_rate) > limits.max_duration {
+            return Err(hound::Error::FormatError(
+                "file exceeds the maximum duration",
+            ));
+        }
+
+        let step = step.max(1);
+        let spec = WavSpec {
+            sample_rate: (spec.sample_rate / step as u32).max(1),
+            ..spec
+        };
+        match spec.channels {
+            1 => Self::read_into_mono(reader, spec, step),
+            2 => Self::read_into_stereo(reader, spec, step),
+            _ => Err(hound::Error::Unsupported),
+        }
+    }
+
+    pub fn to_wav(&self) -> Result<Vec<u8>, hound::Error> {
+        let first = self.channel(0);
+        if first.bytes_per_sample() > 4 {
+            return wide::encode(self);
+        }
+        let spec = WavSpec {
+            channels: self.num_channels() as u16,
+            sample_rate: first.sample_rate,
+            bits_per_sample: first.bits_per_sample,
+            sample_format: first.sample_format,
+        };
+
+        let mut data = Cursor::new(Vec::new());
+        let mut writer = WavWriter::new(&mut data, spec)?;
+        let mut write = |sample| match sample {
+            Sample::Int8(n) => writer.write_sample(n),
+            Sample::Int16(n) => writer.write_sample(n),
+            Sample::Int32(n) => writer.write_sample(n),
+            Sample::Float32(n) => writer.write_sample(n),
+            Sample::Int64(_) | Sample::Float64(_) => unreachable!("written by `wide::encode`"),
+        };
+        match self {
+            Self::Mono(channel) => channel.iter().try_for_each(&mut write)?,
+            Self::Stereo(left, right) => left
+                .iter()
+                .zip(right.iter())
+                .try_for_each(|(l, r)| write(l).and_then(|_| write(r)))?,
+        }
+        writer.finalize()?;
+
+        Ok(data.into_inner())
+    }
+
+    pub fn num_channels(&self) -> usize {
+        match self {
+            Self::Mono(_) => 1,
+            Self::Stereo(..) => 2,
         }
     }
 
@@ -32,16 +192,26 @@ impl Signal {
         }
     }
 
+    pub fn map_channels(&self, mut f: impl FnMut(&Channel) -> Channel) -> Self {
+        match self {
+            Self::Mono(channel) => Self::Mono(f(channel)),
+            Self::Stereo(left, right) => Self::Stereo(f(left), f(right)),
+        }
+    }
+
     fn read_into_mono(
         reader: WavReader<Cursor<Vec<u8>>>,
         spec: WavSpec,
+        step: usize,
     ) -> Result<Self, hound::Error> {
         macro_rules! collect_samples {
             ($type:ty, $fn:ident) => {{
-                let mut data = Vec::new();
-                for result in reader.into_samples::<$type>() {
+                let mut data = Vec::with_capacity((reader.len() as usize).div_ceil(step));
+                for (n, result) in reader.into_samples::<$type>().enumerate() {
                     let sample = result?;
-                    data.push(sample);
+                    if n % step == 0 {
+                        data.push(sample);
+                    }
                 }
                 Channel::$fn(data, spec.bits_per_sample, spec.sample_rate)
             }};
@@ -52,7 +222,7 @@ impl Signal {
             (SampleFormat::Int, 9..=16) => collect_samples!(i16, from_samples_i16),
             (SampleFormat::Int, 17..=32) => collect_samples!(i32, from_samples_i32),
             (SampleFormat::Float, 1..=32) => collect_samples!(f32, from_samples_f32),
-            _ => panic!("unsupported format"),
+            _ => return Err(hound::Error::Unsupported),
         };
 
         Ok(Self::Mono(channel))
@@ -61,21 +231,24 @@ impl Signal {
     fn read_into_stereo(
         reader: WavReader<Cursor<Vec<u8>>>,
         spec: WavSpec,
+        step: usize,
     ) -> Result<Self, hound::Error> {
         macro_rules! collect_samples {
             ($type:ty, $fn:ident) => {{
-                let mut left = Vec::new();
-                let mut right = Vec::new();
+                let frames = (reader.duration() as usize).div_ceil(step);
+                let mut left = Vec::with_capacity(frames);
+                let mut right = Vec::with_capacity(frames);
 
-                let mut is_left = true;
-                for result in reader.into_samples::<$type>() {
+                for (n, result) in reader.into_samples::<$type>().enumerate() {
                     let sample = result?;
-                    if is_left {
+                    if (n / 2) % step != 0 {
+                        continue;
+                    }
+                    if n % 2 == 0 {
                         left.push(sample);
                     } else {
                         right.push(sample);
                     }
-                    is_left = !is_left;
                 }
 
                 (
@@ -90,7 +263,7 @@ impl Signal {
             (SampleFormat::Int, 9..=16) => collect_samples!(i16, from_samples_i16),
             (SampleFormat::Int, 17..=32) => collect_samples!(i32, from_samples_i32),
             (SampleFormat::Float, 1..=32) => collect_samples!(f32, from_samples_f32),
-            _ => panic!("unsupported format"),
+            _ => return Err(hound::Error::Unsupported),
         };
 
         Ok(Self::Stereo(left_channel, right_channel))
@@ -99,6 +272,8 @@ impl Signal {
 
 #[derive(Clone, PartialEq)]
 pub struct Channel {
+    // Samples are stored little-endian whatever the byte order of the file or the architecture, so
+    // that the same bytes mean the same samples everywhere.
     data: Vector<u8>,
     bits_per_sample: u16,
     sample_format: SampleFormat,
@@ -117,7 +292,7 @@ impl Channel {
         );
 
         Self {
-            data: samples.into_iter().flat_map(i8::to_ne_bytes).collect(),
+            data: samples.into_iter().flat_map(i8::to_le_bytes).collect(),
             bits_per_sample,
             sample_format: SampleFormat::Int,
             sample_rate,
@@ -135,7 +310,7 @@ impl Channel {
         );
 
         Self {
-            data: samples.into_iter().flat_map(i16::to_ne_bytes).collect(),
+            data: samples.into_iter().flat_map(i16::to_le_bytes).collect(),
             bits_per_sample,
             sample_format: SampleFormat::Int,
             sample_rate,
@@ -153,7 +328,25 @@ impl Channel {
         );
 
         Self {
-            data: samples.into_iter().flat_map(i32::to_ne_bytes).collect(),
+            data: samples.into_iter().flat_map(i32::to_le_bytes).collect(),
+            bits_per_sample,
+            sample_format: SampleFormat::Int,
+            sample_rate,
+        }
+    }
+
+    pub fn from_samples_i64(
+        samples: impl IntoIterator<Item = i64>,
+        bits_per_sample: u16,
+        sample_rate: u32,
+    ) -> Self {
+        assert!(
+            (33..=64).contains(&bits_per_sample),
+            "unsupported number of bits per sample: {bits_per_sample}",
+        );
+
+        Self {
+            data: samples.into_iter().flat_map(i64::to_le_bytes).collect(),
             bits_per_sample,
             sample_format: SampleFormat::Int,
             sample_rate,
@@ -171,37 +364,58 @@ impl Channel {
         );
 
         Self {
-            data: samples.into_iter().flat_map(f32::to_ne_bytes).collect(),
+            data: samples.into_iter().flat_map(f32::to_le_bytes).collect(),
             bits_per_sample,
             sample_format: SampleFormat::Float,
             sample_rate,
         }
     }
 
-    pub fn sample_rate(&self) -> u32 {
-        self.sample_rate
-    }
+    pub fn from_samples_f64(
+        samples: impl IntoIterator<Item = f64>,
+        bits_per_sample: u16,
+        sample_rate: u32,
+    ) -> Self {
+        assert!(
+            (33..=64).contains(&bits_per_sample),
+            "unsupported number of bits per sample: {bits_per_sample}",
+        );
 
-    pub fn lower_bound(&self) -> Sample {
-        match (self.sample_format, self.bytes_per_sample()) {
-            (SampleFormat::Int, 1) => Sample::Int8(i8::MIN),
-            (SampleFormat::Int, 2) => Sample::Int16(i16::MIN),
-            (SampleFormat::Int, 3..=4) => Sample::Int32(i32::MIN),
-            (SampleFormat::Float, 1..=4) => Sample::Float32(f32::MIN),
-            _ => unreachable!(),
+        Self {
+            data: samples.into_iter().flat_map(f64::to_le_bytes).collect(),
+            bits_per_sample,
+            sample_format: SampleFormat::Float,
+            sample_rate,
         }
     }
 
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
     pub fn upper_bound(&self) -> Sample {
         match (self.sample_format, self.bytes_per_sample()) {
             (SampleFormat::Int, 1) => Sample::Int8(i8::MAX),
             (SampleFormat::Int, 2) => Sample::Int16(i16::MAX),
             (SampleFormat::Int, 3..=4) => Sample::Int32(i32::MAX),
-            (SampleFormat::Float, 1..=4) => Sample::Float32(f32::MAX),
+            (SampleFormat::Int, 5..=8) => Sample::Int64(i64::MAX),
+            // Floats are at full scale at ±1.0, as in WAV files and the analysis crate.
+            (SampleFormat::Float, 1..=4) => Sample::Float32(1.0),
+            (SampleFormat::Float, 5..=8) => Sample::Float64(1.0),
             _ => unreachable!(),
         }
     }
 
+    pub fn full_scale(&self) -> f64 {
+        f64::from(self.upper_bound())
+    }
+
+    pub fn iter_normalized(&self) -> impl Iterator<Item = f64> + '_ {
+        let full_scale = self.full_scale();
+        self.iter()
+            .map(move |sample| f64::from(sample) / full_scale)
+    }
+
     pub fn min(&self) -> Sample {
         self.iter().min().unwrap_or_else(|| self.upper_bound())
     }
@@ -211,10 +425,24 @@ impl Channel {
     }
 
     pub fn count(&self) -> usize {
-        self.data.len() / self.bytes_per_sample() as usize
+        self.data.len() / self.stride()
     }
 
-    pub fn iter(&self) -> ChannelIter {
+    // A view of the samples within `range`, sharing the underlying data, so that only the part of
+    // a signal that is shown needs to be processed.
+    pub fn slice(&self, range: Range<usize>) -> Channel {
+        let end = range.end.min(self.count());
+        let start = range.start.min(end);
+        Channel {
+            data: self
+                .data
+                .skip(start * self.stride())
+                .take((end - start) * self.stride()),
+            ..self.clone()
+        }
+    }
+
+    pub fn iter(&self) -> ChannelIter<'_> {
         ChannelIter {
             inner: self.data.iter(),
             chunk_len: self.bytes_per_sample(),
@@ -223,11 +451,77 @@ impl Channel {
     }
 
     pub fn spectrum(&self) -> Spectrum {
-        Spectrum::from(self)
+        cache::spectrum(self, || Spectrum::from(self))
+    }
+
+    // The sample-wise difference over the length of the shorter channel, or `None` if the two do
+    // not share a sample rate, as their samples then do not line up.
+    pub fn difference(&self, other: &Channel) -> Option<Channel> {
+        (self.sample_rate == other.sample_rate).then(|| {
+            self.with_normalized_samples(
+                self.iter_normalized()
+                    .zip(other.iter_normalized())
+                    .map(|(a, b)| a - b),
+            )
+        })
+    }
+
+    // Subtracts the mean of the channel, removing any constant bias.
+    pub fn remove_dc(&self) -> Channel {
+        let mean = self.iter_normalized().sum::<f64>() / self.count().max(1) as f64;
+        self.with_normalized_samples(self.iter_normalized().map(|x| x - mean))
+    }
+
+    // Creates a channel with the same format and sample rate as this one, from samples relative to
+    // full scale. Values outside of the representable range are saturated.
+    pub fn with_normalized_samples(&self, samples: impl IntoIterator<Item = f64>) -> Channel {
+        let full_scale = self.full_scale();
+        let samples = samples.into_iter().map(|x| x * full_scale);
+
+        match self.upper_bound() {
+            Sample::Int8(_) => Self::from_samples_i8(
+                samples.map(|x| x.round() as i8),
+                self.bits_per_sample,
+                self.sample_rate,
+            ),
+            Sample::Int16(_) => Self::from_samples_i16(
+                samples.map(|x| x.round() as i16),
+                self.bits_per_sample,
+                self.sample_rate,
+            ),
+            Sample::Int32(_) => Self::from_samples_i32(
+                samples.map(|x| x.round() as i32),
+                self.bits_per_sample,
+                self.sample_rate,
+            ),
+            Sample::Int64(_) => Self::from_samples_i64(
+                samples.map(|x| x.round() as i64),
+                self.bits_per_sample,
+                self.sample_rate,
+            ),
+            Sample::Float32(_) => Self::from_samples_f32(
+                samples.map(|x| x as f32),
+                self.bits_per_sample,
+                self.sample_rate,
+            ),
+            Sample::Float64(_) => {
+                Self::from_samples_f64(samples, self.bits_per_sample, self.sample_rate)
+            }
+        }
     }
 
     fn bytes_per_sample(&self) -> u16 {
-        (self.bits_per_sample + 7) / 8
+        self.bits_per_sample.div_ceil(8)
+    }
+
+    // The number of bytes each sample is stored in, which for 24-bit samples is more than the
+    // number of bytes they need.
+    fn stride(&self) -> usize {
+        match (self.sample_format, self.bytes_per_sample()) {
+            (SampleFormat::Int, 3..=4) | (SampleFormat::Float, 1..=4) => 4,
+            (_, 5..=8) => 8,
+            (_, bytes) => bytes as usize,
+        }
     }
 }
 
@@ -237,6 +531,16 @@ pub struct ChannelIter<'a> {
     chunk_len: u16,
 }
 
+impl ChannelIter<'_> {
+    fn word(&mut self) -> Option<[u8; 8]> {
+        let mut bytes = [0; 8];
+        for byte in &mut bytes {
+            *byte = self.inner.next().copied()?;
+        }
+        Some(bytes)
+    }
+}
+
 impl Iterator for ChannelIter<'_> {
     type Item = Sample;
 
@@ -244,11 +548,11 @@ impl Iterator for ChannelIter<'_> {
         match (self.sample_format, self.chunk_len) {
             (SampleFormat::Int, 1) => {
                 let bytes = [self.inner.next().copied()?];
-                Some(Sample::Int8(i8::from_ne_bytes(bytes)))
+                Some(Sample::Int8(i8::from_le_bytes(bytes)))
             }
             (SampleFormat::Int, 2) => {
                 let bytes = [self.inner.next().copied()?, self.inner.next().copied()?];
-                Some(Sample::Int16(i16::from_ne_bytes(bytes)))
+                Some(Sample::Int16(i16::from_le_bytes(bytes)))
             }
             (SampleFormat::Int, 3..=4) => {
                 let bytes = [
@@ -257,8 +561,9 @@ impl Iterator for ChannelIter<'_> {
                     self.inner.next().copied()?,
                     self.inner.next().copied()?,
                 ];
-                Some(Sample::Int32(i32::from_ne_bytes(bytes)))
+                Some(Sample::Int32(i32::from_le_bytes(bytes)))
             }
+            (SampleFormat::Int, 5..=8) => Some(Sample::Int64(i64::from_le_bytes(self.word()?))),
             (SampleFormat::Float, 1..=4) => {
                 let bytes = [
                     self.inner.next().copied()?,
@@ -266,8 +571,9 @@ impl Iterator for ChannelIter<'_> {
                     self.inner.next().copied()?,
                     self.inner.next().copied()?,
                 ];
-                Some(Sample::Float32(f32::from_ne_bytes(bytes)))
+                Some(Sample::Float32(f32::from_le_bytes(bytes)))
             }
+            (SampleFormat::Float, 5..=8) => Some(Sample::Float64(f64::from_le_bytes(self.word()?))),
             _ => unreachable!(),
         }
     }
@@ -278,13 +584,18 @@ pub enum Sample {
     Int8(i8),
     Int16(i16),
     Int32(i32),
+    Int64(i64),
     Float32(f32),
+    Float64(f64),
 }
 
 impl Sample {
     pub fn is_zero(&self) -> bool {
-        matches!(self, Self::Int8(0) | Self::Int16(0) | Self::Int32(0),)
-            || matches!(self, Self::Float32(n) if *n == 0.0)
+        matches!(
+            self,
+            Self::Int8(0) | Self::Int16(0) | Self::Int32(0) | Self::Int64(0)
+        ) || matches!(self, Self::Float32(n) if *n == 0.0)
+            || matches!(self, Self::Float64(n) if *n == 0.0)
     }
 
     pub fn into_zero(self) -> Self {
@@ -292,7 +603,9 @@ impl Sample {
             Self::Int8(_) => Self::Int8(0),
             Self::Int16(_) => Self::Int16(0),
             Self::Int32(_) => Self::Int32(0),
+            Self::Int64(_) => Self::Int64(0),
             Self::Float32(_) => Self::Float32(0.0),
+            Self::Float64(_) => Self::Float64(0.0),
         }
     }
 }
@@ -311,9 +624,13 @@ impl Ord for Sample {
             (Self::Int8(left), Self::Int8(right)) => left.cmp(right),
             (Self::Int16(left), Self::Int16(right)) => left.cmp(right),
             (Self::Int32(left), Self::Int32(right)) => left.cmp(right),
+            (Self::Int64(left), Self::Int64(right)) => left.cmp(right),
             (Self::Float32(left), Self::Float32(right)) => left
                 .partial_cmp(right)
                 .unwrap_or_else(|| panic!("undefined comparison: {left} <> {right}")),
+            (Self::Float64(left), Self::Float64(right)) => left
+                .partial_cmp(right)
+                .unwrap_or_else(|| panic!("undefined comparison: {left} <> {right}")),
             (left, right) => panic!("undefined comparison: {left:?} <> {right:?}"),
         }
     }
@@ -325,7 +642,9 @@ impl From<Sample> for f64 {
             Sample::Int8(n) => n as f64,
             Sample::Int16(n) => n as f64,
             Sample::Int32(n) => n as f64,
+            Sample::Int64(n) => n as f64,
             Sample::Float32(n) => n as f64,
+            Sample::Float64(n) => n,
         }
     }
 }
@@ -335,6 +654,7 @@ pub struct Spectrum {
     transform: Vector<Complex<f64>>,
     sample_rate: u32,
     num_samples: usize,
+    full_scale: f64,
 }
 
 impl Spectrum {
@@ -349,6 +669,78 @@ impl Spectrum {
     pub fn bin_to_frequency(&self, bin: usize) -> f64 {
         bin as f64 * self.sample_rate as f64 / self.num_samples as f64
     }
+
+    pub fn frequency_to_bin(&self, frequency: f64) -> usize {
+        (frequency * self.num_samples as f64 / self.sample_rate as f64).round() as usize
+    }
+
+    // Bin magnitudes relative to a full-scale signal of the same length, which makes spectra of
+    // signals with different lengths and sample formats comparable.
+    pub fn magnitudes(&self) -> impl Iterator<Item = f64> + '_ {
+        let scale = self.num_samples as f64 * self.full_scale;
+        self.transform.iter().map(move |c| c.norm() / scale)
+    }
+
+    // Magnitude-weighted mean frequency.
+    pub fn centroid(&self) -> f64 {
+        let numerator: f64 = self
+            .magnitudes()
+            .enumerate()
+            .map(|(n, magnitude)| self.bin_to_frequency(n) * magnitude)
+            .sum();
+        let denominator: f64 = self.magnitudes().sum();
+        numerator / denominator
+    }
+
+    // The bins above DC grouped into at most `max_points` runs of adjacent bins, spread evenly over
+    // the logarithmic frequency axis the spectrum is drawn on, so that the high frequencies, where
+    // the bins are densest, are drawn with fewer points. Each bin is a run of its own when there is
+    // no limit or it is not exceeded.
+    pub fn point_ranges(&self, max_points: Option<usize>) -> Vec<Range<usize>> {
+        let len = self.len();
+        let max_points = match max_points {
+            Some(max_points) if len > max_points + 1 => max_points.max(1),
+            _ => return (1..len).map(|n| n..n + 1).collect(),
+        };
+
+        let first_log = self.bin_to_frequency(1).log10();
+        let span_log = self.bin_to_frequency(len - 1).log10() - first_log;
+        let bucket = |n: usize| {
+            let position = (self.bin_to_frequency(n).log10() - first_log) / span_log;
+            ((position * max_points as f64) as usize).min(max_points - 1)
+        };
+        let mut ranges: Vec<Range<usize>> = Vec::with_capacity(max_points);
+        for n in 1..len {
+            match ranges.last_mut() {
+                Some(range) if bucket(range.start) == bucket(n) => range.end = n + 1,
+                _ => ranges.push(n..n + 1),
+            }
+        }
+        ranges
+    }
+
+    // The difference in level of each bin, compared by frequency. Magnitudes are subtracted rather
+    // than the bins themselves, so that the phase of either does not matter, and the size of the
+    // difference is kept.
+    pub fn difference(&self, other: &Spectrum) -> Spectrum {
+        let scale = (self.num_samples as f64 * self.full_scale)
+            / (other.num_samples as f64 * other.full_scale);
+        let transform = self
+            .transform
+            .iter()
+            .enumerate()
+            .map(|(n, c)| {
+                let other_bin = other.frequency_to_bin(self.bin_to_frequency(n));
+                let other_c = other.transform.get(other_bin).copied().unwrap_or_default();
+                Complex::from((c.norm() - other_c.norm() * scale).abs())
+            })
+            .collect();
+
+        Self {
+            transform,
+            ..self.clone()
+        }
+    }
 }
 
 impl From<&Channel> for Spectrum {
@@ -368,6 +760,7 @@ impl From<&Channel> for Spectrum {
             transform: Vector::from(transform),
             sample_rate: channel.sample_rate,
             num_samples: channel.count(),
+            full_scale: channel.full_scale(),
         }
     }
 }
@@ -379,3 +772,164 @@ impl Deref for Spectrum {
         &self.transform
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use super::{
+        decimation::Decimation,
+        generator::{BitDepth, Generator},
+        Signal, Spectrum, WavLimits,
+    };
+
+    fn wav(bit_depth: BitDepth, stereo: bool) -> Vec<u8> {
+        let channel = Generator::sine(1000.0, 0.5)
+            .with_duration(0.01)
+            .with_bit_depth(bit_depth)
+            .channel();
+        let signal = if stereo {
+            Signal::Stereo(channel.clone(), channel)
+        } else {
+            Signal::Mono(channel)
+        };
+        signal.to_wav().unwrap()
+    }
+
+    // Overwrites the little-endian field at `offset` of a canonical 44-byte header.
+    fn patch(mut data: Vec<u8>, offset: usize, value: &[u8]) -> Vec<u8> {
+        data[offset..offset + value.len()].copy_from_slice(value);
+        data
+    }
+
+    #[test]
+    fn malformed_wavs_are_errors() {
+        let bases = [
+            wav(BitDepth::Int8, false),
+            wav(BitDepth::Int16, true),
+            wav(BitDepth::Int32, false),
+            wav(BitDepth::Float32, true),
+        ];
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut random = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for i in 0..4000 {
+            let mut data = bases[i % bases.len()].clone();
+            // Mostly the header, where the sizes and the format are.
+            for _ in 0..=random() % 4 {
+                let offset = (random() % 64) as usize % data.len();
+                data[offset] = random() as u8;
+            }
+            if random() % 4 == 0 {
+                data.truncate((random() as usize) % data.len());
+            }
+
+            let result = panic::catch_unwind(|| Signal::from_wav(data.clone()).map(drop));
+            assert!(result.is_ok(), "panicked on {data:02x?}");
+        }
+    }
+
+    #[test]
+    fn absurd_headers_are_rejected() {
+        let data = wav(BitDepth::Int16, false);
+        // Data chunk size, sample rate, channel count and format tag.
+        for (offset, value) in [
+            (40, &u32::MAX.to_le_bytes()[..]),
+            (24, &0_u32.to_le_bytes()),
+            (24, &u32::MAX.to_le_bytes()),
+            (22, &3_u16.to_le_bytes()),
+            (20, &2_u16.to_le_bytes()),
+        ] {
+            assert!(Signal::from_wav(patch(data.clone(), offset, value)).is_err());
+        }
+        assert!(Signal::from_wav(data[..data.len() - 3].to_vec()).is_err());
+        assert!(Signal::from_wav(data).is_ok());
+    }
+
+    #[test]
+    fn limits_are_enforced() {
+        let data = wav(BitDepth::Int16, false);
+        let limits = WavLimits::default();
+        for limits in [
+            WavLimits {
+                max_bytes: data.len() - 1,
+                ..limits
+            },
+            WavLimits {
+                max_duration: 0.005,
+                ..limits
+            },
+        ] {
+            assert!(Signal::from_wav_with_limits(data.clone(), limits).is_err());
+        }
+    }
+
+    #[test]
+    fn decimation_keeps_every_nth_frame() {
+        let channel = Generator::white_noise(0.5)
+            .with_duration(0.01)
+            .with_bit_depth(BitDepth::Int16)
+            .channel();
+        let len = channel.count();
+        let signal = Signal::Stereo(channel.slice(0..len - 1), channel.slice(1..len));
+        let data = signal.to_wav().unwrap();
+        assert_eq!(Signal::peek_sample_rate(&data).unwrap(), 48000);
+
+        let decimated =
+            Signal::from_wav_decimated(data, WavLimits::default(), Decimation::KeepEvery(3))
+                .unwrap();
+        for n in 0..2 {
+            let original = signal.channel(n);
+            let kept = decimated.channel(n);
+            assert_eq!(kept.sample_rate(), 16000);
+            assert_eq!(kept.count(), original.count().div_ceil(3));
+            assert!(kept.iter().eq(original.iter().step_by(3)));
+        }
+    }
+
+    #[test]
+    fn point_ranges_cover_every_bin_within_the_limit() {
+        let channel = Generator::white_noise(0.5)
+            .with_duration(0.1)
+            .with_bit_depth(BitDepth::Int16)
+            .channel();
+        let spectrum = Spectrum::from(&channel);
+        let len = spectrum.len();
+
+        assert_eq!(spectrum.point_ranges(None).len(), len - 1);
+        assert_eq!(spectrum.point_ranges(Some(len)).len(), len - 1);
+        for max_points in [1, 100, 1000] {
+            let ranges = spectrum.point_ranges(Some(max_points));
+            assert!(ranges.len() <= max_points);
+            assert_eq!(ranges[0].start, 1);
+            assert_eq!(ranges[ranges.len() - 1].end, len);
+            assert!(ranges.windows(2).all(|pair| pair[0].end == pair[1].start));
+        }
+    }
+
+    #[test]
+    fn differences_need_matching_rates_and_ignore_phase() {
+        let generator = Generator::sine(1000.0, 0.5).with_duration(1.1);
+        let long = generator.channel();
+        let a = long.slice(0..48000);
+        let b = long.slice(3..48003);
+        let difference = a.difference(&b).expect("the rates match");
+        assert_eq!(difference.count(), 48000);
+        assert!(difference.statistics().peak_dbfs > -30.0);
+
+        // The same tone a few samples later has the same level in every bin.
+        let spectrum = Spectrum::from(&a);
+        let peak = spectrum.magnitudes().fold(0.0, f64::max);
+        let delta = spectrum.difference(&Spectrum::from(&b));
+        assert!(delta.magnitudes().all(|magnitude| magnitude < peak * 1e-6));
+
+        let other_rate = generator.with_sample_rate(44100).channel();
+        assert!(a.difference(&other_rate).is_none());
+        assert_eq!(a.difference(&b.slice(0..100)).map(|d| d.count()), Some(100));
+    }
+}
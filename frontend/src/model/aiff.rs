@@ -0,0 +1,245 @@
+// Reads AIFF and AIFF-C files, whose samples are big-endian unless an AIFF-C compression type says
+// otherwise. Only uncompressed integer and floating-point samples are supported.
+
+use super::{Channel, Signal, WavLimits, MAX_SAMPLE_RATE};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    BigEndian,
+    LittleEndian,
+    Float,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Common {
+    channels: u16,
+    frames: u32,
+    bits_per_sample: u16,
+    sample_rate: u32,
+    encoding: Encoding,
+}
+
+pub(super) fn is_aiff(data: &[u8]) -> bool {
+    data.get(0..4) == Some(b"FORM") && matches!(data.get(8..12), Some(b"AIFF" | b"AIFC"))
+}
+
+pub(super) fn sample_rate(data: &[u8]) -> Option<u32> {
+    read(data).ok().map(|(common, _)| common.sample_rate)
+}
+
+// Keeps every `step`th frame only, like decoding WAV files.
+pub(super) fn decode(data: &[u8], limits: WavLimits, step: usize) -> Result<Signal, hound::Error> {
+    let (common, samples) = read(data)?;
+    if common.sample_rate == 0 || common.sample_rate > MAX_SAMPLE_RATE {
+        return Err(hound::Error::FormatError("invalid sample rate"));
+    }
+    let channels = usize::from(common.channels);
+    if !(1..=2).contains(&channels) {
+        return Err(hound::Error::Unsupported);
+    }
+    let width = match (common.encoding, common.bits_per_sample) {
+        (Encoding::Float, 32 | 64)
+        | (Encoding::BigEndian | Encoding::LittleEndian, 1..=32 | 64) => {
+            usize::from(common.bits_per_sample).div_ceil(8)
+        }
+        _ => return Err(hound::Error::Unsupported),
+    };
+    // The frame count in the header is trusted only as far as the samples are there.
+    let frames = (common.frames as usize).min(samples.len() / (width * channels));
+    if frames as f64 / f64::from(common.sample_rate) > limits.max_duration {
+        return Err(hound::Error::FormatError(
+            "file exceeds the maximum duration",
+        ));
+    }
+
+    let step = step.max(1);
+    let sample_rate = (common.sample_rate / step as u32).max(1);
+    let channel = |n: usize| {
+        let samples = samples
+            .chunks_exact(width)
+            .skip(n)
+            .step_by(channels * step)
+            .take(frames.div_ceil(step));
+        read_channel(samples, &common, sample_rate)
+    };
+
+    Ok(match channels {
+        1 => Signal::Mono(channel(0)),
+        _ => Signal::Stereo(channel(0), channel(1)),
+    })
+}
+
+fn read_channel<'a>(
+    samples: impl Iterator<Item = &'a [u8]>,
+    common: &Common,
+    sample_rate: u32,
+) -> Channel {
+    // Samples are widened to eight bytes with the sample in the most significant ones, where AIFF
+    // also puts samples narrower than their container.
+    let encoding = common.encoding;
+    let read = move |bytes: &[u8]| {
+        let mut word = [0; 8];
+        let sample = &mut word[..bytes.len()];
+        sample.copy_from_slice(bytes);
+        if encoding == Encoding::LittleEndian {
+            sample.reverse();
+        }
+        u64::from_be_bytes(word)
+    };
+    let bits = common.bits_per_sample;
+    // Shifts a sample to the least significant bits, keeping its sign.
+    let integer = move |bytes: &[u8]| read(bytes) as i64 >> (64 - bits);
+
+    match (encoding, bits) {
+        (Encoding::Float, 32) => Channel::from_samples_f32(
+            samples.map(|s| f32::from_bits((read(s) >> 32) as u32)),
+            32,
+            sample_rate,
+        ),
+        (Encoding::Float, _) => {
+            Channel::from_samples_f64(samples.map(|s| f64::from_bits(read(s))), 64, sample_rate)
+        }
+        (_, 1..=8) => {
+            Channel::from_samples_i8(samples.map(|s| integer(s) as i8), bits, sample_rate)
+        }
+        (_, 9..=16) => {
+            Channel::from_samples_i16(samples.map(|s| integer(s) as i16), bits, sample_rate)
+        }
+        (_, 17..=32) => {
+            Channel::from_samples_i32(samples.map(|s| integer(s) as i32), bits, sample_rate)
+        }
+        _ => Channel::from_samples_i64(samples.map(integer), bits, sample_rate),
+    }
+}
+
+fn read(data: &[u8]) -> Result<(Common, &[u8]), hound::Error> {
+    if !is_aiff(data) {
+        return Err(hound::Error::FormatError("no FORM AIFF header"));
+    }
+    let compressed = &data[8..12] == b"AIFC";
+
+    let mut common = None;
+    let mut samples = None;
+    for (id, body) in chunks(&data[12..]) {
+        match &id {
+            b"COMM" => common = parse_common(body, compressed),
+            b"SSND" => {
+                // The samples start after an offset, which is normally zero.
+                let offset = u32_at(body, 0).map_or(0, |offset| offset as usize);
+                samples = body.get(8 + offset..);
+            }
+            _ => {}
+        }
+    }
+    let common = common.ok_or(hound::Error::FormatError(
+        "missing or unsupported COMM chunk",
+    ))?;
+    Ok((common, samples.unwrap_or_default()))
+}
+
+fn parse_common(body: &[u8], compressed: bool) -> Option<Common> {
+    let encoding = if compressed {
+        match body.get(18..22)? {
+            b"NONE" | b"twos" | b"in24" | b"in32" => Encoding::BigEndian,
+            b"sowt" => Encoding::LittleEndian,
+            b"fl32" | b"FL32" | b"fl64" | b"FL64" => Encoding::Float,
+            _ => return None,
+        }
+    } else {
+        Encoding::BigEndian
+    };
+    Some(Common {
+        channels: u16_at(body, 0)?,
+        frames: u32_at(body, 2)?,
+        bits_per_sample: u16_at(body, 6)?,
+        sample_rate: extended(body.get(8..18)?.try_into().ok()?) as u32,
+        encoding,
+    })
+}
+
+// The 80-bit extended precision float the sample rate is given as.
+fn extended(bytes: [u8; 10]) -> f64 {
+    let exponent = i32::from(u16::from_be_bytes([bytes[0], bytes[1]]) & 0x7fff);
+    let mantissa = u64::from_be_bytes(bytes[2..].try_into().unwrap_or_default());
+    let value = mantissa as f64 * 2_f64.powi(exponent - 16383 - 63);
+    if bytes[0] & 0x80 != 0 {
+        -value
+    } else {
+        value
+    }
+}
+
+fn chunks(mut data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    std::iter::from_fn(move || {
+        let id: [u8; 4] = data.get(0..4)?.try_into().ok()?;
+        let size = u32_at(data, 4)? as usize;
+        // The sample data of a truncated file is kept as far as it goes.
+        let body = data.get(8..)?;
+        let body = &body[..size.min(body.len())];
+        // Chunks are padded to an even number of bytes.
+        data = data
+            .get(8_usize.saturating_add(size).saturating_add(size % 2)..)
+            .unwrap_or_default();
+        Some((id, body))
+    })
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{Channel, Signal};
+
+    fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut chunk = id.to_vec();
+        chunk.extend((body.len() as u32).to_be_bytes());
+        chunk.extend(body);
+        if body.len() % 2 == 1 {
+            chunk.push(0);
+        }
+        chunk
+    }
+
+    fn file(form: &[u8; 4], common: &[u8], samples: &[u8]) -> Vec<u8> {
+        let ssnd = [vec![0; 8], samples.to_vec()].concat();
+        let chunks = [form.to_vec(), chunk(b"COMM", common), chunk(b"SSND", &ssnd)].concat();
+        [
+            b"FORM".to_vec(),
+            (chunks.len() as u32).to_be_bytes().to_vec(),
+            chunks,
+        ]
+        .concat()
+    }
+
+    // 44100 Hz as an 80-bit extended float.
+    const RATE: [u8; 10] = [0x40, 0x0e, 0xac, 0x44, 0, 0, 0, 0, 0, 0];
+
+    #[test]
+    fn samples_are_read() {
+        // Stereo 24-bit samples of -2 and 1, big-endian.
+        let common = [&[0, 2, 0, 0, 0, 1, 0, 24][..], &RATE].concat();
+        let data = file(b"AIFF", &common, &[0xff, 0xff, 0xfe, 0x00, 0x00, 0x01]);
+        let Signal::Stereo(left, right) = Signal::from_wav(data).expect("should decode") else {
+            panic!("should be stereo");
+        };
+        assert_eq!(left.sample_rate(), 44100);
+        assert!(left == Channel::from_samples_i32([-2], 24, 44100));
+        assert!(right == Channel::from_samples_i32([1], 24, 44100));
+
+        // Mono little-endian 16-bit samples in an AIFF-C file.
+        let common = [&[0, 1, 0, 0, 0, 2, 0, 16][..], &RATE, b"sowt", &[0, 0]].concat();
+        let data = file(b"AIFC", &common, &[0x00, 0x80, 0xff, 0x7f]);
+        let signal = Signal::from_wav(data).expect("should decode");
+        assert!(signal == Signal::Mono(Channel::from_samples_i16([-32768, 32767], 16, 44100)));
+    }
+}
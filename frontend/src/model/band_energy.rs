@@ -0,0 +1,100 @@
+use super::{levels::to_dbfs, ltas::band_power, Channel};
+
+// Energy within a band given by its edge frequencies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BandEnergy {
+    pub lower: f64,
+    pub upper: f64,
+    // Fraction of the energy of all the bands.
+    pub share: f64,
+    pub level_db: f64,
+}
+
+impl Channel {
+    // Integrates the long-term average spectrum between each pair of adjacent `edges`, in Hz, for
+    // comparing the tonal balance of recordings. Edges above the Nyquist frequency are clamped to
+    // it, leaving the bands beyond it empty.
+    pub fn band_energies(&self, edges: &[f64]) -> Vec<BandEnergy> {
+        let (spectrum, powers) = self.average_powers();
+        let nyquist = self.sample_rate as f64 / 2.0;
+        let bands: Vec<(f64, f64, f64)> = edges
+            .windows(2)
+            .map(|edges| {
+                let (lower, upper) = (edges[0], edges[1]);
+                let power = band_power(&spectrum, &powers, lower.min(nyquist), upper.min(nyquist));
+                (lower, upper, power)
+            })
+            .collect();
+
+        let total: f64 = bands.iter().map(|&(_, _, power)| power).sum();
+        bands
+            .into_iter()
+            .map(|(lower, upper, power)| BandEnergy {
+                lower,
+                upper,
+                share: if total > 0.0 { power / total } else { 0.0 },
+                level_db: to_dbfs(power.sqrt()),
+            })
+            .collect()
+    }
+
+    // RMS level of the part of the channel between `lower` and `upper`, in Hz, summed from the bins
+    // of its spectrum. Without windowing or averaging, the bins add up to the level of the whole
+    // channel, however short it is.
+    pub fn band_rms_dbfs(&self, lower: f64, upper: f64) -> f64 {
+        let spectrum = self.spectrum();
+        let bins = spectrum.frequency_to_bin(lower).min(spectrum.len())
+            ..spectrum.frequency_to_bin(upper).min(spectrum.len());
+        // Each bin above DC stands for the negative frequency mirroring it as well.
+        let mean_square: f64 = spectrum
+            .magnitudes()
+            .enumerate()
+            .skip(bins.start)
+            .take(bins.len())
+            .map(|(n, magnitude)| if n == 0 { 1.0 } else { 2.0 } * magnitude * magnitude)
+            .sum();
+        to_dbfs(mean_square.sqrt())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn energy_is_split_between_bands() {
+        let low = Generator::sine(100.0, 0.5).channel();
+        let high = Generator::sine(4000.0, 0.25).channel();
+        let mix = low.with_normalized_samples(
+            low.iter_normalized()
+                .zip(high.iter_normalized())
+                .map(|(a, b)| a + b),
+        );
+
+        let bands = mix.band_energies(&[20.0, 250.0, 8000.0, 20_000.0]);
+        assert_eq!(bands.len(), 3);
+        assert!((bands[0].share - 0.8).abs() < 0.01, "{bands:?}");
+        assert!((bands[1].share - 0.2).abs() < 0.01, "{bands:?}");
+        assert!(bands[2].share < 1e-4, "{bands:?}");
+        assert!((bands[0].level_db + 9.03).abs() < 0.2, "{bands:?}");
+        assert!((bands[1].level_db + 15.05).abs() < 0.2, "{bands:?}");
+    }
+
+    #[test]
+    fn band_rms_adds_up_to_the_level_of_the_channel() {
+        let low = Generator::sine(100.0, 0.5).channel();
+        let high = Generator::sine(4000.0, 0.25).channel();
+        let mix = low.with_normalized_samples(
+            low.iter_normalized()
+                .zip(high.iter_normalized())
+                .map(|(a, b)| a + b),
+        );
+
+        let below = mix.band_rms_dbfs(0.0, 250.0);
+        let above = mix.band_rms_dbfs(250.0, 20_000.0);
+        let all = mix.band_rms_dbfs(0.0, f64::INFINITY);
+        assert!((below + 9.03).abs() < 0.2, "{below}");
+        assert!((above + 15.05).abs() < 0.2, "{above}");
+        assert!((all - mix.statistics().rms_dbfs).abs() < 0.01, "{all}");
+    }
+}
@@ -0,0 +1,161 @@
+use std::f64::consts::PI;
+
+use rustfft::num_complex::Complex;
+
+use super::Channel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterType {
+    LowPass,
+    HighPass,
+    BandPass,
+    Notch,
+    Peaking,
+    LowShelf,
+    HighShelf,
+}
+
+impl FilterType {
+    pub const ALL: [Self; 7] = [
+        Self::LowPass,
+        Self::HighPass,
+        Self::BandPass,
+        Self::Notch,
+        Self::Peaking,
+        Self::LowShelf,
+        Self::HighShelf,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::LowPass => "Low-pass",
+            Self::HighPass => "High-pass",
+            Self::BandPass => "Band-pass",
+            Self::Notch => "Notch",
+            Self::Peaking => "Peaking",
+            Self::LowShelf => "Low shelf",
+            Self::HighShelf => "High shelf",
+        }
+    }
+
+    pub fn has_gain(self) -> bool {
+        matches!(self, Self::Peaking | Self::LowShelf | Self::HighShelf)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilterDesign {
+    pub filter_type: FilterType,
+    pub frequency: f64,
+    pub q: f64,
+    pub gain_db: f64,
+}
+
+impl Default for FilterDesign {
+    fn default() -> Self {
+        Self {
+            filter_type: FilterType::LowPass,
+            frequency: 1000.0,
+            q: std::f64::consts::FRAC_1_SQRT_2,
+            gain_db: 0.0,
+        }
+    }
+}
+
+impl FilterDesign {
+    // Coefficients according to the RBJ Audio EQ Cookbook.
+    pub fn coefficients(&self, sample_rate: u32) -> Biquad {
+        let nyquist = sample_rate as f64 / 2.0;
+        let w0 = 2.0 * PI * self.frequency.clamp(1.0, nyquist * 0.999) / sample_rate as f64;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * self.q.max(0.01));
+        let a = 10_f64.powf(self.gain_db / 40.0);
+        let shelf = 2.0 * a.sqrt() * alpha;
+
+        let ([b0, b1, b2], [a0, a1, a2]) = match self.filter_type {
+            FilterType::LowPass => (
+                [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0],
+                [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+            ),
+            FilterType::HighPass => (
+                [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+                [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+            ),
+            FilterType::BandPass => ([alpha, 0.0, -alpha], [1.0 + alpha, -2.0 * cos, 1.0 - alpha]),
+            FilterType::Notch => (
+                [1.0, -2.0 * cos, 1.0],
+                [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+            ),
+            FilterType::Peaking => (
+                [1.0 + alpha * a, -2.0 * cos, 1.0 - alpha * a],
+                [1.0 + alpha / a, -2.0 * cos, 1.0 - alpha / a],
+            ),
+            FilterType::LowShelf => (
+                [
+                    a * ((a + 1.0) - (a - 1.0) * cos + shelf),
+                    2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                    a * ((a + 1.0) - (a - 1.0) * cos - shelf),
+                ],
+                [
+                    (a + 1.0) + (a - 1.0) * cos + shelf,
+                    -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                    (a + 1.0) + (a - 1.0) * cos - shelf,
+                ],
+            ),
+            FilterType::HighShelf => (
+                [
+                    a * ((a + 1.0) + (a - 1.0) * cos + shelf),
+                    -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                    a * ((a + 1.0) + (a - 1.0) * cos - shelf),
+                ],
+                [
+                    (a + 1.0) - (a - 1.0) * cos + shelf,
+                    2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                    (a + 1.0) - (a - 1.0) * cos - shelf,
+                ],
+            ),
+        };
+
+        Biquad {
+            b: [b0 / a0, b1 / a0, b2 / a0],
+            a: [a1 / a0, a2 / a0],
+        }
+    }
+}
+
+// Biquad coefficients normalized so that a0 = 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Biquad {
+    pub b: [f64; 3],
+    pub a: [f64; 2],
+}
+
+impl Biquad {
+    pub fn magnitude_db(&self, frequency: f64, sample_rate: u32) -> f64 {
+        let w = 2.0 * PI * frequency / sample_rate as f64;
+        let z1 = Complex::from_polar(1.0, -w);
+        let z2 = z1 * z1;
+        let numerator = self.b[0] + self.b[1] * z1 + self.b[2] * z2;
+        let denominator = 1.0 + self.a[0] * z1 + self.a[1] * z2;
+        20.0 * (numerator.norm() / denominator.norm()).log10()
+    }
+
+    // Transposed direct form II.
+    pub fn process(&self, samples: impl IntoIterator<Item = f64>) -> impl Iterator<Item = f64> {
+        let Self { b, a } = *self;
+        let (mut s1, mut s2) = (0.0, 0.0);
+        samples.into_iter().map(move |x| {
+            let y = b[0] * x + s1;
+            s1 = b[1] * x - a[0] * y + s2;
+            s2 = b[2] * x - a[1] * y;
+            y
+        })
+    }
+}
+
+impl Channel {
+    pub fn filter(&self, design: &FilterDesign) -> Channel {
+        let biquad = design.coefficients(self.sample_rate);
+        self.with_normalized_samples(biquad.process(self.iter_normalized()))
+    }
+}
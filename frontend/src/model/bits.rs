@@ -0,0 +1,75 @@
+use super::{Channel, Sample};
+
+// How often each bit of the integer samples is set, which tells the resolution the content uses
+// apart from that of the container, e.g. 16-bit content padded into 24 bits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitActivity {
+    // Share of the samples with each bit set in two's complement, from the least significant bit
+    // up. Bits carrying signal or dither are set about half of the time.
+    pub activity: Vec<f64>,
+    // The bits from the most significant one down to the lowest one that is ever set.
+    pub effective_bits: u16,
+}
+
+impl Channel {
+    // Returns `None` for floating-point channels, whose resolution depends on the level.
+    pub fn bit_activity(&self) -> Option<BitActivity> {
+        let bits = usize::from(self.bits_per_sample);
+        let mask = u64::MAX >> (64 - bits);
+        let mut counts = vec![0_usize; bits];
+        let mut count = 0;
+        for sample in self.iter() {
+            let value = match sample {
+                Sample::Int8(n) => i64::from(n),
+                Sample::Int16(n) => i64::from(n),
+                Sample::Int32(n) => i64::from(n),
+                Sample::Int64(n) => n,
+                Sample::Float32(_) | Sample::Float64(_) => return None,
+            };
+            let value = value as u64 & mask;
+            for (bit, count) in counts.iter_mut().enumerate() {
+                *count += (value >> bit & 1) as usize;
+            }
+            count += 1;
+        }
+
+        let lowest = counts.iter().position(|&n| n > 0).unwrap_or(bits);
+        Some(BitActivity {
+            activity: counts
+                .iter()
+                .map(|&n| n as f64 / count.max(1) as f64)
+                .collect(),
+            effective_bits: (bits - lowest) as u16,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{
+        generator::{BitDepth, Generator},
+        Channel,
+    };
+
+    #[test]
+    fn padding_is_detected() {
+        let content = Generator::white_noise(0.5)
+            .with_bit_depth(BitDepth::Int16)
+            .channel();
+        let padded = Channel::from_samples_i32(
+            content
+                .iter_normalized()
+                .map(|x| (x * 32767.0).round() as i32 * 256),
+            24,
+            48000,
+        );
+
+        let activity = padded.bit_activity().unwrap();
+        assert_eq!(activity.effective_bits, 16);
+        assert!(activity.activity[..8].iter().all(|&a| a == 0.0));
+        assert!(activity.activity[8..].iter().all(|&a| a > 0.3));
+
+        let float = Generator::white_noise(0.5).channel();
+        assert_eq!(float.bit_activity(), None);
+    }
+}
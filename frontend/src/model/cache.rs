@@ -0,0 +1,220 @@
+use std::{
+    cell::RefCell,
+    collections::{hash_map::DefaultHasher, VecDeque},
+    hash::Hasher,
+    rc::Rc,
+    thread::LocalKey,
+};
+
+use hound::SampleFormat;
+use im::Vector;
+
+use super::{welch::WelchSpectrum, Channel, Spectrum};
+
+const CAPACITY: usize = 8;
+// Waterfall tiles are small and many are needed to fill a view.
+const TILE_CAPACITY: usize = 256;
+// Channels whose fingerprints are remembered, so that looking up the same channel again does not
+// hash all of its samples again.
+const FINGERPRINT_CAPACITY: usize = 4;
+// Bytes of the samples kept in a fingerprint, spread evenly over the channel.
+const SAMPLED_BYTES: usize = 16;
+
+// Spectra are keyed by a fingerprint of the channel rather than the channel itself, so that cached
+// entries do not keep old channels alive. Besides a hash of the samples, it holds the format,
+// length and a few of the samples of the channel, so that channels whose hashes collide are still
+// told apart unless these are the same too.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct ChannelFingerprint {
+    sample_rate: u32,
+    bits_per_sample: u16,
+    float: bool,
+    len: usize,
+    sampled: [u8; SAMPLED_BYTES],
+    hash: u64,
+}
+
+#[derive(PartialEq)]
+struct WelchKey {
+    channel: ChannelFingerprint,
+    segment_len: usize,
+    overlap: u64,
+}
+
+#[derive(PartialEq)]
+struct TileKey {
+    channel: ChannelFingerprint,
+    hop: usize,
+    frame_len: usize,
+    tile: usize,
+}
+
+thread_local! {
+    static SPECTRA: RefCell<Lru<ChannelFingerprint, Spectrum>> = RefCell::new(Lru::new(CAPACITY));
+    static WELCH_SPECTRA: RefCell<Lru<WelchKey, WelchSpectrum>> = RefCell::new(Lru::new(CAPACITY));
+    static WATERFALL_TILES: RefCell<Lru<TileKey, Rc<Vec<Vec<f64>>>>> =
+        RefCell::new(Lru::new(TILE_CAPACITY));
+    // Matched by the identity of the samples rather than by their contents, which would take as
+    // long to compare as to hash.
+    static FINGERPRINTS: RefCell<VecDeque<(Vector<u8>, ChannelFingerprint)>> =
+        RefCell::new(VecDeque::with_capacity(FINGERPRINT_CAPACITY));
+}
+
+// A small least-recently-used cache. Lookups are linear, which is fine for a handful of entries.
+struct Lru<K, V> {
+    entries: VecDeque<(K, V)>,
+    capacity: usize,
+}
+
+impl<K: PartialEq, V: Clone> Lru<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(index)?;
+        let value = entry.1.clone();
+        self.entries.push_front(entry);
+        Some(value)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        self.entries.retain(|(k, _)| *k != key);
+        self.entries.truncate(self.capacity - 1);
+        self.entries.push_front((key, value));
+    }
+}
+
+// The cache is not borrowed while computing, so computations may use other caches.
+fn get_or_compute<K: PartialEq, V: Clone>(
+    cache: &'static LocalKey<RefCell<Lru<K, V>>>,
+    key: K,
+    compute: impl FnOnce() -> V,
+) -> V {
+    if let Some(value) = cache.with(|cache| cache.borrow_mut().get(&key)) {
+        return value;
+    }
+    let value = compute();
+    cache.with(|cache| cache.borrow_mut().insert(key, value.clone()));
+    value
+}
+
+pub(super) fn spectrum(channel: &Channel, compute: impl FnOnce() -> Spectrum) -> Spectrum {
+    get_or_compute(&SPECTRA, channel.fingerprint(), compute)
+}
+
+pub(super) fn welch(
+    channel: &Channel,
+    segment_len: usize,
+    overlap: f64,
+    compute: impl FnOnce() -> WelchSpectrum,
+) -> WelchSpectrum {
+    let key = WelchKey {
+        channel: channel.fingerprint(),
+        segment_len,
+        overlap: overlap.to_bits(),
+    };
+    get_or_compute(&WELCH_SPECTRA, key, compute)
+}
+
+pub(super) fn waterfall_tile(
+    channel: ChannelFingerprint,
+    hop: usize,
+    frame_len: usize,
+    tile: usize,
+    compute: impl FnOnce() -> Rc<Vec<Vec<f64>>>,
+) -> Rc<Vec<Vec<f64>>> {
+    let key = TileKey {
+        channel,
+        hop,
+        frame_len,
+        tile,
+    };
+    get_or_compute(&WATERFALL_TILES, key, compute)
+}
+
+pub(super) fn cached_waterfall_tile(
+    channel: ChannelFingerprint,
+    hop: usize,
+    frame_len: usize,
+    tile: usize,
+) -> Option<Rc<Vec<Vec<f64>>>> {
+    let key = TileKey {
+        channel,
+        hop,
+        frame_len,
+        tile,
+    };
+    WATERFALL_TILES.with(|cache| cache.borrow_mut().get(&key))
+}
+
+impl Channel {
+    pub(super) fn fingerprint(&self) -> ChannelFingerprint {
+        let remembered = FINGERPRINTS.with(|fingerprints| {
+            fingerprints
+                .borrow()
+                .iter()
+                .find(|(data, _)| data.ptr_eq(&self.data))
+                .map(|&(_, fingerprint)| fingerprint)
+        });
+        if let Some(fingerprint) = remembered.filter(|fingerprint| fingerprint.describes(self)) {
+            return fingerprint;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        bench!(["Fingerprinting channel"] => self.data.leaves().for_each(|leaf| hasher.write(leaf)));
+        let len = self.data.len();
+        let fingerprint = ChannelFingerprint {
+            sample_rate: self.sample_rate,
+            bits_per_sample: self.bits_per_sample,
+            float: self.sample_format == SampleFormat::Float,
+            len,
+            sampled: std::array::from_fn(|n| {
+                self.data
+                    .get(n * len / SAMPLED_BYTES)
+                    .copied()
+                    .unwrap_or_default()
+            }),
+            hash: hasher.finish(),
+        };
+        FINGERPRINTS.with(|fingerprints| {
+            let mut fingerprints = fingerprints.borrow_mut();
+            fingerprints.truncate(FINGERPRINT_CAPACITY - 1);
+            fingerprints.push_front((self.data.clone(), fingerprint));
+        });
+        fingerprint
+    }
+}
+
+impl ChannelFingerprint {
+    // The samples are the same whenever their identity is, but the format is kept outside of them.
+    fn describes(&self, channel: &Channel) -> bool {
+        self.sample_rate == channel.sample_rate
+            && self.bits_per_sample == channel.bits_per_sample
+            && self.float == (channel.sample_format == SampleFormat::Float)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn fingerprints_follow_the_samples() {
+        let channel = Generator::sine(1000.0, 0.5).channel();
+        let fingerprint = channel.fingerprint();
+        assert_eq!(channel.fingerprint(), fingerprint);
+        // Slices are new vectors, identified by their contents alone.
+        assert_eq!(
+            channel.slice(0..channel.count()).fingerprint(),
+            channel.slice(0..channel.count()).fingerprint(),
+        );
+
+        let quieter = Generator::sine(1000.0, 0.25).channel();
+        assert_ne!(quieter.fingerprint(), fingerprint);
+    }
+}
@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+use super::{welch::ConfidenceBand, Spectrum};
+
+// The frequency response of a measurement microphone, as given in the calibration file that comes
+// with it, which spectra are corrected by.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Calibration {
+    // The name of the file it was read from.
+    pub name: String,
+    // Frequencies in Hz, in rising order, and how many dB the microphone reads too high there.
+    pub points: Vec<(f64, f64)>,
+    // The sensitivity of the microphone, when the file gives it, as in the "Sens Factor" header of
+    // the files of some USB microphones.
+    pub sensitivity_db: Option<f64>,
+}
+
+impl Calibration {
+    // Reads a calibration file of a frequency and a correction in dB on each line, separated by
+    // whitespace, commas or semicolons. Any further columns, such as a phase, are ignored, as are
+    // lines that do not start with two numbers, such as headers and comments. `None` if no line
+    // does.
+    pub fn parse(name: &str, text: &str) -> Option<Self> {
+        let mut points: Vec<(f64, f64)> = text
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line
+                    .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+                    .filter(|field| !field.is_empty());
+                let frequency: f64 = fields.next()?.parse().ok()?;
+                let correction: f64 = fields.next()?.parse().ok()?;
+                (frequency.is_finite() && frequency > 0.0 && correction.is_finite())
+                    .then_some((frequency, correction))
+            })
+            .collect();
+        if points.is_empty() {
+            return None;
+        }
+        points.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        points.dedup_by(|(a, _), (b, _)| a == b);
+
+        let sensitivity_db = text.lines().find_map(|line| {
+            let (_, rest) = line.split_once("Sens Factor")?;
+            let value = rest.trim_start_matches([' ', '=']);
+            let end = value
+                .find(|c: char| !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.')))
+                .unwrap_or(value.len());
+            value[..end].parse().ok()
+        });
+
+        Some(Self {
+            name: name.to_owned(),
+            points,
+            sensitivity_db,
+        })
+    }
+
+    // The correction at `frequency`, interpolated linearly over the logarithm of the frequency and
+    // held at the ends beyond the range of the file.
+    pub fn correction_db(&self, frequency: f64) -> f64 {
+        let next = self.points.partition_point(|&(f, _)| f < frequency);
+        match (
+            next.checked_sub(1).map(|n| self.points[n]),
+            self.points.get(next),
+        ) {
+            (Some((f0, c0)), Some(&(f1, c1))) => {
+                let t = (frequency / f0).ln() / (f1 / f0).ln();
+                c0 + t * (c1 - c0)
+            }
+            (Some((_, c)), None) | (None, Some(&(_, c))) => c,
+            (None, None) => 0.0,
+        }
+    }
+
+    // The factor each bin of `spectrum` is multiplied by to correct it.
+    fn gains(&self, spectrum: &Spectrum) -> Vec<f64> {
+        (0..spectrum.len())
+            .map(|n| 10_f64.powf(-self.correction_db(spectrum.bin_to_frequency(n)) / 20.0))
+            .collect()
+    }
+}
+
+impl Spectrum {
+    // The spectrum as it would have been measured by a microphone with a flat response.
+    pub fn calibrated(&self, calibration: &Calibration) -> Spectrum {
+        let transform = self
+            .transform
+            .iter()
+            .zip(calibration.gains(self))
+            .map(|(bin, gain)| bin * gain)
+            .collect();
+        Self {
+            transform,
+            ..self.clone()
+        }
+    }
+}
+
+impl ConfidenceBand {
+    // The band around `spectrum`, corrected like it.
+    pub fn calibrated(&self, spectrum: &Spectrum, calibration: &Calibration) -> ConfidenceBand {
+        let gains = calibration.gains(spectrum);
+        let correct = |bound: &[f64]| bound.iter().zip(&gains).map(|(m, g)| m * g).collect();
+        ConfidenceBand {
+            lower: correct(&self.lower),
+            upper: correct(&self.upper),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Calibration;
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn calibration_files_are_read_and_applied() {
+        let text = "\"Sens Factor =-1.378dB, SERNO: 7000000\"\n\
+                    * Freq(Hz) dB\n\
+                    20.0\t-2.0\n\
+                    2000,0.0\n\
+                    200.0 -1.0 12.5\n\
+                    \n";
+        let calibration = Calibration::parse("mic.txt", text).unwrap();
+        assert_eq!(
+            calibration.points,
+            vec![(20.0, -2.0), (200.0, -1.0), (2000.0, 0.0)]
+        );
+        assert_eq!(calibration.sensitivity_db, Some(-1.378));
+        assert_eq!(calibration.correction_db(10.0), -2.0);
+        assert!((calibration.correction_db(632.456) + 0.5).abs() < 1e-3);
+        assert_eq!(calibration.correction_db(20_000.0), 0.0);
+        assert!(Calibration::parse("empty.txt", "Frequency Gain\n").is_none());
+
+        let boost = Calibration::parse("boost.txt", "100 6\n10000 6\n").unwrap();
+        let spectrum = Generator::sine(1000.0, 0.5).channel().spectrum();
+        let corrected = spectrum.calibrated(&boost);
+        let drop = spectrum.level_at(1000.0) - corrected.level_at(1000.0);
+        assert!((drop - 6.0).abs() < 1e-9, "{drop}");
+    }
+}
@@ -0,0 +1,161 @@
+use std::{collections::BTreeMap, ops::Range};
+
+use rustfft::{num_complex::Complex, FftPlanner};
+
+use super::{resample::ResampleQuality, Channel};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    pub position: usize,
+    // The length of the pattern at the sample rate of the searched channel.
+    pub len: usize,
+    pub score: f64,
+}
+
+impl Detection {
+    pub fn range(&self) -> Range<usize> {
+        self.position..self.position + self.len
+    }
+}
+
+// Full cross-correlation of `signal` against `reference`. Index `i` of the result corresponds to
+// the lag `i - (reference.len() - 1)`, i.e. the reference shifted that many samples to the right.
+pub fn cross_correlate(signal: &[f64], reference: &[f64]) -> Vec<f64> {
+    if signal.is_empty() || reference.is_empty() {
+        return Vec::new();
+    }
+
+    let len = signal.len() + reference.len() - 1;
+    let fft_len = len.next_power_of_two();
+
+    let mut planner = FftPlanner::new();
+    let forward = planner.plan_fft_forward(fft_len);
+    let inverse = planner.plan_fft_inverse(fft_len);
+
+    let padded = |data: &[f64]| {
+        let mut buffer: Vec<_> = data.iter().map(|&x| Complex::from(x)).collect();
+        buffer.resize(fft_len, Complex::default());
+        buffer
+    };
+
+    let mut a = padded(signal);
+    let mut b = padded(reference);
+    bench!(["Calculating cross-correlation"] => {
+        forward.process(&mut a);
+        forward.process(&mut b);
+        a.iter_mut().zip(&b).for_each(|(x, y)| *x *= y.conj());
+        inverse.process(&mut a);
+    });
+
+    // Negative lags wrap around to the end of the circular correlation.
+    let scale = fft_len as f64;
+    let negative = a[fft_len - (reference.len() - 1)..].iter();
+    let positive = a[..signal.len()].iter();
+    negative.chain(positive).map(|c| c.re / scale).collect()
+}
+
+impl Channel {
+    // Positions where the pattern matches the channel with a normalized correlation of at least
+    // `threshold`. A pattern recorded at another sample rate is resampled to that of the channel
+    // first, so that positions and lengths are in samples of the channel.
+    pub fn find_pattern(&self, pattern: &Channel, threshold: f64) -> Vec<Detection> {
+        let pattern = pattern.resample(self.sample_rate, ResampleQuality::Standard);
+        let signal: Vec<f64> = self.iter().map(f64::from).collect();
+        let reference: Vec<f64> = pattern.iter().map(f64::from).collect();
+        let len = reference.len();
+
+        if len == 0 || signal.len() < len {
+            return Vec::new();
+        }
+
+        let reference_energy = reference.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if reference_energy == 0.0 {
+            return Vec::new();
+        }
+
+        let mut energy_sums = Vec::with_capacity(signal.len() + 1);
+        energy_sums.push(0.0);
+        for x in &signal {
+            energy_sums.push(energy_sums[energy_sums.len() - 1] + x * x);
+        }
+
+        let correlation = cross_correlate(&signal, &reference);
+        let mut candidates: Vec<_> = (0..=signal.len() - len)
+            .filter_map(|position| {
+                let energy = (energy_sums[position + len] - energy_sums[position]).max(0.0);
+                let norm = reference_energy * energy.sqrt();
+                let score = if norm > 0.0 {
+                    correlation[position + len - 1] / norm
+                } else {
+                    0.0
+                };
+                (score >= threshold).then_some(Detection {
+                    position,
+                    len,
+                    score,
+                })
+            })
+            .collect();
+
+        // Keep only the strongest match within every reference-length neighbourhood.
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        let mut detections = BTreeMap::new();
+        for Detection {
+            position, score, ..
+        } in candidates
+        {
+            let lower = position.saturating_sub(len - 1);
+            if detections.range(lower..position + len).next().is_none() {
+                detections.insert(position, score);
+            }
+        }
+        detections
+            .into_iter()
+            .map(|(position, score)| Detection {
+                position,
+                len,
+                score,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{generator::Generator, resample::ResampleQuality};
+
+    #[test]
+    fn patterns_are_found_at_their_offset_in_noise() {
+        const OFFSET: usize = 12345;
+        let sweep = Generator::sweep(200.0, 4000.0, 0.5)
+            .with_duration(0.1)
+            .channel();
+        let noise = Generator::white_noise(0.05).with_duration(1.0).channel();
+        let pattern: Vec<f64> = sweep.iter_normalized().collect();
+        let samples: Vec<f64> = noise
+            .iter_normalized()
+            .enumerate()
+            .map(|(n, x)| {
+                let pattern = n.checked_sub(OFFSET).and_then(|n| pattern.get(n));
+                x + pattern.copied().unwrap_or(0.0)
+            })
+            .collect();
+        let recording = noise.with_normalized_samples(samples);
+
+        let detections = recording.find_pattern(&sweep, 0.8);
+        assert_eq!(detections.len(), 1, "{detections:?}");
+        assert_eq!(detections[0].position, OFFSET);
+        assert_eq!(detections[0].range(), OFFSET..OFFSET + sweep.count());
+        assert!(detections[0].score > 0.95, "{detections:?}");
+
+        // The same pattern recorded at another rate is found at the same place.
+        let resampled = sweep.resample(44100, ResampleQuality::High);
+        let detections = recording.find_pattern(&resampled, 0.8);
+        assert_eq!(detections.len(), 1, "{detections:?}");
+        assert!(
+            detections[0].position.abs_diff(OFFSET) <= 1,
+            "{detections:?}"
+        );
+        assert!(detections[0].score > 0.9, "{detections:?}");
+    }
+}
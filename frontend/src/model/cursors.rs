@@ -0,0 +1,50 @@
+use super::{levels::to_dbfs, Spectrum};
+
+// The distance from one frequency to another, in the units it is usually given in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub hertz: f64,
+    pub octaves: f64,
+    pub cents: f64,
+}
+
+impl Interval {
+    pub fn between(from: f64, to: f64) -> Self {
+        let octaves = (to / from).log2();
+        Self {
+            hertz: to - from,
+            octaves,
+            cents: 1200.0 * octaves,
+        }
+    }
+}
+
+impl Spectrum {
+    // The level of the bin nearest `frequency`, in dBFS relative to a full-scale sine.
+    pub fn level_at(&self, frequency: f64) -> f64 {
+        let bin = self
+            .frequency_to_bin(frequency.max(0.0))
+            .min(self.len().saturating_sub(1));
+        to_dbfs(2.0 * self.magnitudes().nth(bin).unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interval;
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn cursors_measure_levels_and_intervals() {
+        let interval = Interval::between(440.0, 880.0);
+        assert_eq!(interval.hertz, 440.0);
+        assert!((interval.octaves - 1.0).abs() < 1e-12);
+        assert!((interval.cents - 1200.0).abs() < 1e-9);
+        assert!((Interval::between(1000.0, 500.0).octaves + 1.0).abs() < 1e-12);
+
+        let spectrum = Generator::sine(1000.0, 0.5).channel().spectrum();
+        let level = spectrum.level_at(1000.0);
+        assert!((level + 6.02).abs() < 0.05, "{level}");
+        assert!(spectrum.level_at(5000.0) < -60.0);
+    }
+}
@@ -0,0 +1,179 @@
+use super::{
+    biquad::{FilterDesign, FilterType},
+    levels::FLOOR_DB,
+    Channel,
+};
+
+// Nominal centres of the octave bands the decay is measured in.
+const OCTAVE_CENTERS: [f64; 8] = [63.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0];
+// The Q of a band-pass filter one octave wide.
+const OCTAVE_Q: f64 = std::f64::consts::SQRT_2;
+
+// Reverberation times in seconds, each extrapolated to a decay of 60 dB from a line fitted to part
+// of the energy decay curve, or `None` if the curve does not fall far enough.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DecayTimes {
+    // Early decay time, from 0 to -10 dB.
+    pub edt: Option<f64>,
+    // From -5 to -25 dB.
+    pub t20: Option<f64>,
+    // From -5 to -35 dB.
+    pub t30: Option<f64>,
+}
+
+impl DecayTimes {
+    // T30, or T20 where the curve does not fall far enough for it.
+    pub fn rt60(&self) -> Option<f64> {
+        self.t30.or(self.t20)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecayBand {
+    // The nominal centre of the octave band, or `None` for the whole band.
+    pub center: Option<f64>,
+    // The energy decay curve in dB relative to the total energy, one point per sample from the
+    // highest sample of the response on.
+    pub curve_db: Vec<f64>,
+    pub times: DecayTimes,
+}
+
+// The decay of a room, or anything else, measured from its impulse response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoomDecay {
+    pub sample_rate: u32,
+    pub bands: Vec<DecayBand>,
+}
+
+impl Channel {
+    // Treats the channel as an impulse response and measures its decay over the whole band and in
+    // each octave band below the Nyquist frequency. The response should end where its decay reaches
+    // the noise floor, as the noise after it would otherwise be counted as a slower decay.
+    pub fn room_decay(&self) -> RoomDecay {
+        let samples: Vec<f64> = self.iter_normalized().collect();
+        let sample_rate = self.sample_rate;
+        let band = |center: Option<f64>, samples: Vec<f64>| {
+            let curve_db = energy_decay_curve(&samples);
+            let times = decay_times(&curve_db, sample_rate);
+            DecayBand {
+                center,
+                curve_db,
+                times,
+            }
+        };
+
+        let mut bands = vec![band(None, samples.clone())];
+        bands.extend(
+            OCTAVE_CENTERS
+                .into_iter()
+                .filter(|&center| center * OCTAVE_Q < sample_rate as f64 / 2.0)
+                .map(|center| {
+                    let biquad = FilterDesign {
+                        filter_type: FilterType::BandPass,
+                        frequency: center,
+                        q: OCTAVE_Q,
+                        gain_db: 0.0,
+                    }
+                    .coefficients(sample_rate);
+                    // Two sections fall off faster outside the band than one.
+                    let filtered = biquad
+                        .process(biquad.process(samples.iter().copied()))
+                        .collect();
+                    band(Some(center), filtered)
+                }),
+        );
+        RoomDecay { sample_rate, bands }
+    }
+}
+
+// Schroeder's backward integration of the squared response from its highest sample on, which
+// gives the energy still to come at each sample.
+fn energy_decay_curve(samples: &[f64]) -> Vec<f64> {
+    let start = samples
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.abs().total_cmp(&b.abs()))
+        .map_or(0, |(n, _)| n);
+    let mut remaining: Vec<f64> = samples[start..]
+        .iter()
+        .rev()
+        .scan(0.0, |energy, x| {
+            *energy += x * x;
+            Some(*energy)
+        })
+        .collect();
+    remaining.reverse();
+    let total = remaining.first().copied().unwrap_or_default();
+    remaining
+        .into_iter()
+        .map(|energy| (10.0 * (energy / total).log10()).max(FLOOR_DB))
+        .collect()
+}
+
+fn decay_times(curve_db: &[f64], sample_rate: u32) -> DecayTimes {
+    let time = |from: f64, to: f64| {
+        let start = curve_db.iter().position(|&db| db <= from)?;
+        let end = start + curve_db[start..].iter().position(|&db| db <= to)?;
+        let slope = fit_slope(&curve_db[start..=end]) * sample_rate as f64;
+        (slope < 0.0).then(|| -60.0 / slope)
+    };
+    DecayTimes {
+        edt: time(0.0, -10.0),
+        t20: time(-5.0, -25.0),
+        t30: time(-5.0, -35.0),
+    }
+}
+
+// The slope of the least-squares line through the points, in units per sample.
+fn fit_slope(points: &[f64]) -> f64 {
+    let n = points.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = points.iter().sum::<f64>() / n;
+    let (covariance, variance) = points
+        .iter()
+        .enumerate()
+        .map(|(x, y)| (x as f64 - mean_x, y - mean_y))
+        .fold((0.0, 0.0), |(c, v), (dx, dy)| (c + dx * dy, v + dx * dx));
+    covariance / variance
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn decay_of_exponentially_decaying_noise_is_measured() {
+        const RT60: f64 = 0.5;
+
+        let noise = Generator::white_noise(0.5).channel();
+        let sample_rate = noise.sample_rate() as f64;
+        let response = noise.with_normalized_samples(
+            noise
+                .iter_normalized()
+                .enumerate()
+                .map(|(n, x)| x * 10_f64.powf(-3.0 * n as f64 / sample_rate / RT60)),
+        );
+
+        let decay = response.room_decay();
+        assert_eq!(decay.bands[0].center, None);
+        assert_eq!(decay.bands[0].curve_db[0], 0.0);
+        assert!(decay.bands.iter().any(|band| band.center == Some(8000.0)));
+        // The early decay of a single band depends more on where its noise happens to peak.
+        for (band, edt_tolerance) in [(&decay.bands[0], 0.05), (&decay.bands[5], 0.15)] {
+            let times = band.times;
+            for (time, tolerance) in [
+                (times.edt, edt_tolerance),
+                (times.t20, 0.05),
+                (times.t30, 0.05),
+            ] {
+                let time = time.unwrap();
+                assert!(
+                    (time - RT60).abs() < tolerance * RT60,
+                    "{:?} {times:?}",
+                    band.center
+                );
+            }
+            assert_eq!(times.rt60(), times.t30);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use super::{resample::ResampleQuality, Signal, WavLimits};
+
+// Factors by which frames can be dropped, of which those that divide the sample rate are offered.
+const FACTORS: [u32; 6] = [2, 3, 4, 6, 8, 16];
+// Decimating below this would leave too little of the spectrum to be worth browsing.
+const MIN_SAMPLE_RATE: u32 = 8000;
+const RESAMPLE_RATE: u32 = 48000;
+
+// How a file too large to open in full is reduced while it is decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decimation {
+    // Keeps every nth frame without filtering, so content above the new Nyquist frequency aliases.
+    // Only the kept frames are ever held in memory.
+    KeepEvery(u32),
+    // Band-limited resampling to the given rate, which needs the file decoded in full first.
+    Resample(u32),
+}
+
+impl Decimation {
+    // The reductions that make sense for a file at `sample_rate`, from the least to the most
+    // aggressive, with resampling last.
+    pub fn options(sample_rate: u32) -> Vec<Self> {
+        FACTORS
+            .into_iter()
+            .filter(|&factor| {
+                sample_rate.is_multiple_of(factor) && sample_rate / factor >= MIN_SAMPLE_RATE
+            })
+            .map(Self::KeepEvery)
+            .chain((sample_rate > RESAMPLE_RATE).then_some(Self::Resample(RESAMPLE_RATE)))
+            .collect()
+    }
+
+    pub fn sample_rate(self, original: u32) -> u32 {
+        match self {
+            Self::KeepEvery(factor) => original / factor.max(1),
+            Self::Resample(sample_rate) => sample_rate,
+        }
+    }
+
+    pub fn name(self, original: u32) -> String {
+        let sample_rate = self.sample_rate(original);
+        match self {
+            Self::KeepEvery(factor) => format!("1 in {factor} samples ({sample_rate} Hz)"),
+            Self::Resample(_) => format!("Resampled to {sample_rate} Hz"),
+        }
+    }
+
+    // What was given up, for labelling a signal that was decimated to `sample_rate`.
+    pub fn notice(self, sample_rate: u32) -> String {
+        let nyquist = sample_rate / 2;
+        match self {
+            Self::KeepEvery(factor) => format!(
+                "Decimated: 1 in {factor} samples kept. Content above {nyquist} Hz is aliased."
+            ),
+            Self::Resample(_) => format!(
+                "Decimated: resampled to {sample_rate} Hz. Content above {nyquist} Hz is removed."
+            ),
+        }
+    }
+}
+
+impl Signal {
+    pub fn from_wav_decimated(
+        data: Vec<u8>,
+        limits: WavLimits,
+        decimation: Decimation,
+    ) -> Result<Self, hound::Error> {
+        match decimation {
+            Decimation::KeepEvery(factor) => Self::decode(data, limits, factor as usize),
+            Decimation::Resample(sample_rate) => {
+                let signal = Self::from_wav_with_limits(data, limits)?;
+                Ok(bench!(["Resampling decimated file"] => signal
+                    .map_channels(|channel| channel.resample(sample_rate, ResampleQuality::Fast))))
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::{f64::consts::PI, ops::Range};
+
+use super::Channel;
+
+// Raised-cosine gain of the `n`th of `len` samples of a fade-in, rising from near 0 to near 1. A
+// fade-out is the same backwards, and the two add up to 1 at every sample, so that crossfading
+// between identical signals leaves them unchanged.
+fn fade_in_gain(n: usize, len: usize) -> f64 {
+    0.5 - 0.5 * (PI * (n as f64 + 0.5) / len as f64).cos()
+}
+
+impl Channel {
+    // The channel faded in over its first `len` samples and out over its last, so that it starts
+    // and ends at silence without a click. Fades longer than half of the channel are shortened.
+    pub fn faded(&self, len: usize) -> Channel {
+        let count = self.count();
+        let len = len.min(count / 2);
+        self.with_normalized_samples(self.iter_normalized().enumerate().map(|(n, x)| {
+            let gain = if n < len {
+                fade_in_gain(n, len)
+            } else if n >= count - len {
+                fade_in_gain(count - 1 - n, len)
+            } else {
+                1.0
+            };
+            x * gain
+        }))
+    }
+
+    // `region` of the channel with its last `len` samples crossfaded into the `len` samples before
+    // its start, so that it runs on into its beginning when looped, as the signal did before it. The
+    // crossfade is shortened to what there is before the region and to half of the region.
+    pub fn loop_crossfaded(&self, region: Range<usize>, len: usize) -> Channel {
+        let region = region.start.min(self.count())..region.end.min(self.count());
+        let len = len.min(region.start).min(region.len() / 2);
+        let lead_in: Vec<f64> = self
+            .slice(region.start - len..region.start)
+            .iter_normalized()
+            .collect();
+        let tail_start = region.len() - len;
+        let snippet = self.slice(region);
+        snippet.with_normalized_samples(snippet.iter_normalized().enumerate().map(|(n, x)| {
+            if n < tail_start {
+                return x;
+            }
+            let gain = fade_in_gain(n - tail_start, len);
+            x * (1.0 - gain) + lead_in[n - tail_start] * gain
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn edges_are_faded_and_loops_crossfaded() {
+        let tone = Generator::sine(1000.0, 0.5).channel();
+
+        let faded = tone.slice(100..10_100).faded(480);
+        let samples: Vec<f64> = faded.iter_normalized().collect();
+        assert_eq!(samples.len(), 10_000);
+        assert!(samples[0].abs() < 1e-3 && samples[9_999].abs() < 1e-3);
+        let original: Vec<f64> = tone.slice(100..10_100).iter_normalized().collect();
+        assert_eq!(samples[5_000], original[5_000]);
+
+        // A region of a whole number of periods already loops cleanly, and a crossfade with what
+        // precedes it, which is the same, leaves it as it is.
+        let looped = tone.loop_crossfaded(960..5_760, 480);
+        let original = tone.slice(960..5_760);
+        assert!(looped
+            .iter_normalized()
+            .zip(original.iter_normalized())
+            .all(|(a, b)| (a - b).abs() < 1e-6));
+
+        // Otherwise, the end runs on into the samples before the start.
+        let looped: Vec<f64> = tone
+            .loop_crossfaded(1_000..5_000, 480)
+            .iter_normalized()
+            .collect();
+        let before = tone.slice(999..1_000).iter_normalized().next().unwrap();
+        assert!((looped[looped.len() - 1] - before).abs() < 0.01);
+    }
+}
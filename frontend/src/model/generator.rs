@@ -0,0 +1,373 @@
+use std::f64::consts::PI;
+
+use super::{Channel, Signal};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Waveform {
+    Sine,
+    Square,
+    Triangle,
+    Sawtooth,
+    WhiteNoise,
+    PinkNoise,
+    Chirp,
+    Impulse,
+    Dc,
+}
+
+impl Waveform {
+    pub const ALL: [Self; 9] = [
+        Self::Sine,
+        Self::Square,
+        Self::Triangle,
+        Self::Sawtooth,
+        Self::WhiteNoise,
+        Self::PinkNoise,
+        Self::Chirp,
+        Self::Impulse,
+        Self::Dc,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sine => "Sine",
+            Self::Square => "Square",
+            Self::Triangle => "Triangle",
+            Self::Sawtooth => "Sawtooth",
+            Self::WhiteNoise => "White noise",
+            Self::PinkNoise => "Pink noise",
+            Self::Chirp => "Sweep (chirp)",
+            Self::Impulse => "Impulse",
+            Self::Dc => "DC",
+        }
+    }
+
+    pub fn has_frequency(self) -> bool {
+        !matches!(
+            self,
+            Self::WhiteNoise | Self::PinkNoise | Self::Impulse | Self::Dc
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitDepth {
+    Int8,
+    Int16,
+    Int32,
+    Float32,
+}
+
+impl BitDepth {
+    pub const ALL: [Self; 4] = [Self::Int8, Self::Int16, Self::Int32, Self::Float32];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Int8 => "8-bit integer",
+            Self::Int16 => "16-bit integer",
+            Self::Int32 => "32-bit integer",
+            Self::Float32 => "32-bit float",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generator {
+    pub waveform: Waveform,
+    pub frequency: f64,
+    pub end_frequency: f64,
+    pub amplitude: f64,
+    pub duration: f64,
+    pub sample_rate: u32,
+    pub bit_depth: BitDepth,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self {
+            waveform: Waveform::Sine,
+            frequency: 5.0,
+            end_frequency: 20_000.0,
+            amplitude: 1.0,
+            duration: 1.0,
+            sample_rate: 44100,
+            bit_depth: BitDepth::Float32,
+        }
+    }
+}
+
+// Test signals with exact parameters, for checking analyzers against analytically known results.
+// Each starts out as one second of 48 kHz 32-bit float samples, which the `with_` methods change.
+impl Generator {
+    pub fn sine(frequency: f64, amplitude: f64) -> Self {
+        Self::fixture(Waveform::Sine, frequency, amplitude)
+    }
+
+    // Exponential sweep from `start` to `end` Hz over the whole duration.
+    pub fn sweep(start: f64, end: f64, amplitude: f64) -> Self {
+        Self {
+            end_frequency: end,
+            ..Self::fixture(Waveform::Chirp, start, amplitude)
+        }
+    }
+
+    // Uniformly distributed noise between ± `amplitude`.
+    pub fn white_noise(amplitude: f64) -> Self {
+        Self::fixture(Waveform::WhiteNoise, 0.0, amplitude)
+    }
+
+    pub fn pink_noise(amplitude: f64) -> Self {
+        Self::fixture(Waveform::PinkNoise, 0.0, amplitude)
+    }
+
+    pub fn dc(level: f64) -> Self {
+        Self::fixture(Waveform::Dc, 0.0, level)
+    }
+
+    // A single sample of `amplitude` at the start, followed by silence.
+    pub fn impulse(amplitude: f64) -> Self {
+        Self::fixture(Waveform::Impulse, 0.0, amplitude)
+    }
+
+    pub fn with_duration(self, duration: f64) -> Self {
+        Self { duration, ..self }
+    }
+
+    pub fn with_sample_rate(self, sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            ..self
+        }
+    }
+
+    pub fn with_bit_depth(self, bit_depth: BitDepth) -> Self {
+        Self { bit_depth, ..self }
+    }
+
+    fn fixture(waveform: Waveform, frequency: f64, amplitude: f64) -> Self {
+        Self {
+            waveform,
+            frequency,
+            end_frequency: frequency,
+            amplitude,
+            duration: 1.0,
+            sample_rate: 48000,
+            bit_depth: BitDepth::Float32,
+        }
+    }
+}
+
+impl Generator {
+    pub fn generate(&self) -> Signal {
+        Signal::Mono(self.channel())
+    }
+
+    pub fn channel(&self) -> Channel {
+        let wave = self.wave();
+
+        match self.bit_depth {
+            BitDepth::Int8 => Channel::from_samples_i8(
+                wave.map(|v| (v * i8::MAX as f64).round() as i8),
+                8,
+                self.sample_rate,
+            ),
+            BitDepth::Int16 => Channel::from_samples_i16(
+                wave.map(|v| (v * i16::MAX as f64).round() as i16),
+                16,
+                self.sample_rate,
+            ),
+            BitDepth::Int32 => Channel::from_samples_i32(
+                wave.map(|v| (v * i32::MAX as f64).round() as i32),
+                32,
+                self.sample_rate,
+            ),
+            BitDepth::Float32 => {
+                Channel::from_samples_f32(wave.map(|v| v as f32), 32, self.sample_rate)
+            }
+        }
+    }
+
+    pub fn num_samples(&self) -> usize {
+        (self.duration.max(0.0) * self.sample_rate as f64).round() as usize
+    }
+
+    fn wave(&self) -> impl Iterator<Item = f64> {
+        let sample_rate = self.sample_rate as f64;
+        let amplitude = self.amplitude.clamp(0.0, 1.0);
+        let frequency = self.frequency;
+        let duration = self.num_samples() as f64 / sample_rate;
+        let sweep_rate = (self.end_frequency / self.frequency).ln();
+
+        let mut noise = Noise::new();
+        let waveform = self.waveform;
+
+        (0..self.num_samples()).map(move |i| {
+            let t = i as f64 / sample_rate;
+            let phase = |offset: f64| (frequency * t + offset).fract();
+
+            let value = match waveform {
+                Waveform::Sine => (2.0 * PI * frequency * t).sin(),
+                Waveform::Square => {
+                    if phase(0.0) < 0.5 {
+                        1.0
+                    } else {
+                        -1.0
+                    }
+                }
+                Waveform::Triangle => 1.0 - 4.0 * (phase(0.25) - 0.5).abs(),
+                Waveform::Sawtooth => 2.0 * phase(0.5) - 1.0,
+                Waveform::WhiteNoise => noise.white(),
+                Waveform::PinkNoise => noise.pink(),
+                Waveform::Chirp if sweep_rate.is_finite() && sweep_rate != 0.0 => {
+                    let k = duration / sweep_rate;
+                    (2.0 * PI * frequency * k * ((t / k).exp() - 1.0)).sin()
+                }
+                Waveform::Chirp => (2.0 * PI * frequency * t).sin(),
+                Waveform::Impulse => {
+                    if i == 0 {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }
+                Waveform::Dc => 1.0,
+            };
+
+            amplitude * value
+        })
+    }
+}
+
+// Deterministic xorshift noise source, so that the same settings always produce the same signal.
+struct Noise {
+    state: u64,
+    pink: [f64; 7],
+}
+
+impl Noise {
+    fn new() -> Self {
+        Self {
+            state: 0x9e37_79b9_7f4a_7c15,
+            pink: [0.0; 7],
+        }
+    }
+
+    fn white(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 11) as f64 / (1_u64 << 52) as f64 - 1.0
+    }
+
+    // Paul Kellet's refined pink noise filter.
+    fn pink(&mut self) -> f64 {
+        let white = self.white();
+        let b = &mut self.pink;
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.96900 * b[2] + white * 0.1538520;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55000 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+        let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+        b[6] = white * 0.115926;
+        (pink * 0.11).clamp(-1.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{actual} is not within {tolerance} of {expected}"
+        );
+    }
+
+    #[test]
+    fn sine_has_analytic_levels() {
+        let statistics = Generator::sine(1000.0, 0.5).channel().statistics();
+        assert_close(statistics.peak_dbfs, 20.0 * 0.5_f64.log10(), 0.01);
+        assert_close(
+            statistics.rms_dbfs,
+            20.0 * (0.5 / 2_f64.sqrt()).log10(),
+            0.01,
+        );
+        assert_close(statistics.dc_offset, 0.0, 1e-9);
+        // Two per period, except for the one at the very end.
+        assert_eq!(statistics.zero_crossings, 2 * 1000 - 1);
+    }
+
+    #[test]
+    fn sine_peaks_at_its_frequency() {
+        let welch = Generator::sine(1000.0, 0.5).channel().welch(4096, 0.5);
+        let (peak_bin, _) = welch
+            .spectrum
+            .magnitudes()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap();
+        let bin_width = 48000.0 / 4096.0;
+        assert_close(
+            welch.spectrum.bin_to_frequency(peak_bin),
+            1000.0,
+            bin_width / 2.0,
+        );
+    }
+
+    #[test]
+    fn white_noise_is_uniform() {
+        let statistics = Generator::white_noise(0.5).channel().statistics();
+        assert_close(
+            statistics.rms_dbfs,
+            20.0 * (0.5 / 3_f64.sqrt()).log10(),
+            0.1,
+        );
+        assert_close(statistics.dc_offset, 0.0, 0.01);
+    }
+
+    #[test]
+    fn dc_is_constant() {
+        let statistics = Generator::dc(0.25)
+            .with_bit_depth(BitDepth::Int16)
+            .channel()
+            .statistics();
+        assert_close(statistics.dc_offset, 0.25, 1e-4);
+        assert_close(statistics.crest_factor_db, 0.0, 1e-9);
+        assert_eq!(statistics.zero_crossings, 0);
+    }
+
+    #[test]
+    fn impulse_has_flat_spectrum() {
+        let channel = Generator::impulse(1.0)
+            .with_duration(0.01)
+            .with_sample_rate(8000)
+            .channel();
+        assert_eq!(channel.count(), 80);
+        assert_eq!(channel.iter().filter(|sample| !sample.is_zero()).count(), 1);
+
+        let spectrum = channel.spectrum();
+        let first = spectrum.magnitudes().next().unwrap();
+        for magnitude in spectrum.magnitudes() {
+            assert_close(magnitude, first, 1e-9);
+        }
+    }
+
+    #[test]
+    fn sweep_covers_its_range() {
+        let channel = Generator::sweep(100.0, 1000.0, 0.5)
+            .with_duration(2.0)
+            .channel();
+        let spectrum = channel.spectrum();
+        let level_at = |frequency: f64| {
+            spectrum
+                .magnitudes()
+                .nth(spectrum.frequency_to_bin(frequency))
+                .unwrap()
+        };
+        assert!(level_at(500.0) > 10.0 * level_at(5000.0));
+        assert!(level_at(200.0) > 10.0 * level_at(50.0));
+    }
+}
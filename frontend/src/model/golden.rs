@@ -0,0 +1,360 @@
+// Regression harness for the analyzers. Programmatically generated fixtures are encoded to WAV,
+// decoded again and run through every analyzer, and the results are compared against those
+// recorded in golden.json. After an intentional change to the results, rerun the tests with
+// `UPDATE_GOLDEN=1` to record the new ones.
+
+use std::{collections::BTreeMap, f64::consts::PI, fs};
+
+use super::{
+    biquad::{FilterDesign, FilterType},
+    generator::{BitDepth, Generator, Waveform},
+    pitch::PitchMethod,
+    processing::{self, Operation},
+    resample::ResampleQuality,
+    Channel, Signal,
+};
+
+const GOLDEN_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/model/golden.json");
+
+const DB_TOLERANCE: f64 = 0.01;
+const HZ_TOLERANCE: f64 = 0.1;
+const RATIO_TOLERANCE: f64 = 1e-6;
+
+type Golden = BTreeMap<String, BTreeMap<String, f64>>;
+
+// Measured values along with how far they may deviate from the recorded ones.
+#[derive(Default)]
+struct Measurements(BTreeMap<String, (f64, f64)>);
+
+impl Measurements {
+    fn approx(&mut self, name: impl Into<String>, value: f64, tolerance: f64) {
+        let name = name.into();
+        assert!(value.is_finite(), "{name} is not finite: {value}");
+        self.0.insert(name, (value, tolerance));
+    }
+
+    fn exact(&mut self, name: impl Into<String>, value: usize) {
+        self.approx(name, value as f64, 0.0);
+    }
+}
+
+// Harmonic bursts alternating with near-silence, for the speech and silence detectors.
+fn bursts() -> Signal {
+    const SAMPLE_RATE: u32 = 16000;
+    let mut noise = 0x2545_f491_u32;
+    let samples = (0..2 * SAMPLE_RATE).map(|i| {
+        let t = f64::from(i) / f64::from(SAMPLE_RATE);
+        noise = noise.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        let floor = (f64::from(noise >> 16) / 32768.0 - 1.0) * 1e-4;
+        let burst = if ((t / 0.5) as u32).is_multiple_of(2) {
+            [200.0, 400.0, 800.0]
+                .into_iter()
+                .zip([0.3, 0.15, 0.075])
+                .map(|(frequency, amplitude)| amplitude * (2.0 * PI * frequency * t).sin())
+                .sum()
+        } else {
+            0.0
+        };
+        ((burst + floor) * f64::from(i16::MAX)).round() as i16
+    });
+    Signal::Mono(Channel::from_samples_i16(samples, 16, SAMPLE_RATE))
+}
+
+fn fixtures() -> Vec<(&'static str, Signal)> {
+    let square = Generator {
+        waveform: Waveform::Square,
+        ..Generator::sine(100.0, 0.25).with_bit_depth(BitDepth::Int16)
+    };
+    let triangle = Generator {
+        waveform: Waveform::Triangle,
+        ..Generator::sine(3000.0, 0.7).with_bit_depth(BitDepth::Int16)
+    };
+    let sine = Generator::sine(1000.0, 0.5).channel();
+
+    vec![
+        (
+            "sine_1k_int16",
+            Generator::sine(1000.0, 0.5)
+                .with_bit_depth(BitDepth::Int16)
+                .generate(),
+        ),
+        (
+            "pink_noise_int32",
+            Generator::pink_noise(0.5)
+                .with_bit_depth(BitDepth::Int32)
+                .generate(),
+        ),
+        (
+            "chirp_float32",
+            Generator::sweep(20.0, 20_000.0, 0.8).generate(),
+        ),
+        ("bursts_int16", bursts()),
+        (
+            "sine_1k_float64",
+            Signal::Mono(Channel::from_samples_f64(
+                sine.iter().map(f64::from),
+                64,
+                sine.sample_rate(),
+            )),
+        ),
+        (
+            "stereo_int16",
+            Signal::Stereo(square.channel(), triangle.channel()),
+        ),
+    ]
+}
+
+fn measure_channel(measurements: &mut Measurements, prefix: &str, channel: &Channel) {
+    let name = |metric: &str| format!("{prefix}{metric}");
+
+    let statistics = channel.statistics();
+    measurements.exact(name("count"), statistics.count);
+    measurements.approx(name("peak_dbfs"), statistics.peak_dbfs, DB_TOLERANCE);
+    measurements.approx(name("rms_dbfs"), statistics.rms_dbfs, DB_TOLERANCE);
+    measurements.approx(
+        name("crest_factor_db"),
+        statistics.crest_factor_db,
+        DB_TOLERANCE,
+    );
+    measurements.approx(name("dc_offset"), statistics.dc_offset, RATIO_TOLERANCE);
+    measurements.exact(name("zero_crossings"), statistics.zero_crossings);
+    measurements.exact(name("clipping_events"), statistics.clipping_events);
+
+    measurements.approx(
+        name("true_peak_dbtp"),
+        channel.true_peak_dbtp(),
+        DB_TOLERANCE,
+    );
+    let overs = channel.true_peak_overs(-3.0);
+    measurements.exact(name("true_peak.overs"), overs.len());
+    measurements.exact(
+        name("true_peak.over_samples"),
+        overs.iter().map(|r| r.len()).sum(),
+    );
+    measurements.approx(
+        name("noise_floor_dbfs"),
+        channel.noise_floor_dbfs(),
+        DB_TOLERANCE,
+    );
+    measurements.approx(
+        name("centroid"),
+        channel.spectrum().centroid(),
+        HZ_TOLERANCE,
+    );
+
+    let welch = channel.welch(4096, 0.5);
+    measurements.exact(name("welch.segments"), welch.segments);
+    let peak_bin = welch
+        .spectrum
+        .magnitudes()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map_or(0, |(bin, _)| bin);
+    measurements.approx(
+        name("welch.peak_frequency"),
+        welch.spectrum.bin_to_frequency(peak_bin),
+        HZ_TOLERANCE,
+    );
+
+    for band in channel.ltas(3).bands {
+        let band_name = name(&format!("ltas.{}", band.nominal_center()));
+        measurements.approx(band_name, band.level_db, DB_TOLERANCE);
+    }
+    for band in channel.ltas(1).bands {
+        let band_name = name(&format!("octave_bands.{}", band.nominal_center()));
+        measurements.approx(band_name, band.level_db, DB_TOLERANCE);
+    }
+    for band in channel.band_energies(&[20.0, 250.0, 2000.0, 20_000.0]) {
+        let band_name = name(&format!("band_energy.{}-{}", band.lower, band.upper));
+        measurements.approx(band_name, band.share, RATIO_TOLERANCE);
+    }
+
+    if let Some(crest_factor) = channel.levels(2048, 1024).median_crest_factor(-60.0) {
+        measurements.approx(
+            name("levels.median_crest_factor"),
+            crest_factor,
+            DB_TOLERANCE,
+        );
+    }
+
+    for method in PitchMethod::ALL {
+        if let Some(pitch) = channel.pitch(method) {
+            let method_name = format!("{method:?}").to_lowercase();
+            measurements.approx(
+                name(&format!("pitch.{method_name}.frequency")),
+                pitch.frequency,
+                HZ_TOLERANCE,
+            );
+            measurements.approx(
+                name(&format!("pitch.{method_name}.confidence")),
+                pitch.confidence,
+                RATIO_TOLERANCE,
+            );
+        }
+    }
+
+    let track = channel.pitch_track(0..channel.count(), 4800);
+    let mut voiced: Vec<f64> = track
+        .pitches
+        .iter()
+        .flatten()
+        .map(|p| p.frequency)
+        .collect();
+    measurements.exact(name("pitch_track.frames"), track.pitches.len());
+    measurements.exact(name("pitch_track.voiced"), voiced.len());
+    if !voiced.is_empty() {
+        voiced.sort_by(f64::total_cmp);
+        measurements.approx(
+            name("pitch_track.median_frequency"),
+            voiced[voiced.len() / 2],
+            HZ_TOLERANCE,
+        );
+    }
+
+    if let Some(activity) = channel.bit_activity() {
+        measurements.exact(name("bits.effective"), activity.effective_bits.into());
+    }
+
+    let silence = channel.detect_silence(-50.0, 0.1);
+    measurements.exact(name("silence.regions"), silence.len());
+    measurements.exact(
+        name("silence.samples"),
+        silence.iter().map(|r| r.len()).sum(),
+    );
+
+    let speech = channel.detect_speech(10.0);
+    measurements.exact(name("speech.regions"), speech.len());
+    measurements.exact(name("speech.samples"), speech.iter().map(|r| r.len()).sum());
+
+    let pattern_start = channel.count() / 3;
+    let pattern = channel.slice(pattern_start..pattern_start + 1000);
+    let detections = channel.find_pattern(&pattern, 0.9);
+    measurements.exact(name("pattern.detections"), detections.len());
+    if let Some(first) = detections.first() {
+        measurements.exact(name("pattern.first"), first.position);
+    }
+
+    let Signal::Mono(attenuated) =
+        Operation::Gain { db: -1.0 }.apply(&Signal::Mono(channel.clone()))
+    else {
+        unreachable!()
+    };
+    let null_test = channel.null_test(&attenuated);
+    measurements.approx(name("null_test.lag"), null_test.lag as f64, 0.0);
+    measurements.approx(
+        name("null_test.rejection_db"),
+        null_test.rejection_db,
+        DB_TOLERANCE,
+    );
+
+    let resampled = channel.resample(44100, ResampleQuality::Standard);
+    measurements.exact(name("resampled.count"), resampled.count());
+    measurements.approx(
+        name("resampled.rms_dbfs"),
+        resampled.statistics().rms_dbfs,
+        DB_TOLERANCE,
+    );
+}
+
+fn measure(signal: &Signal) -> Measurements {
+    let mut measurements = Measurements::default();
+
+    let decoded = Signal::from_wav(signal.to_wav().expect("should encode"))
+        .expect("should decode what was encoded");
+    assert!(
+        decoded == *signal,
+        "decoding should reproduce the encoded signal"
+    );
+
+    for n in 0..decoded.num_channels() {
+        measure_channel(
+            &mut measurements,
+            &format!("channel{n}."),
+            decoded.channel(n),
+        );
+    }
+
+    if let Some(correlation) = decoded.phase_correlation(0..usize::MAX) {
+        measurements.approx("phase_correlation", correlation, RATIO_TOLERANCE);
+    }
+
+    let metrics = decoded.metrics();
+    measurements.approx("duration", metrics.duration, RATIO_TOLERANCE);
+    if let Some(loudness) = metrics.loudness {
+        measurements.approx("loudness", loudness, DB_TOLERANCE);
+    }
+
+    let processed = processing::process(
+        &decoded,
+        &[
+            Operation::Filter(FilterDesign {
+                filter_type: FilterType::HighPass,
+                frequency: 80.0,
+                ..FilterDesign::default()
+            }),
+            Operation::Normalize { peak_db: -3.0 },
+            Operation::FadeIn { seconds: 0.1 },
+        ],
+    );
+    measurements.approx(
+        "processed.rms_dbfs",
+        processed.channel(0).statistics().rms_dbfs,
+        DB_TOLERANCE,
+    );
+
+    measurements
+}
+
+#[test]
+fn analyzers_match_golden_results() {
+    let measured: BTreeMap<_, _> = fixtures()
+        .into_iter()
+        .map(|(name, signal)| (name.to_owned(), measure(&signal).0))
+        .collect();
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        let golden: Golden = measured
+            .iter()
+            .map(|(fixture, measurements)| {
+                let values = measurements
+                    .iter()
+                    .map(|(metric, (value, _))| (metric.clone(), *value))
+                    .collect();
+                (fixture.clone(), values)
+            })
+            .collect();
+        let json = serde_json::to_string_pretty(&golden).expect("should serialize");
+        fs::write(GOLDEN_PATH, json + "\n").expect("should write golden results");
+        return;
+    }
+
+    let golden: Golden =
+        serde_json::from_str(&fs::read_to_string(GOLDEN_PATH).expect("should read golden results"))
+            .expect("golden results should be valid");
+
+    let mut failures = Vec::new();
+    for (fixture, measurements) in &measured {
+        let expected = golden.get(fixture).cloned().unwrap_or_default();
+        for (metric, (value, tolerance)) in measurements {
+            match expected.get(metric) {
+                Some(expected) if (value - expected).abs() <= *tolerance => (),
+                Some(expected) => failures.push(format!(
+                    "{fixture}: {metric} is {value}, expected {expected} ± {tolerance}"
+                )),
+                None => failures.push(format!("{fixture}: {metric} is {value}, expected none")),
+            }
+        }
+        for metric in expected.keys().filter(|m| !measurements.contains_key(*m)) {
+            failures.push(format!("{fixture}: {metric} is missing"));
+        }
+    }
+    for fixture in golden.keys().filter(|f| !measured.contains_key(*f)) {
+        failures.push(format!("{fixture}: fixture is missing"));
+    }
+
+    assert!(
+        failures.is_empty(),
+        "analysis results differ from golden.json (rerun with UPDATE_GOLDEN=1 if intended):\n{}",
+        failures.join("\n"),
+    );
+}
@@ -0,0 +1,74 @@
+use rustfft::num_complex::Complex;
+
+use super::Spectrum;
+
+impl Spectrum {
+    // Whether the bins of the two spectra are for the same frequencies, so that one can be held or
+    // averaged with the other.
+    pub fn same_bins(&self, other: &Spectrum) -> bool {
+        self.len() == other.len() && self.bin_to_frequency(1) == other.bin_to_frequency(1)
+    }
+
+    // The louder of the two spectra in each bin. The phase is lost, so it is only fit for drawing.
+    pub fn peak_hold(&self, held: &Spectrum) -> Spectrum {
+        self.combined(held, f64::max)
+    }
+
+    // An exponential average of the power in each bin, weighing this spectrum by `weight` and the
+    // average of the earlier ones by the rest.
+    pub fn exponential_average(&self, average: &Spectrum, weight: f64) -> Spectrum {
+        self.combined(average, |new, old| {
+            (weight * new * new + (1.0 - weight) * old * old).sqrt()
+        })
+    }
+
+    // The spectrum with each magnitude combined with that of the same bin of `other`, on the scale
+    // of `Spectrum::magnitudes`.
+    fn combined(&self, other: &Spectrum, combine: impl Fn(f64, f64) -> f64) -> Spectrum {
+        let scale = self.num_samples as f64 * self.full_scale;
+        let transform = self
+            .magnitudes()
+            .zip(other.magnitudes())
+            .map(|(new, old)| Complex::new(combine(new, old) * scale, 0.0))
+            .collect();
+        Self {
+            transform,
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn spectra_are_held_and_averaged_bin_by_bin() {
+        let low = Generator::sine(100.0, 0.5).channel().spectrum();
+        let high = Generator::sine(4000.0, 0.5).channel().spectrum();
+        assert!(low.same_bins(&high));
+        let bin = |frequency| low.frequency_to_bin(frequency);
+
+        let held = high.peak_hold(&low);
+        let magnitudes: Vec<_> = held.magnitudes().collect();
+        let expected = low.magnitudes().nth(bin(100.0)).unwrap();
+        assert!((magnitudes[bin(100.0)] - expected).abs() < 1e-12);
+        let expected = high.magnitudes().nth(bin(4000.0)).unwrap();
+        assert!((magnitudes[bin(4000.0)] - expected).abs() < 1e-12);
+
+        let averaged = high.exponential_average(&low, 0.25);
+        let magnitudes: Vec<_> = averaged.magnitudes().collect();
+        let expected = 0.75_f64.sqrt() * low.magnitudes().nth(bin(100.0)).unwrap();
+        assert!(
+            (magnitudes[bin(100.0)] - expected).abs() < 1e-3,
+            "{magnitudes:?}"
+        );
+
+        let welch = Generator::sine(100.0, 0.5).channel().welch(4096, 0.5);
+        assert!(welch
+            .maximum
+            .magnitudes()
+            .zip(welch.spectrum.magnitudes())
+            .all(|(maximum, mean)| maximum >= mean - 1e-12));
+    }
+}
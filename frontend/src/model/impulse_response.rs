@@ -0,0 +1,75 @@
+use rustfft::{num_complex::Complex, FftPlanner};
+
+use super::Channel;
+
+// Floor on the stimulus power, relative to its highest, below which frequencies are not divided by
+// it. This keeps frequencies the stimulus barely covers, such as those outside a sweep, from
+// amplifying whatever noise the recording has there.
+const REGULARIZATION: f64 = 1e-4;
+
+impl Channel {
+    // Impulse response of the system that turned `stimulus` into this recording, by dividing their
+    // spectra. The first `len` samples are kept, which should cover both the latency of the system
+    // and the decay of its response. The result has the format of the recording, so that of a
+    // floating-point one is not clipped.
+    pub fn impulse_response(&self, stimulus: &Channel, len: usize) -> Channel {
+        let fft_len = (self.count() + stimulus.count()).next_power_of_two();
+        let padded = |channel: &Channel| {
+            let mut buffer: Vec<_> = channel.iter_normalized().map(Complex::from).collect();
+            buffer.resize(fft_len, Complex::default());
+            buffer
+        };
+        let mut response = padded(self);
+        let mut stimulus = padded(stimulus);
+
+        let mut planner = FftPlanner::new();
+        bench!(["Calculating impulse response"] => {
+            let forward = planner.plan_fft_forward(fft_len);
+            forward.process(&mut response);
+            forward.process(&mut stimulus);
+
+            let floor = REGULARIZATION * stimulus.iter().map(|x| x.norm_sqr()).fold(0.0, f64::max);
+            for (y, x) in response.iter_mut().zip(&stimulus) {
+                *y = *y * x.conj() / (x.norm_sqr() + floor);
+            }
+            planner.plan_fft_inverse(fft_len).process(&mut response);
+        });
+
+        self.with_normalized_samples(
+            response[..len.min(fft_len)]
+                .iter()
+                .map(|c| c.re / fft_len as f64),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn delay_and_gain_are_recovered() {
+        const DELAY: usize = 100;
+
+        let stimulus = Generator::sweep(20.0, 20_000.0, 0.5)
+            .with_duration(0.5)
+            .channel();
+        let recording = stimulus.with_normalized_samples(
+            std::iter::repeat_n(0.0, DELAY).chain(stimulus.iter_normalized().map(|x| 0.5 * x)),
+        );
+
+        let response: Vec<f64> = recording
+            .impulse_response(&stimulus, 1000)
+            .iter_normalized()
+            .collect();
+        let (peak, level) = response
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.abs().total_cmp(&b.abs()))
+            .unwrap();
+        assert_eq!(peak, DELAY);
+        // The response is band-limited to the sweep, which covers this share of the spectrum.
+        let expected = 0.5 * (20_000.0 - 20.0) / 24_000.0;
+        assert!((level - expected).abs() < 0.02, "peak level is {level}");
+    }
+}
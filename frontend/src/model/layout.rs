@@ -0,0 +1,192 @@
+use super::{Channel, Signal};
+
+// Nonzero samples of which at least this share look like audio for the samples to be taken to be
+// floats despite an integer header.
+const FLOAT_SHARE: f64 = 0.99;
+// Floats outside this magnitude range are unlikely in audio, e.g. integer samples read as floats.
+const MIN_FLOAT: f32 = 1e-30;
+const MAX_FLOAT: f32 = 4.0;
+// Share of nonzero samples that are not numbers or denormal above which floats are taken to be
+// integer samples.
+const BROKEN_FLOAT_SHARE: f64 = 0.05;
+// Samples that fit in 24 bits need to reach this peak to be taken to be 24-bit samples, as quiet
+// 32-bit signals also fit.
+const MIN_24_BIT_PEAK: u32 = 1 << 16;
+
+// How the samples of files with 32-bit containers are read. Some recorders write headers that
+// do not match the samples, which then look like noise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleLayout {
+    Int32,
+    // 24-bit samples in the low bytes, with the high byte as padding.
+    Int24,
+    Float32,
+}
+
+impl SampleLayout {
+    pub const ALL: [Self; 3] = [Self::Int32, Self::Int24, Self::Float32];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Int32 => "32-bit integer",
+            Self::Int24 => "24-bit integer in 32 bits",
+            Self::Float32 => "32-bit float",
+        }
+    }
+}
+
+impl Channel {
+    // The layout of samples stored in 32 bits, or `None` for narrower ones.
+    pub fn layout(&self) -> Option<SampleLayout> {
+        match (self.sample_format, self.bits_per_sample) {
+            (hound::SampleFormat::Int, 17..=24) => Some(SampleLayout::Int24),
+            (hound::SampleFormat::Int, 25..=32) => Some(SampleLayout::Int32),
+            (hound::SampleFormat::Float, 1..=32) => Some(SampleLayout::Float32),
+            _ => None,
+        }
+    }
+
+    // The layout the samples appear to have, from their bit patterns rather than the header.
+    pub fn guess_layout(&self) -> Option<SampleLayout> {
+        let declared = self.layout()?;
+        let words: Vec<u32> = self.words().filter(|&word| word != 0).collect();
+        if words.is_empty() {
+            return Some(declared);
+        }
+        let share = |f: fn(u32) -> bool| {
+            words.iter().filter(|&&word| f(word)).count() as f64 / words.len() as f64
+        };
+
+        if declared == SampleLayout::Float32 {
+            let broken = share(|word| {
+                let x = f32::from_bits(word).abs();
+                !x.is_finite() || x < MIN_FLOAT
+            });
+            return Some(if broken > BROKEN_FLOAT_SHARE {
+                SampleLayout::Int32
+            } else {
+                declared
+            });
+        }
+
+        let floats = share(|word| (MIN_FLOAT..=MAX_FLOAT).contains(&f32::from_bits(word).abs()));
+        if floats >= FLOAT_SHARE {
+            return Some(SampleLayout::Float32);
+        }
+        // The high byte of 24-bit samples only repeats the sign of the rest.
+        let fits_24_bits = words
+            .iter()
+            .all(|&word| sign_extend_24(word) as u32 == word);
+        let peak = words.iter().map(|&word| (word as i32).unsigned_abs()).max();
+        if declared == SampleLayout::Int32
+            && fits_24_bits
+            && peak.is_some_and(|peak| peak >= MIN_24_BIT_PEAK)
+        {
+            return Some(SampleLayout::Int24);
+        }
+        Some(declared)
+    }
+
+    // Reads the stored bits as `layout`, or returns the channel as is if it has no 32-bit samples.
+    pub fn with_layout(&self, layout: SampleLayout) -> Channel {
+        if self.layout().is_none() {
+            return self.clone();
+        }
+        let words = self.words();
+        match layout {
+            SampleLayout::Int32 => {
+                Channel::from_samples_i32(words.map(|word| word as i32), 32, self.sample_rate)
+            }
+            SampleLayout::Int24 => {
+                Channel::from_samples_i32(words.map(sign_extend_24), 24, self.sample_rate)
+            }
+            SampleLayout::Float32 => Channel::from_samples_f32(
+                words
+                    .map(f32::from_bits)
+                    .map(|x| if x.is_finite() { x } else { 0.0 }),
+                32,
+                self.sample_rate,
+            ),
+        }
+    }
+
+    // The stored bits of 32-bit samples.
+    fn words(&self) -> impl Iterator<Item = u32> + '_ {
+        let mut bytes = self.data.iter().copied();
+        std::iter::from_fn(move || {
+            Some(u32::from_le_bytes([
+                bytes.next()?,
+                bytes.next()?,
+                bytes.next()?,
+                bytes.next()?,
+            ]))
+        })
+    }
+}
+
+impl Signal {
+    pub fn layout(&self) -> Option<SampleLayout> {
+        self.channel(0).layout()
+    }
+
+    // The layout guessed for all channels, or `None` if they disagree.
+    pub fn guess_layout(&self) -> Option<SampleLayout> {
+        let first = self.channel(0).guess_layout();
+        (1..self.num_channels())
+            .all(|n| self.channel(n).guess_layout() == first)
+            .then_some(first)
+            .flatten()
+    }
+
+    pub fn with_layout(&self, layout: SampleLayout) -> Signal {
+        self.map_channels(|channel| channel.with_layout(layout))
+    }
+}
+
+fn sign_extend_24(word: u32) -> i32 {
+    ((word << 8) as i32) >> 8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SampleLayout;
+    use crate::model::Channel;
+
+    fn sine(amplitude: f64) -> impl Iterator<Item = f64> {
+        (0..4800).map(move |n| amplitude * (n as f64 * 0.05).sin())
+    }
+
+    #[test]
+    fn mislabelled_samples_are_detected() {
+        let floats_as_ints =
+            Channel::from_samples_i32(sine(0.5).map(|x| (x as f32).to_bits() as i32), 32, 48000);
+        assert_eq!(floats_as_ints.guess_layout(), Some(SampleLayout::Float32));
+        let floats = floats_as_ints.with_layout(SampleLayout::Float32);
+        assert!(floats
+            .iter()
+            .zip(sine(0.5))
+            .all(|(a, b)| f64::from(a) == f64::from(b as f32)));
+
+        let ints_as_floats = Channel::from_samples_f32(
+            sine(0.5).map(|x| f32::from_bits((x * f64::from(i32::MAX)) as i32 as u32)),
+            32,
+            48000,
+        );
+        assert_eq!(ints_as_floats.guess_layout(), Some(SampleLayout::Int32));
+
+        let padded_24 =
+            Channel::from_samples_i32(sine(0.5).map(|x| (x * 8388607.0) as i32), 32, 48000);
+        assert_eq!(padded_24.guess_layout(), Some(SampleLayout::Int24));
+
+        let ints = Channel::from_samples_i32(
+            sine(0.5).map(|x| (x * f64::from(i32::MAX)) as i32),
+            32,
+            48000,
+        );
+        assert_eq!(ints.guess_layout(), Some(SampleLayout::Int32));
+        let floats = Channel::from_samples_f32(sine(0.5).map(|x| x as f32), 32, 48000);
+        assert_eq!(floats.guess_layout(), Some(SampleLayout::Float32));
+        let ints_16 = Channel::from_samples_i16(sine(0.5).map(|x| (x * 32767.0) as i16), 16, 48000);
+        assert_eq!(ints_16.guess_layout(), None);
+    }
+}
@@ -0,0 +1,79 @@
+use super::Channel;
+
+pub const FLOOR_DB: f64 = -120.0;
+
+const NOISE_FLOOR_FRAME_SECONDS: f64 = 0.05;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelCurve {
+    pub window: usize,
+    pub hop: usize,
+    pub rms_db: Vec<f64>,
+    pub peak_db: Vec<f64>,
+}
+
+impl LevelCurve {
+    pub fn frame_center(&self, frame: usize) -> usize {
+        frame * self.hop + self.window / 2
+    }
+
+    // Median difference between peak and RMS level over the frames with an RMS level above
+    // `gate_db`, so that silence does not skew the result.
+    pub fn median_crest_factor(&self, gate_db: f64) -> Option<f64> {
+        let mut crest_factors: Vec<f64> = self
+            .rms_db
+            .iter()
+            .zip(&self.peak_db)
+            .filter(|(&rms, _)| rms > gate_db)
+            .map(|(rms, peak)| peak - rms)
+            .collect();
+        crest_factors.sort_by(f64::total_cmp);
+        crest_factors.get(crest_factors.len() / 2).copied()
+    }
+}
+
+pub fn to_dbfs(value: f64) -> f64 {
+    (20.0 * value.log10()).max(FLOOR_DB)
+}
+
+impl Channel {
+    // Level exceeded by all but the quietest tenth of 50 ms frames, which approximates the
+    // background noise for material with some pauses in it.
+    pub fn noise_floor_dbfs(&self) -> f64 {
+        let frame = ((NOISE_FLOOR_FRAME_SECONDS * self.sample_rate as f64) as usize).max(1);
+        let mut rms_db = self.levels(frame, frame).rms_db;
+        rms_db.sort_by(f64::total_cmp);
+        rms_db.get(rms_db.len() / 10).copied().unwrap_or(FLOOR_DB)
+    }
+
+    pub fn levels(&self, window: usize, hop: usize) -> LevelCurve {
+        let window = window.max(1);
+        let hop = hop.max(1);
+        let samples: Vec<f64> = self.iter_normalized().collect();
+
+        let mut square_sums = Vec::with_capacity(samples.len() + 1);
+        square_sums.push(0.0);
+        for x in &samples {
+            square_sums.push(square_sums[square_sums.len() - 1] + x * x);
+        }
+
+        let (rms_db, peak_db) = (0..samples.len().saturating_sub(window - 1))
+            .step_by(hop)
+            .map(|start| {
+                let end = start + window;
+                let mean_square = (square_sums[end] - square_sums[start]).max(0.0) / window as f64;
+                let peak = samples[start..end]
+                    .iter()
+                    .fold(0.0_f64, |peak, x| peak.max(x.abs()));
+                (to_dbfs(mean_square.sqrt()), to_dbfs(peak))
+            })
+            .unzip();
+
+        LevelCurve {
+            window,
+            hop,
+            rms_db,
+            peak_db,
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use signal_inspector_analysis as analysis;
+
+use super::{levels::FLOOR_DB, Signal};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Loudness {
+    // Gated integrated loudness in LUFS, or `None` if the signal is too short or too quiet.
+    pub integrated: Option<f64>,
+    pub true_peak_dbtp: f64,
+}
+
+impl Loudness {
+    // Gain that brings the integrated loudness to `target_lufs` without the true peak exceeding
+    // `ceiling_dbtp`, and whether it is the ceiling that limits it.
+    pub fn recommended_gain(&self, target_lufs: f64, ceiling_dbtp: f64) -> (f64, bool) {
+        let peak_gain = ceiling_dbtp - self.true_peak_dbtp;
+        match self.integrated {
+            Some(integrated) if target_lufs - integrated <= peak_gain => {
+                (target_lufs - integrated, false)
+            }
+            _ => (peak_gain, true),
+        }
+    }
+}
+
+impl Signal {
+    // Measured by the analysis crate, as the backend and command-line tool measure it.
+    pub fn loudness(&self) -> Loudness {
+        let channels: Vec<Vec<f64>> = (0..self.num_channels())
+            .map(|n| self.channel(n).iter_normalized().collect())
+            .collect();
+        let sample_rate = self.channel(0).sample_rate();
+        Loudness {
+            integrated: analysis::gated_loudness(&analysis::block_powers(&channels, sample_rate)),
+            true_peak_dbtp: channels
+                .iter()
+                .map(|channel| analysis::true_peak_dbtp(channel))
+                .fold(FLOOR_DB, f64::max),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::PI, io::Cursor};
+
+    use hound::{SampleFormat, WavSpec, WavWriter};
+    use signal_inspector_analysis as analysis;
+
+    use crate::model::Signal;
+
+    #[test]
+    fn float_files_measure_as_on_the_server() {
+        // A 997 Hz sine at half of full scale, stored as floats between -1.0 and 1.0.
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate: 48000,
+            bits_per_sample: 32,
+            sample_format: SampleFormat::Float,
+        };
+        let mut wav = Cursor::new(Vec::new());
+        let mut writer = WavWriter::new(&mut wav, spec).expect("should write header");
+        for n in 0..2 * spec.sample_rate {
+            let t = f64::from(n) / f64::from(spec.sample_rate);
+            let sample = 0.5 * (2.0 * PI * 997.0 * t).sin();
+            writer
+                .write_sample(sample as f32)
+                .expect("should write sample");
+        }
+        writer.finalize().expect("should finalize");
+        let wav = wav.into_inner();
+
+        let path = std::env::temp_dir().join(format!("float-test-{}.wav", std::process::id()));
+        std::fs::write(&path, &wav).expect("should write file");
+        let decoded = analysis::decode(&path);
+        std::fs::remove_file(&path).ok();
+        let (channels, sample_rate) = decoded.expect("should decode on the server");
+        let server = analysis::loudness(&channels, sample_rate);
+
+        let browser = Signal::from_wav(wav)
+            .expect("should decode in the browser")
+            .loudness();
+        let integrated = browser.integrated.expect("should be loud enough");
+        assert!((integrated + 9.03).abs() < 0.05, "{integrated}");
+        let server_integrated = server.integrated_lufs.expect("should be loud enough");
+        assert!((integrated - server_integrated).abs() < 1e-6);
+        assert!((browser.true_peak_dbtp + 6.02).abs() < 0.1);
+        assert!((browser.true_peak_dbtp - server.true_peak_dbtp).abs() < 1e-6);
+    }
+}
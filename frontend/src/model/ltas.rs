@@ -0,0 +1,92 @@
+use super::{levels::to_dbfs, Channel, Spectrum};
+
+const SEGMENT_LEN: usize = 4096;
+const OVERLAP: f64 = 0.5;
+// Equivalent noise bandwidth of the Hann window, in bins.
+pub(super) const HANN_ENBW: f64 = 1.5;
+// Range of the band centre frequencies.
+const LOWEST_CENTER: f64 = 25.0;
+const HIGHEST_CENTER: f64 = 20_000.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Band {
+    pub center: f64,
+    pub lower: f64,
+    pub upper: f64,
+    pub level_db: f64,
+}
+
+impl Band {
+    // Centre frequency rounded to the preferred value of IEC 61260, for labels.
+    pub fn nominal_center(&self) -> f64 {
+        let magnitude = 10_f64.powf(self.center.log10().floor());
+        let mantissa = self.center / magnitude;
+        let preferred = [1.0, 1.25, 1.6, 2.0, 2.5, 3.15, 4.0, 5.0, 6.3, 8.0, 10.0]
+            .into_iter()
+            .min_by(|a, b| (a - mantissa).abs().total_cmp(&(b - mantissa).abs()))
+            .unwrap_or(mantissa);
+        preferred * magnitude
+    }
+}
+
+// Long-term average spectrum in fractional-octave bands, with band levels in dBFS (RMS).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ltas {
+    pub bands_per_octave: u32,
+    pub bands: Vec<Band>,
+}
+
+impl Channel {
+    // Averages the power spectrum over the whole channel (Welch's method) and integrates it within
+    // base-10 bands of 1/1 or 1/3 octave, with the centre frequencies of IEC 61260 from 31.5 or
+    // 25 Hz up to 16 or 20 kHz. Bands above the Nyquist frequency are left out.
+    pub fn ltas(&self, bands_per_octave: u32) -> Ltas {
+        let bands_per_octave = bands_per_octave.max(1);
+        let (spectrum, powers) = self.average_powers();
+        let nyquist = self.sample_rate as f64 / 2.0;
+
+        // Band numbers relative to 1 kHz, with an octave ratio of 10^(3/10).
+        let band_number =
+            |frequency: f64| frequency.log10() * f64::from(10 * bands_per_octave) / 3.0;
+        let first = band_number(LOWEST_CENTER / 1000.0).ceil() as i32;
+        let last = band_number(HIGHEST_CENTER / 1000.0).floor() as i32;
+
+        let bands = (first..=last)
+            .map(|n| {
+                let octaves = f64::from(n) / f64::from(bands_per_octave);
+                let center = 1000.0 * 10_f64.powf(0.3 * octaves);
+                let half_width = 10_f64.powf(0.15 / f64::from(bands_per_octave));
+                (center, center / half_width, center * half_width)
+            })
+            .take_while(|&(_, _, upper)| upper <= nyquist)
+            .map(|(center, lower, upper)| Band {
+                center,
+                lower,
+                upper,
+                level_db: to_dbfs(band_power(&spectrum, &powers, lower, upper).sqrt()),
+            })
+            .collect();
+
+        Ltas {
+            bands_per_octave,
+            bands,
+        }
+    }
+
+    // Power spectrum averaged over the whole channel, per bin relative to a full-scale sine, which
+    // has a bin magnitude of 1/2 and a mean square of 1/2 relative to full scale.
+    pub(super) fn average_powers(&self) -> (Spectrum, Vec<f64>) {
+        let spectrum = self.welch(SEGMENT_LEN, OVERLAP).spectrum;
+        let powers = spectrum.magnitudes().map(|m| 2.0 * m * m).collect();
+        (spectrum, powers)
+    }
+}
+
+// Power between `lower` and `upper` Hz, as a mean square relative to full scale.
+pub(super) fn band_power(spectrum: &Spectrum, powers: &[f64], lower: f64, upper: f64) -> f64 {
+    let bins = spectrum.frequency_to_bin(lower)..spectrum.frequency_to_bin(upper);
+    powers
+        .get(bins.start.min(powers.len())..bins.end.min(powers.len()))
+        .map_or(0.0, |band| band.iter().sum::<f64>())
+        / HANN_ENBW
+}
@@ -0,0 +1,36 @@
+use super::Signal;
+
+// Key measurements for validating a batch of deliverables against each other.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Metrics {
+    pub duration: f64,
+    pub loudness: Option<f64>,
+    pub true_peak_dbtp: f64,
+    pub centroid: f64,
+    pub noise_floor_dbfs: f64,
+}
+
+impl Signal {
+    // For multichannel signals, the centroid is averaged across channels while the noise floor is
+    // that of the noisiest one.
+    pub fn metrics(&self) -> Metrics {
+        let channels: Vec<_> = (0..self.num_channels()).map(|n| self.channel(n)).collect();
+        let first = channels[0];
+        let loudness = self.loudness();
+
+        Metrics {
+            duration: first.count() as f64 / first.sample_rate() as f64,
+            loudness: loudness.integrated,
+            true_peak_dbtp: loudness.true_peak_dbtp,
+            centroid: channels
+                .iter()
+                .map(|channel| channel.spectrum().centroid())
+                .sum::<f64>()
+                / channels.len() as f64,
+            noise_floor_dbfs: channels
+                .iter()
+                .map(|channel| channel.noise_floor_dbfs())
+                .fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use super::{
+    levels::{to_dbfs, FLOOR_DB},
+    ltas::HANN_ENBW,
+    Channel,
+};
+
+// How far the signal stands out from the noise under it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Noise {
+    // The level of the noise, as the RMS level of a channel with nothing else in it.
+    pub floor_dbfs: f64,
+    // The power of the channel less that of the noise, relative to the noise.
+    pub snr_db: f64,
+    // From the noise floor up to the sample peak.
+    pub dynamic_range_db: f64,
+}
+
+impl Channel {
+    // The noise floor taken from the median of the long-term average spectrum, which tones and
+    // their harmonics, taking up few bins, hardly move. Unlike the quietest frames, it needs no
+    // pauses in the material, but assumes the noise to be about white.
+    pub fn spectral_noise_floor_dbfs(&self) -> f64 {
+        let (_, mut powers) = self.average_powers();
+        if powers.len() < 2 {
+            return FLOOR_DB;
+        }
+        // DC is left out, as any offset would otherwise count as noise.
+        powers.remove(0);
+        powers.sort_by(f64::total_cmp);
+        let median = powers[powers.len() / 2];
+        to_dbfs((median * powers.len() as f64 / HANN_ENBW).sqrt())
+    }
+
+    // The signal-to-noise ratio and dynamic range of the channel over a noise floor of
+    // `floor_dbfs`.
+    pub fn noise(&self, floor_dbfs: f64) -> Noise {
+        let statistics = self.statistics();
+        let power = |dbfs: f64| 10_f64.powf(dbfs / 10.0);
+        let signal_power = (power(statistics.rms_dbfs) - power(floor_dbfs)).max(0.0);
+        Noise {
+            floor_dbfs,
+            snr_db: (10.0 * (signal_power / power(floor_dbfs)).log10()).max(FLOOR_DB),
+            dynamic_range_db: statistics.peak_dbfs - floor_dbfs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn noise_under_a_tone_is_measured() {
+        let noise = Generator::white_noise(0.01).channel();
+        let tone = Generator::sine(1000.0, 0.5).channel();
+        let mix = tone.with_normalized_samples(
+            tone.iter_normalized()
+                .zip(noise.iter_normalized())
+                .map(|(a, b)| a + b),
+        );
+        let noise_dbfs = noise.statistics().rms_dbfs;
+
+        let floor = mix.spectral_noise_floor_dbfs();
+        assert!((floor - noise_dbfs).abs() < 0.5, "{floor} {noise_dbfs}");
+
+        let measured = mix.noise(noise_dbfs);
+        let tone_dbfs = tone.statistics().rms_dbfs;
+        assert!(
+            (measured.snr_db - (tone_dbfs - noise_dbfs)).abs() < 0.2,
+            "{measured:?}"
+        );
+        assert!(
+            (measured.dynamic_range_db - (mix.statistics().peak_dbfs - noise_dbfs)).abs() < 1e-9
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use super::{correlation::cross_correlate, levels::to_dbfs, Channel};
+
+#[derive(Clone, PartialEq)]
+pub struct NullTest {
+    pub lag: isize,
+    pub residual: Channel,
+    pub residual_dbfs: f64,
+    pub rejection_db: f64,
+}
+
+impl Channel {
+    // Aligns `test` to this channel using the peak of their cross-correlation, and subtracts it.
+    // A positive lag means that the test signal is delayed relative to this one.
+    pub fn null_test(&self, test: &Channel) -> NullTest {
+        let reference: Vec<f64> = self.iter_normalized().collect();
+        let test: Vec<f64> = test.iter_normalized().collect();
+
+        let correlation = cross_correlate(&test, &reference);
+        let lag = correlation
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(0, |(i, _)| i as isize - (reference.len() as isize - 1));
+
+        // Only the region where both signals overlap after alignment takes part in the test.
+        let start = (-lag).max(0) as usize;
+        let end = (test.len() as isize - lag).clamp(start as isize, reference.len() as isize);
+        let overlap = start.min(reference.len())..end as usize;
+
+        let residual: Vec<f64> = reference
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                if overlap.contains(&i) {
+                    r - test[(i as isize + lag) as usize]
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        let rms = |data: &[f64]| {
+            (data.iter().map(|x| x * x).sum::<f64>() / data.len().max(1) as f64).sqrt()
+        };
+        let residual_rms = rms(&residual[overlap.clone()]);
+        let reference_rms = rms(&reference[overlap]);
+
+        NullTest {
+            lag,
+            residual: self.with_normalized_samples(residual),
+            residual_dbfs: to_dbfs(residual_rms),
+            rejection_db: to_dbfs(residual_rms / reference_rms),
+        }
+    }
+}
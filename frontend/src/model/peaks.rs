@@ -0,0 +1,287 @@
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use super::{levels::to_dbfs, Spectrum};
+
+// Peaks are the highest bin within this many bins either side of them.
+const NEIGHBOURHOOD: usize = 3;
+// Peaks further below the highest one than this are left out as noise.
+const RANGE_DB: f64 = 80.0;
+// Harmonics may be off by this share of their expected frequency, or by this many bins if that is
+// more, as the peaks are only located to within a fraction of a bin.
+const HARMONIC_TOLERANCE: f64 = 0.005;
+const HARMONIC_TOLERANCE_BINS: f64 = 2.0;
+// A chosen fundamental is moved to the highest bin within this share of its frequency, as it is
+// picked with the pointer on a logarithmic axis.
+const FUNDAMENTAL_TOLERANCE: f64 = 0.03;
+
+// A local maximum of the spectrum, grouped with the peak it is a harmonic of.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpectralPeak {
+    pub frequency: f64,
+    // In dBFS, relative to a full-scale sine.
+    pub level_db: f64,
+    // The number of the fundamental of the group, counting from one in order of level.
+    pub fundamental: usize,
+    // One for the fundamental itself.
+    pub harmonic: u32,
+    // Relative to the fundamental.
+    pub relative_db: f64,
+}
+
+// A multiple of a chosen fundamental, measured at the highest bin near where it is expected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Harmonic {
+    // One for the fundamental itself.
+    pub number: u32,
+    pub frequency: f64,
+    // In dBFS, relative to a full-scale sine.
+    pub level_db: f64,
+    // Relative to the fundamental.
+    pub relative_db: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeakFormat {
+    Csv,
+    Json,
+}
+
+impl PeakFormat {
+    pub const ALL: [Self; 2] = [Self::Csv, Self::Json];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Csv => "CSV",
+            Self::Json => "JSON",
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Csv => "peaks.csv",
+            Self::Json => "peaks.json",
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Json => "application/json",
+        }
+    }
+}
+
+impl Spectrum {
+    // The `max_peaks` highest peaks, the highest first. Each peak not at a multiple of a higher one
+    // is the fundamental of a group, which the lower peaks at its multiples are harmonics of.
+    pub fn peaks(&self, max_peaks: usize) -> Vec<SpectralPeak> {
+        let levels: Vec<f64> = self.magnitudes().map(|m| to_dbfs(2.0 * m)).collect();
+        let Some(highest) = levels.iter().copied().reduce(f64::max) else {
+            return Vec::new();
+        };
+
+        let mut candidates: Vec<(f64, f64)> = (1..levels.len().saturating_sub(1))
+            .filter(|&bin| levels[bin] >= highest - RANGE_DB)
+            .filter(|&bin| {
+                let neighbours = bin.saturating_sub(NEIGHBOURHOOD)..(bin + NEIGHBOURHOOD + 1);
+                levels[neighbours.start..neighbours.end.min(levels.len())]
+                    .iter()
+                    .enumerate()
+                    .all(|(n, &level)| {
+                        // Of equal bins on a plateau, only the first is a peak.
+                        let neighbour = neighbours.start + n;
+                        level < levels[bin] || (level == levels[bin] && neighbour >= bin)
+                    })
+            })
+            .map(|bin| self.interpolate(&levels, bin))
+            .collect();
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+        candidates.truncate(max_peaks);
+
+        let bin_width = self.bin_to_frequency(1);
+        let mut peaks: Vec<SpectralPeak> = Vec::with_capacity(candidates.len());
+        let mut fundamentals = 0;
+        for (frequency, level_db) in candidates {
+            let harmonic_of =
+                peaks
+                    .iter()
+                    .filter(|peak| peak.harmonic == 1)
+                    .find_map(|fundamental| {
+                        let harmonic = (frequency / fundamental.frequency).round();
+                        let expected = harmonic * fundamental.frequency;
+                        let tolerance = (HARMONIC_TOLERANCE * expected)
+                            .max(HARMONIC_TOLERANCE_BINS * bin_width);
+                        (harmonic >= 2.0 && (frequency - expected).abs() <= tolerance)
+                            .then_some((fundamental, harmonic as u32))
+                    });
+            let (fundamental, harmonic, relative_db) = match harmonic_of {
+                Some((fundamental, harmonic)) => (
+                    fundamental.fundamental,
+                    harmonic,
+                    level_db - fundamental.level_db,
+                ),
+                None => {
+                    fundamentals += 1;
+                    (fundamentals, 1, 0.0)
+                }
+            };
+            peaks.push(SpectralPeak {
+                frequency,
+                level_db,
+                fundamental,
+                harmonic,
+                relative_db,
+            });
+        }
+        peaks
+    }
+
+    // The fundamental nearest `frequency` and its first `count` multiples, those up to the Nyquist
+    // frequency, for reading off distortion products.
+    pub fn harmonics_of(&self, frequency: f64, count: u32) -> Vec<Harmonic> {
+        let levels: Vec<f64> = self.magnitudes().map(|m| to_dbfs(2.0 * m)).collect();
+        if levels.len() < 3 {
+            return Vec::new();
+        }
+        let bin_width = self.bin_to_frequency(1);
+        // The highest bin within `tolerance` of `frequency`, at its interpolated frequency and level.
+        let measure = |frequency: f64, tolerance: f64| {
+            let lowest = self.frequency_to_bin(frequency - tolerance).max(1);
+            let highest = self.frequency_to_bin(frequency + tolerance);
+            let bin = (lowest..=highest.min(levels.len() - 2))
+                .max_by(|&a, &b| levels[a].total_cmp(&levels[b]))?;
+            Some(self.interpolate(&levels, bin))
+        };
+
+        let Some((fundamental, fundamental_db)) =
+            measure(frequency, FUNDAMENTAL_TOLERANCE * frequency)
+        else {
+            return Vec::new();
+        };
+        let nyquist = self.sample_rate as f64 / 2.0;
+        (1..=count)
+            .map_while(|number| {
+                let expected = f64::from(number) * fundamental;
+                let tolerance =
+                    (HARMONIC_TOLERANCE * expected).max(HARMONIC_TOLERANCE_BINS * bin_width);
+                let (frequency, level_db) =
+                    measure(expected, tolerance).filter(|_| expected < nyquist)?;
+                Some(Harmonic {
+                    number,
+                    frequency,
+                    level_db,
+                    relative_db: level_db - fundamental_db,
+                })
+            })
+            .collect()
+    }
+
+    // The frequency and level of the top of the parabola through the peak and its neighbours.
+    fn interpolate(&self, levels: &[f64], bin: usize) -> (f64, f64) {
+        let (left, center, right) = (levels[bin - 1], levels[bin], levels[bin + 1]);
+        let curvature = left - 2.0 * center + right;
+        let offset = if curvature < 0.0 {
+            (0.5 * (left - right) / curvature).clamp(-0.5, 0.5)
+        } else {
+            0.0
+        };
+        let frequency = self.bin_to_frequency(bin) + offset * self.bin_to_frequency(1);
+        (frequency, center - 0.25 * (left - right) * offset)
+    }
+}
+
+// The total harmonic distortion, as the RMS sum of the harmonics relative to the fundamental, or
+// `None` without any harmonics to sum.
+pub fn total_harmonic_distortion(harmonics: &[Harmonic]) -> Option<f64> {
+    let powers: Vec<f64> = harmonics
+        .iter()
+        .filter(|harmonic| harmonic.number > 1)
+        .map(|harmonic| 10_f64.powf(harmonic.relative_db / 10.0))
+        .collect();
+    (!powers.is_empty()).then(|| powers.iter().sum::<f64>().sqrt())
+}
+
+pub fn export_peaks(peaks: &[SpectralPeak], format: PeakFormat) -> String {
+    match format {
+        PeakFormat::Csv => {
+            let mut out =
+                String::from("frequency_hz,level_dbfs,fundamental,harmonic,relative_db\n");
+            for peak in peaks {
+                let _ = writeln!(
+                    out,
+                    "{:.3},{:.2},{},{},{:.2}",
+                    peak.frequency,
+                    peak.level_db,
+                    peak.fundamental,
+                    peak.harmonic,
+                    peak.relative_db,
+                );
+            }
+            out
+        }
+        PeakFormat::Json => serde_json::to_string_pretty(peaks).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::total_harmonic_distortion;
+    use crate::model::Channel;
+
+    #[test]
+    fn harmonics_are_grouped() {
+        const SAMPLE_RATE: u32 = 48000;
+        // A tone at 1 kHz with harmonics 20 and 40 dB down, along with an unrelated one at 1.7 kHz.
+        let components = [
+            (1000.0, 0.5),
+            (2000.0, 0.05),
+            (3000.0, 0.005),
+            (1700.0, 0.1),
+        ];
+        let samples = (0..SAMPLE_RATE).map(|n| {
+            let t = f64::from(n) / f64::from(SAMPLE_RATE);
+            let x: f64 = components
+                .iter()
+                .map(|(frequency, amplitude)| amplitude * (2.0 * PI * frequency * t).sin())
+                .sum();
+            (x * f64::from(i32::MAX)).round() as i32
+        });
+        let spectrum = Channel::from_samples_i32(samples, 32, SAMPLE_RATE).spectrum();
+
+        let peaks = spectrum.peaks(4);
+        let summary: Vec<_> = peaks
+            .iter()
+            .map(|peak| (peak.frequency.round(), peak.fundamental, peak.harmonic))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (1000.0, 1, 1),
+                (1700.0, 2, 1),
+                (2000.0, 1, 2),
+                (3000.0, 1, 3)
+            ]
+        );
+        assert!((peaks[0].level_db + 6.02).abs() < 0.01);
+        assert!((peaks[2].relative_db + 20.0).abs() < 0.01);
+        assert!((peaks[3].relative_db + 40.0).abs() < 0.01);
+
+        // Picked a little off, the fundamental is found all the same.
+        let harmonics = spectrum.harmonics_of(1020.0, 3);
+        let summary: Vec<_> = harmonics
+            .iter()
+            .map(|harmonic| (harmonic.number, harmonic.frequency.round()))
+            .collect();
+        assert_eq!(summary, [(1, 1000.0), (2, 2000.0), (3, 3000.0)]);
+        assert!((harmonics[1].relative_db + 20.0).abs() < 0.01);
+        assert!((harmonics[2].relative_db + 40.0).abs() < 0.01);
+        assert_eq!(spectrum.harmonics_of(1000.0, 100).len(), 23);
+        let thd = total_harmonic_distortion(&harmonics).unwrap();
+        assert!((thd - 0.0101_f64.sqrt()).abs() < 1e-4, "{thd}");
+        assert_eq!(total_harmonic_distortion(&harmonics[..1]), None);
+    }
+}
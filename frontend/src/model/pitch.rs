@@ -0,0 +1,249 @@
+use std::{f64::consts::PI, ops::Range};
+
+use rustfft::{num_complex::Complex, FftPlanner};
+
+use super::Channel;
+
+// Range of fundamental frequencies searched for.
+const MIN_FREQUENCY: f64 = 50.0;
+const MAX_FREQUENCY: f64 = 2000.0;
+// Longer channels are analysed from the start only, which is plenty for a stable estimate.
+const MAX_SAMPLES: usize = 1 << 16;
+// Peaks within this share of the highest one count as candidates, of which the shortest lag wins,
+// so that the period is not mistaken for a multiple of it.
+const CANDIDATE_RATIO: f64 = 0.9;
+
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C♯", "D", "D♯", "E", "F", "F♯", "G", "G♯", "A", "A♯", "B",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PitchMethod {
+    Autocorrelation,
+    Cepstrum,
+}
+
+impl PitchMethod {
+    pub const ALL: [Self; 2] = [Self::Autocorrelation, Self::Cepstrum];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Autocorrelation => "Autocorrelation",
+            Self::Cepstrum => "Cepstrum",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pitch {
+    pub frequency: f64,
+    // Between 0 and 1.
+    pub confidence: f64,
+}
+
+impl Pitch {
+    // The nearest note in twelve-tone equal temperament with A4 at 440 Hz, and the deviation from
+    // it in cents.
+    pub fn note(&self) -> (String, f64) {
+        let midi = 69.0 + 12.0 * (self.frequency / 440.0).log2();
+        let nearest = midi.round();
+        let name = NOTE_NAMES[(nearest as i64).rem_euclid(12) as usize];
+        let octave = (nearest as i64).div_euclid(12) - 1;
+        (format!("{name}{octave}"), (midi - nearest) * 100.0)
+    }
+}
+
+// Pitch estimates of consecutive, overlapping frames.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PitchTrack {
+    // Position of the first frame.
+    pub start: usize,
+    pub frame_len: usize,
+    pub hop: usize,
+    pub pitches: Vec<Option<Pitch>>,
+}
+
+impl PitchTrack {
+    pub fn frame_center(&self, frame: usize) -> usize {
+        self.start + frame * self.hop + self.frame_len / 2
+    }
+}
+
+impl Channel {
+    // Estimates the fundamental frequency between 50 Hz and 2 kHz, or returns `None` for silence
+    // and for channels shorter than two periods of the lowest frequency.
+    pub fn pitch(&self, method: PitchMethod) -> Option<Pitch> {
+        let samples: Vec<f64> = self.iter_normalized().take(MAX_SAMPLES).collect();
+        bench!(["Calculating pitch"] => estimate(&samples, self.sample_rate, method))
+    }
+
+    // Autocorrelation pitch of frames `hop` samples apart within `range`, each two periods of the
+    // lowest frequency long.
+    pub fn pitch_track(&self, range: Range<usize>, hop: usize) -> PitchTrack {
+        let frame_len = 2 * search_lags(self.sample_rate).end;
+        let hop = hop.max(1);
+        let range = range.start.min(self.count())..range.end.min(self.count());
+        let samples: Vec<f64> = self.slice(range.clone()).iter_normalized().collect();
+
+        let pitches = bench!(["Calculating pitch track"] => (0..)
+            .map(|frame| frame * hop)
+            .take_while(|offset| offset + frame_len <= samples.len())
+            .map(|offset| {
+                estimate(
+                    &samples[offset..offset + frame_len],
+                    self.sample_rate,
+                    PitchMethod::Autocorrelation,
+                )
+            })
+            .collect());
+
+        PitchTrack {
+            start: range.start,
+            frame_len,
+            hop,
+            pitches,
+        }
+    }
+}
+
+// Lags, in samples, of the periods of the frequencies searched for.
+fn search_lags(sample_rate: u32) -> Range<usize> {
+    let sample_rate = f64::from(sample_rate);
+    (sample_rate / MAX_FREQUENCY).floor() as usize..(sample_rate / MIN_FREQUENCY).ceil() as usize
+}
+
+fn estimate(samples: &[f64], sample_rate: u32, method: PitchMethod) -> Option<Pitch> {
+    let lags = search_lags(sample_rate);
+    if samples.len() < 2 * lags.end || samples.iter().all(|&x| x == 0.0) {
+        return None;
+    }
+
+    let (period, confidence) = match method {
+        PitchMethod::Autocorrelation => autocorrelation_period(samples, lags)?,
+        PitchMethod::Cepstrum => cepstrum_period(samples, lags)?,
+    };
+
+    Some(Pitch {
+        frequency: f64::from(sample_rate) / period,
+        confidence: confidence.clamp(0.0, 1.0),
+    })
+}
+
+// The period in samples is the lag at which the channel best matches itself. Each lag is normalized
+// by the number of overlapping samples, and the confidence is the correlation at the period.
+fn autocorrelation_period(samples: &[f64], lags: Range<usize>) -> Option<(f64, f64)> {
+    let len = samples.len();
+    let correlation = autocorrelate(samples);
+    let energy = correlation[0];
+    let normalized: Vec<f64> = (0..lags.end + 1)
+        .map(|lag| correlation[lag] / energy * len as f64 / (len - lag) as f64)
+        .collect();
+
+    let highest = peaks(&normalized, lags.clone())
+        .map(|lag| normalized[lag])
+        .fold(0.0, f64::max);
+    let lag = peaks(&normalized, lags)
+        .find(|&lag| normalized[lag] >= CANDIDATE_RATIO * highest && highest > 0.0)?;
+
+    Some((
+        lag as f64 + parabolic_offset(&normalized, lag),
+        normalized[lag],
+    ))
+}
+
+// Non-negative lags of the autocorrelation, as the inverse transform of the power spectrum. The
+// padding to twice the length keeps the circular correlation from wrapping around.
+fn autocorrelate(samples: &[f64]) -> Vec<f64> {
+    let len = (2 * samples.len()).next_power_of_two();
+    let mut buffer: Vec<_> = samples.iter().map(|&x| Complex::from(x)).collect();
+    buffer.resize(len, Complex::default());
+
+    let mut planner = FftPlanner::new();
+    planner.plan_fft_forward(len).process(&mut buffer);
+    for c in &mut buffer {
+        *c = Complex::from(c.norm_sqr());
+    }
+    planner.plan_fft_inverse(len).process(&mut buffer);
+    buffer[..samples.len()]
+        .iter()
+        .map(|c| c.re / len as f64)
+        .collect()
+}
+
+// The period in samples is the quefrency of the first prominent peak in the real cepstrum,
+// averaged over frames of a few periods of the lowest frequency, i.e. the spacing of the harmonics
+// in the log spectrum. This needs harmonics, so a pure tone has none. The confidence grows with how
+// far the peak stands out from the rest of the searched range.
+fn cepstrum_period(samples: &[f64], lags: Range<usize>) -> Option<(f64, f64)> {
+    let frame_len = (4 * lags.end).next_power_of_two();
+    let window: Vec<f64> = (0..frame_len)
+        .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f64 / frame_len as f64).cos())
+        .collect();
+    let mut planner = FftPlanner::new();
+    let forward = planner.plan_fft_forward(frame_len);
+    let inverse = planner.plan_fft_inverse(frame_len);
+
+    let mut cepstrum = vec![0.0; lags.end + 1];
+    let mut frames = 0;
+    for frame in samples.windows(frame_len).step_by(frame_len / 2) {
+        let mut buffer: Vec<_> = frame
+            .iter()
+            .zip(&window)
+            .map(|(x, w)| Complex::from(x * w))
+            .collect();
+        forward.process(&mut buffer);
+        for c in &mut buffer {
+            *c = Complex::from((c.norm() + f64::EPSILON).ln());
+        }
+        inverse.process(&mut buffer);
+        for (sum, c) in cepstrum.iter_mut().zip(&buffer) {
+            *sum += c.re;
+        }
+        frames += 1;
+    }
+    if frames == 0 {
+        return None;
+    }
+
+    let range = &cepstrum[lags.clone()];
+    let count = range.len() as f64;
+    let mean = range.iter().sum::<f64>() / count;
+    let deviation = (range.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / count).sqrt();
+    let highest = peaks(&cepstrum, lags.clone())
+        .map(|lag| cepstrum[lag])
+        .fold(mean, f64::max);
+    let lag = peaks(&cepstrum, lags)
+        .find(|&lag| cepstrum[lag] - mean >= CANDIDATE_RATIO * (highest - mean))?;
+
+    // The highest of a few hundred values of noise is typically three standard deviations above
+    // the mean, which gives no confidence.
+    let z_score = (cepstrum[lag] - mean) / deviation;
+    Some((
+        lag as f64 + parabolic_offset(&cepstrum, lag),
+        1.0 - 3.0 / z_score,
+    ))
+}
+
+// Lags at local maxima, in increasing order.
+fn peaks(values: &[f64], lags: Range<usize>) -> impl Iterator<Item = usize> + '_ {
+    lags.filter(move |&lag| {
+        lag > 0
+            && lag + 1 < values.len()
+            && values[lag] > values[lag - 1]
+            && values[lag] >= values[lag + 1]
+    })
+}
+
+// Fractional offset of the vertex of the parabola through a peak and its neighbours.
+fn parabolic_offset(values: &[f64], peak: usize) -> f64 {
+    let (Some(&before), Some(&after)) = (values.get(peak.wrapping_sub(1)), values.get(peak + 1))
+    else {
+        return 0.0;
+    };
+    let denominator = before - 2.0 * values[peak] + after;
+    if denominator == 0.0 {
+        0.0
+    } else {
+        (0.5 * (before - after) / denominator).clamp(-0.5, 0.5)
+    }
+}
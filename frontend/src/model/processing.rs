@@ -0,0 +1,148 @@
+use std::f64::consts::FRAC_PI_2;
+
+use super::{biquad::FilterDesign, resample::ResampleQuality, Signal};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    Gain { db: f64 },
+    Normalize { peak_db: f64 },
+    FadeIn { seconds: f64 },
+    FadeOut { seconds: f64 },
+    Reverse,
+    Invert,
+    Resample { rate: u32, quality: ResampleQuality },
+    Filter(FilterDesign),
+}
+
+impl Operation {
+    pub const DEFAULTS: [Self; 7] = [
+        Self::Gain { db: 0.0 },
+        Self::Normalize { peak_db: -1.0 },
+        Self::FadeIn { seconds: 0.1 },
+        Self::FadeOut { seconds: 0.1 },
+        Self::Reverse,
+        Self::Invert,
+        Self::Resample {
+            rate: 48000,
+            quality: ResampleQuality::Standard,
+        },
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Gain { .. } => "Gain",
+            Self::Normalize { .. } => "Normalize",
+            Self::FadeIn { .. } => "Fade in",
+            Self::FadeOut { .. } => "Fade out",
+            Self::Reverse => "Reverse",
+            Self::Invert => "Invert polarity",
+            Self::Resample { .. } => "Resample",
+            Self::Filter(design) => design.filter_type.name(),
+        }
+    }
+
+    // The adjustable parameter of the operation, if any, along with its unit.
+    pub fn parameter(self) -> Option<(f64, &'static str)> {
+        match self {
+            Self::Gain { db } => Some((db, "dB")),
+            Self::Normalize { peak_db } => Some((peak_db, "dBFS")),
+            Self::FadeIn { seconds } | Self::FadeOut { seconds } => Some((seconds, "s")),
+            Self::Resample { rate, .. } => Some((rate as f64, "Hz")),
+            Self::Filter(design) => Some((design.frequency, "Hz")),
+            Self::Reverse | Self::Invert => None,
+        }
+    }
+
+    pub fn with_parameter(self, value: f64) -> Self {
+        match self {
+            Self::Gain { .. } => Self::Gain { db: value },
+            Self::Normalize { .. } => Self::Normalize {
+                peak_db: value.min(0.0),
+            },
+            Self::FadeIn { .. } => Self::FadeIn {
+                seconds: value.max(0.0),
+            },
+            Self::FadeOut { .. } => Self::FadeOut {
+                seconds: value.max(0.0),
+            },
+            Self::Resample { quality, .. } => Self::Resample {
+                rate: value.round().max(1.0) as u32,
+                quality,
+            },
+            Self::Filter(design) => Self::Filter(FilterDesign {
+                frequency: value.max(1.0),
+                ..design
+            }),
+            Self::Reverse | Self::Invert => self,
+        }
+    }
+
+    pub fn apply(self, signal: &Signal) -> Signal {
+        match self {
+            Self::Gain { db } => scale(signal, 10_f64.powf(db / 20.0)),
+            Self::Normalize { peak_db } => {
+                // Channels are normalized together to preserve the stereo balance.
+                let peak = (0..signal.num_channels())
+                    .flat_map(|n| signal.channel(n).iter_normalized())
+                    .fold(0.0, |peak: f64, x| peak.max(x.abs()));
+                if peak == 0.0 {
+                    return signal.clone();
+                }
+                scale(signal, 10_f64.powf(peak_db / 20.0) / peak)
+            }
+            Self::FadeIn { seconds } => signal.map_channels(|channel| {
+                let len = fade_len(seconds, channel.sample_rate());
+                channel.with_normalized_samples(
+                    channel
+                        .iter_normalized()
+                        .enumerate()
+                        .map(|(i, x)| x * fade_gain(i, len)),
+                )
+            }),
+            Self::FadeOut { seconds } => signal.map_channels(|channel| {
+                let len = fade_len(seconds, channel.sample_rate());
+                let count = channel.count();
+                channel.with_normalized_samples(
+                    channel
+                        .iter_normalized()
+                        .enumerate()
+                        .map(|(i, x)| x * fade_gain(count - 1 - i, len)),
+                )
+            }),
+            Self::Reverse => signal.map_channels(|channel| {
+                let samples: Vec<_> = channel.iter_normalized().collect();
+                channel.with_normalized_samples(samples.into_iter().rev())
+            }),
+            Self::Invert => scale(signal, -1.0),
+            Self::Resample { rate, quality } => signal
+                .map_channels(|channel| bench!(["Resampling"] => channel.resample(rate, quality))),
+            Self::Filter(design) => signal.map_channels(|channel| channel.filter(&design)),
+        }
+    }
+}
+
+// Applies the operations in order.
+pub fn process(signal: &Signal, chain: &[Operation]) -> Signal {
+    chain
+        .iter()
+        .fold(signal.clone(), |signal, operation| operation.apply(&signal))
+}
+
+fn scale(signal: &Signal, factor: f64) -> Signal {
+    signal.map_channels(|channel| {
+        channel.with_normalized_samples(channel.iter_normalized().map(|x| x * factor))
+    })
+}
+
+fn fade_len(seconds: f64, sample_rate: u32) -> usize {
+    (seconds * sample_rate as f64) as usize
+}
+
+// Equal-power fade gain for the sample `i` samples from the silent end of a fade of `len` samples.
+fn fade_gain(i: usize, len: usize) -> f64 {
+    if i >= len {
+        1.0
+    } else {
+        (i as f64 / len as f64 * FRAC_PI_2).sin()
+    }
+}
@@ -0,0 +1,196 @@
+use super::{Channel, Signal, MAX_SAMPLE_RATE};
+
+// Sample formats of headerless PCM, as dumped by e.g. embedded devices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawFormat {
+    Int8,
+    Int16,
+    Int24,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+}
+
+impl RawFormat {
+    pub const ALL: [Self; 7] = [
+        Self::Int8,
+        Self::Int16,
+        Self::Int24,
+        Self::Int32,
+        Self::Int64,
+        Self::Float32,
+        Self::Float64,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Int8 => "8-bit integer",
+            Self::Int16 => "16-bit integer",
+            Self::Int24 => "24-bit integer",
+            Self::Int32 => "32-bit integer",
+            Self::Int64 => "64-bit integer",
+            Self::Float32 => "32-bit float",
+            Self::Float64 => "64-bit float",
+        }
+    }
+
+    pub fn bytes_per_sample(self) -> usize {
+        match self {
+            Self::Int8 => 1,
+            Self::Int16 => 2,
+            Self::Int24 => 3,
+            Self::Int32 | Self::Float32 => 4,
+            Self::Int64 | Self::Float64 => 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl Endianness {
+    pub const ALL: [Self; 2] = [Self::Little, Self::Big];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Little => "Little-endian",
+            Self::Big => "Big-endian",
+        }
+    }
+}
+
+// How to interpret the bytes of a headerless file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RawSpec {
+    pub format: RawFormat,
+    pub endianness: Endianness,
+    // Interleaved channels, of which only mono and stereo are supported.
+    pub channels: u16,
+    pub sample_rate: u32,
+}
+
+impl Default for RawSpec {
+    fn default() -> Self {
+        Self {
+            format: RawFormat::Int16,
+            endianness: Endianness::Little,
+            channels: 1,
+            sample_rate: 48000,
+        }
+    }
+}
+
+impl Signal {
+    // Reads interleaved samples without a header. Bytes after the last complete frame are ignored.
+    pub fn from_raw(data: &[u8], spec: RawSpec) -> Result<Self, hound::Error> {
+        if spec.sample_rate == 0 || spec.sample_rate > MAX_SAMPLE_RATE {
+            return Err(hound::Error::FormatError("invalid sample rate"));
+        }
+        let channels = usize::from(spec.channels);
+        if !(1..=2).contains(&channels) {
+            return Err(hound::Error::Unsupported);
+        }
+
+        let width = spec.format.bytes_per_sample();
+        let frames = data.len() / (width * channels);
+        let channel = |n: usize| {
+            let samples = data
+                .chunks_exact(width)
+                .skip(n)
+                .step_by(channels)
+                .take(frames);
+            read_channel(samples, spec)
+        };
+
+        Ok(match channels {
+            1 => Self::Mono(channel(0)),
+            _ => Self::Stereo(channel(0), channel(1)),
+        })
+    }
+}
+
+fn read_channel<'a>(samples: impl Iterator<Item = &'a [u8]>, spec: RawSpec) -> Channel {
+    // Samples are widened to eight bytes so that every format is read the same way, with the
+    // sample in the most significant bytes in either byte order.
+    let read = move |bytes: &[u8]| {
+        let mut word = [0; 8];
+        let sample = &mut word[8 - bytes.len()..];
+        sample.copy_from_slice(bytes);
+        if spec.endianness == Endianness::Big {
+            sample.reverse();
+        }
+        u64::from_le_bytes(word)
+    };
+    let sample_rate = spec.sample_rate;
+
+    match spec.format {
+        RawFormat::Int8 => {
+            Channel::from_samples_i8(samples.map(|s| (read(s) >> 56) as i8), 8, sample_rate)
+        }
+        RawFormat::Int16 => {
+            Channel::from_samples_i16(samples.map(|s| (read(s) >> 48) as i16), 16, sample_rate)
+        }
+        // Right-aligned, as 24-bit samples decoded from WAV files are.
+        RawFormat::Int24 => Channel::from_samples_i32(
+            samples.map(|s| (read(s) as i64 >> 40) as i32),
+            24,
+            sample_rate,
+        ),
+        RawFormat::Int32 => {
+            Channel::from_samples_i32(samples.map(|s| (read(s) >> 32) as i32), 32, sample_rate)
+        }
+        RawFormat::Float32 => Channel::from_samples_f32(
+            samples.map(|s| f32::from_bits((read(s) >> 32) as u32)),
+            32,
+            sample_rate,
+        ),
+        RawFormat::Int64 => {
+            Channel::from_samples_i64(samples.map(|s| read(s) as i64), 64, sample_rate)
+        }
+        RawFormat::Float64 => {
+            Channel::from_samples_f64(samples.map(|s| f64::from_bits(read(s))), 64, sample_rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interleaved_samples_are_read() {
+        let spec = RawSpec {
+            format: RawFormat::Int24,
+            endianness: Endianness::Big,
+            channels: 2,
+            sample_rate: 8000,
+        };
+        // Two frames of -2 and 1, then an incomplete frame.
+        let data = [
+            0xff, 0xff, 0xfe, 0x00, 0x00, 0x01, 0xff, 0xff, 0xfe, 0x00, 0x00, 0x01, 0x7f,
+        ];
+        let Signal::Stereo(left, right) = Signal::from_raw(&data, spec).expect("should read")
+        else {
+            panic!("should be stereo");
+        };
+        assert!(left == Channel::from_samples_i32([-2, -2], 24, 8000));
+        assert!(right == Channel::from_samples_i32([1, 1], 24, 8000));
+
+        let spec = RawSpec {
+            format: RawFormat::Float64,
+            ..RawSpec::default()
+        };
+        let data: Vec<u8> = [0.5f64, -0.25]
+            .into_iter()
+            .flat_map(f64::to_le_bytes)
+            .collect();
+        let Signal::Mono(channel) = Signal::from_raw(&data, spec).expect("should read") else {
+            panic!("should be mono");
+        };
+        assert!(channel == Channel::from_samples_f64([0.5, -0.25], 64, 48000));
+    }
+}
@@ -0,0 +1,124 @@
+use super::Channel;
+
+// The shortest and longest segments Welch averaging can be set to.
+const MIN_SEGMENT_LEN: usize = 256;
+const MAX_SEGMENT_LEN: usize = 65536;
+// Broadband signals gain little detail from segments longer than this, only fewer to average.
+const MAX_BROADBAND_SEGMENT_LEN: usize = 8192;
+// How many segments a broadband signal is split into, for a steady average.
+const BROADBAND_SEGMENTS: usize = 32;
+// The longest segments the selection is analyzed with.
+const ANALYSIS_SEGMENT_LEN: usize = 4096;
+// A bin this far above the median of the bins around it is taken to be a tone.
+const PROMINENCE_DB: f64 = 20.0;
+const NEIGHBOURHOOD: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalCharacter {
+    // Mostly made up of discrete tones, such as a test tone or a sustained note.
+    Tonal,
+    // Energy spread over frequency, such as noise, speech or a full mix.
+    Broadband,
+}
+
+// Spectrum settings suited to a signal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowRecommendation {
+    pub character: SignalCharacter,
+    // The length of the Hann-windowed segments to average, or `None` for the unwindowed spectrum of
+    // the whole signal, where it is too short to average.
+    pub segment_len: Option<usize>,
+    pub overlap: f64,
+    // How many segments are averaged with these settings.
+    pub segments: usize,
+}
+
+impl Channel {
+    // Recommends how to transform the channel depending on whether it is tonal. Tones call for long
+    // segments, to resolve them, each windowed, so that their leakage does not hide what lies
+    // between them. Broadband signals call for many shorter segments instead, so that the average
+    // settles.
+    pub fn recommend_window(&self) -> WindowRecommendation {
+        const OVERLAP: f64 = 0.5;
+
+        let count = self.count();
+        let character = self.character();
+        let segment_len = (count >= 2 * MIN_SEGMENT_LEN).then(|| {
+            let longest = match character {
+                SignalCharacter::Tonal => MAX_SEGMENT_LEN.min(count / 2),
+                SignalCharacter::Broadband => {
+                    MAX_BROADBAND_SEGMENT_LEN.min(count / BROADBAND_SEGMENTS)
+                }
+            };
+            previous_power_of_two(longest).max(MIN_SEGMENT_LEN)
+        });
+        let segments = segment_len.map_or(1, |len| {
+            let hop = (len as f64 * (1.0 - OVERLAP)) as usize;
+            (count - len) / hop + 1
+        });
+
+        WindowRecommendation {
+            character,
+            segment_len,
+            overlap: OVERLAP,
+            segments,
+        }
+    }
+
+    fn character(&self) -> SignalCharacter {
+        let segment_len = previous_power_of_two(self.count() / 4)
+            .clamp(MIN_SEGMENT_LEN, ANALYSIS_SEGMENT_LEN)
+            .min(self.count());
+        let powers: Vec<f64> = self
+            .welch(segment_len, 0.5)
+            .spectrum
+            .magnitudes()
+            .map(|m| m * m)
+            .collect();
+
+        let threshold = 10_f64.powf(PROMINENCE_DB / 10.0);
+        let tonal = (NEIGHBOURHOOD..powers.len().saturating_sub(NEIGHBOURHOOD)).any(|bin| {
+            let mut neighbours = powers[bin - NEIGHBOURHOOD..=bin + NEIGHBOURHOOD].to_vec();
+            neighbours.sort_by(f64::total_cmp);
+            let median = neighbours[NEIGHBOURHOOD];
+            powers[bin] > 0.0 && powers[bin] > median * threshold
+        });
+        if tonal {
+            SignalCharacter::Tonal
+        } else {
+            SignalCharacter::Broadband
+        }
+    }
+}
+
+fn previous_power_of_two(n: usize) -> usize {
+    match n {
+        0 => 0,
+        n => 1 << (usize::BITS - 1 - n.leading_zeros()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignalCharacter;
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn tones_get_long_segments_and_noise_many_short_ones() {
+        let tone = Generator::sine(1000.0, 0.5).channel().recommend_window();
+        assert_eq!(tone.character, SignalCharacter::Tonal);
+        assert_eq!(tone.segment_len, Some(16384));
+
+        let noise = Generator::white_noise(0.5).channel().recommend_window();
+        assert_eq!(noise.character, SignalCharacter::Broadband);
+        assert_eq!(noise.segment_len, Some(1024));
+        assert!(noise.segments >= 32, "{noise:?}");
+
+        // Too short to average at all.
+        let short = Generator::sine(1000.0, 0.5)
+            .with_duration(0.01)
+            .channel()
+            .recommend_window();
+        assert_eq!(short.segment_len, None);
+    }
+}
@@ -0,0 +1,78 @@
+use std::f64::consts::PI;
+
+use super::Channel;
+
+// Cut-off relative to the lower of the two Nyquist frequencies, leaving room for the transition
+// band of the anti-aliasing filter.
+const ROLLOFF: f64 = 0.95;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResampleQuality {
+    Fast,
+    Standard,
+    High,
+}
+
+impl ResampleQuality {
+    pub const ALL: [Self; 3] = [Self::Fast, Self::Standard, Self::High];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Fast => "Fast",
+            Self::Standard => "Standard",
+            Self::High => "High",
+        }
+    }
+
+    // Number of zero crossings of the sinc kernel on either side of its centre.
+    fn zero_crossings(self) -> usize {
+        match self {
+            Self::Fast => 8,
+            Self::Standard => 32,
+            Self::High => 128,
+        }
+    }
+}
+
+impl Channel {
+    // Band-limited resampling with a Blackman-windowed sinc kernel. When downsampling, the kernel is
+    // widened so that it also acts as the anti-aliasing filter.
+    pub fn resample(&self, target_rate: u32, quality: ResampleQuality) -> Channel {
+        if target_rate == self.sample_rate || target_rate == 0 {
+            return self.clone();
+        }
+
+        let samples: Vec<f64> = self.iter_normalized().collect();
+        let ratio = target_rate as f64 / self.sample_rate as f64;
+        let cutoff = ratio.min(1.0) * ROLLOFF;
+        let half_len = (quality.zero_crossings() as f64 / cutoff).ceil() as isize;
+        let out_len = (samples.len() as f64 * ratio).round() as usize;
+
+        let kernel = |x: f64| {
+            let u = x / half_len as f64;
+            if u.abs() >= 1.0 {
+                return 0.0;
+            }
+            let window = 0.42 + 0.5 * (PI * u).cos() + 0.08 * (2.0 * PI * u).cos();
+            let sinc = if x == 0.0 {
+                1.0
+            } else {
+                (PI * cutoff * x).sin() / (PI * cutoff * x)
+            };
+            cutoff * sinc * window
+        };
+
+        let resampled = (0..out_len).map(|n| {
+            let t = n as f64 / ratio;
+            let centre = t.floor() as isize;
+            (centre - half_len + 1..=centre + half_len)
+                .filter(|&k| k >= 0 && (k as usize) < samples.len())
+                .map(|k| samples[k as usize] * kernel(t - k as f64))
+                .sum::<f64>()
+        });
+
+        let mut channel = self.with_normalized_samples(resampled);
+        channel.sample_rate = target_rate;
+        channel
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt::Write;
+
+use rustfft::num_complex::Complex;
+
+use super::Spectrum;
+
+// Reference impedance written to Touchstone files, which circuit tools expect even though it means
+// nothing for an acoustic measurement.
+const REFERENCE_IMPEDANCE: f64 = 50.0;
+
+// File formats for exporting a measured frequency response to circuit tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    // Touchstone one-port file, with the response as S11 in real/imaginary form.
+    Touchstone,
+    // Frequency, real and imaginary parts, magnitude and phase.
+    Csv,
+}
+
+impl ResponseFormat {
+    pub const ALL: [Self; 2] = [Self::Touchstone, Self::Csv];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Touchstone => "Touchstone (.s1p)",
+            Self::Csv => "CSV",
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Touchstone => "response.s1p",
+            Self::Csv => "response.csv",
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Touchstone => "text/plain",
+            Self::Csv => "text/csv",
+        }
+    }
+}
+
+impl Spectrum {
+    // The frequency response, taking the signal to be an impulse response. Unlike `magnitudes`, the
+    // bins are not scaled by the length of the signal, so that a full-scale impulse has a response
+    // of one at every frequency.
+    pub fn response(&self) -> impl Iterator<Item = (f64, Complex<f64>)> + '_ {
+        self.transform
+            .iter()
+            .enumerate()
+            .map(|(bin, &c)| (self.bin_to_frequency(bin), c / self.full_scale))
+    }
+
+    pub fn export_response(&self, format: ResponseFormat) -> String {
+        let mut out = String::new();
+        match format {
+            ResponseFormat::Touchstone => {
+                out.push_str("! Frequency response exported by Signal Inspector\n");
+                let _ = writeln!(out, "# HZ S RI R {REFERENCE_IMPEDANCE}");
+                for (frequency, c) in self.response() {
+                    let _ = writeln!(out, "{frequency} {:e} {:e}", c.re, c.im);
+                }
+            }
+            ResponseFormat::Csv => {
+                out.push_str("frequency_hz,real,imaginary,magnitude_db,phase_deg\n");
+                for (frequency, c) in self.response() {
+                    let _ = writeln!(
+                        out,
+                        "{frequency},{:e},{:e},{:.3},{:.3}",
+                        c.re,
+                        c.im,
+                        Spectrum::decibel(c.norm(), 1.0),
+                        c.arg().to_degrees(),
+                    );
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Channel;
+
+    use super::*;
+
+    #[test]
+    fn impulse_has_flat_response() {
+        let samples = (0..8).map(|n| if n == 1 { i16::MAX } else { 0 });
+        let spectrum = Channel::from_samples_i16(samples, 16, 8000).spectrum();
+
+        let response: Vec<_> = spectrum.response().collect();
+        assert_eq!(response.len(), 4);
+        for (frequency, c) in &response {
+            assert!((c.norm() - 1.0).abs() < 1e-3, "{frequency} Hz: {c}");
+        }
+        // A delay of one sample lags by a quarter turn at a quarter of the sample rate.
+        assert!((response[2].1.arg().to_degrees() + 90.0).abs() < 1e-6);
+
+        let csv = spectrum.export_response(ResponseFormat::Csv);
+        assert_eq!(csv.lines().count(), 5);
+        let touchstone = spectrum.export_response(ResponseFormat::Touchstone);
+        assert!(touchstone.lines().any(|line| line == "# HZ S RI R 50"));
+    }
+}
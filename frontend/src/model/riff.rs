@@ -0,0 +1,443 @@
+// Reads the chunks of a RIFF WAVE file other than the samples, which hound skips: the format in
+// full, LIST/INFO tags, the broadcast extension, cue points and sampler loops. Parsing is lenient,
+// keeping whatever could be read before a malformed or truncated chunk. Cue points can also be
+// written, as hound does not write them either.
+
+pub(super) const FORMAT_PCM: u16 = 1;
+pub(super) const FORMAT_IEEE_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xfffe;
+
+// Sizes of the fixed-length parts of the chunks.
+const CUE_POINT_LEN: usize = 24;
+const SAMPLER_LEN: usize = 36;
+const SAMPLE_LOOP_LEN: usize = 24;
+const BROADCAST_LEN: usize = 602;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    pub id: String,
+    // In bytes, without the header.
+    pub size: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Format {
+    pub format_tag: u16,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub byte_rate: u32,
+    pub block_align: u16,
+    pub bits_per_sample: u16,
+    // From WAVE_FORMAT_EXTENSIBLE.
+    pub valid_bits: Option<u16>,
+    pub channel_mask: Option<u32>,
+    pub sub_format: Option<u16>,
+}
+
+impl Format {
+    pub fn is_float(&self) -> bool {
+        self.sub_format.unwrap_or(self.format_tag) == FORMAT_IEEE_FLOAT
+    }
+
+    pub fn name(&self) -> String {
+        let name = |tag| match tag {
+            FORMAT_PCM => "PCM".to_owned(),
+            FORMAT_IEEE_FLOAT => "IEEE float".to_owned(),
+            FORMAT_EXTENSIBLE => "Extensible".to_owned(),
+            tag => format!("0x{tag:04x}"),
+        };
+        match self.sub_format {
+            Some(sub_format) => format!("{} ({})", name(self.format_tag), name(sub_format)),
+            None => name(self.format_tag),
+        }
+    }
+}
+
+// The broadcast extension (bext) of EBU Tech 3285.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Broadcast {
+    pub description: String,
+    pub originator: String,
+    pub originator_reference: String,
+    pub origination_date: String,
+    pub origination_time: String,
+    // Samples since midnight at the start of the recording.
+    pub time_reference: u64,
+    pub version: u16,
+    pub coding_history: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CuePoint {
+    pub id: u32,
+    // In sample frames.
+    pub position: u32,
+    // From the associated data list (LIST/adtl).
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleLoop {
+    pub id: u32,
+    // 0 for forward, 1 for alternating and 2 for backward.
+    pub loop_type: u32,
+    // In sample frames, both inclusive.
+    pub start: u32,
+    pub end: u32,
+    // 0 for infinite.
+    pub play_count: u32,
+}
+
+// The sampler chunk (smpl).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sampler {
+    pub midi_unity_note: u32,
+    // Fraction of a semitone above the unity note.
+    pub midi_pitch_fraction: u32,
+    pub loops: Vec<SampleLoop>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    pub chunks: Vec<Chunk>,
+    pub format: Option<Format>,
+    // Tags by their four-letter IDs, in file order.
+    pub info: Vec<(String, String)>,
+    pub broadcast: Option<Broadcast>,
+    pub cue_points: Vec<CuePoint>,
+    pub sampler: Option<Sampler>,
+}
+
+impl Metadata {
+    // Returns `None` if `data` is not a RIFF WAVE file.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.get(0..4)? != b"RIFF" || data.get(8..12)? != b"WAVE" {
+            return None;
+        }
+
+        let mut metadata = Self::default();
+        let mut labels = Vec::new();
+        for (id, body) in chunks(&data[12..]) {
+            match &id {
+                b"fmt " => metadata.format = parse_format(body),
+                b"LIST" => match body.get(0..4) {
+                    Some(b"INFO") => {
+                        metadata.info.extend(chunks(&body[4..]).map(|(id, body)| {
+                            (String::from_utf8_lossy(&id).into_owned(), text(body))
+                        }))
+                    }
+                    Some(b"adtl") => labels.extend(
+                        chunks(&body[4..])
+                            .filter(|(id, _)| id == b"labl")
+                            .filter_map(|(_, body)| Some((u32_at(body, 0)?, text(&body[4..])))),
+                    ),
+                    _ => {}
+                },
+                b"bext" => metadata.broadcast = parse_broadcast(body),
+                b"cue " => metadata.cue_points = parse_cue_points(body),
+                b"smpl" => metadata.sampler = parse_sampler(body),
+                _ => {}
+            }
+            metadata.chunks.push(Chunk {
+                id: String::from_utf8_lossy(&id).into_owned(),
+                size: body.len() as u32,
+            });
+        }
+
+        for cue_point in &mut metadata.cue_points {
+            cue_point.label = labels
+                .iter()
+                .find(|(id, _)| *id == cue_point.id)
+                .map(|(_, label)| label.clone());
+        }
+        Some(metadata)
+    }
+}
+
+// Appends `cue_points` to a WAV file, with their labels in an associated data list, and updates
+// the size in its header. The file should not already have cue points.
+pub fn append_cue_points(data: &mut Vec<u8>, cue_points: &[CuePoint]) {
+    if cue_points.is_empty() || data.len() < 12 {
+        return;
+    }
+
+    let mut cue = (cue_points.len() as u32).to_le_bytes().to_vec();
+    for cue_point in cue_points {
+        cue.extend(cue_point.id.to_le_bytes());
+        // The position in play order, which without a playlist is the sample position.
+        cue.extend(cue_point.position.to_le_bytes());
+        cue.extend(b"data");
+        cue.extend([0; 8]);
+        cue.extend(cue_point.position.to_le_bytes());
+    }
+    append_chunk(data, b"cue ", &cue);
+
+    let mut adtl = b"adtl".to_vec();
+    for cue_point in cue_points {
+        if let Some(label) = &cue_point.label {
+            let body = [&cue_point.id.to_le_bytes(), label.as_bytes(), &[0]].concat();
+            append_chunk(&mut adtl, b"labl", &body);
+        }
+    }
+    if adtl.len() > 4 {
+        append_chunk(data, b"LIST", &adtl);
+    }
+
+    let riff_size = (data.len() - 8) as u32;
+    data[4..8].copy_from_slice(&riff_size.to_le_bytes());
+}
+
+fn append_chunk(data: &mut Vec<u8>, id: &[u8; 4], body: &[u8]) {
+    data.extend(id);
+    data.extend((body.len() as u32).to_le_bytes());
+    data.extend(body);
+    if body.len() % 2 == 1 {
+        data.push(0);
+    }
+}
+
+// The chunks in `data`, each with its ID and body, up to the first one that is truncated.
+// The format and the sample data, for reading the samples that hound does not.
+pub(super) fn format_and_samples(data: &[u8]) -> Option<(Format, &[u8])> {
+    if data.get(0..4)? != b"RIFF" || data.get(8..12)? != b"WAVE" {
+        return None;
+    }
+
+    let mut format = None;
+    let mut samples = None;
+    for (id, body) in chunks(&data[12..]) {
+        match &id {
+            b"fmt " => format = parse_format(body),
+            b"data" => samples = Some(body),
+            _ => {}
+        }
+    }
+    Some((format?, samples?))
+}
+
+fn chunks(mut data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    std::iter::from_fn(move || {
+        let id: [u8; 4] = data.get(0..4)?.try_into().ok()?;
+        let size = u32_at(data, 4)? as usize;
+        let body = data.get(8..8_usize.checked_add(size)?)?;
+        // Chunks are padded to an even number of bytes.
+        data = data.get(8 + size + size % 2..).unwrap_or_default();
+        Some((id, body))
+    })
+}
+
+fn parse_format(body: &[u8]) -> Option<Format> {
+    let format_tag = u16_at(body, 0)?;
+    let extensible = format_tag == FORMAT_EXTENSIBLE;
+    Some(Format {
+        format_tag,
+        channels: u16_at(body, 2)?,
+        sample_rate: u32_at(body, 4)?,
+        byte_rate: u32_at(body, 8)?,
+        block_align: u16_at(body, 12)?,
+        bits_per_sample: u16_at(body, 14)?,
+        valid_bits: u16_at(body, 18).filter(|_| extensible),
+        channel_mask: u32_at(body, 20).filter(|_| extensible),
+        // The first two bytes of the GUID are the format tag it stands for.
+        sub_format: u16_at(body, 24).filter(|_| extensible),
+    })
+}
+
+fn parse_broadcast(body: &[u8]) -> Option<Broadcast> {
+    if body.len() < BROADCAST_LEN {
+        return None;
+    }
+    let low = u32_at(body, 338)?;
+    let high = u32_at(body, 342)?;
+    Some(Broadcast {
+        description: text(&body[0..256]),
+        originator: text(&body[256..288]),
+        originator_reference: text(&body[288..320]),
+        origination_date: text(&body[320..330]),
+        origination_time: text(&body[330..338]),
+        time_reference: u64::from(high) << 32 | u64::from(low),
+        version: u16_at(body, 346)?,
+        coding_history: text(&body[BROADCAST_LEN..]),
+    })
+}
+
+fn parse_cue_points(body: &[u8]) -> Vec<CuePoint> {
+    let count = u32_at(body, 0).unwrap_or(0) as usize;
+    body.get(4..)
+        .unwrap_or_default()
+        .chunks_exact(CUE_POINT_LEN)
+        .take(count)
+        .filter_map(|point| {
+            Some(CuePoint {
+                id: u32_at(point, 0)?,
+                position: u32_at(point, 20)?,
+                label: None,
+            })
+        })
+        .collect()
+}
+
+fn parse_sampler(body: &[u8]) -> Option<Sampler> {
+    let count = u32_at(body, 28)? as usize;
+    let loops = body
+        .get(SAMPLER_LEN..)
+        .unwrap_or_default()
+        .chunks_exact(SAMPLE_LOOP_LEN)
+        .take(count)
+        .filter_map(|sample_loop| {
+            Some(SampleLoop {
+                id: u32_at(sample_loop, 0)?,
+                loop_type: u32_at(sample_loop, 4)?,
+                start: u32_at(sample_loop, 8)?,
+                end: u32_at(sample_loop, 12)?,
+                play_count: u32_at(sample_loop, 20)?,
+            })
+        })
+        .collect();
+    Some(Sampler {
+        midi_unity_note: u32_at(body, 12)?,
+        midi_pitch_fraction: u32_at(body, 16)?,
+        loops,
+    })
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+// Text up to the first null, as the fields are null-padded, with invalid UTF-8 replaced.
+fn text(data: &[u8]) -> String {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).trim().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{append_cue_points, CuePoint, Metadata, SampleLoop, BROADCAST_LEN};
+    use crate::model::generator::{BitDepth, Generator};
+
+    fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut chunk = id.to_vec();
+        chunk.extend((body.len() as u32).to_le_bytes());
+        chunk.extend(body);
+        if body.len() % 2 == 1 {
+            chunk.push(0);
+        }
+        chunk
+    }
+
+    fn words(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn chunks_are_read() {
+        let mut data = Generator::sine(1000.0, 0.5)
+            .with_bit_depth(BitDepth::Int16)
+            .generate()
+            .to_wav()
+            .unwrap();
+
+        let info = [b"INFO".to_vec(), chunk(b"INAM", b"Take 1\0")].concat();
+        data.extend(chunk(b"LIST", &info));
+        let adtl = [
+            b"adtl".to_vec(),
+            chunk(b"labl", &[words(&[2]), b"Chorus\0".to_vec()].concat()),
+        ]
+        .concat();
+        data.extend(chunk(b"LIST", &adtl));
+        let cue = [
+            words(&[2]),
+            words(&[1, 0]),
+            b"data".to_vec(),
+            words(&[0, 0, 100]),
+            words(&[2, 0]),
+            b"data".to_vec(),
+            words(&[0, 0, 2000]),
+        ]
+        .concat();
+        data.extend(chunk(b"cue ", &cue));
+        let smpl = words(&[0, 0, 20833, 60, 0, 0, 0, 1, 0, 7, 0, 10, 99, 0, 0]);
+        data.extend(chunk(b"smpl", &smpl));
+        let mut bext = vec![0; BROADCAST_LEN];
+        bext[..8].copy_from_slice(b"Overdubs");
+        bext[338..342].copy_from_slice(&48000_u32.to_le_bytes());
+        data.extend(chunk(b"bext", &[bext, b"A=PCM\0".to_vec()].concat()));
+        let riff_size = (data.len() - 8) as u32;
+        data[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+        let metadata = Metadata::parse(&data).unwrap();
+        let ids: Vec<&str> = metadata.chunks.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            ["fmt ", "data", "LIST", "LIST", "cue ", "smpl", "bext"]
+        );
+        let format = metadata.format.unwrap();
+        assert_eq!((format.format_tag, format.bits_per_sample), (1, 16));
+        assert_eq!(format.name(), "PCM");
+        assert_eq!(metadata.info, [("INAM".to_owned(), "Take 1".to_owned())]);
+        assert_eq!(
+            metadata.cue_points,
+            [
+                CuePoint {
+                    id: 1,
+                    position: 100,
+                    label: None
+                },
+                CuePoint {
+                    id: 2,
+                    position: 2000,
+                    label: Some("Chorus".to_owned())
+                },
+            ]
+        );
+        let sampler = metadata.sampler.unwrap();
+        assert_eq!(sampler.midi_unity_note, 60);
+        assert_eq!(
+            sampler.loops,
+            [SampleLoop {
+                id: 7,
+                loop_type: 0,
+                start: 10,
+                end: 99,
+                play_count: 0
+            }]
+        );
+        let broadcast = metadata.broadcast.unwrap();
+        assert_eq!(broadcast.description, "Overdubs");
+        assert_eq!(broadcast.time_reference, 48000);
+        assert_eq!(broadcast.coding_history, "A=PCM");
+
+        assert!(Metadata::parse(&data[..20]).is_some());
+        assert_eq!(Metadata::parse(b"not a wav file"), None);
+    }
+
+    #[test]
+    fn cue_points_round_trip() {
+        let mut data = Generator::dc(0.0).generate().to_wav().unwrap();
+        let cue_points = [
+            CuePoint {
+                id: 1,
+                position: 480,
+                label: Some("Verse".to_owned()),
+            },
+            CuePoint {
+                id: 2,
+                position: 9600,
+                label: None,
+            },
+        ];
+        append_cue_points(&mut data, &cue_points);
+
+        assert_eq!(Metadata::parse(&data).unwrap().cue_points, cue_points);
+        assert!(crate::model::Signal::from_wav(data).is_ok());
+    }
+}
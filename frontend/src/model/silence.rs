@@ -0,0 +1,34 @@
+use std::ops::Range;
+
+use super::Channel;
+
+impl Channel {
+    // Finds runs of samples whose magnitude stays below `threshold_db` (relative to full scale) for
+    // at least `min_duration` seconds.
+    pub fn detect_silence(&self, threshold_db: f64, min_duration: f64) -> Vec<Range<usize>> {
+        let threshold = 10_f64.powf(threshold_db / 20.0);
+        let min_len = ((min_duration * self.sample_rate as f64) as usize).max(1);
+
+        let mut regions = Vec::new();
+        let mut start = None;
+        for (i, x) in self.iter_normalized().enumerate() {
+            match (x.abs() < threshold, start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    if i - s >= min_len {
+                        regions.push(s..i);
+                    }
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            if self.count() - s >= min_len {
+                regions.push(s..self.count());
+            }
+        }
+
+        regions
+    }
+}
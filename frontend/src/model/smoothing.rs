@@ -0,0 +1,79 @@
+use rustfft::num_complex::Complex;
+
+use super::{welch::ConfidenceBand, Spectrum};
+
+// The fractions of an octave the spectrum can be smoothed over.
+pub const FRACTIONS: [u32; 4] = [3, 6, 12, 24];
+
+// Averages `magnitudes`, one per bin from DC up, over 1/`fraction` octave centred on each bin, as
+// the root mean square so that the level of broadband sound is kept. As bins are evenly spaced in
+// frequency, the window only depends on the bin number, and widens with it.
+pub fn smooth(magnitudes: &[f64], fraction: u32) -> Vec<f64> {
+    let half_width = (0.5 / f64::from(fraction.max(1))).exp2();
+    let mut sums = Vec::with_capacity(magnitudes.len() + 1);
+    sums.push(0.0);
+    for magnitude in magnitudes {
+        sums.push(sums[sums.len() - 1] + magnitude * magnitude);
+    }
+
+    (0..magnitudes.len())
+        .map(|n| {
+            if n == 0 {
+                return magnitudes[0];
+            }
+            let lower = ((n as f64 / half_width).round() as usize).clamp(1, n);
+            let upper = ((n as f64 * half_width).round() as usize).clamp(n, magnitudes.len() - 1);
+            let power = (sums[upper + 1] - sums[lower]) / (upper + 1 - lower) as f64;
+            power.max(0.0).sqrt()
+        })
+        .collect()
+}
+
+impl Spectrum {
+    // The spectrum with its magnitudes smoothed over 1/`fraction` octave. The phase is lost, so it
+    // is only fit for drawing.
+    pub fn smoothed(&self, fraction: u32) -> Spectrum {
+        let scale = self.num_samples as f64 * self.full_scale;
+        let magnitudes: Vec<_> = self.magnitudes().collect();
+        let transform = smooth(&magnitudes, fraction)
+            .into_iter()
+            .map(|magnitude| Complex::new(magnitude * scale, 0.0))
+            .collect();
+        Self {
+            transform,
+            ..self.clone()
+        }
+    }
+}
+
+impl ConfidenceBand {
+    pub fn smoothed(&self, fraction: u32) -> ConfidenceBand {
+        ConfidenceBand {
+            lower: smooth(&self.lower, fraction),
+            upper: smooth(&self.upper, fraction),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::smooth;
+
+    #[test]
+    fn smoothing_spreads_peaks_and_keeps_flat_spectra() {
+        let flat = vec![0.25; 4096];
+        assert!(smooth(&flat, 3).iter().all(|m| (m - 0.25).abs() < 1e-12));
+
+        let mut peak = vec![0.0; 4096];
+        peak[1000] = 1.0;
+        let smoothed = smooth(&peak, 3);
+        // A third of an octave around bin 1000 spans bins 891 to 1122.
+        let width = (1000.0 * (1.0 / 6.0_f64).exp2()).round()
+            - (1000.0 / (1.0 / 6.0_f64).exp2()).round()
+            + 1.0;
+        assert!((smoothed[1000] - width.recip().sqrt()).abs() < 1e-9);
+        assert!(smoothed[950] > 0.0 && smoothed[1050] > 0.0);
+        assert_eq!(smoothed[800], 0.0);
+        assert_eq!(smoothed[1200], 0.0);
+    }
+}
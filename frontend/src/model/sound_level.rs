@@ -0,0 +1,203 @@
+use rustfft::{num_complex::Complex, FftPlanner};
+use serde::{Deserialize, Serialize};
+
+use super::{levels::FLOOR_DB, Channel};
+
+// Levels are sampled this often for the statistical levels.
+const HOP_SECONDS: f64 = 0.01;
+
+// Frequency weightings of IEC 61672-1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FrequencyWeighting {
+    #[default]
+    A,
+    C,
+    Z,
+}
+
+impl FrequencyWeighting {
+    pub const ALL: [Self; 3] = [Self::A, Self::C, Self::Z];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::A => "A",
+            Self::C => "C",
+            Self::Z => "Z (none)",
+        }
+    }
+
+    // The gain of the weighting at `frequency`, from the analog poles given in the standard and
+    // normalized to 0 dB at 1 kHz.
+    fn gain(self, frequency: f64) -> f64 {
+        let f2 = frequency * frequency;
+        let pole = |p: f64| f2 + p * p;
+        match self {
+            Self::A => {
+                10_f64.powf(2.0 / 20.0) * 12194_f64.powi(2) * f2 * f2
+                    / (pole(20.6) * (pole(107.7) * pole(737.9)).sqrt() * pole(12194.0))
+            }
+            Self::C => {
+                10_f64.powf(0.062 / 20.0) * 12194_f64.powi(2) * f2 / (pole(20.6) * pole(12194.0))
+            }
+            Self::Z => 1.0,
+        }
+    }
+}
+
+// Exponential time weightings of a sound level meter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TimeWeighting {
+    #[default]
+    Fast,
+    Slow,
+}
+
+impl TimeWeighting {
+    pub const ALL: [Self; 2] = [Self::Fast, Self::Slow];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Fast => "Fast (125 ms)",
+            Self::Slow => "Slow (1 s)",
+        }
+    }
+
+    fn seconds(self) -> f64 {
+        match self {
+            Self::Fast => 0.125,
+            Self::Slow => 1.0,
+        }
+    }
+}
+
+// The readings of a sound level meter, in dB above whatever reference the levels were given
+// relative to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoundLevels {
+    // The equivalent continuous level, which is not time-weighted.
+    pub leq: f64,
+    // The highest and lowest time-weighted level.
+    pub lmax: f64,
+    pub lmin: f64,
+    // The time-weighted levels exceeded 10, 50 and 90 % of the time.
+    pub l10: f64,
+    pub l50: f64,
+    pub l90: f64,
+}
+
+impl Channel {
+    // Sound levels of the channel through the weightings, with `offset_db` added to levels in dBFS,
+    // such as to give them in dB SPL. The time-weighted level starts out at the mean square of its
+    // first time constant, rather than rising from silence.
+    pub fn sound_levels(
+        &self,
+        frequency: FrequencyWeighting,
+        time: TimeWeighting,
+        offset_db: f64,
+    ) -> SoundLevels {
+        let samples = self.frequency_weighted(frequency);
+        let level = |mean_square: f64| (10.0 * mean_square.log10()).max(FLOOR_DB) + offset_db;
+        if samples.is_empty() {
+            let floor = level(0.0);
+            return SoundLevels {
+                leq: floor,
+                lmax: floor,
+                lmin: floor,
+                l10: floor,
+                l50: floor,
+                l90: floor,
+            };
+        }
+
+        let sample_rate = self.sample_rate as f64;
+        let time_constant = (time.seconds() * sample_rate).max(1.0);
+        let alpha = 1.0 - (-1.0 / time_constant).exp();
+        let settle = (time_constant as usize).clamp(1, samples.len());
+        let mut mean_square = samples[..settle].iter().map(|x| x * x).sum::<f64>() / settle as f64;
+
+        let hop = ((HOP_SECONDS * sample_rate) as usize).max(1);
+        let (mut highest, mut lowest) = (0.0_f64, f64::INFINITY);
+        let mut sampled = Vec::with_capacity(samples.len() / hop + 1);
+        for (n, x) in samples.iter().enumerate() {
+            mean_square += alpha * (x * x - mean_square);
+            highest = highest.max(mean_square);
+            lowest = lowest.min(mean_square);
+            if n % hop == 0 {
+                sampled.push(mean_square);
+            }
+        }
+        sampled.sort_by(f64::total_cmp);
+        let exceeded = |percent: f64| {
+            let n = ((1.0 - percent / 100.0) * (sampled.len() - 1) as f64).round() as usize;
+            level(sampled[n])
+        };
+
+        SoundLevels {
+            leq: level(samples.iter().map(|x| x * x).sum::<f64>() / samples.len() as f64),
+            lmax: level(highest),
+            lmin: level(lowest),
+            l10: exceeded(10.0),
+            l50: exceeded(50.0),
+            l90: exceeded(90.0),
+        }
+    }
+
+    // The samples through the frequency weighting, applied to the spectrum of the whole channel at
+    // once. This gives the weighting exactly and without a phase shift, which matters little for
+    // levels.
+    fn frequency_weighted(&self, weighting: FrequencyWeighting) -> Vec<f64> {
+        let count = self.count();
+        if weighting == FrequencyWeighting::Z || count == 0 {
+            return self.iter_normalized().collect();
+        }
+
+        let fft_len = count.next_power_of_two();
+        let mut buffer: Vec<_> = self.iter_normalized().map(Complex::from).collect();
+        buffer.resize(fft_len, Complex::default());
+        let mut planner = FftPlanner::new();
+        planner.plan_fft_forward(fft_len).process(&mut buffer);
+        for (bin, x) in buffer.iter_mut().enumerate() {
+            let bin = bin.min(fft_len - bin);
+            *x *= weighting.gain(bin as f64 * self.sample_rate as f64 / fft_len as f64);
+        }
+        planner.plan_fft_inverse(fft_len).process(&mut buffer);
+        buffer[..count]
+            .iter()
+            .map(|x| x.re / fft_len as f64)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FrequencyWeighting, TimeWeighting};
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn steady_tones_read_their_weighted_level() {
+        let tone = Generator::sine(1000.0, 0.5).channel();
+        let expected = 20.0 * (0.5 / 2_f64.sqrt()).log10() + 94.0;
+        let levels = tone.sound_levels(FrequencyWeighting::A, TimeWeighting::Fast, 94.0);
+        for level in [
+            levels.leq,
+            levels.lmax,
+            levels.lmin,
+            levels.l10,
+            levels.l50,
+            levels.l90,
+        ] {
+            assert!((level - expected).abs() < 0.1, "{levels:?}");
+        }
+
+        // At 100 Hz, A-weighting is 19.1 dB down and C-weighting 0.3 dB.
+        let low = Generator::sine(100.0, 0.5).channel();
+        let weighted = |weighting| {
+            low.sound_levels(weighting, TimeWeighting::Slow, 0.0).leq
+                - low
+                    .sound_levels(FrequencyWeighting::Z, TimeWeighting::Slow, 0.0)
+                    .leq
+        };
+        assert!((weighted(FrequencyWeighting::A) + 19.1).abs() < 0.1);
+        assert!((weighted(FrequencyWeighting::C) + 0.3).abs() < 0.1);
+    }
+}
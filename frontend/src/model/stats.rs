@@ -0,0 +1,66 @@
+use super::{levels::to_dbfs, Channel};
+
+// Normalized magnitude at or above which a sample is considered to be at full scale.
+const CLIP_LEVEL: f64 = 1.0 - 1e-6;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statistics {
+    pub count: usize,
+    pub duration: f64,
+    pub peak_dbfs: f64,
+    pub rms_dbfs: f64,
+    pub crest_factor_db: f64,
+    pub dc_offset: f64,
+    pub zero_crossings: usize,
+    pub clipping_events: usize,
+}
+
+impl Channel {
+    pub fn statistics(&self) -> Statistics {
+        let mut count = 0;
+        let mut sum = 0.0;
+        let mut square_sum = 0.0;
+        let mut peak = 0.0_f64;
+        let mut zero_crossings = 0;
+        let mut clipping_events = 0;
+        let mut clip_run = 0;
+        let mut previous = None;
+
+        for x in self.iter_normalized() {
+            count += 1;
+            sum += x;
+            square_sum += x * x;
+            peak = peak.max(x.abs());
+
+            if let Some(previous) = previous {
+                if (previous < 0.0) != (x < 0.0) {
+                    zero_crossings += 1;
+                }
+            }
+            previous = Some(x);
+
+            // A clipping event is a run of at least two consecutive full-scale samples.
+            if x.abs() >= CLIP_LEVEL {
+                clip_run += 1;
+                if clip_run == 2 {
+                    clipping_events += 1;
+                }
+            } else {
+                clip_run = 0;
+            }
+        }
+
+        let rms = (square_sum / count.max(1) as f64).sqrt();
+
+        Statistics {
+            count,
+            duration: count as f64 / self.sample_rate as f64,
+            peak_dbfs: to_dbfs(peak),
+            rms_dbfs: to_dbfs(rms),
+            crest_factor_db: to_dbfs(peak) - to_dbfs(rms),
+            dc_offset: sum / count.max(1) as f64,
+            zero_crossings,
+            clipping_events,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::ops::Range;
+
+use super::{Channel, Signal};
+
+// Zero-lag correlation of two channels, from 1 for identical content through 0 for unrelated
+// content to -1 for content in opposite polarity, which cancels when summed to mono. Silence gives
+// 0.
+pub fn phase_correlation(left: &Channel, right: &Channel) -> f64 {
+    let (mut product, mut left_energy, mut right_energy) = (0.0, 0.0, 0.0);
+    for (l, r) in left.iter_normalized().zip(right.iter_normalized()) {
+        product += l * r;
+        left_energy += l * l;
+        right_energy += r * r;
+    }
+    let energy = (left_energy * right_energy).sqrt();
+    if energy == 0.0 {
+        0.0
+    } else {
+        product / energy
+    }
+}
+
+impl Signal {
+    // Phase correlation of the two channels within `range`, or `None` for a mono signal.
+    pub fn phase_correlation(&self, range: Range<usize>) -> Option<f64> {
+        match self {
+            Self::Mono(_) => None,
+            Self::Stereo(left, right) => Some(phase_correlation(
+                &left.slice(range.clone()),
+                &right.slice(range),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::phase_correlation;
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn correlation_follows_polarity() {
+        let sine = Generator::sine(1000.0, 0.5).channel();
+        let inverted = sine.with_normalized_samples(sine.iter_normalized().map(|x| -x));
+        let noise = Generator::white_noise(0.5).channel();
+        let silence = Generator::dc(0.0).channel();
+
+        assert!((phase_correlation(&sine, &sine) - 1.0).abs() < 1e-9);
+        assert!((phase_correlation(&sine, &inverted) + 1.0).abs() < 1e-9);
+        assert!(phase_correlation(&sine, &noise).abs() < 0.05);
+        assert_eq!(phase_correlation(&sine, &silence), 0.0);
+    }
+}
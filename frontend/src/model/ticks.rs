@@ -0,0 +1,65 @@
+// Intervals between the ticks of an axis, chosen among round numbers so that a span of any length,
+// from a few samples to hours, gets about the same number of ticks.
+
+use std::ops::Range;
+
+// The number of ticks aimed for across a span, and the most there may be, beyond which labels
+// start to run into each other.
+pub const TARGET_TICKS: f64 = 10.0;
+pub const MAX_TICKS: f64 = 12.0;
+
+// The steps of 1, 2 and 5 times a power of ten within `range`, in ascending order.
+pub fn round_steps(range: Range<f64>) -> impl Iterator<Item = f64> {
+    let first = range.start.max(f64::MIN_POSITIVE).log10().floor() as i32;
+    let last = range.end.max(f64::MIN_POSITIVE).log10().ceil() as i32;
+    (first..=last)
+        .flat_map(|exponent| [1.0, 2.0, 5.0].map(|factor| factor * 10_f64.powi(exponent)))
+        .filter(move |step| range.contains(step))
+}
+
+// The step among `steps` giving the number of ticks over `span` closest to `TARGET_TICKS`, as a
+// ratio, without exceeding `MAX_TICKS`, or the longest step if they all do.
+pub fn closest_step(span: f64, steps: impl IntoIterator<Item = f64>) -> Option<f64> {
+    let distance = |step: f64| match span / step {
+        count if count > MAX_TICKS => f64::INFINITY,
+        count => (count / TARGET_TICKS).ln().abs(),
+    };
+    steps
+        .into_iter()
+        .filter(|step| *step > 0.0)
+        .min_by(|&a, &b| distance(a).total_cmp(&distance(b)).then(b.total_cmp(&a)))
+}
+
+// The multiples of `step` within `start..=end`.
+pub fn ticks(start: f64, end: f64, step: f64) -> impl Iterator<Item = f64> {
+    let (first, last) = ((start / step).ceil() as i64, (end / step).floor() as i64);
+    (first..=last).map(move |n| n as f64 * step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{closest_step, round_steps, ticks};
+
+    #[test]
+    fn spans_of_any_length_get_about_ten_ticks() {
+        assert_eq!(closest_step(3.0, round_steps(0.001..100.0)), Some(0.5));
+        let mut span = 1e-4;
+        while span < 1e5 {
+            for start in [0.0, span / 3.0, 7.0 * span] {
+                let step = closest_step(span, round_steps(1e-6..1e6)).unwrap();
+                let count = ticks(start, start + span, step).count();
+                assert!(
+                    (4..=13).contains(&count),
+                    "{count} ticks over {span} from {start}"
+                );
+            }
+            span *= 1.37;
+        }
+    }
+
+    #[test]
+    fn round_steps_are_one_two_or_five() {
+        let steps: Vec<_> = round_steps(0.1..100.0).collect();
+        assert_eq!(steps, [0.1, 0.2, 0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0]);
+    }
+}
@@ -0,0 +1,74 @@
+use std::ops::Range;
+
+use super::Spectrum;
+
+// Width of the bands the spectrum is averaged within before the fit, in octaves, and the spacing
+// of their centres.
+const BAND_OCTAVES: f64 = 1.0 / 3.0;
+
+impl Spectrum {
+    // Spectral tilt: the slope, in dB per octave, of the line fitted by least squares to the levels
+    // of the spectrum averaged within third-octave bands between the frequencies of `range`, in Hz.
+    // Averaging the power within bands first keeps the many bins at high frequencies from
+    // outweighing the few at low ones. `None` if fewer than two bands have any bins.
+    pub fn tilt(&self, range: Range<f64>) -> Option<f64> {
+        let nyquist = self.sample_rate() as f64 / 2.0;
+        let (low, high) = (range.start.max(1.0), range.end.min(nyquist));
+        if low >= high {
+            return None;
+        }
+        let powers: Vec<_> = self.magnitudes().map(|m| m * m).collect();
+
+        let num_bands = ((high / low).log2() / BAND_OCTAVES).ceil() as usize;
+        let points: Vec<(f64, f64)> = (0..num_bands)
+            .filter_map(|n| {
+                let octave = n as f64 * BAND_OCTAVES;
+                let lower = low * octave.exp2();
+                let upper = (lower * BAND_OCTAVES.exp2()).min(high);
+                let bins = self.frequency_to_bin(lower).max(1)..self.frequency_to_bin(upper);
+                let band = powers.get(bins.start..bins.end.min(powers.len()))?;
+                if band.is_empty() {
+                    return None;
+                }
+                let mean = band.iter().sum::<f64>() / band.len() as f64;
+                let center = (lower * upper).sqrt().log2();
+                (mean > 0.0).then(|| (center, 10.0 * mean.log10()))
+            })
+            .collect();
+        if points.len() < 2 {
+            return None;
+        }
+
+        let count = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+        let (covariance, variance) = points.iter().fold((0.0, 0.0), |(c, v), (x, y)| {
+            (c + (x - mean_x) * (y - mean_y), v + (x - mean_x).powi(2))
+        });
+        (variance > 0.0).then(|| covariance / variance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn pink_noise_tilts_down_three_db_per_octave() {
+        let white = Generator::white_noise(0.5).with_duration(2.0).channel();
+        let pink = Generator::pink_noise(0.5).with_duration(2.0).channel();
+        let range = 100.0..10_000.0;
+
+        let white_tilt = white.welch(4096, 0.5).spectrum.tilt(range.clone()).unwrap();
+        let pink_tilt = pink.welch(4096, 0.5).spectrum.tilt(range.clone()).unwrap();
+        assert!(
+            white_tilt.abs() < 0.5,
+            "white noise tilts {white_tilt} dB/octave"
+        );
+        assert!(
+            (pink_tilt + 3.0).abs() < 0.5,
+            "pink noise tilts {pink_tilt} dB/octave"
+        );
+        assert_eq!(white.spectrum().tilt(range.end..range.start), None);
+    }
+}
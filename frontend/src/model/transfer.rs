@@ -0,0 +1,153 @@
+use std::f64::consts::PI;
+
+use rustfft::{num_complex::Complex, FftPlanner};
+
+use super::{calibration::Calibration, Channel};
+
+// The transfer function from one channel to another, estimated from their averaged cross- and
+// auto-spectra, along with how much of the output it explains.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferFunction {
+    pub sample_rate: u32,
+    pub segment_len: usize,
+    pub segments: usize,
+    // H(f) for the bins from DC up to the Nyquist frequency.
+    pub response: Vec<Complex<f64>>,
+    // The magnitude-squared coherence of each bin, from 0 for an output that has nothing to do
+    // with the reference to 1 for one that is all a linear response to it.
+    pub coherence: Vec<f64>,
+}
+
+impl TransferFunction {
+    pub fn bin_to_frequency(&self, bin: usize) -> f64 {
+        bin as f64 * self.sample_rate as f64 / self.segment_len as f64
+    }
+
+    pub fn magnitude_db(&self, bin: usize) -> f64 {
+        20.0 * self.response[bin].norm().log10()
+    }
+
+    // Wrapped to ±180°.
+    pub fn phase_degrees(&self, bin: usize) -> f64 {
+        self.response[bin].arg().to_degrees()
+    }
+
+    // The transfer function with the output corrected for the microphone it was recorded with.
+    pub fn calibrated(&self, calibration: &Calibration) -> TransferFunction {
+        let response = self
+            .response
+            .iter()
+            .enumerate()
+            .map(|(bin, h)| {
+                h * 10_f64.powf(-calibration.correction_db(self.bin_to_frequency(bin)) / 20.0)
+            })
+            .collect();
+        TransferFunction {
+            response,
+            ..self.clone()
+        }
+    }
+}
+
+impl Channel {
+    // H(f) = Sxy / Sxx from this channel, the reference, to `output`, with the spectra averaged
+    // over overlapping, Hann-windowed segments as in `welch`. Only the part of the channels that
+    // both cover is used.
+    pub fn transfer_function(
+        &self,
+        output: &Channel,
+        segment_len: usize,
+        overlap: f64,
+    ) -> TransferFunction {
+        let reference: Vec<f64> = self.iter_normalized().collect();
+        let output: Vec<f64> = output.iter_normalized().collect();
+        let len = reference.len().min(output.len());
+        let segment_len = segment_len.min(len).max(2);
+        let hop = ((segment_len as f64 * (1.0 - overlap.clamp(0.0, 0.95))) as usize).max(1);
+        let half = segment_len / 2 + 1;
+
+        let window: Vec<f64> = (0..segment_len)
+            .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f64 / segment_len as f64).cos())
+            .collect();
+        let fft = FftPlanner::new().plan_fft_forward(segment_len);
+        let transform = |samples: &[f64]| {
+            let mut buffer: Vec<_> = samples
+                .iter()
+                .zip(&window)
+                .map(|(x, w)| Complex::from(x * w))
+                .collect();
+            fft.process(&mut buffer);
+            buffer
+        };
+
+        let mut sxx = vec![0.0; half];
+        let mut syy = vec![0.0; half];
+        let mut sxy = vec![Complex::default(); half];
+        let mut segments = 0;
+        if len >= segment_len {
+            bench!(["Calculating cross-spectra"] => {
+                for start in (0..=len - segment_len).step_by(hop) {
+                    let x = transform(&reference[start..start + segment_len]);
+                    let y = transform(&output[start..start + segment_len]);
+                    for k in 0..half {
+                        sxx[k] += x[k].norm_sqr();
+                        syy[k] += y[k].norm_sqr();
+                        sxy[k] += x[k].conj() * y[k];
+                    }
+                    segments += 1;
+                }
+            });
+        }
+
+        let (response, coherence) = (0..half)
+            .map(|k| {
+                if sxx[k] == 0.0 || syy[k] == 0.0 {
+                    return (Complex::default(), 0.0);
+                }
+                (sxy[k] / sxx[k], sxy[k].norm_sqr() / (sxx[k] * syy[k]))
+            })
+            .unzip();
+        TransferFunction {
+            sample_rate: self.sample_rate,
+            segment_len,
+            segments,
+            response,
+            coherence,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn gain_delay_and_coherence_are_estimated() {
+        const DELAY: usize = 8;
+        const SEGMENT_LEN: usize = 4096;
+
+        let reference = Generator::white_noise(0.5).channel();
+        let output = reference.with_normalized_samples(
+            std::iter::repeat_n(0.0, DELAY).chain(reference.iter_normalized().map(|x| 0.5 * x)),
+        );
+
+        let transfer = reference.transfer_function(&output, SEGMENT_LEN, 0.5);
+        assert!(transfer.segments > 10);
+        let bin = 85;
+        assert!((transfer.magnitude_db(bin) + 6.02).abs() < 0.2);
+        let expected_phase = -360.0 * (bin * DELAY) as f64 / SEGMENT_LEN as f64;
+        assert!((transfer.phase_degrees(bin) - expected_phase).abs() < 2.0);
+        assert!(transfer.coherence[bin] > 0.99);
+
+        // Noise the reference does not explain, here a later part of the same noise, lowers the
+        // coherence.
+        let noisy = output.with_normalized_samples(
+            output
+                .iter_normalized()
+                .zip(reference.iter_normalized().skip(10_000))
+                .map(|(y, n)| 0.1 * y + n),
+        );
+        let transfer = reference.transfer_function(&noisy, SEGMENT_LEN, 0.5);
+        assert!(transfer.coherence[bin] < 0.9, "{}", transfer.coherence[bin]);
+    }
+}
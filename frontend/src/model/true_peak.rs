@@ -0,0 +1,62 @@
+use std::ops::Range;
+
+use signal_inspector_analysis as analysis;
+
+use super::{levels::to_dbfs, Channel};
+
+impl Channel {
+    // Peak of the waveform reconstructed between the samples, estimated by oversampling four
+    // times as described in ITU-R BS.1770-4 Annex 2.
+    pub fn true_peak_dbtp(&self) -> f64 {
+        to_dbfs(self.true_peaks().into_iter().fold(0.0, f64::max))
+    }
+
+    // Runs of samples where the reconstructed waveform exceeds `ceiling_dbtp`, including overs
+    // between samples that are themselves below it. An over between two samples spans both.
+    pub fn true_peak_overs(&self, ceiling_dbtp: f64) -> Vec<Range<usize>> {
+        let ceiling = 10_f64.powf(ceiling_dbtp / 20.0);
+        let peaks = self.true_peaks();
+        let mut overs: Vec<Range<usize>> = Vec::new();
+        for (n, &peak) in peaks.iter().enumerate() {
+            if peak <= ceiling {
+                continue;
+            }
+            let end = (n + 2).min(peaks.len());
+            match overs.last_mut() {
+                Some(over) if over.end >= n => over.end = end,
+                _ => overs.push(n..end),
+            }
+        }
+        overs
+    }
+
+    // Magnitude of each sample, or of the waveform between it and the next one if that is larger,
+    // relative to full scale.
+    fn true_peaks(&self) -> Vec<f64> {
+        analysis::true_peaks(&self.iter_normalized().collect::<Vec<_>>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::generator::Generator;
+
+    #[test]
+    fn overs_between_samples_are_found() {
+        // A quarter of the sample rate with a 45° phase puts every sample at 1/√2 of the peak.
+        let samples = (0..4800).map(|n| {
+            let phase = std::f64::consts::FRAC_PI_2 * n as f64 + std::f64::consts::FRAC_PI_4;
+            0.9 * phase.sin()
+        });
+        let sine = Generator::dc(0.0)
+            .channel()
+            .with_normalized_samples(samples);
+
+        assert!(sine.statistics().peak_dbfs < -3.9);
+        assert!((sine.true_peak_dbtp() + 0.92).abs() < 0.2);
+        assert!(sine.true_peak_overs(0.0).is_empty());
+        let overs = sine.true_peak_overs(-3.0);
+        assert_eq!(overs.len(), 1, "{overs:?}");
+        assert!(overs[0].len() > 4700, "{overs:?}");
+    }
+}
@@ -0,0 +1,107 @@
+use std::{f64::consts::PI, ops::Range};
+
+use rustfft::{num_complex::Complex, FftPlanner};
+
+use super::Channel;
+
+const FRAME_SECONDS: f64 = 0.02;
+const HOP_SECONDS: f64 = 0.01;
+const HANGOVER_SECONDS: f64 = 0.2;
+const MIN_SPEECH_SECONDS: f64 = 0.1;
+const SPEECH_BAND: Range<f64> = 100.0..4000.0;
+
+struct Frame {
+    energy_db: f64,
+    band_ratio: f64,
+    flatness: f64,
+}
+
+impl Channel {
+    // Energy-based voice activity detection, gated by the share of energy within the speech band
+    // and the spectral flatness of each frame, so that broadband noise is not picked up as speech.
+    pub fn detect_speech(&self, margin_db: f64) -> Vec<Range<usize>> {
+        let sample_rate = self.sample_rate as f64;
+        let frame_len = ((FRAME_SECONDS * sample_rate) as usize).max(1);
+        let hop = ((HOP_SECONDS * sample_rate) as usize).max(1);
+        let samples: Vec<f64> = self.iter_normalized().collect();
+
+        if samples.len() < frame_len {
+            return Vec::new();
+        }
+
+        let fft_len = frame_len.next_power_of_two();
+        let fft = FftPlanner::new().plan_fft_forward(fft_len);
+        let window: Vec<f64> = (0..frame_len)
+            .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f64 / frame_len as f64).cos())
+            .collect();
+        let band = (SPEECH_BAND.start * fft_len as f64 / sample_rate) as usize
+            ..(SPEECH_BAND.end * fft_len as f64 / sample_rate) as usize;
+
+        let frames: Vec<Frame> = (0..=samples.len() - frame_len)
+            .step_by(hop)
+            .map(|start| {
+                let frame = &samples[start..start + frame_len];
+                let mean_square = frame.iter().map(|x| x * x).sum::<f64>() / frame_len as f64;
+
+                let mut buffer: Vec<_> = frame
+                    .iter()
+                    .zip(&window)
+                    .map(|(x, w)| Complex::from(x * w))
+                    .collect();
+                buffer.resize(fft_len, Complex::default());
+                fft.process(&mut buffer);
+
+                let power: Vec<f64> = buffer[1..fft_len / 2]
+                    .iter()
+                    .map(|c| c.norm_sqr())
+                    .collect();
+                let total = power.iter().sum::<f64>();
+                let in_band = power
+                    .iter()
+                    .enumerate()
+                    .filter(|(n, _)| band.contains(&(n + 1)))
+                    .map(|(_, p)| p)
+                    .sum::<f64>();
+                let log_mean =
+                    power.iter().map(|p| (p + 1e-20).ln()).sum::<f64>() / power.len() as f64;
+                let mean = total / power.len() as f64;
+
+                Frame {
+                    energy_db: 10.0 * (mean_square + 1e-12).log10(),
+                    band_ratio: if total > 0.0 { in_band / total } else { 0.0 },
+                    flatness: if mean > 0.0 {
+                        log_mean.exp() / mean
+                    } else {
+                        1.0
+                    },
+                }
+            })
+            .collect();
+
+        let mut energies: Vec<f64> = frames.iter().map(|f| f.energy_db).collect();
+        energies.sort_by(f64::total_cmp);
+        let noise_floor = energies[energies.len() / 10];
+
+        let mut regions: Vec<Range<usize>> = Vec::new();
+        let hangover = (HANGOVER_SECONDS * sample_rate) as usize;
+        for (i, frame) in frames.iter().enumerate() {
+            let is_speech = frame.energy_db > noise_floor + margin_db
+                && frame.energy_db > -60.0
+                && frame.band_ratio > 0.6
+                && frame.flatness < 0.3;
+            if !is_speech {
+                continue;
+            }
+
+            let range = i * hop..i * hop + frame_len;
+            match regions.last_mut() {
+                Some(last) if range.start <= last.end + hangover => last.end = range.end,
+                _ => regions.push(range),
+            }
+        }
+
+        let min_len = (MIN_SPEECH_SECONDS * sample_rate) as usize;
+        regions.retain(|r| r.len() >= min_len);
+        regions
+    }
+}
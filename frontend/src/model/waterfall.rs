@@ -0,0 +1,314 @@
+use std::{f64::consts::PI, ops::Range, rc::Rc};
+
+use rustfft::{num_complex::Complex, FftPlanner};
+
+use super::{
+    cache::{self, ChannelFingerprint},
+    levels::to_dbfs,
+    Channel,
+};
+
+// Frames are transformed and cached this many at a time, so that scrolling or zooming back only
+// transforms the frames not seen before.
+const TILE_FRAMES: usize = 16;
+// Bounds on the frame length, which follows the hop so that frames just about overlap by half. Deep
+// zooms get short frames rather than a hop smaller than needed, and wide views long frames that
+// leave gaps between them rather than transforming every sample.
+const MIN_FRAME_LEN: usize = 256;
+const MAX_FRAME_LEN: usize = 8192;
+
+// Short-time spectra of consecutive frames, oldest first, with bin levels in dBFS relative to a
+// full-scale sine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waterfall {
+    pub frames: Vec<Vec<f64>>,
+    pub frame_len: usize,
+    // Samples from the start of one frame to the start of the next.
+    pub hop: usize,
+    pub sample_rate: u32,
+}
+
+impl Waterfall {
+    pub fn frequency_to_bin(&self, frequency: f64) -> usize {
+        (frequency * self.frame_len as f64 / self.sample_rate as f64).round() as usize
+    }
+}
+
+// A channel along with the fingerprint its frames are cached by, which is taken once rather than
+// each time the waterfall moves.
+#[derive(Clone)]
+pub struct Spectrogram {
+    channel: Channel,
+    fingerprint: ChannelFingerprint,
+}
+
+impl PartialEq for Spectrogram {
+    fn eq(&self, other: &Self) -> bool {
+        self.fingerprint == other.fingerprint
+    }
+}
+
+impl Channel {
+    pub fn spectrogram(&self) -> Spectrogram {
+        Spectrogram {
+            channel: self.clone(),
+            fingerprint: self.fingerprint(),
+        }
+    }
+}
+
+impl Spectrogram {
+    // Hann-windowed frames spanning at least `span` samples, the last of which ends at or before
+    // `end`. The hop is `span` divided between the frames, rounded up to a power of two so that the
+    // frames of nearby zoom levels are shared. Frames that would start before the channel are left
+    // out.
+    pub fn waterfall(&self, end: usize, span: usize, num_frames: usize) -> Waterfall {
+        let Layout {
+            hop,
+            frame_len,
+            frames,
+        } = self.layout(end, span, num_frames);
+        let (first, frames_to_end) = (frames.start, frames.end);
+
+        let mut frames = Vec::with_capacity(frames_to_end - first);
+        let mut index = first;
+        while index < frames_to_end {
+            let tile = index / TILE_FRAMES;
+            let frames_in_tile =
+                cache::waterfall_tile(self.fingerprint, hop, frame_len, tile, || {
+                    Rc::new(self.tile(hop, frame_len, tile))
+                });
+            let offset = index - tile * TILE_FRAMES;
+            let count = (frames_to_end - index).min(TILE_FRAMES - offset);
+            frames.extend_from_slice(&frames_in_tile[offset..offset + count]);
+            index += count;
+        }
+
+        Waterfall {
+            frames,
+            frame_len,
+            hop,
+            sample_rate: self.channel.sample_rate,
+        }
+    }
+
+    // The tiles of the waterfall `waterfall` would return that are not cached yet, with their
+    // frames windowed for transforming elsewhere, such as on the GPU.
+    pub fn uncached_tiles(&self, end: usize, span: usize, num_frames: usize) -> Vec<PendingTile> {
+        let Layout {
+            hop,
+            frame_len,
+            frames,
+        } = self.layout(end, span, num_frames);
+        if frames.is_empty() {
+            return Vec::new();
+        }
+
+        let window = hann(frame_len);
+        (frames.start / TILE_FRAMES..=(frames.end - 1) / TILE_FRAMES)
+            .filter(|&tile| {
+                cache::cached_waterfall_tile(self.fingerprint, hop, frame_len, tile).is_none()
+            })
+            .map(|tile| PendingTile {
+                hop,
+                frame_len,
+                tile,
+                samples: self
+                    .frame_offsets(hop, frame_len, tile)
+                    .flat_map(|offset| self.windowed(offset, &window))
+                    .map(|x| x as f32)
+                    .collect(),
+            })
+            .collect()
+    }
+
+    fn layout(&self, end: usize, span: usize, num_frames: usize) -> Layout {
+        let hop = span.div_ceil(num_frames.max(1)).max(1).next_power_of_two();
+        let frame_len = (2 * hop).clamp(MIN_FRAME_LEN, MAX_FRAME_LEN);
+        let end = end.min(self.channel.count());
+        // The frame with index `n` starts at sample `n * hop`.
+        let frames_to_end = if end < frame_len {
+            0
+        } else {
+            (end - frame_len) / hop + 1
+        };
+        Layout {
+            hop,
+            frame_len,
+            frames: frames_to_end.saturating_sub(num_frames)..frames_to_end,
+        }
+    }
+
+    // The offsets of the frames of a tile that fit within the channel.
+    fn frame_offsets(
+        &self,
+        hop: usize,
+        frame_len: usize,
+        tile: usize,
+    ) -> impl Iterator<Item = usize> + '_ {
+        let start = tile * TILE_FRAMES * hop;
+        (0..TILE_FRAMES)
+            .map(move |frame| start + frame * hop)
+            .take_while(move |&offset| offset + frame_len <= self.channel.count())
+    }
+
+    fn windowed(&self, offset: usize, window: &[f64]) -> Vec<f64> {
+        self.channel
+            .slice(offset..offset + window.len())
+            .iter_normalized()
+            .zip(window)
+            .map(|(x, w)| x * w)
+            .collect()
+    }
+
+    // The frames of a tile that fit within the channel.
+    fn tile(&self, hop: usize, frame_len: usize, tile: usize) -> Vec<Vec<f64>> {
+        let window = hann(frame_len);
+        let scale = level_scale(&window);
+        let fft = FftPlanner::new().plan_fft_forward(frame_len);
+
+        bench!(["Calculating waterfall frames"] => self
+            .frame_offsets(hop, frame_len, tile)
+            .map(|offset| {
+                let mut buffer: Vec<_> = self.windowed(offset, &window).into_iter().map(Complex::from).collect();
+                fft.process(&mut buffer);
+                buffer[..frame_len / 2]
+                    .iter()
+                    .map(|c| to_dbfs(c.norm() * scale))
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+struct Layout {
+    hop: usize,
+    frame_len: usize,
+    // The indices of the frames in the waterfall.
+    frames: Range<usize>,
+}
+
+// The windowed frames of a waterfall tile, to be transformed and handed back with `finish`.
+pub struct PendingTile {
+    hop: usize,
+    frame_len: usize,
+    tile: usize,
+    samples: Vec<f32>,
+}
+
+impl PendingTile {
+    pub fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+
+    // The frames one after the other.
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    // Caches the tile from the complex spectra of its frames, given as interleaved real and
+    // imaginary parts of all `frame_len` bins of each frame, so that the waterfall uses them.
+    pub fn finish(self, spectrogram: &Spectrogram, spectra: &[f32]) {
+        let scale = level_scale(&hann(self.frame_len));
+        let frames = spectra
+            .chunks_exact(2 * self.frame_len)
+            .map(|frame| {
+                frame[..self.frame_len]
+                    .chunks_exact(2)
+                    .map(|c| to_dbfs(f64::from(c[0]).hypot(f64::from(c[1])) * scale))
+                    .collect()
+            })
+            .collect();
+        let frames = Rc::new(frames);
+        cache::waterfall_tile(
+            spectrogram.fingerprint,
+            self.hop,
+            self.frame_len,
+            self.tile,
+            || frames,
+        );
+    }
+}
+
+fn hann(len: usize) -> Vec<f64> {
+    (0..len)
+        .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f64 / len as f64).cos())
+        .collect()
+}
+
+// A full-scale sine has a bin magnitude of half the window sum.
+fn level_scale(window: &[f64]) -> f64 {
+    2.0 / window.iter().sum::<f64>()
+}
+
+#[cfg(test)]
+mod tests {
+    use rustfft::{num_complex::Complex, FftPlanner};
+
+    use crate::model::{
+        cache,
+        generator::{BitDepth, Generator},
+    };
+
+    #[test]
+    fn frames_follow_the_zoom() {
+        let channel = Generator::sine(1000.0, 0.5)
+            .with_bit_depth(BitDepth::Int16)
+            .channel();
+        let spectrogram = channel.spectrogram();
+
+        let wide = spectrogram.waterfall(channel.count(), channel.count(), 128);
+        let narrow = spectrogram.waterfall(channel.count(), 2048, 128);
+        assert!(wide.hop > narrow.hop && wide.frame_len > narrow.frame_len);
+        assert!(wide.hop * wide.frames.len() >= channel.count() - wide.frame_len);
+        assert_eq!(narrow.frames.len(), 128);
+
+        // Frames shared between the waterfalls are identical however they were tiled.
+        let earlier = spectrogram.waterfall(channel.count() - 5 * narrow.hop, 2048, 128);
+        assert_eq!(earlier.frames[5..], narrow.frames[..123]);
+
+        let peak = |waterfall: &super::Waterfall| {
+            let frame = &waterfall.frames[waterfall.frames.len() / 2];
+            let bin = (0..frame.len())
+                .max_by(|&a, &b| frame[a].total_cmp(&frame[b]))
+                .unwrap_or(0);
+            bin as f64 * waterfall.sample_rate as f64 / waterfall.frame_len as f64
+        };
+        for waterfall in [&wide, &narrow] {
+            let resolution = waterfall.sample_rate as f64 / waterfall.frame_len as f64;
+            assert!((peak(waterfall) - 1000.0).abs() <= resolution);
+        }
+    }
+
+    #[test]
+    fn tiles_transformed_elsewhere_are_used() {
+        let channel = Generator::sine(1000.0, 0.5)
+            .with_bit_depth(BitDepth::Int16)
+            .channel()
+            .slice(0..20000);
+        let spectrogram = channel.spectrogram();
+
+        let pending = spectrogram.uncached_tiles(channel.count(), 4096, 16);
+        assert_eq!(pending.len(), 2);
+        for tile in pending {
+            let expected = spectrogram.tile(tile.hop, tile.frame_len, tile.tile);
+            let mut buffer: Vec<_> = tile.samples().iter().map(|&x| Complex::from(x)).collect();
+            let fft = FftPlanner::new().plan_fft_forward(tile.frame_len());
+            fft.process(&mut buffer);
+            let spectra: Vec<f32> = buffer.iter().flat_map(|c| [c.re, c.im]).collect();
+
+            let index = tile.tile;
+            tile.finish(&spectrogram, &spectra);
+            let cached = cache::cached_waterfall_tile(spectrogram.fingerprint, 256, 512, index)
+                .expect("should be cached");
+            for (frame, expected) in cached.iter().zip(&expected) {
+                for (level, expected) in frame.iter().zip(expected) {
+                    assert!((level - expected).abs() < 0.01 || *expected < -100.0);
+                }
+            }
+        }
+        assert!(spectrogram
+            .uncached_tiles(channel.count(), 4096, 16)
+            .is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+use std::f64::consts::PI;
+
+use im::Vector;
+use rustfft::{num_complex::Complex, FftPlanner};
+
+use super::{cache, Channel, Spectrum};
+
+// Two-sided 95 % quantile of the normal distribution.
+const Z_95: f64 = 1.96;
+
+// Per-bin bounds of the confidence interval of an averaged spectrum, on the same scale as
+// `Spectrum::magnitudes`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfidenceBand {
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct WelchSpectrum {
+    pub spectrum: Spectrum,
+    pub confidence: ConfidenceBand,
+    pub segments: usize,
+    // The loudest each bin is in any of the segments, for what is averaged away in the spectrum.
+    pub maximum: Spectrum,
+}
+
+impl Channel {
+    // Welch's method: the power spectra of overlapping, Hann-windowed segments are averaged. The
+    // confidence band spans the mean power ± 1.96 standard errors across the segments.
+    pub fn welch(&self, segment_len: usize, overlap: f64) -> WelchSpectrum {
+        cache::welch(self, segment_len, overlap, || {
+            self.welch_uncached(segment_len, overlap)
+        })
+    }
+
+    fn welch_uncached(&self, segment_len: usize, overlap: f64) -> WelchSpectrum {
+        let samples: Vec<f64> = self.iter().map(f64::from).collect();
+        let segment_len = segment_len.min(samples.len()).max(2);
+        let hop = ((segment_len as f64 * (1.0 - overlap.clamp(0.0, 0.95))) as usize).max(1);
+        let half = segment_len / 2;
+
+        let window: Vec<f64> = (0..segment_len)
+            .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f64 / segment_len as f64).cos())
+            .collect();
+        let fft = FftPlanner::new().plan_fft_forward(segment_len);
+
+        let mut sum = vec![0.0; half];
+        let mut sum_sq = vec![0.0; half];
+        let mut max = vec![0.0_f64; half];
+        let mut segments = 0;
+        if samples.len() >= segment_len {
+            bench!(["Calculating Welch segments"] => {
+                for start in (0..=samples.len() - segment_len).step_by(hop) {
+                    let mut buffer: Vec<_> = samples[start..start + segment_len]
+                        .iter()
+                        .zip(&window)
+                        .map(|(x, w)| Complex::from(x * w))
+                        .collect();
+                    fft.process(&mut buffer);
+
+                    for (k, c) in buffer[..half].iter().enumerate() {
+                        let power = c.norm_sqr();
+                        sum[k] += power;
+                        sum_sq[k] += power * power;
+                        max[k] = max[k].max(power);
+                    }
+                    segments += 1;
+                }
+            });
+        }
+
+        // Compensates for the window, so that a sine has the same magnitude as in an unwindowed
+        // transform of the whole channel.
+        let gain = segment_len as f64 / window.iter().sum::<f64>();
+        let scale = segment_len as f64 * self.full_scale();
+        let count = segments.max(1) as f64;
+
+        let mut transform = Vector::new();
+        let mut lower = Vec::with_capacity(half);
+        let mut upper = Vec::with_capacity(half);
+        for (sum, sum_sq) in sum.into_iter().zip(sum_sq) {
+            let mean = sum / count;
+            let variance = if segments > 1 {
+                (sum_sq / count - mean * mean).max(0.0) * count / (count - 1.0)
+            } else {
+                0.0
+            };
+            let margin = Z_95 * (variance / count).sqrt();
+
+            transform.push_back(Complex::from(mean.sqrt() * gain));
+            lower.push((mean - margin).max(0.0).sqrt() * gain / scale);
+            upper.push((mean + margin).sqrt() * gain / scale);
+        }
+
+        let spectrum = Spectrum {
+            transform,
+            sample_rate: self.sample_rate,
+            num_samples: segment_len,
+            full_scale: self.full_scale(),
+        };
+        let maximum = Spectrum {
+            transform: max
+                .into_iter()
+                .map(|power| Complex::from(power.sqrt() * gain))
+                .collect(),
+            ..spectrum.clone()
+        };
+        WelchSpectrum {
+            spectrum,
+            confidence: ConfidenceBand { lower, upper },
+            segments,
+            maximum,
+        }
+    }
+}
@@ -0,0 +1,146 @@
+// Reads and writes WAV files with 64-bit samples, which hound supports neither of. Samples are
+// stored as little-endian integers or IEEE floats, interleaved like those of narrower formats.
+
+use hound::SampleFormat;
+
+use super::{
+    riff::{self, FORMAT_IEEE_FLOAT, FORMAT_PCM},
+    Channel, Sample, Signal, WavLimits, MAX_SAMPLE_RATE,
+};
+
+const BYTES_PER_SAMPLE: usize = 8;
+
+pub(super) fn is_wide(data: &[u8]) -> bool {
+    riff::format_and_samples(data).is_some_and(|(format, _)| format.bits_per_sample == 64)
+}
+
+// The sample rate of a file with 64-bit samples, or `None` for other files.
+pub(super) fn sample_rate(data: &[u8]) -> Option<u32> {
+    riff::format_and_samples(data)
+        .filter(|(format, _)| format.bits_per_sample == 64)
+        .map(|(format, _)| format.sample_rate)
+}
+
+// Keeps every `step`th frame only, like decoding narrower samples.
+pub(super) fn decode(data: &[u8], limits: WavLimits, step: usize) -> Result<Signal, hound::Error> {
+    let (format, samples) = riff::format_and_samples(data)
+        .ok_or(hound::Error::FormatError("missing format or data chunk"))?;
+    if format.sample_rate == 0 || format.sample_rate > MAX_SAMPLE_RATE {
+        return Err(hound::Error::FormatError("invalid sample rate"));
+    }
+    let channels = usize::from(format.channels);
+    if !(1..=2).contains(&channels) {
+        return Err(hound::Error::Unsupported);
+    }
+    let frames = samples.len() / (BYTES_PER_SAMPLE * channels);
+    if frames as f64 / f64::from(format.sample_rate) > limits.max_duration {
+        return Err(hound::Error::FormatError(
+            "file exceeds the maximum duration",
+        ));
+    }
+
+    let step = step.max(1);
+    let sample_rate = (format.sample_rate / step as u32).max(1);
+    let float = format.is_float();
+    let channel = |n: usize| {
+        let words = samples
+            .chunks_exact(BYTES_PER_SAMPLE)
+            .skip(n)
+            .step_by(channels * step)
+            .take(frames.div_ceil(step))
+            .map(|bytes| <[u8; BYTES_PER_SAMPLE]>::try_from(bytes).unwrap_or_default());
+        if float {
+            Channel::from_samples_f64(words.map(f64::from_le_bytes), 64, sample_rate)
+        } else {
+            Channel::from_samples_i64(words.map(i64::from_le_bytes), 64, sample_rate)
+        }
+    };
+
+    Ok(match channels {
+        1 => Signal::Mono(channel(0)),
+        _ => Signal::Stereo(channel(0), channel(1)),
+    })
+}
+
+pub(super) fn encode(signal: &Signal) -> Result<Vec<u8>, hound::Error> {
+    let first = signal.channel(0);
+    let channels = signal.num_channels();
+    let block_align = BYTES_PER_SAMPLE * channels;
+    let samples_len = first.count() * block_align;
+    let format_tag = match first.sample_format {
+        SampleFormat::Int => FORMAT_PCM,
+        SampleFormat::Float => FORMAT_IEEE_FLOAT,
+    };
+
+    let (riff_len, data_len) = chunk_sizes(samples_len)?;
+
+    let mut data = Vec::with_capacity(44 + samples_len);
+    data.extend(b"RIFF");
+    data.extend(riff_len.to_le_bytes());
+    data.extend(b"WAVE");
+    data.extend(b"fmt ");
+    data.extend(16_u32.to_le_bytes());
+    data.extend(format_tag.to_le_bytes());
+    data.extend((channels as u16).to_le_bytes());
+    data.extend(first.sample_rate.to_le_bytes());
+    data.extend((first.sample_rate * block_align as u32).to_le_bytes());
+    data.extend((block_align as u16).to_le_bytes());
+    data.extend(64_u16.to_le_bytes());
+    data.extend(b"data");
+    data.extend(data_len.to_le_bytes());
+
+    let mut write = |sample| match sample {
+        Sample::Int64(n) => data.extend(n.to_le_bytes()),
+        Sample::Float64(x) => data.extend(x.to_le_bytes()),
+        _ => unreachable!("only 64-bit samples are written here"),
+    };
+    match signal {
+        Signal::Mono(channel) => channel.iter().for_each(&mut write),
+        Signal::Stereo(left, right) => left.iter().zip(right.iter()).for_each(|(l, r)| {
+            write(l);
+            write(r);
+        }),
+    }
+    Ok(data)
+}
+
+// The sizes of the RIFF and data chunks holding `samples_len` bytes of samples, which must fit in
+// the 32 bits a WAV file has for them.
+fn chunk_sizes(samples_len: usize) -> Result<(u32, u32), hound::Error> {
+    let too_large = || hound::Error::FormatError("samples exceed the 4 GiB a WAV file can hold");
+    let data_len = u32::try_from(samples_len).map_err(|_| too_large())?;
+    let riff_len = data_len.checked_add(36).ok_or_else(too_large)?;
+    Ok((riff_len, data_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{Channel, Signal};
+
+    #[test]
+    fn wide_samples_round_trip() {
+        // Neither fits in the 32-bit formats without losing precision.
+        let left = Channel::from_samples_f64([0.1, -1e-300, 1.0 + 1e-12], 64, 96000);
+        let right = Channel::from_samples_f64([0.0, 0.5, -0.5], 64, 96000);
+        let floats = Signal::Stereo(left, right);
+        let ints = Signal::Mono(Channel::from_samples_i64([i64::MIN, 1, i64::MAX], 64, 8000));
+
+        for signal in [floats, ints] {
+            let data = signal.to_wav().expect("should encode");
+            let decoded = Signal::from_wav(data).expect("should decode");
+            assert!(decoded == signal);
+        }
+    }
+
+    #[test]
+    fn sizes_beyond_32_bits_are_errors() {
+        let max = u32::MAX as usize;
+        assert_eq!(super::chunk_sizes(8).ok(), Some((44, 8)));
+        assert_eq!(
+            super::chunk_sizes(max - 36).ok(),
+            Some((u32::MAX, u32::MAX - 36))
+        );
+        assert!(super::chunk_sizes(max - 35).is_err());
+        assert!(super::chunk_sizes(max + 1).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use std::ops::Range;
+
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{
+    AudioBufferSourceNode, AudioContext, AudioScheduledSourceNode, DynamicsCompressorNode, GainNode,
+};
+
+use crate::{model::Signal, settings::PlaybackSettings};
+
+// The limiter is a compressor with a hard knee, a high ratio and a fast attack, which lets peaks
+// through only briefly and a little above the ceiling.
+const LIMITER_RATIO: f32 = 20.0;
+const LIMITER_ATTACK_SECONDS: f32 = 0.001;
+const LIMITER_RELEASE_SECONDS: f32 = 0.05;
+
+// Plays part of a signal through the Web Audio API. Pausing suspends the whole audio context, so
+// that playback resumes where it left off. Looping is left to the source node, which wraps around
+// sample-accurately and therefore without clicks or gaps at the boundaries. The output goes through
+// a gain and a limiter on the way to the speakers.
+pub struct Player {
+    context: AudioContext,
+    source: AudioBufferSourceNode,
+    gain: GainNode,
+    limiter: DynamicsCompressorNode,
+    // The sample of the signal at the start of the buffer, and the time in the buffer at which
+    // playback started.
+    offset: usize,
+    start: f64,
+    _on_ended: Closure<dyn FnMut()>,
+}
+
+impl Player {
+    pub fn play(
+        signal: &Signal,
+        range: Range<usize>,
+        output: PlaybackSettings,
+        on_ended: impl FnMut() + 'static,
+    ) -> Result<Self, JsValue> {
+        let player = Self::new(signal, range, 0.0, output, on_ended)?;
+        player.source.start()?;
+        Ok(player)
+    }
+
+    // Repeats `region` until stopped. The whole signal is buffered, so that the loop points can be
+    // moved anywhere while playing.
+    pub fn play_loop(
+        signal: &Signal,
+        region: Range<usize>,
+        output: PlaybackSettings,
+    ) -> Result<Self, JsValue> {
+        let start = region.start as f64 / f64::from(signal.channel(0).sample_rate());
+        let player = Self::new(signal, 0..signal.channel(0).count(), start, output, || ())?;
+        player.source.set_loop(true);
+        player.set_loop(region);
+        player.source.start_with_when_and_grain_offset(0.0, start)?;
+        Ok(player)
+    }
+
+    fn new(
+        signal: &Signal,
+        range: Range<usize>,
+        start: f64,
+        output: PlaybackSettings,
+        on_ended: impl FnMut() + 'static,
+    ) -> Result<Self, JsValue> {
+        let context = AudioContext::new()?;
+        let first = signal.channel(0);
+        let len = range.len().min(first.count().saturating_sub(range.start));
+        let buffer = context.create_buffer(
+            signal.num_channels() as u32,
+            len.max(1) as u32,
+            first.sample_rate() as f32,
+        )?;
+        for n in 0..signal.num_channels() {
+            let samples: Vec<f32> = signal
+                .channel(n)
+                .iter_normalized()
+                .skip(range.start)
+                .take(len)
+                .map(|x| x as f32)
+                .collect();
+            buffer.copy_to_channel(&samples, n as i32)?;
+        }
+
+        let source = context.create_buffer_source()?;
+        source.set_buffer(Some(&buffer));
+        let gain = context.create_gain()?;
+        let limiter = context.create_dynamics_compressor()?;
+        source.connect_with_audio_node(&gain)?;
+        gain.connect_with_audio_node(&limiter)?;
+        limiter.connect_with_audio_node(&context.destination())?;
+        let on_ended = Closure::<dyn FnMut()>::new(on_ended);
+        AudioScheduledSourceNode::set_onended(&source, Some(on_ended.as_ref().unchecked_ref()));
+
+        let player = Self {
+            context,
+            source,
+            gain,
+            limiter,
+            offset: range.start,
+            start,
+            _on_ended: on_ended,
+        };
+        player.set_output(output);
+        Ok(player)
+    }
+
+    // Changes the gain and the limiter, taking effect right away. A limiter that is off passes
+    // everything through unchanged.
+    pub fn set_output(&self, output: PlaybackSettings) {
+        self.gain
+            .gain()
+            .set_value(10_f64.powf(output.gain_db / 20.0) as f32);
+        let (threshold, ratio) = if output.limiter {
+            (output.ceiling_dbfs as f32, LIMITER_RATIO)
+        } else {
+            (0.0, 1.0)
+        };
+        self.limiter.threshold().set_value(threshold);
+        self.limiter.ratio().set_value(ratio);
+        self.limiter.knee().set_value(0.0);
+        self.limiter.attack().set_value(LIMITER_ATTACK_SECONDS);
+        self.limiter.release().set_value(LIMITER_RELEASE_SECONDS);
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.source.loop_()
+    }
+
+    // Moves the loop points. Takes effect on the next pass, or immediately if the playhead is
+    // outside the new region.
+    pub fn set_loop(&self, region: Range<usize>) {
+        self.source.set_loop_start(self.seconds(region.start));
+        self.source.set_loop_end(self.seconds(region.end));
+    }
+
+    // The sample of the signal being played, going by the time the audio context has been running.
+    pub fn position(&self) -> usize {
+        let Some(buffer) = self.source.buffer() else {
+            return self.offset;
+        };
+        let mut time = self.start + self.context.current_time();
+        let (loop_start, loop_end) = (self.source.loop_start(), self.source.loop_end());
+        if self.is_looping() && loop_end > loop_start && time > loop_end {
+            time = loop_start + (time - loop_end) % (loop_end - loop_start);
+        }
+        self.offset + (time.min(buffer.duration()) * f64::from(buffer.sample_rate())) as usize
+    }
+
+    fn seconds(&self, sample: usize) -> f64 {
+        let sample_rate = self
+            .source
+            .buffer()
+            .map_or(1.0, |buffer| buffer.sample_rate());
+        sample as f64 / f64::from(sample_rate)
+    }
+
+    pub fn pause(&self) -> Result<(), JsValue> {
+        self.context.suspend().map(drop)
+    }
+
+    pub fn resume(&self) -> Result<(), JsValue> {
+        self.context.resume().map(drop)
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        AudioScheduledSourceNode::set_onended(&self.source, None);
+        let _ = self.context.close();
+    }
+}
@@ -0,0 +1,50 @@
+use std::collections::BTreeMap;
+
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::AnalysisSettings;
+
+const STORAGE_KEY: &str = "signal-inspector.presets";
+
+// Named analysis presets, persisted in local storage, with an optional default preset and presets
+// assigned to individual files by name.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Presets {
+    pub presets: BTreeMap<String, AnalysisSettings>,
+    pub default: Option<String>,
+    pub files: BTreeMap<String, String>,
+}
+
+impl Presets {
+    pub fn load() -> Self {
+        LocalStorage::get(STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn store(&self) {
+        if let Err(error) = LocalStorage::set(STORAGE_KEY, self) {
+            crate::log(&format!("failed to store presets: {error}"));
+        }
+    }
+
+    pub fn default_settings(&self) -> Option<&AnalysisSettings> {
+        self.presets.get(self.default.as_ref()?)
+    }
+
+    // The preset assigned to the file, falling back to the default preset.
+    pub fn settings_for(&self, file: &str) -> Option<&AnalysisSettings> {
+        self.files
+            .get(file)
+            .and_then(|name| self.presets.get(name))
+            .or_else(|| self.default_settings())
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.presets.remove(name);
+        if self.default.as_deref() == Some(name) {
+            self.default = None;
+        }
+        self.files.retain(|_, preset| preset != name);
+    }
+}
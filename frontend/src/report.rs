@@ -0,0 +1,229 @@
+// A report of the measurements of the processed signal as a single HTML document, for handing off to
+// people who do not use the tool. It embeds the waveform and, when it is shown, the spectrum as they
+// are on screen, along with the loudness, the statistics of each channel, the markers and the
+// metadata of the file. Printing it, to paper or to PDF, is left to the browser.
+
+use std::fmt::Write;
+
+use wasm_bindgen::JsValue;
+
+use crate::{
+    components::metadata::INFO_NAMES,
+    export::{self, escape},
+    markers::Marker,
+    model::{riff::Metadata, Signal},
+};
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em auto; max-width: 60em; color: #222; }
+h1 { margin-bottom: 0; }
+.generated { color: #666; margin-top: 0.25em; }
+figure { margin: 1em 0; break-inside: avoid; }
+figure svg { max-width: 100%; height: auto; }
+figcaption { color: #666; font-size: 0.9em; }
+table { border-collapse: collapse; margin: 1em 0; break-inside: avoid; }
+th, td { padding: 0.25em 1em 0.25em 0; text-align: left; }
+td + td, th + th { text-align: right; }
+tr + tr { border-top: 1px solid #ddd; }
+";
+
+// The report on `signal`, loaded from the file called `name` with `metadata`, and marked with
+// `markers`. A report to be printed opens the print dialog once it has loaded.
+pub fn report_html(
+    signal: &Signal,
+    name: &str,
+    metadata: Option<&Metadata>,
+    markers: &[Marker],
+    print: bool,
+) -> Result<String, JsValue> {
+    let document = gloo::utils::document();
+    let mut figures = String::new();
+    let plots = [
+        (".plot.signal-view", "Waveform"),
+        (".plot.spectrum-view", "Spectrum"),
+    ];
+    for (selector, caption) in plots {
+        if let Some(plot) = document.query_selector(selector)? {
+            let (svg, _, _) = export::plot_svg(&plot)?;
+            let _ = write!(
+                figures,
+                "<figure>{svg}<figcaption>{caption}</figcaption></figure>"
+            );
+        }
+    }
+
+    let generated = js_sys::Date::new_0().to_locale_string("en-GB", &JsValue::UNDEFINED);
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        r#"<!DOCTYPE html><html lang="en"><head><meta charset="utf-8"><title>{name}</title><style>{STYLE}</style>"#,
+        name = escape(name),
+    );
+    if print {
+        html.push_str(r#"<script>addEventListener("load", () => print());</script>"#);
+    }
+    let _ = write!(
+        html,
+        r#"</head><body><h1>{}</h1><p class="generated">Generated by Signal Inspector {} on {}</p>"#,
+        escape(name),
+        env!("CARGO_PKG_VERSION"),
+        escape(&String::from(generated)),
+    );
+    html.push_str(&figures);
+    html.push_str(&measurements_html(signal, metadata, markers));
+    html.push_str("</body></html>");
+    Ok(html)
+}
+
+// The tables of the report.
+fn measurements_html(signal: &Signal, metadata: Option<&Metadata>, markers: &[Marker]) -> String {
+    let channels: Vec<_> = (0..signal.num_channels())
+        .map(|n| signal.channel(n))
+        .collect();
+    let first = channels[0];
+    let mut html = String::new();
+
+    html.push_str("<h2>Format</h2><table>");
+    if let Some(format) = metadata.and_then(|metadata| metadata.format.as_ref()) {
+        row(&mut html, "Encoding", &[format.name()]);
+        row(
+            &mut html,
+            "Bits per sample",
+            &[format.bits_per_sample.to_string()],
+        );
+    }
+    row(
+        &mut html,
+        "Sample rate",
+        &[format!("{} Hz", first.sample_rate())],
+    );
+    row(&mut html, "Channels", &[channels.len().to_string()]);
+    row(
+        &mut html,
+        "Duration",
+        &[format!(
+            "{:.3} s",
+            first.count() as f64 / first.sample_rate() as f64
+        )],
+    );
+    html.push_str("</table>");
+
+    let loudness = bench!(["Calculating loudness"] => signal.loudness());
+    html.push_str("<h2>Loudness</h2><table>");
+    let integrated = loudness.integrated.map_or_else(
+        || "too quiet or short to measure".to_owned(),
+        |integrated| format!("{integrated:.1} LUFS"),
+    );
+    row(&mut html, "Integrated loudness", &[integrated]);
+    row(
+        &mut html,
+        "True peak",
+        &[format!("{:.1} dBTP", loudness.true_peak_dbtp)],
+    );
+    html.push_str("</table>");
+
+    let names: &[&str] = match signal {
+        Signal::Mono(_) => &["Mono"],
+        Signal::Stereo(..) => &["Left", "Right"],
+    };
+    let stats: Vec<_> = channels
+        .iter()
+        .map(|channel| bench!(["Calculating statistics"] => channel.statistics()))
+        .collect();
+    let true_peaks: Vec<_> = channels
+        .iter()
+        .map(|channel| bench!(["Calculating true peak"] => channel.true_peak_dbtp()))
+        .collect();
+    let column = |f: &dyn Fn(usize) -> String| (0..channels.len()).map(f).collect::<Vec<_>>();
+    html.push_str("<h2>Statistics</h2><table><tr><th></th>");
+    for name in names {
+        let _ = write!(html, "<th>{name}</th>");
+    }
+    html.push_str("</tr>");
+    row(
+        &mut html,
+        "Peak",
+        &column(&|n| format!("{:.1} dBFS", stats[n].peak_dbfs)),
+    );
+    row(
+        &mut html,
+        "True peak",
+        &column(&|n| format!("{:.1} dBTP", true_peaks[n])),
+    );
+    row(
+        &mut html,
+        "RMS",
+        &column(&|n| format!("{:.1} dBFS", stats[n].rms_dbfs)),
+    );
+    row(
+        &mut html,
+        "Crest factor",
+        &column(&|n| format!("{:.1} dB", stats[n].crest_factor_db)),
+    );
+    row(
+        &mut html,
+        "DC offset",
+        &column(&|n| format!("{:.4} %", stats[n].dc_offset * 100.0)),
+    );
+    row(
+        &mut html,
+        "Zero crossings",
+        &column(&|n| stats[n].zero_crossings.to_string()),
+    );
+    row(
+        &mut html,
+        "Clipping",
+        &column(&|n| stats[n].clipping_events.to_string()),
+    );
+    html.push_str("</table>");
+
+    if !markers.is_empty() {
+        html.push_str("<h2>Markers</h2><table><tr><th>Time</th><th>Sample</th><th>Label</th></tr>");
+        for marker in markers {
+            let seconds = marker.position as f64 / first.sample_rate() as f64;
+            let _ = write!(
+                html,
+                "<tr><td>{seconds:.3} s</td><td>{}</td><td>{}</td></tr>",
+                marker.position,
+                escape(&marker.label),
+            );
+        }
+        html.push_str("</table>");
+    }
+
+    let Some(metadata) = metadata else {
+        return html;
+    };
+    if !metadata.info.is_empty() || metadata.broadcast.is_some() {
+        html.push_str("<h2>Metadata</h2><table>");
+        for (id, value) in &metadata.info {
+            let name = INFO_NAMES
+                .iter()
+                .find(|(info_id, _)| info_id == id)
+                .map_or(id.as_str(), |(_, name)| name);
+            row(&mut html, name, &[value]);
+        }
+        if let Some(broadcast) = &metadata.broadcast {
+            row(&mut html, "Description", &[&broadcast.description]);
+            row(&mut html, "Originator", &[&broadcast.originator]);
+            row(
+                &mut html,
+                "Originated",
+                &[format!(
+                    "{} {}",
+                    broadcast.origination_date, broadcast.origination_time
+                )],
+            );
+        }
+        html.push_str("</table>");
+    }
+    html
+}
+
+fn row(html: &mut String, name: &str, values: &[impl AsRef<str>]) {
+    let _ = write!(html, "<tr><td>{}</td>", escape(name));
+    for value in values {
+        let _ = write!(html, "<td>{}</td>", escape(value.as_ref()));
+    }
+    html.push_str("</tr>");
+}
@@ -0,0 +1,335 @@
+use std::ops::Range;
+
+use gloo::{
+    events::EventListener,
+    net::http::Request,
+    storage::{LocalStorage, Storage},
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::{EventSource, MessageEvent};
+use yew::Callback;
+
+use crate::session::SessionState;
+
+const TOKEN_KEY: &str = "signal-inspector-token";
+
+// The state of an analysis job run by the backend on an uploaded file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum JobState {
+    // The share of the analysis done so far, and the number of spectrogram tiles ready.
+    Running { progress: f64, tiles: usize },
+    Done { analysis: ServerAnalysis },
+    Failed { error: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ServerAnalysis {
+    pub sample_rate: u32,
+    pub channels: usize,
+    pub duration_seconds: f64,
+    pub integrated_lufs: Option<f64>,
+    pub true_peak_dbtp: f64,
+    pub spectrogram: SpectrogramTiles,
+    pub series: Vec<Series>,
+}
+
+// A time series of levels kept by the backend, such as the momentary loudness, with point `n`
+// measured around `start_seconds + n * step_seconds`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Series {
+    pub name: String,
+    pub unit: String,
+    pub start_seconds: f64,
+    pub step_seconds: f64,
+    pub points: usize,
+}
+
+// Part of a series, downsampled by the backend to the extremes of buckets of consecutive points.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SeriesPage {
+    // The time of the first point of the first bucket, and the time between buckets.
+    pub start_seconds: f64,
+    pub step_seconds: f64,
+    pub min: Vec<f32>,
+    pub max: Vec<f32>,
+}
+
+// How the backend tiled the spectrogram of the channels mixed to mono, in a pyramid of `zooms`
+// levels. Each level above level 0 averages pairs of frames of the one below.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SpectrogramTiles {
+    pub frame_len: usize,
+    pub hop: usize,
+    pub frames: usize,
+    pub tile_frames: usize,
+    pub zooms: usize,
+}
+
+impl SpectrogramTiles {
+    pub fn frames_at_zoom(&self, zoom: usize) -> usize {
+        (0..zoom).fold(self.frames, |frames, _| frames.div_ceil(2))
+    }
+}
+
+// A file uploaded for the backend to analyse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerJob {
+    pub name: String,
+    // `None` while uploading.
+    pub id: Option<u64>,
+    pub state: JobState,
+}
+
+#[derive(Deserialize)]
+struct Created {
+    id: u64,
+}
+
+// A session saved on the backend, without its state.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SessionSummary {
+    pub id: u64,
+    pub name: String,
+    // The name of the file the session was of, if it had one.
+    pub file_name: Option<String>,
+    pub notes: String,
+    // ISO 8601, in UTC.
+    pub saved_at: String,
+}
+
+#[derive(Serialize)]
+struct NewSession<'a> {
+    name: &'a str,
+    file_name: Option<&'a str>,
+    notes: &'a str,
+    state: &'a SessionState,
+}
+
+#[derive(Deserialize)]
+struct SavedSession {
+    state: SessionState,
+}
+
+// Events pushed by the backend as a job progresses, followed until the job has finished or this is
+// dropped.
+pub struct JobEvents {
+    event_source: EventSource,
+    _listeners: [EventListener; 2],
+}
+
+impl JobEvents {
+    // Emits the state of the job each time it changes, starting with the current one, and the
+    // index of each spectrogram tile as soon as it is ready.
+    pub fn new(
+        id: u64,
+        on_state: Callback<JobState>,
+        on_tile: Callback<usize>,
+    ) -> Result<Self, String> {
+        let event_source = EventSource::new(&event_source_url(&format!("/api/jobs/{id}/events")))
+            .map_err(|error| format!("failed to follow job {id}: {error:?}"))?;
+        let data = |event: &web_sys::Event| {
+            event
+                .dyn_ref::<MessageEvent>()
+                .and_then(|event| event.data().as_string())
+        };
+
+        let state_listener = {
+            let event_source = event_source.clone();
+            EventListener::new(&event_source.clone(), "state", move |event| {
+                let Some(state) = data(event).and_then(|data| serde_json::from_str(&data).ok())
+                else {
+                    return;
+                };
+                // Once finished, the stream ends and must not be reconnected to.
+                if !matches!(state, JobState::Running { .. }) {
+                    event_source.close();
+                }
+                on_state.emit(state);
+            })
+        };
+        let tile_listener = EventListener::new(&event_source, "tile", move |event| {
+            if let Some(tile) = data(event).and_then(|data| data.parse().ok()) {
+                on_tile.emit(tile);
+            }
+        });
+
+        Ok(Self {
+            event_source,
+            _listeners: [state_listener, tile_listener],
+        })
+    }
+}
+
+impl Drop for JobEvents {
+    fn drop(&mut self) {
+        self.event_source.close();
+    }
+}
+
+// Uploads a WAV file for the backend to analyse, returning the ID of its job. The name is recorded
+// in the audit trail of the backend.
+pub async fn upload(name: &str, data: Vec<u8>) -> Result<u64, String> {
+    let url = format!("/api/upload?name={}", js_sys::encode_uri_component(name));
+    let response = authorized(Request::post(&url))
+        .body(js_sys::Uint8Array::from(&data[..]))
+        .send()
+        .await
+        .map_err(|error| format!("failed to upload: {error}"))?;
+    if !response.ok() {
+        return Err(format!("failed to upload: {}", response.status()));
+    }
+    let created: Created = response
+        .json()
+        .await
+        .map_err(|error| format!("failed to upload: {error}"))?;
+    Ok(created.id)
+}
+
+// A tile of the spectrogram of an uploaded file, with the `frame_len / 2` bin levels of each of its
+// frames one after the other.
+pub async fn spectrogram_tile(id: u64, zoom: usize, tile: usize) -> Result<Vec<f32>, String> {
+    let url = format!("/api/spectrogram/{id}/{zoom}/{tile}");
+    let response = authorized(Request::get(&url))
+        .send()
+        .await
+        .map_err(|error| format!("failed to fetch {url}: {error}"))?;
+    if !response.ok() {
+        return Err(format!("failed to fetch {url}: {}", response.status()));
+    }
+    let bytes = response
+        .binary()
+        .await
+        .map_err(|error| format!("failed to fetch {url}: {error}"))?;
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect())
+}
+
+// The part of a series of an uploaded file within `seconds`, in at most `max_points` buckets.
+pub async fn series(
+    id: u64,
+    name: &str,
+    seconds: Range<f64>,
+    max_points: usize,
+) -> Result<SeriesPage, String> {
+    let url = format!(
+        "/api/jobs/{id}/series/{name}?start={}&end={}&max_points={max_points}",
+        seconds.start, seconds.end
+    );
+    let response = authorized(Request::get(&url))
+        .send()
+        .await
+        .map_err(|error| format!("failed to fetch {url}: {error}"))?;
+    if !response.ok() {
+        return Err(format!("failed to fetch {url}: {}", response.status()));
+    }
+    response
+        .json()
+        .await
+        .map_err(|error| format!("failed to fetch {url}: {error}"))
+}
+
+// Saves a session on the backend, returning its ID.
+pub async fn save_session(
+    name: &str,
+    file_name: Option<&str>,
+    notes: &str,
+    state: &SessionState,
+) -> Result<u64, String> {
+    let session = NewSession {
+        name,
+        file_name,
+        notes,
+        state,
+    };
+    let response = authorized(Request::post("/api/sessions"))
+        .json(&session)
+        .map_err(|error| format!("failed to save session: {error}"))?
+        .send()
+        .await
+        .map_err(|error| format!("failed to save session: {error}"))?;
+    if !response.ok() {
+        return Err(format!("failed to save session: {}", response.status()));
+    }
+    let created: Created = response
+        .json()
+        .await
+        .map_err(|error| format!("failed to save session: {error}"))?;
+    Ok(created.id)
+}
+
+// The sessions saved on the backend, the most recently saved first.
+pub async fn sessions() -> Result<Vec<SessionSummary>, String> {
+    let response = authorized(Request::get("/api/sessions"))
+        .send()
+        .await
+        .map_err(|error| format!("failed to list sessions: {error}"))?;
+    if !response.ok() {
+        return Err(format!("failed to list sessions: {}", response.status()));
+    }
+    response
+        .json()
+        .await
+        .map_err(|error| format!("failed to list sessions: {error}"))
+}
+
+pub async fn session(id: u64) -> Result<SessionState, String> {
+    let response = authorized(Request::get(&format!("/api/sessions/{id}")))
+        .send()
+        .await
+        .map_err(|error| format!("failed to open session {id}: {error}"))?;
+    if !response.ok() {
+        return Err(format!(
+            "failed to open session {id}: {}",
+            response.status()
+        ));
+    }
+    let saved: SavedSession = response
+        .json()
+        .await
+        .map_err(|error| format!("failed to open session {id}: {error}"))?;
+    Ok(saved.state)
+}
+
+// The token given to the backend with each request, as entered on the login screen.
+pub fn token() -> Option<String> {
+    LocalStorage::get(TOKEN_KEY).ok()
+}
+
+pub fn set_token(token: &str) {
+    if let Err(error) = LocalStorage::set(TOKEN_KEY, token) {
+        crate::log(&format!("failed to store token: {error}"));
+    }
+}
+
+// Adds the token, if there is one, to a request to the backend.
+pub fn authorized(request: Request) -> Request {
+    match token() {
+        Some(token) => request.header("Authorization", &format!("Bearer {token}")),
+        None => request,
+    }
+}
+
+// Adds the token, if there is one, to the URL of an event source, which cannot send headers.
+pub fn event_source_url(url: &str) -> String {
+    match token() {
+        Some(token) => format!(
+            "{url}{}access_token={}",
+            if url.contains('?') { '&' } else { '?' },
+            js_sys::encode_uri_component(&token)
+        ),
+        None => url.to_owned(),
+    }
+}
+
+// Whether the backend accepts the token, or needs none. Without a backend, there is nothing to log
+// in to.
+pub async fn is_authorized() -> bool {
+    match authorized(Request::get("/api/whoami")).send().await {
+        Ok(response) => response.status() != 401,
+        Err(_) => true,
+    }
+}
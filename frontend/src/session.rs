@@ -0,0 +1,207 @@
+use std::ops::Range;
+
+use js_sys::{Promise, Uint8Array};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+
+use crate::{fs_access::FileHandle, markers::Marker, model::Signal, settings::AnalysisSettings};
+
+const DATABASE: &str = "signal-inspector";
+const STORE: &str = "session";
+const SIGNAL_KEY: &str = "signal";
+const STATE_KEY: &str = "state";
+const EXPORT_HANDLE_KEY: &str = "export_handle";
+
+// Everything about the session except for the signal itself, which is stored separately as WAV so
+// that changing settings does not re-encode it.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    pub settings: AnalysisSettings,
+    pub view: Option<Range<usize>>,
+    pub markers: Vec<Marker>,
+    // What changed each time the session was exported, the oldest first.
+    pub change_log: Vec<ChangeLogEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ChangeLogEntry {
+    // ISO 8601, in UTC.
+    pub saved_at: String,
+    pub changes: Vec<String>,
+}
+
+impl SessionState {
+    // Describes how the settings, view and markers differ from `before`, e.g. for recording in the
+    // change log when the session is saved again.
+    pub fn changes_since(&self, before: &SessionState) -> Vec<String> {
+        let mut changes = Vec::new();
+        let settings = |state: &SessionState| {
+            serde_json::to_value(&state.settings).unwrap_or(serde_json::Value::Null)
+        };
+        diff_values("", &settings(before), &settings(self), &mut changes);
+
+        if self.view != before.view {
+            let format = |view: &Option<Range<usize>>| match view {
+                Some(view) => format!("samples {}–{}", view.start, view.end),
+                None => "the whole signal".to_owned(),
+            };
+            changes.push(format!(
+                "View changed from {} to {}",
+                format(&before.view),
+                format(&self.view)
+            ));
+        }
+
+        for marker in &before.markers {
+            if !self.markers.contains(marker) {
+                changes.push(format!(
+                    "Marker \"{}\" at sample {} removed",
+                    marker.label, marker.position
+                ));
+            }
+        }
+        for marker in &self.markers {
+            if !before.markers.contains(marker) {
+                changes.push(format!(
+                    "Marker \"{}\" at sample {} added",
+                    marker.label, marker.position
+                ));
+            }
+        }
+        changes
+    }
+}
+
+// Settings are compared by their serialized form, so that new settings are covered without
+// listing them here, and are named by their path, e.g. `spectrum.segment_len`.
+fn diff_values(
+    path: &str,
+    before: &serde_json::Value,
+    after: &serde_json::Value,
+    changes: &mut Vec<String>,
+) {
+    use serde_json::Value;
+
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            for (key, value) in after {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                diff_values(
+                    &path,
+                    before.get(key).unwrap_or(&Value::Null),
+                    value,
+                    changes,
+                );
+            }
+        }
+        (before, after) if before != after => {
+            changes.push(format!("{path} changed from {before} to {after}"))
+        }
+        _ => (),
+    }
+}
+
+// Resolves with the result of the request once it succeeds.
+async fn complete(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let succeeded = request.clone();
+        let failed = request.clone();
+        let on_success = Closure::once_into_js(move || {
+            let _ = resolve.call1(&JsValue::NULL, &succeeded.result().unwrap_or_default());
+        });
+        let on_error = Closure::once_into_js(move || {
+            let error = failed
+                .error()
+                .ok()
+                .flatten()
+                .map_or(JsValue::NULL, Into::into);
+            let _ = reject.call1(&JsValue::NULL, &error);
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    JsFuture::from(promise).await
+}
+
+async fn store(writable: bool) -> Result<IdbObjectStore, JsValue> {
+    let factory = web_sys::window()
+        .ok_or("no window")?
+        .indexed_db()?
+        .ok_or("IndexedDB is not supported")?;
+    let request = factory.open_with_u32(DATABASE, 1)?;
+    let upgrading = request.clone();
+    let on_upgrade = Closure::once_into_js(move || {
+        if let Ok(database) = upgrading.result() {
+            let _ = database
+                .unchecked_into::<IdbDatabase>()
+                .create_object_store(STORE);
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+
+    let database: IdbDatabase = complete(&request).await?.dyn_into()?;
+    let transaction = if writable {
+        database.transaction_with_str_and_mode(STORE, IdbTransactionMode::Readwrite)?
+    } else {
+        database.transaction_with_str(STORE)?
+    };
+    transaction.object_store(STORE)
+}
+
+pub async fn load() -> Result<Option<(Signal, SessionState)>, JsValue> {
+    let store = store(false).await?;
+    let signal = complete(&store.get(&SIGNAL_KEY.into())?).await?;
+    let state = complete(&store.get(&STATE_KEY.into())?).await?;
+    if signal.is_undefined() {
+        return Ok(None);
+    }
+
+    let signal =
+        Signal::from_wav(Uint8Array::new(&signal).to_vec()).map_err(|error| error.to_string())?;
+    let state = state
+        .as_string()
+        .and_then(|state| serde_json::from_str(&state).ok())
+        .unwrap_or_default();
+    Ok(Some((signal, state)))
+}
+
+pub async fn save_signal(signal: &Signal) -> Result<(), JsValue> {
+    let data = signal.to_wav().map_err(|error| error.to_string())?;
+    let store = store(true).await?;
+    complete(&store.put_with_key(&Uint8Array::from(&data[..]), &SIGNAL_KEY.into())?).await?;
+    Ok(())
+}
+
+pub async fn save_state(state: &SessionState) -> Result<(), JsValue> {
+    let state = serde_json::to_string(state).map_err(|error| error.to_string())?;
+    let store = store(true).await?;
+    complete(&store.put_with_key(&state.into(), &STATE_KEY.into())?).await?;
+    Ok(())
+}
+
+// The file the signal was last exported to, so that it can be saved to again after a reload. File
+// handles are stored as they are, as IndexedDB can hold them but not serialize them.
+pub async fn load_export_handle() -> Result<Option<FileHandle>, JsValue> {
+    let store = store(false).await?;
+    let handle = complete(&store.get(&EXPORT_HANDLE_KEY.into())?).await?;
+    Ok((!handle.is_undefined()).then(|| handle.unchecked_into()))
+}
+
+pub async fn save_export_handle(handle: &FileHandle) -> Result<(), JsValue> {
+    let store = store(true).await?;
+    complete(&store.put_with_key(handle, &EXPORT_HANDLE_KEY.into())?).await?;
+    Ok(())
+}
+
+pub async fn clear() -> Result<(), JsValue> {
+    let store = store(true).await?;
+    complete(&store.clear()?).await?;
+    Ok(())
+}
@@ -0,0 +1,667 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    calibration::Calibration,
+    sound_level::{FrequencyWeighting, TimeWeighting},
+    ticks::{closest_step, round_steps, ticks, TARGET_TICKS},
+};
+
+// Analysis and view settings that can be saved as presets.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalysisSettings {
+    pub show_spectrum: bool,
+    pub amplitude_unit: AmplitudeUnit,
+    pub time_format: TimeFormat,
+    pub spectrum: SpectrumSettings,
+    pub levels: LevelSettings,
+    pub pitch: PitchSettings,
+    pub speech: SpeechSettings,
+    pub silence: SilenceSettings,
+    pub gain_staging: GainStagingSettings,
+    pub bands: BandSettings,
+    pub true_peak: TruePeakSettings,
+    pub playback: PlaybackSettings,
+    pub noise_floor: NoiseFloorMethod,
+    pub export: ExportSettings,
+    // The measurement microphone the spectra are corrected for, if any.
+    pub calibration: Option<Calibration>,
+    pub decay: DecaySettings,
+    pub sound_level: SoundLevelSettings,
+    pub transfer: TransferSettings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpectrumSettings {
+    pub ltas: bool,
+    // 1 for octave bands or 3 for third-octave bands.
+    pub bands_per_octave: u32,
+    pub waterfall: bool,
+    // Transform waterfall frames on the GPU where WebGPU is supported.
+    pub gpu: bool,
+    // Otherwise, transform them in a background worker rather than on the main thread.
+    pub worker: bool,
+    pub welch: bool,
+    pub segment_len: usize,
+    pub overlap: f64,
+    pub show_confidence: bool,
+    // The most points the spectrum is drawn with, trading detail for faster drawing, or `None` for
+    // one per bin.
+    pub max_points: Option<usize>,
+    pub stereo: StereoSpectra,
+    // The fraction of an octave the drawn spectrum is smoothed over, if it is.
+    pub smoothing: Option<u32>,
+    // The range the spectral tilt is fitted over, in Hz.
+    pub tilt_low: f64,
+    pub tilt_high: f64,
+    pub hold: SpectrumHold,
+    // Two cursors on the spectrum for measuring between.
+    pub cursors: bool,
+    // Markers at the multiples of a fundamental picked on the spectrum, and how many.
+    pub harmonics: bool,
+    pub harmonic_count: u32,
+}
+
+// Which spectra of a stereo signal are drawn, besides that of the selected channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StereoSpectra {
+    #[default]
+    Selected,
+    // The other channel overlaid.
+    Both,
+    // Also that of the left channel minus the right one, i.e. of the side signal.
+    BothAndDifference,
+}
+
+impl StereoSpectra {
+    pub const ALL: [Self; 3] = [Self::Selected, Self::Both, Self::BothAndDifference];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Selected => "Selected channel",
+            Self::Both => "Left and right",
+            Self::BothAndDifference => "Left, right and difference",
+        }
+    }
+}
+
+// What the spectrum view draws besides the spectrum as it is now. Holding and averaging go on over
+// the spectra drawn as the file, the processing or the channel changes, until they are reset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpectrumHold {
+    #[default]
+    Off,
+    // The loudest each bin has been.
+    PeakHold,
+    // An exponential average of the power in each bin.
+    Average,
+    // The loudest each bin is in any segment of the selection.
+    MaxOverSelection,
+}
+
+impl SpectrumHold {
+    pub const ALL: [Self; 4] = [
+        Self::Off,
+        Self::PeakHold,
+        Self::Average,
+        Self::MaxOverSelection,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::PeakHold => "Peak hold",
+            Self::Average => "Exponential average",
+            Self::MaxOverSelection => "Maximum over selection",
+        }
+    }
+
+    // Whether earlier spectra are kept until reset.
+    pub fn accumulates(self) -> bool {
+        matches!(self, Self::PeakHold | Self::Average)
+    }
+}
+
+impl Default for SpectrumSettings {
+    fn default() -> Self {
+        Self {
+            ltas: false,
+            bands_per_octave: 3,
+            waterfall: false,
+            gpu: false,
+            worker: false,
+            welch: false,
+            segment_len: 4096,
+            overlap: 0.5,
+            show_confidence: true,
+            max_points: None,
+            stereo: StereoSpectra::Selected,
+            smoothing: None,
+            tilt_low: 100.0,
+            tilt_high: 10_000.0,
+            hold: SpectrumHold::Off,
+            cursors: false,
+            harmonics: false,
+            harmonic_count: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelSettings {
+    pub enabled: bool,
+    pub window_ms: f64,
+    pub hop_ms: f64,
+}
+
+impl Default for LevelSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_ms: 50.0,
+            hop_ms: 25.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundLevelSettings {
+    pub frequency_weighting: FrequencyWeighting,
+    pub time_weighting: TimeWeighting,
+    // The level of the calibrator the SPL calibration is made with.
+    pub calibrator_db: f64,
+    // The level in dB SPL of a full-scale signal, once calibrated.
+    pub spl_offset_db: Option<f64>,
+}
+
+impl Default for SoundLevelSettings {
+    fn default() -> Self {
+        Self {
+            frequency_weighting: FrequencyWeighting::A,
+            time_weighting: TimeWeighting::Fast,
+            calibrator_db: 94.0,
+            spl_offset_db: None,
+        }
+    }
+}
+
+// The transfer function between the channels of a stereo signal, or from the signal to the
+// comparison file, drawn in place of the spectrum.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransferSettings {
+    pub enabled: bool,
+    pub show_phase: bool,
+    pub show_coherence: bool,
+}
+
+impl Default for TransferSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            show_phase: true,
+            show_coherence: true,
+        }
+    }
+}
+
+// Analysis of the selection, or the channel, as an impulse response.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DecaySettings {
+    pub enabled: bool,
+    // How far down the energy decay curves are drawn.
+    pub range_db: f64,
+}
+
+impl Default for DecaySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            range_db: 60.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PitchSettings {
+    pub track: bool,
+    pub hop_ms: f64,
+}
+
+impl Default for PitchSettings {
+    fn default() -> Self {
+        Self {
+            track: false,
+            hop_ms: 10.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeechSettings {
+    pub enabled: bool,
+    pub margin_db: f64,
+}
+
+impl Default for SpeechSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            margin_db: 12.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SilenceSettings {
+    pub enabled: bool,
+    pub threshold_db: f64,
+    pub min_duration: f64,
+}
+
+impl Default for SilenceSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_db: -60.0,
+            min_duration: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GainStagingSettings {
+    pub target_lufs: f64,
+    pub ceiling_dbtp: f64,
+}
+
+impl Default for GainStagingSettings {
+    fn default() -> Self {
+        Self {
+            target_lufs: -14.0,
+            ceiling_dbtp: -1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BandSettings {
+    // Edge frequencies in Hz, in ascending order, with a band between each adjacent pair.
+    pub edges: Vec<f64>,
+    // The band the RMS level of the region is given for, in Hz.
+    pub rms_lower: f64,
+    pub rms_upper: f64,
+}
+
+impl Default for BandSettings {
+    fn default() -> Self {
+        Self {
+            edges: vec![20.0, 60.0, 250.0, 2000.0, 8000.0, 20_000.0],
+            rms_lower: 0.0,
+            rms_upper: 40.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TruePeakSettings {
+    pub mark_overs: bool,
+    pub ceiling_dbtp: f64,
+}
+
+// Where the noise floor that the signal-to-noise ratio is measured against is taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseFloorMethod {
+    #[default]
+    QuietestFrames,
+    // The median of the spectrum, leaving out its peaks.
+    Spectrum,
+    // A region chosen as being silent.
+    Region,
+}
+
+impl NoiseFloorMethod {
+    pub const ALL: [Self; 3] = [Self::QuietestFrames, Self::Spectrum, Self::Region];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::QuietestFrames => "Quietest frames",
+            Self::Spectrum => "Median of the spectrum",
+            Self::Region => "Silent region",
+        }
+    }
+}
+
+// How the edges of an exported selection are treated, so that the snippet does not click.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionEdges {
+    #[default]
+    Cut,
+    // Faded in at the start and out at the end.
+    Fade,
+    // The end crossfaded into what comes before the start, for snippets that are looped.
+    LoopCrossfade,
+}
+
+impl SelectionEdges {
+    pub const ALL: [Self; 3] = [Self::Cut, Self::Fade, Self::LoopCrossfade];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Cut => "Cut",
+            Self::Fade => "Fade in and out",
+            Self::LoopCrossfade => "Crossfade for looping",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportSettings {
+    pub edges: SelectionEdges,
+    // The length of the fades or the crossfade.
+    pub fade_ms: f64,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            edges: SelectionEdges::Cut,
+            fade_ms: 5.0,
+        }
+    }
+}
+
+// The output of playback, which does not change the signal being analysed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaybackSettings {
+    pub gain_db: f64,
+    // Keeps peaks from going far above the ceiling, so that full-scale test tones are not played
+    // at full scale.
+    pub limiter: bool,
+    pub ceiling_dbfs: f64,
+}
+
+impl Default for PlaybackSettings {
+    fn default() -> Self {
+        Self {
+            gain_db: 0.0,
+            limiter: true,
+            ceiling_dbfs: -6.0,
+        }
+    }
+}
+
+impl PlaybackSettings {
+    // The peak played for a signal peaking at `peak_dbfs`, along with how much the limiter takes
+    // off it.
+    pub fn output_peak(&self, peak_dbfs: f64) -> (f64, f64) {
+        let amplified = peak_dbfs + self.gain_db;
+        let reduction = if self.limiter {
+            (amplified - self.ceiling_dbfs).max(0.0)
+        } else {
+            0.0
+        };
+        (amplified - reduction, reduction)
+    }
+}
+
+impl Default for TruePeakSettings {
+    fn default() -> Self {
+        Self {
+            mark_overs: false,
+            ceiling_dbtp: -1.0,
+        }
+    }
+}
+
+// The unit of the amplitude axis of the waveform and of the readout under the pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AmplitudeUnit {
+    // Of full scale.
+    #[default]
+    Percent,
+    Dbfs,
+    // The sample value as stored.
+    Raw,
+    // Full scale as ±1.
+    Normalized,
+}
+
+impl AmplitudeUnit {
+    pub const ALL: [Self; 4] = [Self::Percent, Self::Dbfs, Self::Raw, Self::Normalized];
+    // Levels ticked on the amplitude axis in dBFS, on both sides of zero, where they fit.
+    const DBFS_TICKS: [f64; 4] = [0.0, -6.0, -12.0, -24.0];
+    // The least distance between ticks, as a share of the axis.
+    const MIN_TICK_GAP: f64 = 0.06;
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Percent => "Percent",
+            Self::Dbfs => "dBFS",
+            Self::Raw => "Raw",
+            Self::Normalized => "Normalized",
+        }
+    }
+
+    // What follows a number in the unit in text.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Self::Percent => " %",
+            Self::Dbfs => " dBFS",
+            Self::Raw | Self::Normalized => "",
+        }
+    }
+
+    // The class of labels in the unit, for the stylesheet to add the unit after the number.
+    pub fn class(self) -> &'static str {
+        match self {
+            Self::Percent => "percentage",
+            Self::Dbfs => "dbfs",
+            Self::Raw | Self::Normalized => "",
+        }
+    }
+
+    // `amplitude` of a channel with the given full scale, in the unit, without the unit itself.
+    pub fn format(self, amplitude: f64, full_scale: f64) -> String {
+        let normalized = amplitude / full_scale;
+        match self {
+            Self::Percent => format!("{:.0}", normalized * 100.0),
+            Self::Dbfs if normalized == 0.0 => "−∞".to_owned(),
+            Self::Dbfs => format!("{:.1}", 20.0 * normalized.abs().log10()),
+            Self::Raw => format!("{amplitude:.0}"),
+            Self::Normalized => format!("{normalized:.3}"),
+        }
+    }
+
+    // The amplitudes to tick between `min` and `max`: both ends and zero, and in dBFS also the
+    // levels of `DBFS_TICKS` that are not too close to another tick.
+    pub fn ticks(self, min: f64, max: f64, full_scale: f64) -> Vec<f64> {
+        let mut ticks = vec![min, 0.0, max];
+        if self != Self::Dbfs {
+            return ticks;
+        }
+        let min_gap = (max - min) * Self::MIN_TICK_GAP;
+        for db in Self::DBFS_TICKS {
+            let level = full_scale * 10_f64.powf(db / 20.0);
+            for amplitude in [level, -level] {
+                let fits = (min..=max).contains(&amplitude)
+                    && ticks.iter().all(|tick| (tick - amplitude).abs() >= min_gap);
+                if fits {
+                    ticks.push(amplitude);
+                }
+            }
+        }
+        ticks
+    }
+}
+
+// How times are shown on the time axis of the waveform and in the readout under the pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeFormat {
+    #[default]
+    Seconds,
+    // Minutes and seconds, with hours when there are any.
+    Clock,
+    // The index of the sample frame.
+    Samples,
+    // Hours, minutes, seconds and frames at the given number of frames per second.
+    Timecode(u32),
+}
+
+impl TimeFormat {
+    pub const ALL: [Self; 7] = [
+        Self::Seconds,
+        Self::Clock,
+        Self::Samples,
+        Self::Timecode(24),
+        Self::Timecode(25),
+        Self::Timecode(30),
+        Self::Timecode(60),
+    ];
+    // Intervals between ticks of a second or more in clock time, beyond which whole hours are
+    // ticked.
+    const CLOCK_STEPS: [f64; 12] = [
+        1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 900.0, 1800.0,
+    ];
+
+    pub fn name(self) -> String {
+        match self {
+            Self::Seconds => "Seconds".to_owned(),
+            Self::Clock => "mm:ss.mmm".to_owned(),
+            Self::Samples => "Samples".to_owned(),
+            Self::Timecode(rate) => format!("Timecode ({rate} fps)"),
+        }
+    }
+
+    // What follows a time in the format in text.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Self::Seconds => " s",
+            Self::Clock | Self::Samples | Self::Timecode(_) => "",
+        }
+    }
+
+    // The class of labels in the format, for the stylesheet to add the unit after the number.
+    pub fn class(self) -> &'static str {
+        match self {
+            Self::Seconds => "second",
+            Self::Clock | Self::Samples | Self::Timecode(_) => "",
+        }
+    }
+
+    // The time of the sample frame at `position`, to `resolution` seconds where the format has
+    // fractions of a second, without the unit itself.
+    pub fn format(self, position: f64, sample_rate: u32, resolution: f64) -> String {
+        let seconds = position / f64::from(sample_rate.max(1));
+        let decimals = if resolution >= 1.0 {
+            0
+        } else {
+            (-resolution.log10() - 1e-9).ceil() as usize
+        };
+        match self {
+            Self::Seconds => format!("{seconds:.decimals$}"),
+            Self::Clock => {
+                let scale = 10_u64.pow(decimals as u32);
+                let units = (seconds * scale as f64).round() as u64;
+                let (whole, fraction) = (units / scale, units % scale);
+                let (hours, minutes, seconds) = (whole / 3600, whole / 60 % 60, whole % 60);
+                let mut text = match hours {
+                    0 => format!("{minutes}:{seconds:02}"),
+                    _ => format!("{hours}:{minutes:02}:{seconds:02}"),
+                };
+                if decimals > 0 {
+                    text.push_str(&format!(".{fraction:0decimals$}"));
+                }
+                text
+            }
+            Self::Samples => format!("{position:.0}"),
+            Self::Timecode(rate) => {
+                let rate = u64::from(rate.max(1));
+                let frames = (seconds * rate as f64 + 1e-9).floor() as u64;
+                let whole = frames / rate;
+                format!(
+                    "{:02}:{:02}:{:02}:{:02}",
+                    whole / 3600,
+                    whole / 60 % 60,
+                    whole % 60,
+                    frames % rate,
+                )
+            }
+        }
+    }
+
+    // The time of the sample frame at `position` as precisely as the readout under the pointer
+    // shows it.
+    pub fn format_precise(self, position: f64, sample_rate: u32) -> String {
+        let resolution = match self {
+            Self::Clock => 1e-3,
+            _ => 1e-6,
+        };
+        self.format(position, sample_rate, resolution)
+    }
+
+    // The sample frames to tick within `view`, at round intervals in the format, and their labels.
+    // The interval is the one giving closest to `TARGET_TICKS` ticks, but at least a sample frame,
+    // and for timecodes at least a video frame.
+    pub fn ticks(self, view: &Range<usize>, sample_rate: u32) -> Vec<(f64, String)> {
+        let sample_rate = f64::from(sample_rate.max(1));
+        let (start, end) = (
+            view.start as f64 / sample_rate,
+            view.end as f64 / sample_rate,
+        );
+        let span = end - start;
+        let sample = 1.0 / sample_rate;
+        let longest = TARGET_TICKS * span.max(sample);
+        let hours = round_steps(1.0..longest / 3600.0 + 1.0).map(|hours| 3600.0 * hours);
+        let steps: Vec<_> = match self {
+            Self::Seconds => round_steps(sample..longest).collect(),
+            Self::Samples => round_steps(1.0..longest * sample_rate)
+                .map(|samples| samples / sample_rate)
+                .collect(),
+            Self::Clock => round_steps(sample..1.0)
+                .chain(Self::CLOCK_STEPS)
+                .chain(hours)
+                .collect(),
+            // Numbers of frames that divide a second, so that every second is ticked.
+            Self::Timecode(rate) => {
+                let rate = rate.max(1);
+                (1..rate)
+                    .filter(|frames| rate % frames == 0)
+                    .map(|frames| f64::from(frames) / f64::from(rate))
+                    .chain(Self::CLOCK_STEPS)
+                    .chain(hours)
+                    .collect()
+            }
+        };
+        let Some(step) = closest_step(span, steps) else {
+            return Vec::new();
+        };
+
+        ticks(start, end, step)
+            .map(|seconds| {
+                let position = seconds * sample_rate;
+                (position, self.format(position, sample_rate as u32, step))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,126 @@
+#[cfg(feature = "server")]
+use gloo::net::http::Request;
+use wasm_bindgen_futures::JsFuture;
+
+#[cfg(feature = "server")]
+use crate::server;
+use crate::{
+    fs_access::{self, FileHandle},
+    model::{decimation::Decimation, raw::RawSpec, riff::Metadata, Signal, WavLimits},
+};
+
+// Files larger than this are offered to be opened decimated, as decoding them in full may exhaust
+// the memory available to WebAssembly.
+pub const LARGE_FILE_BYTES: usize = 256 << 20;
+
+// A file that has been read but not yet decoded, e.g. while the user decides how to open a large
+// one.
+pub struct Undecoded {
+    pub name: String,
+    pub data: Vec<u8>,
+    pub source: Option<Source>,
+}
+
+impl Undecoded {
+    pub fn is_large(&self) -> bool {
+        self.data.len() > LARGE_FILE_BYTES
+    }
+
+    // Whether the file has a WAV or AIFF header, as opposed to holding bare samples.
+    pub fn has_header(&self) -> bool {
+        match self.data.get(..4) {
+            Some(b"RIFF") => self.data.get(8..12) == Some(b"WAVE"),
+            Some(b"FORM") => matches!(self.data.get(8..12), Some(b"AIFF" | b"AIFC")),
+            _ => false,
+        }
+    }
+
+    // The chunks other than the samples, or `None` if the file is not a WAV file.
+    pub fn metadata(&self) -> Option<Metadata> {
+        bench!(["Reading metadata of {}", self.name] => Metadata::parse(&self.data))
+    }
+
+    pub fn decode(self, decimation: Option<Decimation>) -> Result<Signal, String> {
+        let result = bench!(["Decoding {}", self.name] => match decimation {
+            Some(decimation) => {
+                Signal::from_wav_decimated(self.data, WavLimits::default(), decimation)
+            }
+            None => Signal::from_wav(self.data),
+        });
+        result.map_err(|error| format!("failed to decode {}: {error}", self.name))
+    }
+
+    pub fn decode_raw(self, spec: RawSpec) -> Result<Signal, String> {
+        let result =
+            bench!(["Reading {} as raw samples", self.name] => Signal::from_raw(&self.data, spec));
+        result.map_err(|error| format!("failed to read {}: {error}", self.name))
+    }
+}
+
+// Where the primary signal was loaded from, for files that can be reloaded when they change.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    File(FileHandle),
+    #[cfg(feature = "server")]
+    WatchFolder(String),
+}
+
+impl Source {
+    pub fn name(&self) -> String {
+        match self {
+            Self::File(handle) => handle.name(),
+            #[cfg(feature = "server")]
+            Self::WatchFolder(name) => name.clone(),
+        }
+    }
+
+    #[cfg_attr(not(feature = "server"), allow(dead_code))]
+    pub async fn read(&self) -> Result<Signal, String> {
+        self.read_undecoded().await?.decode(None)
+    }
+
+    pub async fn read_undecoded(&self) -> Result<Undecoded, String> {
+        let data = match self {
+            Self::File(handle) => fs_access::read(handle)
+                .await
+                .map_err(|error| format!("failed to read {}: {error:?}", self.name()))?,
+            #[cfg(feature = "server")]
+            Self::WatchFolder(name) => {
+                let url = format!("/api/watch/files/{}", js_sys::encode_uri_component(name));
+                let response = server::authorized(Request::get(&url))
+                    .send()
+                    .await
+                    .map_err(|error| format!("failed to fetch {name}: {error}"))?;
+                if !response.ok() {
+                    return Err(format!("failed to fetch {name}: {}", response.status()));
+                }
+                response
+                    .binary()
+                    .await
+                    .map_err(|error| format!("failed to fetch {name}: {error}"))?
+            }
+        };
+
+        Ok(Undecoded {
+            name: self.name(),
+            data,
+            source: Some(self.clone()),
+        })
+    }
+}
+
+// Reads and decodes a file picked or pasted by the user.
+pub async fn read_file(file: &web_sys::File) -> Result<Signal, String> {
+    read_file_undecoded(file).await?.decode(None)
+}
+
+pub async fn read_file_undecoded(file: &web_sys::File) -> Result<Undecoded, String> {
+    let buffer = JsFuture::from(file.array_buffer())
+        .await
+        .map_err(|error| format!("failed to read {}: {error:?}", file.name()))?;
+    Ok(Undecoded {
+        name: file.name(),
+        data: js_sys::Uint8Array::new(&buffer).to_vec(),
+        source: None,
+    })
+}
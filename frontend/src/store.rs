@@ -0,0 +1,300 @@
+use std::{ops::Range, rc::Rc};
+
+use im::Vector;
+use yew::prelude::*;
+
+use crate::{
+    markers::{self, Marker},
+    model::{
+        decimation::Decimation, generator::Generator, layout::SampleLayout, processing::Operation,
+        riff::Metadata, Channel, Signal,
+    },
+    presets::Presets,
+    session::{ChangeLogEntry, SessionState},
+    settings::AnalysisSettings,
+    source::Source,
+};
+
+// How many edits can be undone.
+const HISTORY_LEN: usize = 50;
+
+// What a destructive edit or a change to the processing chain replaced. Signals keep their samples
+// in persistent vectors, so keeping earlier ones around only costs what differs between them.
+#[derive(Clone, PartialEq)]
+pub struct Edit {
+    signal: Signal,
+    chain: Vec<Operation>,
+}
+
+// The state that several features depend on at once, such as sessions, undo and shared links, so
+// that they all read and update the same copy. Transient UI state stays with its component.
+#[derive(Clone, PartialEq)]
+pub struct Store {
+    // The primary signal, followed by the comparison signal if one is loaded.
+    pub signals: Vec<Signal>,
+    pub source: Option<Source>,
+    // How the primary signal was reduced when its file was opened, if it was.
+    pub decimation: Option<Decimation>,
+    // The chunks of the file the primary signal was opened from other than the samples.
+    pub metadata: Option<Metadata>,
+    // The primary signal as its header declares it, if its samples are read differently.
+    pub declared: Option<Signal>,
+    // Whether the source has changed on disk since it was loaded.
+    pub changed: bool,
+    // Edits to undo, the latest last, and those undone since the last edit, to redo.
+    pub undo: Vector<Edit>,
+    pub redo: Vector<Edit>,
+    // The processing chain is applied non-destructively; the processed signal is what is analysed,
+    // displayed and exported.
+    pub chain: Vec<Operation>,
+    pub settings: AnalysisSettings,
+    pub view: Option<Range<usize>>,
+    pub markers: Vec<Marker>,
+    pub change_log: Vec<ChangeLogEntry>,
+    // The session as it was last exported, imported or restored, for logging what has changed
+    // since when it is exported again.
+    pub saved: Option<SessionState>,
+    pub selection: Option<Range<usize>>,
+    pub channel: usize,
+}
+
+impl Store {
+    pub fn new() -> Self {
+        Self {
+            signals: vec![bench!(["Generating default signal"] => Generator::default().generate())],
+            source: None,
+            decimation: None,
+            metadata: None,
+            declared: None,
+            changed: false,
+            undo: Vector::new(),
+            redo: Vector::new(),
+            chain: Vec::new(),
+            settings: Presets::load()
+                .default_settings()
+                .cloned()
+                .unwrap_or_default(),
+            view: None,
+            markers: Vec::new(),
+            change_log: Vec::new(),
+            saved: None,
+            selection: None,
+            channel: 0,
+        }
+    }
+
+    pub fn signal(&self) -> &Signal {
+        &self.signals[0]
+    }
+
+    // Reads the samples of the primary signal as declared, then as `layout` if that differs.
+    fn read_as(&mut self, layout: SampleLayout) {
+        let declared = self
+            .declared
+            .take()
+            .unwrap_or_else(|| self.signal().clone());
+        if declared.layout() == Some(layout) {
+            self.signals[0] = declared;
+        } else {
+            self.signals[0] = declared.with_layout(layout);
+            self.declared = Some(declared);
+        }
+    }
+
+    fn edit(&self) -> Edit {
+        Edit {
+            signal: self.signal().clone(),
+            chain: self.chain.clone(),
+        }
+    }
+
+    // Records the current signal and chain before they are edited.
+    fn record(&mut self) {
+        self.undo.push_back(self.edit());
+        if self.undo.len() > HISTORY_LEN {
+            self.undo.pop_front();
+        }
+        self.redo.clear();
+    }
+
+    // Replaces the signal and chain with `edit`, returning what they were.
+    fn apply(&mut self, edit: Edit) -> Edit {
+        let current = self.edit();
+        self.signals[0] = edit.signal;
+        self.chain = edit.chain;
+        current
+    }
+
+    fn clear_history(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    pub fn session(&self) -> SessionState {
+        SessionState {
+            settings: self.settings.clone(),
+            view: self.view.clone(),
+            markers: self.markers.clone(),
+            change_log: self.change_log.clone(),
+        }
+    }
+
+    // The change log entry for exporting the session at `saved_at`.
+    pub fn change_log_entry(&self, saved_at: String) -> ChangeLogEntry {
+        let changes = match &self.saved {
+            Some(saved) => self.session().changes_since(saved),
+            None => vec!["Session saved for the first time".to_owned()],
+        };
+        ChangeLogEntry { saved_at, changes }
+    }
+}
+
+pub enum StoreAction {
+    // Replaces the primary signal. Newly opened files get their assigned or the default preset.
+    Load(Signal, Option<Source>, Option<Decimation>, Option<Metadata>),
+    // Reads the samples of the primary signal as `SampleLayout` rather than as declared, which
+    // like reopening it discards destructive edits.
+    Layout(SampleLayout),
+    // Replaces the primary signal, if any, and how it was viewed, e.g. from a stored session or a
+    // shared link.
+    Restore(Option<Signal>, SessionState),
+    // Records that the session was exported, with what changed since it was last saved.
+    Saved(ChangeLogEntry),
+    Compare(Option<Signal>),
+    Changed,
+    RemoveDc,
+    Undo,
+    Redo,
+    Chain(Vec<Operation>),
+    PushOperation(Operation),
+    Settings(AnalysisSettings),
+    ToggleSpectrum,
+    View(Option<Range<usize>>),
+    Markers(Vec<Marker>),
+    AddMarker(usize),
+    Select(Option<Range<usize>>),
+    Channel(usize),
+    Clear,
+}
+
+impl Reducible for Store {
+    type Action = StoreAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut store = (*self).clone();
+        match action {
+            StoreAction::Load(signal, source, decimation, metadata) => {
+                // A layout chosen for the same file is kept when it is reloaded.
+                let chosen = store
+                    .declared
+                    .as_ref()
+                    .filter(|_| source.is_some() && store.source == source)
+                    .and(store.signal().layout());
+                let layout =
+                    chosen.or_else(|| bench!(["Guessing sample layout"] => signal.guess_layout()));
+                store.declared = None;
+                store.signals[0] = signal;
+                if let Some(layout) = layout {
+                    store.read_as(layout);
+                }
+                // Keep the zoom when reloading the same file, e.g. while iterating on a render.
+                if source.is_none() || store.source != source {
+                    store.view = None;
+                    store.markers = metadata.as_ref().map_or_else(Vec::new, |metadata| {
+                        markers::from_cue_points(
+                            metadata,
+                            store.signals[0].channel(0).sample_rate(),
+                        )
+                    });
+                    store.selection = None;
+                }
+                if let Some(preset) = source
+                    .as_ref()
+                    .filter(|source| store.source.as_ref() != Some(source))
+                    .and_then(|source| Presets::load().settings_for(&source.name()).cloned())
+                {
+                    store.settings = preset;
+                }
+                store.clear_history();
+                store.source = source;
+                store.decimation = decimation;
+                store.metadata = metadata;
+                store.changed = false;
+            }
+            StoreAction::Layout(layout) => {
+                store.read_as(layout);
+                store.clear_history();
+            }
+            StoreAction::Restore(signal, state) => {
+                if let Some(signal) = signal {
+                    store.signals = vec![signal];
+                    store.metadata = None;
+                    store.declared = None;
+                    store.clear_history();
+                }
+                store.settings = state.settings.clone();
+                store.view = state.view.clone();
+                store.markers = state.markers.clone();
+                store.change_log = state.change_log.clone();
+                store.saved = Some(state);
+            }
+            StoreAction::Saved(entry) => {
+                store.change_log.push(entry);
+                store.saved = Some(store.session());
+            }
+            StoreAction::Compare(signal) => {
+                store.signals.truncate(1);
+                store.signals.extend(signal);
+            }
+            StoreAction::Changed => store.changed = true,
+            StoreAction::RemoveDc => {
+                let signal = bench!(["Removing DC offset"] => store
+                    .signal()
+                    .map_channels(Channel::remove_dc));
+                store.record();
+                store.signals[0] = signal;
+            }
+            StoreAction::Undo => {
+                if let Some(previous) = store.undo.pop_back() {
+                    let current = store.apply(previous);
+                    store.redo.push_back(current);
+                }
+            }
+            StoreAction::Redo => {
+                if let Some(next) = store.redo.pop_back() {
+                    let current = store.apply(next);
+                    store.undo.push_back(current);
+                }
+            }
+            StoreAction::Chain(chain) => {
+                if chain != store.chain {
+                    store.record();
+                    store.chain = chain;
+                }
+            }
+            StoreAction::PushOperation(operation) => {
+                store.record();
+                store.chain.push(operation);
+            }
+            StoreAction::Settings(settings) => store.settings = settings,
+            StoreAction::ToggleSpectrum => {
+                store.settings.show_spectrum = !store.settings.show_spectrum
+            }
+            StoreAction::View(view) => store.view = view,
+            StoreAction::Markers(markers) => store.markers = markers,
+            StoreAction::AddMarker(position) => {
+                let label = format!("Marker {}", store.markers.len() + 1);
+                store.markers = markers::insert(&store.markers, Marker { position, label });
+            }
+            StoreAction::Select(selection) => store.selection = selection,
+            StoreAction::Channel(channel) => store.channel = channel,
+            StoreAction::Clear => {
+                store = Self {
+                    settings: AnalysisSettings::default(),
+                    ..Self::new()
+                }
+            }
+        }
+        Rc::new(store)
+    }
+}
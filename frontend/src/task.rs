@@ -0,0 +1,65 @@
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use wasm_bindgen_futures::spawn_local;
+
+// A spawned future that is aborted when its handle is dropped. Aborting takes effect at the next
+// await point, so anything after it, such as emitting the result, never runs.
+pub struct Task {
+    aborted: Rc<Cell<bool>>,
+}
+
+impl Task {
+    pub fn spawn(future: impl Future<Output = ()> + 'static) -> Self {
+        let aborted = Rc::new(Cell::new(false));
+        spawn_local(Abortable {
+            future: Box::pin(future),
+            aborted: aborted.clone(),
+        });
+        Self { aborted }
+    }
+}
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        self.aborted.set(true);
+    }
+}
+
+struct Abortable<F> {
+    future: Pin<Box<F>>,
+    aborted: Rc<Cell<bool>>,
+}
+
+impl<F: Future<Output = ()>> Future for Abortable<F> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.aborted.get() {
+            return Poll::Ready(());
+        }
+        self.future.as_mut().poll(cx)
+    }
+}
+
+// Runs at most one task at a time. Spawning a task aborts the previous one, so that only the most
+// recently requested result is delivered, rather than whichever happens to finish last.
+#[derive(Clone, Default)]
+pub struct TaskSlot(Rc<RefCell<Option<Task>>>);
+
+impl TaskSlot {
+    pub fn spawn(&self, future: impl Future<Output = ()> + 'static) {
+        // The previous task is dropped, and thereby aborted, before the new one starts.
+        self.abort();
+        *self.0.borrow_mut() = Some(Task::spawn(future));
+    }
+
+    pub fn abort(&self) {
+        self.0.borrow_mut().take();
+    }
+}
@@ -0,0 +1,145 @@
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::HtmlElement;
+
+const STORAGE_KEY: &str = "signal-inspector.theme";
+
+// The themes to choose from, the chosen one persisted in local storage. A theme, such as a palette
+// safe for color blindness, is added as a variant along with its colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+}
+
+// The colors of the interface. They are set as CSS variables on the document, named after the
+// fields, for the stylesheet to use, and passed to what is drawn on canvases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub dark: bool,
+    pub background: &'static str,
+    // Behind labels and prompts laid over the plots.
+    pub translucent_background: &'static str,
+    pub foreground: &'static str,
+    // Hovered items.
+    pub emphasis: &'static str,
+    pub grid: &'static str,
+    pub waveform: &'static str,
+    // Traces compared with the waveform, and selections.
+    pub overlay: &'static str,
+    pub marker: &'static str,
+    pub clip: &'static str,
+    // Notices, detected regions and secondary traces.
+    pub highlight: &'static str,
+    // Passed checks and speech.
+    pub positive: &'static str,
+}
+
+impl ThemeName {
+    pub const ALL: [Self; 2] = [Self::Dark, Self::Light];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Dark => "Dark",
+            Self::Light => "Light",
+        }
+    }
+
+    pub fn theme(self) -> Theme {
+        match self {
+            Self::Dark => Theme {
+                dark: true,
+                background: "#0a0f0d",
+                translucent_background: "rgba(10, 15, 13, 0.7)",
+                foreground: "#c4cbca",
+                emphasis: "#ffffff",
+                grid: "#c4cbca",
+                waveform: "#ee4266",
+                overlay: "#3fa7d6",
+                marker: "#ffd23f",
+                clip: "#ee4266",
+                highlight: "#ffd23f",
+                positive: "#3bceac",
+            },
+            Self::Light => Theme {
+                dark: false,
+                background: "#f7f8f6",
+                translucent_background: "rgba(247, 248, 246, 0.7)",
+                foreground: "#2e3533",
+                emphasis: "#000000",
+                grid: "#5c6664",
+                waveform: "#d1224a",
+                overlay: "#1f78b4",
+                marker: "#b8860b",
+                clip: "#c4122f",
+                highlight: "#b8860b",
+                positive: "#138a6e",
+            },
+        }
+    }
+
+    // The theme called `name`, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|theme| theme.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn load() -> Self {
+        LocalStorage::get(STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn store(self) {
+        if let Err(error) = LocalStorage::set(STORAGE_KEY, self) {
+            crate::log(&format!("failed to store theme: {error}"));
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        ThemeName::default().theme()
+    }
+}
+
+impl Theme {
+    fn variables(&self) -> [(&'static str, &'static str); 11] {
+        [
+            ("--background", self.background),
+            ("--translucent-background", self.translucent_background),
+            ("--foreground", self.foreground),
+            ("--emphasis", self.emphasis),
+            ("--grid", self.grid),
+            ("--waveform", self.waveform),
+            ("--overlay", self.overlay),
+            ("--marker", self.marker),
+            ("--clip", self.clip),
+            ("--highlight", self.highlight),
+            ("--positive", self.positive),
+        ]
+    }
+
+    // Sets the variables on `element`, for it and everything in it, along with the color scheme
+    // of form controls.
+    pub fn apply(&self, element: &HtmlElement) -> Result<(), JsValue> {
+        let style = element.style();
+        style.set_property("color-scheme", if self.dark { "dark" } else { "light" })?;
+        for (name, value) in self.variables() {
+            style.set_property(name, value)?;
+        }
+        Ok(())
+    }
+
+    // Applies the theme to the whole page, and to the browser around it.
+    pub fn apply_to_document(&self) -> Result<(), JsValue> {
+        let document = gloo::utils::document();
+        let root = document.document_element().ok_or("no root element")?;
+        self.apply(root.unchecked_ref())?;
+        if let Some(meta) = document.query_selector("meta[name=theme-color]")? {
+            meta.set_attribute("content", self.background)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,325 @@
+// A typed message protocol between the page and a background worker running this same module,
+// used to take computation off the main thread.
+//
+// Each message is an envelope with the protocol version, the ID of the request it belongs to and
+// its type, with sample data carried in typed arrays whose buffers are transferred rather than
+// cloned. A buffer is still copied once out of and once into WebAssembly memory at either end, but
+// never by the browser in between.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use gloo::events::EventListener;
+use js_sys::{Array, Float32Array, Function, Object, Promise, Reflect};
+use rustfft::{num_complex::Complex, FftPlanner};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    Blob, BlobPropertyBag, DedicatedWorkerGlobalScope, MessageEvent, Url, WorkerOptions, WorkerType,
+};
+
+use crate::gpu::object;
+
+// Bumped whenever a message changes, so that a worker left over from an earlier deployment, such
+// as one served from the cache of the service worker, is told apart.
+const PROTOCOL_VERSION: u32 = 1;
+
+thread_local! {
+    static WORKER: RefCell<Option<Rc<Worker>>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Request {
+    // The complex spectra of consecutive frames of `frame_len` real samples, as `gpu::fft` returns.
+    Fft { frame_len: usize, samples: Vec<f32> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Response {
+    Fft { spectra: Vec<f32> },
+    Error { message: String },
+}
+
+// A message with its fields as properties of a plain object, along with the buffers to transfer.
+trait Message: Sized {
+    fn encode(self) -> (Object, Vec<JsValue>);
+    fn decode(data: &JsValue) -> Result<Self, String>;
+}
+
+impl Message for Request {
+    fn encode(self) -> (Object, Vec<JsValue>) {
+        match self {
+            Self::Fft { frame_len, samples } => {
+                let samples = Float32Array::from(&samples[..]);
+                let transfer = vec![samples.buffer().into()];
+                let message = object(&[
+                    ("type", &"fft".into()),
+                    ("frameLen", &(frame_len as f64).into()),
+                    ("samples", &samples),
+                ]);
+                (message, transfer)
+            }
+        }
+    }
+
+    fn decode(data: &JsValue) -> Result<Self, String> {
+        match message_type(data)?.as_str() {
+            "fft" => Ok(Self::Fft {
+                frame_len: property(data, "frameLen")?
+                    .as_f64()
+                    .ok_or("frame length is not a number")? as usize,
+                samples: float32_array(data, "samples")?,
+            }),
+            other => Err(format!("unknown request type {other}")),
+        }
+    }
+}
+
+impl Message for Response {
+    fn encode(self) -> (Object, Vec<JsValue>) {
+        match self {
+            Self::Fft { spectra } => {
+                let spectra = Float32Array::from(&spectra[..]);
+                let transfer = vec![spectra.buffer().into()];
+                let message = object(&[("type", &"fft".into()), ("spectra", &spectra)]);
+                (message, transfer)
+            }
+            Self::Error { message } => {
+                let message = object(&[("type", &"error".into()), ("message", &message.into())]);
+                (message, Vec::new())
+            }
+        }
+    }
+
+    fn decode(data: &JsValue) -> Result<Self, String> {
+        match message_type(data)?.as_str() {
+            "fft" => Ok(Self::Fft {
+                spectra: float32_array(data, "spectra")?,
+            }),
+            "error" => Ok(Self::Error {
+                message: property(data, "message")?
+                    .as_string()
+                    .ok_or("error message is not a string")?,
+            }),
+            other => Err(format!("unknown response type {other}")),
+        }
+    }
+}
+
+// The page side of a worker, matching responses to requests by ID.
+pub struct Worker {
+    worker: web_sys::Worker,
+    next_id: Cell<u64>,
+    // The functions resolving the promises of requests awaiting a response.
+    pending: Rc<RefCell<HashMap<u64, Function>>>,
+    _listener: EventListener,
+}
+
+impl Worker {
+    // The worker shared by the page, started on first use.
+    pub fn get() -> Result<Rc<Self>, String> {
+        if let Some(worker) = WORKER.with(|worker| worker.borrow().clone()) {
+            return Ok(worker);
+        }
+        let worker = Rc::new(Self::spawn().map_err(|error| format!("{error:?}"))?);
+        WORKER.with(|cell| *cell.borrow_mut() = Some(worker.clone()));
+        Ok(worker)
+    }
+
+    // Starts a module worker loading the same script and WebAssembly module as the page, whose
+    // `main` then serves requests instead of rendering.
+    fn spawn() -> Result<Self, JsValue> {
+        let document = gloo::utils::document();
+        let href = |selector: &str| -> Result<String, JsValue> {
+            let link = document
+                .query_selector(selector)?
+                .ok_or_else(|| format!("no element matches {selector}"))?;
+            // The property, unlike the attribute, is an absolute URL.
+            Reflect::get(&link, &"href".into())?
+                .as_string()
+                .ok_or_else(|| "link has no URL".into())
+        };
+        let script = href("link[rel=modulepreload]")?;
+        let module = href("link[rel=preload][type='application/wasm']")?;
+
+        let source = format!("import init from {script:?};\ninit({module:?});\n");
+        let properties = BlobPropertyBag::new();
+        properties.set_type("text/javascript");
+        let blob =
+            Blob::new_with_str_sequence_and_options(&Array::of1(&source.into()), &properties)?;
+        // The URL is not revoked, as the worker loads it asynchronously.
+        let url = Url::create_object_url_with_blob(&blob)?;
+        let options = WorkerOptions::new();
+        options.set_type(WorkerType::Module);
+        let worker = web_sys::Worker::new_with_options(&url, &options)?;
+
+        let pending: Rc<RefCell<HashMap<u64, Function>>> = Rc::default();
+        let listener = {
+            let pending = pending.clone();
+            EventListener::new(&worker, "message", move |event| {
+                let data = event.unchecked_ref::<MessageEvent>().data();
+                let resolve = match envelope_id(&data) {
+                    Ok(id) => pending.borrow_mut().remove(&id),
+                    Err(error) => {
+                        crate::log(&format!("invalid message from worker: {error}"));
+                        return;
+                    }
+                };
+                if let Some(resolve) = resolve {
+                    let _ = resolve.call1(&JsValue::UNDEFINED, &data);
+                }
+            })
+        };
+
+        Ok(Self {
+            worker,
+            next_id: Cell::new(0),
+            pending,
+            _listener: listener,
+        })
+    }
+
+    pub async fn request(&self, request: Request) -> Result<Response, String> {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        let promise = Promise::new(&mut |resolve, _| {
+            self.pending.borrow_mut().insert(id, resolve);
+        });
+        if let Err(error) = post(
+            |message, transfer| self.worker.post_message_with_transfer(message, transfer),
+            id,
+            request,
+        ) {
+            self.pending.borrow_mut().remove(&id);
+            return Err(format!("failed to post to worker: {error:?}"));
+        }
+
+        let data = JsFuture::from(promise)
+            .await
+            .map_err(|error| format!("{error:?}"))?;
+        decode(&data)
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.worker.terminate();
+    }
+}
+
+pub fn is_worker() -> bool {
+    js_sys::global().is_instance_of::<DedicatedWorkerGlobalScope>()
+}
+
+// Answers requests from the page for as long as the worker runs.
+pub fn serve() {
+    let scope: DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+    EventListener::new(&scope.clone(), "message", move |event| {
+        let data = event.unchecked_ref::<MessageEvent>().data();
+        let id = match envelope_id(&data) {
+            Ok(id) => id,
+            Err(error) => {
+                crate::log(&format!("invalid message from page: {error}"));
+                return;
+            }
+        };
+        let response = match decode(&data) {
+            Ok(request) => handle(request),
+            Err(message) => Response::Error { message },
+        };
+        let result = post(
+            |message, transfer| scope.post_message_with_transfer(message, transfer),
+            id,
+            response,
+        );
+        if let Err(error) = result {
+            crate::log(&format!("failed to post to page: {error:?}"));
+        }
+    })
+    .forget();
+}
+
+fn handle(request: Request) -> Response {
+    match request {
+        Request::Fft { frame_len, samples } => {
+            if !frame_len.is_power_of_two() {
+                return Response::Error {
+                    message: format!("frame length {frame_len} is not a power of two"),
+                };
+            }
+            let fft = FftPlanner::new().plan_fft_forward(frame_len);
+            let mut buffer: Vec<_> = samples[..samples.len() / frame_len * frame_len]
+                .iter()
+                .map(|&x| Complex::from(x))
+                .collect();
+            fft.process(&mut buffer);
+            Response::Fft {
+                spectra: buffer.iter().flat_map(|c| [c.re, c.im]).collect(),
+            }
+        }
+    }
+}
+
+// The complex spectra of consecutive frames of `frame_len` real samples, a power of two, as
+// interleaved real and imaginary parts, transformed in the worker.
+pub async fn fft(samples: &[f32], frame_len: usize) -> Result<Vec<f32>, String> {
+    let request = Request::Fft {
+        frame_len,
+        samples: samples.to_vec(),
+    };
+    match Worker::get()?.request(request).await? {
+        Response::Fft { spectra } => Ok(spectra),
+        Response::Error { message } => Err(message),
+    }
+}
+
+fn post(
+    post_message: impl FnOnce(&JsValue, &JsValue) -> Result<(), JsValue>,
+    id: u64,
+    message: impl Message,
+) -> Result<(), JsValue> {
+    let (message, transfer) = message.encode();
+    Reflect::set(&message, &"version".into(), &PROTOCOL_VERSION.into())?;
+    Reflect::set(&message, &"id".into(), &(id as f64).into())?;
+    post_message(&message, &transfer.into_iter().collect::<Array>())
+}
+
+// A message, once its version is known to match. Mismatched messages are still answered by ID, so
+// that no request is left waiting.
+fn decode<M: Message>(data: &JsValue) -> Result<M, String> {
+    let version = property(data, "version")?.as_f64();
+    if version != Some(f64::from(PROTOCOL_VERSION)) {
+        return Err(format!(
+            "protocol version {version:?}, expected {PROTOCOL_VERSION}"
+        ));
+    }
+    M::decode(data)
+}
+
+// The ID of the request a message belongs to.
+fn envelope_id(data: &JsValue) -> Result<u64, String> {
+    property(data, "id")?
+        .as_f64()
+        .map(|id| id as u64)
+        .ok_or_else(|| "message has no ID".to_owned())
+}
+
+fn message_type(data: &JsValue) -> Result<String, String> {
+    property(data, "type")?
+        .as_string()
+        .ok_or_else(|| "message has no type".to_owned())
+}
+
+fn property(data: &JsValue, name: &str) -> Result<JsValue, String> {
+    Reflect::get(data, &name.into()).map_err(|error| format!("{error:?}"))
+}
+
+fn float32_array(data: &JsValue, name: &str) -> Result<Vec<f32>, String> {
+    property(data, name)?
+        .dyn_into::<Float32Array>()
+        .map(|array| array.to_vec())
+        .map_err(|_| format!("{name} is not a Float32Array"))
+}
@@ -0,0 +1,46 @@
+// Service worker making Signal Inspector usable offline. Trunk emits hashed asset names, so assets
+// are cached as they are fetched rather than precached by name.
+const CACHE = "signal-inspector-v1";
+const SHELL = ["./", "./index.html", "./manifest.webmanifest", "./icon.svg"];
+
+self.addEventListener("install", (event) => {
+    event.waitUntil(caches.open(CACHE).then((cache) => cache.addAll(SHELL)));
+    self.skipWaiting();
+});
+
+self.addEventListener("activate", (event) => {
+    event.waitUntil(
+        caches.keys().then((keys) =>
+            Promise.all(keys.filter((key) => key !== CACHE).map((key) => caches.delete(key)))
+        )
+    );
+    self.clients.claim();
+});
+
+// Serve from the cache while refreshing it from the network, so that a new deployment is picked up
+// on the next load while offline use keeps working.
+self.addEventListener("fetch", (event) => {
+    const url = new URL(event.request.url);
+    if (
+        event.request.method !== "GET" ||
+        url.origin !== location.origin ||
+        url.pathname.startsWith("/api/")
+    ) {
+        return;
+    }
+
+    event.respondWith(
+        caches.open(CACHE).then(async (cache) => {
+            const cached = await cache.match(event.request);
+            const fetched = fetch(event.request)
+                .then((response) => {
+                    if (response.ok) {
+                        cache.put(event.request, response.clone());
+                    }
+                    return response;
+                })
+                .catch(() => cached);
+            return cached || fetched;
+        })
+    );
+});
@@ -107,6 +107,8 @@ def bump_version(bump_comp_idx, repository_owner):
     update_hocfile(next_version, "hocfile.yaml", current_version)
     update_manifest(next_version, "frontend/Cargo.toml", current_version)
     update_manifest(next_version, "backend/Cargo.toml", current_version)
+    update_manifest(next_version, "analysis/Cargo.toml", current_version)
+    update_manifest(next_version, "cli/Cargo.toml", current_version)
     update_changelog(next_version, "CHANGELOG.md", repository_owner)
 
     return next_version